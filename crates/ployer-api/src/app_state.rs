@@ -3,15 +3,25 @@ use ployer_core::models::WsEvent;
 use ployer_docker::DockerClient;
 use ployer_proxy::CaddyClient;
 use sqlx::SqlitePool;
+use std::path::PathBuf;
 use std::sync::Arc;
 use tokio::sync::broadcast;
 
+use crate::services::DeploymentTracker;
+use crate::websocket::ConnectionRegistry;
+
+/// Events buffered for WebSocket clients. A busy deploy logs thousands of lines,
+/// so a client that falls further behind than this gets an `events_missed` notice.
+const WS_BROADCAST_CAPACITY: usize = 4096;
+
 pub struct AppState {
     pub db: SqlitePool,
     pub docker: Option<Arc<DockerClient>>,
     pub caddy: CaddyClient,
     pub config: AppConfig,
     pub ws_broadcast: broadcast::Sender<WsEvent>,
+    pub deployments: DeploymentTracker,
+    pub ws_connections: ConnectionRegistry,
 }
 
 pub type SharedState = Arc<AppState>;
@@ -23,13 +33,21 @@ impl AppState {
         caddy: CaddyClient,
         config: AppConfig,
     ) -> SharedState {
-        let (ws_broadcast, _) = broadcast::channel(256);
+        let (ws_broadcast, _) = broadcast::channel(WS_BROADCAST_CAPACITY);
+        let deployments = DeploymentTracker::new(
+            config.docker.max_concurrent_builds,
+            config.docker.repo_cache_dir.as_ref().map(PathBuf::from),
+        )
+        .with_log_limit(config.docker.max_build_log_kb * 1024)
+        .with_pre_build_image(config.docker.pre_build_image.clone());
         Arc::new(Self {
             db,
             docker: docker.map(Arc::new),
             caddy,
             config,
             ws_broadcast,
+            deployments,
+            ws_connections: ConnectionRegistry::new(),
         })
     }
 }
@@ -1,19 +1,34 @@
 use axum::http::{HeaderMap, StatusCode};
+use crate::error::ApiError;
+use super::jwt::Claims;
 use super::validate_token;
 
 /// Extract and validate user ID from Authorization header
-pub fn extract_user_id(headers: &HeaderMap, jwt_secret: &str) -> Result<String, (StatusCode, String)> {
+pub fn extract_user_id(headers: &HeaderMap, jwt_secret: &str) -> Result<String, ApiError> {
+    Ok(extract_claims(headers, jwt_secret)?.sub)
+}
+
+/// Like `extract_user_id`, but rejects non-admin users with 403
+pub fn require_admin(headers: &HeaderMap, jwt_secret: &str) -> Result<String, ApiError> {
+    let claims = extract_claims(headers, jwt_secret)?;
+    if claims.role != "admin" {
+        return Err(ApiError::new(StatusCode::FORBIDDEN, "Admin role required"));
+    }
+    Ok(claims.sub)
+}
+
+fn extract_claims(headers: &HeaderMap, jwt_secret: &str) -> Result<Claims, ApiError> {
     let auth_header = headers
         .get("Authorization")
         .and_then(|h| h.to_str().ok())
-        .ok_or_else(|| (StatusCode::UNAUTHORIZED, "Missing Authorization header".to_string()))?;
+        .ok_or_else(|| ApiError::new(StatusCode::UNAUTHORIZED, "Missing Authorization header"))?;
 
     let token = auth_header
         .strip_prefix("Bearer ")
-        .ok_or_else(|| (StatusCode::UNAUTHORIZED, "Invalid Authorization header format".to_string()))?;
+        .ok_or_else(|| ApiError::new(StatusCode::UNAUTHORIZED, "Invalid Authorization header format"))?;
 
     let claims = validate_token(token, jwt_secret)
-        .map_err(|_| (StatusCode::UNAUTHORIZED, "Invalid or expired token".to_string()))?;
+        .map_err(|_| ApiError::new(StatusCode::UNAUTHORIZED, "Invalid or expired token"))?;
 
-    Ok(claims.sub)
+    Ok(claims)
 }
@@ -6,4 +6,4 @@ pub mod helpers;
 
 pub use jwt::validate_token;
 pub use service::AuthService;
-pub use helpers::extract_user_id;
+pub use helpers::{extract_user_id, require_admin};
@@ -1,6 +1,8 @@
 use anyhow::Result;
+use argon2::password_hash::rand_core::{OsRng, RngCore};
 use ployer_core::models::{User, UserRole};
-use ployer_db::repositories::{ApiKeyRepository, UserRepository};
+use ployer_db::repositories::{ApiKeyRepository, RefreshTokenRepository, UserRepository};
+use sha2::{Digest, Sha256};
 use sqlx::SqlitePool;
 
 use super::password::{hash_password, verify_password};
@@ -10,13 +12,15 @@ pub struct AuthService {
     user_repo: UserRepository,
     #[allow(dead_code)]
     api_key_repo: ApiKeyRepository,
+    refresh_token_repo: RefreshTokenRepository,
 }
 
 impl AuthService {
     pub fn new(pool: SqlitePool) -> Self {
         Self {
             user_repo: UserRepository::new(pool.clone()),
-            api_key_repo: ApiKeyRepository::new(pool),
+            api_key_repo: ApiKeyRepository::new(pool.clone()),
+            refresh_token_repo: RefreshTokenRepository::new(pool),
         }
     }
 
@@ -69,8 +73,82 @@ impl AuthService {
         Ok((user, token))
     }
 
+    /// Issue a new refresh token for a user. Only its hash is stored.
+    pub async fn issue_refresh_token(&self, user_id: &str, expiry_days: u64) -> Result<String> {
+        let mut bytes = [0u8; 32];
+        OsRng.fill_bytes(&mut bytes);
+        let token = hex::encode(bytes);
+
+        let expires_at = chrono::Utc::now() + chrono::Duration::days(expiry_days as i64);
+        self.refresh_token_repo
+            .create(user_id, &hash_refresh_token(&token), expires_at)
+            .await?;
+
+        Ok(token)
+    }
+
+    /// Exchange a refresh token for a new access token and a rotated refresh token
+    pub async fn refresh(
+        &self,
+        refresh_token: &str,
+        jwt_secret: &str,
+        token_expiry_hours: u64,
+        refresh_expiry_days: u64,
+    ) -> Result<(User, String, String)> {
+        let stored = self.refresh_token_repo
+            .find_by_hash(&hash_refresh_token(refresh_token))
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Invalid refresh token"))?;
+
+        if stored.revoked_at.is_some() {
+            // A rotated token being replayed means it may have leaked; cut off the whole family
+            self.refresh_token_repo.revoke_all_for_user(&stored.user_id).await?;
+            anyhow::bail!("Refresh token has been revoked");
+        }
+
+        if stored.expires_at < chrono::Utc::now() {
+            anyhow::bail!("Refresh token has expired");
+        }
+
+        // Revoking is conditional, so only one of two concurrent refreshes can succeed
+        if !self.refresh_token_repo.revoke(&stored.id).await? {
+            anyhow::bail!("Refresh token has been revoked");
+        }
+
+        let user = self.user_repo
+            .find_by_id(&stored.user_id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("User not found"))?;
+
+        let token = generate_token(
+            &user.id,
+            &user.email,
+            user.role.as_str(),
+            jwt_secret,
+            token_expiry_hours,
+        )?;
+        let new_refresh_token = self.issue_refresh_token(&user.id, refresh_expiry_days).await?;
+
+        Ok((user, token, new_refresh_token))
+    }
+
+    /// Revoke a refresh token. Unknown or already revoked tokens are ignored.
+    pub async fn logout(&self, refresh_token: &str) -> Result<()> {
+        if let Some(stored) = self.refresh_token_repo
+            .find_by_hash(&hash_refresh_token(refresh_token))
+            .await?
+        {
+            self.refresh_token_repo.revoke(&stored.id).await?;
+        }
+        Ok(())
+    }
+
     /// Get user by ID
     pub async fn get_user(&self, user_id: &str) -> Result<Option<User>> {
         self.user_repo.find_by_id(user_id).await
     }
 }
+
+fn hash_refresh_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
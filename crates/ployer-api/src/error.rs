@@ -0,0 +1,117 @@
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use ployer_docker::DockerError;
+use serde::Serialize;
+use std::fmt::Display;
+use utoipa::ToSchema;
+
+/// Error returned by API handlers, rendered as `{ "error": { "code", "message" } }`
+#[derive(Debug)]
+pub struct ApiError {
+    pub status: StatusCode,
+    pub message: String,
+}
+
+impl ApiError {
+    pub fn new(status: StatusCode, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            message: message.into(),
+        }
+    }
+
+    /// 500 carrying the underlying error's message
+    pub fn internal(err: impl Display) -> Self {
+        Self::new(StatusCode::INTERNAL_SERVER_ERROR, err.to_string())
+    }
+
+    /// Status for a failed Docker call on `resource`, e.g. 404 for a missing "Container"
+    pub fn docker(err: DockerError, resource: &str) -> Self {
+        match err {
+            DockerError::NotFound(_) => Self::new(StatusCode::NOT_FOUND, format!("{} not found", resource)),
+            DockerError::AlreadyStarted => Self::new(StatusCode::CONFLICT, format!("{} already running", resource)),
+            DockerError::NotRunning => Self::new(StatusCode::CONFLICT, format!("{} not running", resource)),
+            DockerError::StillRunning => Self::new(
+                StatusCode::CONFLICT,
+                format!("{} is running; stop it first or remove it with force=true", resource),
+            ),
+            DockerError::InUse(_) => Self::new(StatusCode::CONFLICT, format!("{} is in use by containers", resource)),
+            e @ DockerError::PortInUse(_) => Self::new(StatusCode::CONFLICT, e.to_string()),
+            DockerError::Other(e) => Self::internal(e),
+        }
+    }
+
+    /// Machine-readable code derived from the status, e.g. `not_found`
+    pub fn code(&self) -> String {
+        self.status
+            .canonical_reason()
+            .unwrap_or("error")
+            .to_lowercase()
+            .replace([' ', '-'], "_")
+    }
+}
+
+/// Body of every error response
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ErrorBody {
+    #[schema(inline)]
+    error: ErrorDetail,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+struct ErrorDetail {
+    /// Machine-readable code, e.g. `not_found` or `conflict`
+    code: String,
+    message: String,
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let body = ErrorBody {
+            error: ErrorDetail {
+                code: self.code(),
+                message: self.message,
+            },
+        };
+        (self.status, Json(body)).into_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_error_body_shape() {
+        let response = ApiError::new(StatusCode::NOT_FOUND, "Application not found").into_response();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(body["error"]["code"], "not_found");
+        assert_eq!(body["error"]["message"], "Application not found");
+
+        assert_eq!(ApiError::internal("boom").code(), "internal_server_error");
+        assert_eq!(ApiError::new(StatusCode::PAYLOAD_TOO_LARGE, "").code(), "payload_too_large");
+    }
+
+    #[test]
+    fn test_docker_error_statuses() {
+        let not_found = ApiError::docker(DockerError::NotFound("No such container: abc".to_string()), "Container");
+        assert_eq!(not_found.status, StatusCode::NOT_FOUND);
+        assert_eq!(not_found.message, "Container not found");
+
+        assert_eq!(ApiError::docker(DockerError::AlreadyStarted, "Container").status, StatusCode::CONFLICT);
+        assert_eq!(ApiError::docker(DockerError::NotRunning, "Container").status, StatusCode::CONFLICT);
+        assert_eq!(ApiError::docker(DockerError::StillRunning, "Container").status, StatusCode::CONFLICT);
+        let in_use = ApiError::docker(DockerError::InUse("volume is in use".to_string()), "Volume");
+        assert_eq!(in_use.message, "Volume is in use by containers");
+
+        let other = ApiError::docker(DockerError::Other(anyhow::anyhow!("daemon hung up")), "Container");
+        assert_eq!(other.status, StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(other.message, "daemon hung up");
+    }
+}
@@ -1,17 +1,20 @@
 mod app_state;
 mod auth;
+mod error;
 mod middleware;
 mod routes;
 mod services;
 mod websocket;
 
 use anyhow::Result;
-use axum::{middleware as axum_middleware, Extension, Router};
+use axum::{extract::DefaultBodyLimit, middleware as axum_middleware, Extension, Router};
 use clap::{Parser, Subcommand};
 use ployer_core::config::AppConfig;
 use ployer_docker::DockerClient;
 use ployer_proxy::CaddyClient;
+use std::net::SocketAddr;
 use tower_http::cors::{AllowHeaders, AllowMethods, AllowOrigin, CorsLayer};
+use tower_http::limit::RequestBodyLimitLayer;
 use tower_http::services::{ServeDir, ServeFile};
 use tower_http::trace::TraceLayer;
 use tracing::info;
@@ -22,6 +25,9 @@ use tracing_subscriber::{fmt, EnvFilter};
 struct Cli {
     #[command(subcommand)]
     command: Option<Commands>,
+    /// Print the outcome of a one-off command as a single JSON object instead of log lines
+    #[arg(long, global = true)]
+    json: bool,
 }
 
 #[derive(Subcommand)]
@@ -39,50 +45,113 @@ enum Commands {
         #[arg(long)]
         password: String,
     },
+    /// Create the first admin user without going through the web UI
+    CreateAdmin {
+        /// Admin email address
+        #[arg(long)]
+        email: String,
+        /// Password (min 8 chars)
+        #[arg(long)]
+        password: String,
+        /// Display name
+        #[arg(long)]
+        name: String,
+    },
+    /// Re-encrypt stored secrets after changing the JWT secret
+    RotateKey {
+        /// Previous JWT secret
+        #[arg(long)]
+        old_secret: String,
+        /// New JWT secret
+        #[arg(long)]
+        new_secret: String,
+    },
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
+    let cli = Cli::parse();
+    let command = cli.command.unwrap_or(Commands::Start);
+
+    // JSON output of one-off commands must be the only thing on stdout
+    let quiet = cli.json && !matches!(command, Commands::Start);
+    let filter = || {
+        if quiet {
+            EnvFilter::new("off")
+        } else {
+            EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"))
+        }
+    };
+
     // Determine log format from env var (LOG_FORMAT=json for structured logging)
     let log_format = std::env::var("LOG_FORMAT").unwrap_or_default();
 
     if log_format == "json" {
         tracing_subscriber::fmt()
             .json()
-            .with_env_filter(
-                EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info")),
-            )
+            .with_env_filter(filter())
             .init();
     } else {
         fmt()
-            .with_env_filter(
-                EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info")),
-            )
+            .with_env_filter(filter())
             .init();
     }
 
-    let cli = Cli::parse();
+    if let Commands::Start = command {
+        // Load config: env vars > ployer.toml (or PLOYER_CONFIG) > defaults
+        return start_server(AppConfig::load()?).await;
+    }
 
-    // Load config from env vars (falls back to defaults)
-    let config = AppConfig::from_env();
+    let outcome = run_command(command).await;
+    if !cli.json {
+        return outcome.map(|_| ());
+    }
 
-    match cli.command.unwrap_or(Commands::Start) {
-        Commands::Start => start_server(config).await,
+    let body = match &outcome {
+        Ok(fields) => {
+            let mut body = serde_json::json!({ "ok": true });
+            if let (Some(body), serde_json::Value::Object(fields)) = (body.as_object_mut(), fields) {
+                body.extend(fields.clone());
+            }
+            body
+        }
+        Err(e) => serde_json::json!({ "ok": false, "error": format!("{:#}", e) }),
+    };
+    println!("{}", body);
+    if outcome.is_err() {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+/// Run a one-off command; its result fields are printed with `--json`
+async fn run_command(command: Commands) -> Result<serde_json::Value> {
+    let config = AppConfig::load()?;
+
+    match command {
+        Commands::Start => unreachable!("the server is started directly"),
         Commands::Migrate => run_migrations(config).await,
         Commands::ResetPassword { email, password } => {
             reset_password(config, &email, &password).await
         }
+        Commands::CreateAdmin { email, password, name } => {
+            create_admin(config, &email, &password, &name).await
+        }
+        Commands::RotateKey { old_secret, new_secret } => {
+            rotate_key(config, &old_secret, &new_secret).await
+        }
     }
 }
 
-async fn run_migrations(config: AppConfig) -> Result<()> {
+async fn run_migrations(config: AppConfig) -> Result<serde_json::Value> {
     let pool = ployer_db::create_pool(&config.database.url).await?;
     ployer_db::run_migrations(&pool).await?;
+    services::key_encryption::encrypt_plaintext_keys(&pool, &config.get_secret_key()).await?;
     info!("Migrations complete");
-    Ok(())
+    Ok(serde_json::json!({}))
 }
 
-async fn reset_password(config: AppConfig, email: &str, password: &str) -> Result<()> {
+async fn reset_password(config: AppConfig, email: &str, password: &str) -> Result<serde_json::Value> {
     if password.len() < 8 {
         anyhow::bail!("Password must be at least 8 characters");
     }
@@ -106,8 +175,138 @@ async fn reset_password(config: AppConfig, email: &str, password: &str) -> Resul
         .to_string();
 
     repo.update_password(&user.id, &hash).await?;
+    ployer_db::repositories::AuditRepository::new(pool)
+        .record(None, ployer_core::models::AuditAction::PasswordReset, &user.id, None)
+        .await?;
     info!("Password reset for user '{}'", email);
-    Ok(())
+    Ok(serde_json::json!({ "user_id": user.id, "email": email }))
+}
+
+async fn create_admin(
+    config: AppConfig,
+    email: &str,
+    password: &str,
+    name: &str,
+) -> Result<serde_json::Value> {
+    if password.len() < 8 {
+        anyhow::bail!("Password must be at least 8 characters");
+    }
+
+    // Bootstrap may run before the server has ever started, so bring the schema up first
+    let pool = ployer_db::create_pool(&config.database.url).await?;
+    ployer_db::run_migrations(&pool).await?;
+    let repo = ployer_db::repositories::UserRepository::new(pool.clone());
+
+    if repo.count_admins().await? > 0 {
+        anyhow::bail!("An admin user already exists; use reset-password to regain access");
+    }
+    if repo.find_by_email(email).await?.is_some() {
+        anyhow::bail!("User '{}' already exists", email);
+    }
+
+    let hash = auth::password::hash_password(password)?;
+    let user = repo
+        .create(email, &hash, name, ployer_core::models::UserRole::Admin)
+        .await?;
+    ployer_db::repositories::AuditRepository::new(pool)
+        .record(None, ployer_core::models::AuditAction::AdminCreate, &user.id, None)
+        .await?;
+    info!("Admin user '{}' created", email);
+    Ok(serde_json::json!({ "user_id": user.id, "email": email }))
+}
+
+async fn rotate_key(config: AppConfig, old_secret: &str, new_secret: &str) -> Result<serde_json::Value> {
+    use ployer_core::crypto;
+
+    if new_secret.is_empty() {
+        anyhow::bail!("New secret must not be empty");
+    }
+
+    let old_key = crypto::derive_key(old_secret);
+    let new_key = crypto::derive_key(new_secret);
+
+    let reencrypt = |table: &str, id: &str, ciphertext: &str| -> Result<String> {
+        let plaintext = crypto::decrypt(ciphertext, &old_key).map_err(|e| {
+            anyhow::anyhow!("Failed to decrypt {} row '{}' with the old secret: {}", table, id, e)
+        })?;
+        crypto::encrypt(&plaintext, &new_key)
+    };
+
+    let pool = ployer_db::create_pool(&config.database.url).await?;
+    services::key_encryption::encrypt_plaintext_keys(&pool, &old_key).await?;
+    let mut tx = pool.begin().await?;
+
+    // Any decryption failure returns early and drops the transaction, rolling back every row
+    let env_vars: Vec<(String, String)> =
+        sqlx::query_as("SELECT id, value_encrypted FROM environment_variables")
+            .fetch_all(&mut *tx)
+            .await?;
+    for (id, value) in &env_vars {
+        let value = reencrypt("environment_variables", id, value)?;
+        sqlx::query("UPDATE environment_variables SET value_encrypted = ? WHERE id = ?")
+            .bind(value)
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+    }
+
+    let deploy_keys: Vec<(String, String)> =
+        sqlx::query_as("SELECT id, private_key_encrypted FROM deploy_keys")
+            .fetch_all(&mut *tx)
+            .await?;
+    for (id, key) in &deploy_keys {
+        let key = reencrypt("deploy_keys", id, key)?;
+        sqlx::query("UPDATE deploy_keys SET private_key_encrypted = ? WHERE id = ?")
+            .bind(key)
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+    }
+
+    let servers: Vec<(String, String)> = sqlx::query_as(
+        "SELECT id, ssh_key_encrypted FROM servers WHERE ssh_key_encrypted IS NOT NULL",
+    )
+    .fetch_all(&mut *tx)
+    .await?;
+    for (id, key) in &servers {
+        let key = reencrypt("servers", id, key)?;
+        sqlx::query("UPDATE servers SET ssh_key_encrypted = ? WHERE id = ?")
+            .bind(key)
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+    }
+
+    let registries: Vec<(String, String)> = sqlx::query_as(
+        "SELECT application_id, password_encrypted FROM application_registries WHERE password_encrypted IS NOT NULL",
+    )
+    .fetch_all(&mut *tx)
+    .await?;
+    for (application_id, password) in &registries {
+        let password = reencrypt("application_registries", application_id, password)?;
+        sqlx::query("UPDATE application_registries SET password_encrypted = ? WHERE application_id = ?")
+            .bind(password)
+            .bind(application_id)
+            .execute(&mut *tx)
+            .await?;
+    }
+
+    tx.commit().await?;
+
+    info!(
+        "Re-encrypted {} environment variables, {} deploy keys, {} server SSH keys, {} registry passwords",
+        env_vars.len(),
+        deploy_keys.len(),
+        servers.len(),
+        registries.len()
+    );
+    info!("Set PLOYER_JWT_SECRET to the new secret before restarting Ployer");
+    Ok(serde_json::json!({
+        "env_vars": env_vars.len(),
+        "deploy_keys": deploy_keys.len(),
+        "server_ssh_keys": servers.len(),
+        "registry_passwords": registries.len(),
+    }))
 }
 
 async fn register_local_server(pool: &sqlx::SqlitePool) -> Result<()> {
@@ -160,6 +359,8 @@ async fn start_server(config: AppConfig) -> Result<()> {
     // Database
     let pool = ployer_db::create_pool(&config.database.url).await?;
     ployer_db::run_migrations(&pool).await?;
+    // Keys saved before encryption at rest was added can't be decrypted otherwise
+    services::key_encryption::encrypt_plaintext_keys(&pool, &config.get_secret_key()).await?;
 
     // Auto-register local server if not exists
     register_local_server(&pool).await?;
@@ -182,28 +383,69 @@ async fn start_server(config: AppConfig) -> Result<()> {
     };
 
     // Caddy client
-    let caddy = CaddyClient::new(&config.caddy.admin_url, &config.caddy.caddyfile_path);
+    let caddy = CaddyClient::new(&config.caddy.admin_url, &config.caddy.caddyfile_path, &config.proxy)?;
 
     let addr = format!("{}:{}", config.server.host, config.server.port);
     let cors = build_cors(&config.server.allowed_origins);
 
-    // Rate limiter: 300 req/min globally
-    let rate_limiter = middleware::rate_limit::new_rate_limiter(300);
+    // Rate limiters: one global bucket, and a separate one for webhook receivers
+    let rate_limits = std::sync::Arc::new(middleware::rate_limit::RateLimits::new(&config.server));
 
     // Build shared state
     let state = app_state::AppState::new(pool.clone(), docker, caddy, config);
 
+    // Reconcile containers left behind by a previous run
+    if let Some(ref docker) = state.docker {
+        if let Err(e) = services::reconciler::reconcile_containers(&pool, docker, &state.caddy).await {
+            tracing::warn!("Container reconciliation failed: {}", e);
+        }
+    }
+
+    // Remove build directories leaked by deployments of a previous run
+    services::build_dir::sweep_on_startup().await;
+
     // Start health monitors
-    services::health_monitor::spawn_health_monitor(pool.clone(), state.ws_broadcast.clone());
+    services::health_monitor::spawn_health_monitor(
+        pool.clone(),
+        state.docker.clone(),
+        state.ws_broadcast.clone(),
+        state.config.get_secret_key(),
+    );
     services::app_health_monitor::spawn_app_health_monitor(
         pool.clone(),
         state.docker.clone(),
         state.ws_broadcast.clone(),
     );
 
+    // Start live host stats for WebSocket clients
+    services::server_stats::spawn_server_stats(
+        pool.clone(),
+        state.ws_broadcast.clone(),
+        state.config.server.stats_interval_secs,
+    );
+
+    // Start certificate status monitor
+    services::ssl_monitor::spawn_ssl_monitor(
+        pool.clone(),
+        state.caddy.clone(),
+        state.ws_broadcast.clone(),
+    );
+
     // Start stats aggregator
     services::stats_aggregator::spawn_stats_aggregator(pool, state.docker.clone());
 
+    // Start build log retention
+    services::log_retention::spawn_log_retention(
+        state.db.clone(),
+        state.config.docker.build_log_retention_days,
+    );
+
+    // Start webhook delivery retrier
+    services::webhook_retrier::spawn_webhook_retrier(state.clone());
+
+    // Start scheduled redeploys
+    services::deploy_scheduler::spawn_deploy_scheduler(state.clone());
+
     // Serve frontend static files if FRONTEND_DIR is set or ./frontend/build exists
     let frontend_dir = std::env::var("FRONTEND_DIR")
         .unwrap_or_else(|_| "frontend/build".to_string());
@@ -211,24 +453,31 @@ async fn start_server(config: AppConfig) -> Result<()> {
 
     // Build router — optionally with frontend static file fallback
     let api = routes::api_router();
+    let base = Router::new()
+        .nest("/api/v1", api)
+        .merge(routes::metrics::router());
     let app = if serve_frontend {
         let index = format!("{}/index.html", frontend_dir);
         info!("Serving frontend from {}", frontend_dir);
-        Router::new()
-            .nest("/api/v1", api)
-            .fallback_service(
-                ServeDir::new(&frontend_dir)
-                    .not_found_service(ServeFile::new(index)),
-            )
+        base.fallback_service(
+            ServeDir::new(&frontend_dir)
+                .not_found_service(ServeFile::new(index)),
+        )
     } else {
-        Router::new().nest("/api/v1", api)
+        base
     };
 
     let app = app
         .layer(axum_middleware::from_fn(
             middleware::rate_limit::rate_limit_middleware,
         ))
-        .layer(Extension(rate_limiter))
+        .layer(Extension(rate_limits))
+        // Replace axum's per-extractor default with one limit for the whole body
+        .layer(DefaultBodyLimit::disable())
+        .layer(RequestBodyLimitLayer::new(middleware::body_limit::MAX_REQUEST_BODY_BYTES))
+        .layer(axum_middleware::from_fn(
+            middleware::body_limit::body_limit_middleware,
+        ))
         .layer(TraceLayer::new_for_http())
         .layer(cors)
         .with_state(state);
@@ -238,7 +487,8 @@ async fn start_server(config: AppConfig) -> Result<()> {
     let listener = tokio::net::TcpListener::bind(&addr).await?;
 
     // Graceful shutdown on SIGTERM or Ctrl-C
-    axum::serve(listener, app)
+    // Connection info gives the audit log the client's address
+    axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>())
         .with_graceful_shutdown(shutdown_signal())
         .await?;
 
@@ -0,0 +1,56 @@
+use axum::{
+    extract::Request,
+    http::{header, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+use crate::error::ApiError;
+
+/// Largest request body accepted by any endpoint; bigger payloads get 413
+pub const MAX_REQUEST_BODY_BYTES: usize = 10 * 1024 * 1024;
+
+/// `RequestBodyLimitLayer`, and extractors that hit the limit while reading,
+/// answer with a plain-text 413. Swap it for the usual JSON error body.
+pub async fn body_limit_middleware(request: Request, next: Next) -> Response {
+    as_json_error(next.run(request).await)
+}
+
+fn as_json_error(response: Response) -> Response {
+    let is_json = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with("application/json"));
+    if response.status() != StatusCode::PAYLOAD_TOO_LARGE || is_json {
+        return response;
+    }
+
+    ApiError::new(
+        StatusCode::PAYLOAD_TOO_LARGE,
+        format!("Request body is larger than {} MB", MAX_REQUEST_BODY_BYTES / (1024 * 1024)),
+    )
+    .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_plain_413_becomes_error_body() {
+        let plain = (StatusCode::PAYLOAD_TOO_LARGE, "length limit exceeded").into_response();
+        let response = as_json_error(plain);
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(body["error"]["code"], "payload_too_large");
+        assert_eq!(body["error"]["message"], "Request body is larger than 10 MB");
+
+        let other = as_json_error((StatusCode::BAD_REQUEST, "nope").into_response());
+        assert_eq!(other.status(), StatusCode::BAD_REQUEST);
+        let bytes = axum::body::to_bytes(other.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(&bytes[..], b"nope");
+    }
+}
@@ -1,2 +1,3 @@
+pub mod body_limit;
 pub mod rate_limit;
 pub mod validation;
@@ -3,7 +3,6 @@ use axum::{
     http::StatusCode,
     middleware::Next,
     response::{IntoResponse, Response},
-    Json,
 };
 use governor::{
     clock::DefaultClock,
@@ -11,9 +10,11 @@ use governor::{
     state::{InMemoryState, NotKeyed},
     Quota, RateLimiter,
 };
-use serde_json::json;
+use ployer_core::config::ServerConfig;
 use std::{num::NonZeroU32, sync::Arc};
 
+use crate::error::ApiError;
+
 pub type SharedRateLimiter = Arc<RateLimiter<NotKeyed, InMemoryState, DefaultClock, NoOpMiddleware>>;
 
 /// Create a rate limiter that allows `requests_per_second` burst.
@@ -24,18 +25,88 @@ pub fn new_rate_limiter(requests_per_minute: u32) -> SharedRateLimiter {
     Arc::new(RateLimiter::direct(quota))
 }
 
-/// Axum middleware that applies a shared rate limiter to every request.
+/// The global bucket plus a separate one for webhook receivers, so a busy
+/// git org pushing many repos cannot lock users out of the API or vice versa.
+/// `None` means the bucket is unlimited.
+pub struct RateLimits {
+    global: Option<SharedRateLimiter>,
+    webhooks: Option<SharedRateLimiter>,
+    webhook_paths: Vec<String>,
+}
+
+impl RateLimits {
+    pub fn new(config: &ServerConfig) -> Self {
+        let limiter = |per_minute: u32| (per_minute > 0).then(|| new_rate_limiter(per_minute));
+        Self {
+            global: limiter(config.rate_limit_per_minute),
+            webhooks: limiter(config.webhook_rate_limit_per_minute),
+            webhook_paths: config
+                .webhook_rate_limit_paths
+                .split(',')
+                .map(str::trim)
+                .filter(|p| !p.is_empty())
+                .map(String::from)
+                .collect(),
+        }
+    }
+
+    /// Whether a request to `path` fits in its bucket
+    pub fn check(&self, path: &str) -> bool {
+        let limiter = if self.webhook_paths.iter().any(|p| path.starts_with(p.as_str())) {
+            &self.webhooks
+        } else {
+            &self.global
+        };
+        limiter.as_ref().is_none_or(|l| l.check().is_ok())
+    }
+}
+
+/// Axum middleware that applies the shared rate limiters to every request.
 pub async fn rate_limit_middleware(
-    limiter: axum::extract::Extension<SharedRateLimiter>,
+    limits: axum::extract::Extension<Arc<RateLimits>>,
     req: Request,
     next: Next,
 ) -> Response {
-    match limiter.check() {
-        Ok(_) => next.run(req).await,
-        Err(_) => (
-            StatusCode::TOO_MANY_REQUESTS,
-            Json(json!({ "error": "Too many requests. Please slow down." })),
-        )
-            .into_response(),
+    if limits.check(req.uri().path()) {
+        next.run(req).await
+    } else {
+        ApiError::new(StatusCode::TOO_MANY_REQUESTS, "Too many requests. Please slow down.")
+            .into_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_webhooks_use_their_own_bucket() {
+        let limits = RateLimits::new(&ServerConfig::default());
+
+        for _ in 0..400 {
+            assert!(limits.check("/api/v1/webhooks/github"));
+        }
+        for _ in 0..300 {
+            assert!(limits.check("/api/v1/applications"));
+        }
+        assert!(!limits.check("/api/v1/applications"));
+        // The authenticated webhook settings routes stay on the global bucket
+        assert!(!limits.check("/api/v1/applications/abc/webhooks"));
+        assert!(limits.check("/api/v1/webhooks/gitlab"));
+    }
+
+    #[test]
+    fn test_zero_disables_a_bucket() {
+        let limits = RateLimits::new(&ServerConfig {
+            rate_limit_per_minute: 1,
+            webhook_rate_limit_per_minute: 0,
+            ..ServerConfig::default()
+        });
+
+        for _ in 0..1000 {
+            assert!(limits.check("/api/v1/webhooks/github"));
+        }
+        assert!(limits.check("/api/v1/servers"));
+        assert!(!limits.check("/api/v1/servers"));
     }
 }
@@ -1,9 +1,14 @@
 use axum::http::StatusCode;
+use ployer_core::cron::CronSchedule;
+use ployer_core::models::{HeaderRules, PortMapping, ProxyHeaders};
+use std::collections::BTreeMap;
 
-type ValidationResult = Result<(), (StatusCode, String)>;
+use crate::error::ApiError;
 
-fn err(msg: &str) -> (StatusCode, String) {
-    (StatusCode::BAD_REQUEST, msg.to_string())
+type ValidationResult = Result<(), ApiError>;
+
+fn err(msg: &str) -> ApiError {
+    ApiError::new(StatusCode::BAD_REQUEST, msg)
 }
 
 /// Ensure a string field is non-empty and within max length.
@@ -57,6 +62,51 @@ pub fn git_url(value: &str) -> ValidationResult {
     Ok(())
 }
 
+/// Validate a hostname and return it normalized (lowercased, trailing dot removed).
+pub fn domain(value: &str) -> Result<String, ApiError> {
+    let normalized = value.trim().trim_end_matches('.').to_ascii_lowercase();
+    if normalized.is_empty() {
+        return Err(err("Domain name is required"));
+    }
+    if normalized.len() > 253 {
+        return Err(err("Domain name must be 253 characters or fewer"));
+    }
+
+    let labels: Vec<&str> = normalized.split('.').collect();
+    if labels.len() < 2 {
+        return Err(err("Domain name must include a top-level domain"));
+    }
+    for label in &labels {
+        if label.is_empty() || label.len() > 63 {
+            return Err(err("Each domain label must be 1 to 63 characters"));
+        }
+        if label.starts_with('-') || label.ends_with('-') {
+            return Err(err("Domain labels may not start or end with '-'"));
+        }
+        if !label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+            return Err(err("Domain name may only contain letters, digits, '-', and '.'"));
+        }
+    }
+    if labels.last().is_some_and(|tld| tld.chars().all(|c| c.is_ascii_digit())) {
+        return Err(err("Domain name must end in an alphabetic top-level domain"));
+    }
+
+    Ok(normalized)
+}
+
+/// Validate a proxy hostname, which may be a wildcard covering one label
+/// (`*.example.com`), and return it normalized like [`domain`].
+pub fn domain_pattern(value: &str) -> Result<String, ApiError> {
+    let normalized = value.trim().to_ascii_lowercase();
+    match normalized.strip_prefix("*.") {
+        // Caddy needs at least two labels under the wildcard, like a certificate would
+        Some(base) if base.contains('.') => Ok(format!("*.{}", domain(base)?)),
+        Some(_) => Err(err("Wildcard domains need at least two labels after '*.'")),
+        None if normalized.contains('*') => Err(err("'*' is only allowed as the whole first label, e.g. *.example.com")),
+        None => domain(&normalized),
+    }
+}
+
 /// Validate a TCP port number.
 pub fn port(value: u16) -> ValidationResult {
     if value == 0 {
@@ -65,6 +115,198 @@ pub fn port(value: u16) -> ValidationResult {
     Ok(())
 }
 
+/// Longest an application may give its container to stop. A rolling update waits
+/// this long on the old container, so it has to stay well inside the build timeout.
+pub const MAX_STOP_GRACE_SECONDS: u32 = 600;
+
+/// Validate an application's stop grace period.
+pub fn stop_grace_seconds(value: u32) -> ValidationResult {
+    if value > MAX_STOP_GRACE_SECONDS {
+        return Err(err(&format!("stop_grace_seconds must be at most {}", MAX_STOP_GRACE_SECONDS)));
+    }
+    Ok(())
+}
+
+/// Validate the `hours` window of a stats query.
+pub fn stats_hours(value: i64) -> ValidationResult {
+    if value < 1 {
+        return Err(err("hours must be at least 1"));
+    }
+    Ok(())
+}
+
+/// Validate a pre-build or post-deploy command: not blank, at most 4096
+/// characters, no NUL bytes.
+pub fn hook_command(value: &str, field: &str) -> ValidationResult {
+    if value.trim().is_empty() {
+        return Err(err(&format!("{} must not be blank", field)));
+    }
+    if value.len() > 4096 || value.contains('\0') {
+        return Err(err(&format!("{} must be at most 4096 characters without NUL bytes", field)));
+    }
+    Ok(())
+}
+
+/// Validate a published port mapping.
+pub fn port_mapping(value: &PortMapping) -> ValidationResult {
+    port(value.container_port)?;
+    if let Some(host_port) = value.host_port {
+        port(host_port)?;
+    }
+    if value.protocol != "tcp" && value.protocol != "udp" {
+        return Err(err("Port protocol must be \"tcp\" or \"udp\""));
+    }
+    Ok(())
+}
+
+/// Validate a Docker restart policy (e.g. "always", "unless-stopped", "on-failure:5").
+pub fn restart_policy(value: &str) -> ValidationResult {
+    ployer_docker::parse_restart_policy(value)
+        .map(|_| ())
+        .map_err(|e| err(&e.to_string()))
+}
+
+/// Validate a Dockerfile build stage name (letters, digits, `_`, `.`, `-`).
+pub fn docker_target(value: &str) -> ValidationResult {
+    if value.trim().is_empty() {
+        return Err(err("docker_target may not be empty"));
+    }
+    if value.len() > 128 {
+        return Err(err("docker_target must be 128 characters or fewer"));
+    }
+    let valid = value.starts_with(|c: char| c.is_ascii_alphabetic())
+        && value.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '-'));
+    if !valid {
+        return Err(err("docker_target must be a Dockerfile stage name, e.g. \"production\""));
+    }
+    Ok(())
+}
+
+/// Validate an image platform, `os[/arch[/variant]]` (e.g. `linux/amd64`, `linux/arm/v7`).
+pub fn platform(value: &str) -> ValidationResult {
+    let parts: Vec<&str> = value.split('/').collect();
+    let valid = parts.len() <= 3
+        && parts.iter().all(|part| {
+            !part.is_empty()
+                && part.len() <= 32
+                && part.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+        });
+    if !valid {
+        return Err(err("platform must be os[/arch[/variant]], e.g. \"linux/amd64\" or \"linux/arm64\""));
+    }
+    Ok(())
+}
+
+/// Validate a signal to send to a container: a name (`SIGINT`, `INT`,
+/// `SIGRTMIN+3`) or a number from 1 to 64.
+pub fn signal(value: &str) -> ValidationResult {
+    let valid = match value.parse::<u8>() {
+        Ok(number) => (1..=64).contains(&number),
+        Err(_) => {
+            let name = value.strip_prefix("SIG").unwrap_or(value);
+            !name.is_empty()
+                && name.len() <= 16
+                && name.starts_with(|c: char| c.is_ascii_uppercase())
+                && name.chars().all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || matches!(c, '+' | '-'))
+        }
+    };
+    if !valid {
+        return Err(err("signal must be a signal name like \"SIGINT\" or a number from 1 to 64"));
+    }
+    Ok(())
+}
+
+/// Validate a commit SHA, full or abbreviated to at least 4 hex digits.
+pub fn commit_sha(value: &str) -> ValidationResult {
+    if !(4..=64).contains(&value.len()) || !value.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(err("commit SHA must be 4 to 64 hex characters"));
+    }
+    Ok(())
+}
+
+/// Validate an image repository to push to, without a tag (e.g. `ghcr.io/acme/web`).
+pub fn image_repository(value: &str) -> ValidationResult {
+    if value.trim().is_empty() {
+        return Err(err("image is required"));
+    }
+    if value.len() > 255 {
+        return Err(err("image must be 255 characters or fewer"));
+    }
+    if value.contains('@') || value.rsplit('/').next().is_some_and(|name| name.contains(':')) {
+        return Err(err("image must not include a tag or digest"));
+    }
+    let valid = value
+        .chars()
+        .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || matches!(c, '.' | '_' | '-' | '/' | ':'))
+        && value.split('/').all(|part| !part.is_empty());
+    if !valid {
+        return Err(err("image must be a lowercase repository name, e.g. \"ghcr.io/acme/web\""));
+    }
+    Ok(())
+}
+
+/// Validate an image to pull, with an optional tag or digest (e.g. `postgres:16`).
+pub fn image_reference(value: &str) -> ValidationResult {
+    if value.trim().is_empty() {
+        return Err(err("image is required"));
+    }
+    if value.len() > 512 {
+        return Err(err("image must be 512 characters or fewer"));
+    }
+    let (name, _digest) = value.split_once('@').unwrap_or((value, ""));
+    let repository = match name.rsplit_once(':') {
+        // A colon after the last '/' starts the tag; before it, it's a registry port
+        Some((repo, tag)) if !tag.contains('/') => {
+            let valid_tag = !tag.is_empty()
+                && tag.len() <= 128
+                && tag.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '-'));
+            if !valid_tag {
+                return Err(err("image tag may only contain letters, digits, '_', '.', and '-'"));
+            }
+            repo
+        }
+        _ => name,
+    };
+    image_repository(repository)
+}
+
+/// Validate a five-field cron expression for scheduled redeploys.
+pub fn cron_schedule(value: &str) -> ValidationResult {
+    CronSchedule::parse(value)
+        .map(|_| ())
+        .map_err(|e| err(&format!("Invalid cron expression: {}", e)))
+}
+
+/// Validate a Docker volume name (starts alphanumeric, then letters, digits, `_`, `.`, `-`).
+pub fn volume_name(value: &str) -> ValidationResult {
+    if value.trim().is_empty() {
+        return Err(err("Volume name is required"));
+    }
+    if value.len() > 255 {
+        return Err(err("Volume name must be 255 characters or fewer"));
+    }
+    let valid = value.starts_with(|c: char| c.is_ascii_alphanumeric())
+        && value.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '-'));
+    if !valid {
+        return Err(err("Volume name may only contain letters, digits, '_', '.', and '-', and must start with a letter or digit"));
+    }
+    Ok(())
+}
+
+/// Validate an absolute mount path inside a container.
+pub fn container_path(value: &str) -> ValidationResult {
+    if !value.starts_with('/') {
+        return Err(err("Container path must be absolute"));
+    }
+    if value.len() > 4096 {
+        return Err(err("Container path must be 4096 characters or fewer"));
+    }
+    if value.contains(':') {
+        return Err(err("Container path may not contain ':'"));
+    }
+    Ok(())
+}
+
 /// Validate an environment variable key (alphanumeric + underscore, no spaces).
 pub fn env_key(value: &str) -> ValidationResult {
     if value.trim().is_empty() {
@@ -79,3 +321,215 @@ pub fn env_key(value: &str) -> ValidationResult {
     }
     Ok(())
 }
+
+pub const MAX_TAGS: usize = 32;
+
+/// Validate a tag key. Like an environment variable key, but `-` and `.` are
+/// allowed too, e.g. `region` or `team.owner`.
+pub fn tag_key(value: &str) -> ValidationResult {
+    if value.is_empty() {
+        return Err(err("Tag key is required"));
+    }
+    if value.len() > 64 {
+        return Err(err("Tag key must be 64 characters or fewer"));
+    }
+    let valid = value.chars().all(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | '.'));
+    if !valid {
+        return Err(err("Tag key may only contain letters, digits, '_', '-' and '.'"));
+    }
+    Ok(())
+}
+
+/// Validate a set of tags: valid keys, values of at most 256 characters.
+pub fn tags(tags: &BTreeMap<String, String>) -> ValidationResult {
+    if tags.len() > MAX_TAGS {
+        return Err(err(&format!("At most {} tags are allowed", MAX_TAGS)));
+    }
+    for (key, value) in tags {
+        tag_key(key)?;
+        if value.len() > 256 || value.chars().any(char::is_control) {
+            return Err(err(&format!("Tag \"{}\" must have a value of 256 characters or fewer without control characters", key)));
+        }
+    }
+    Ok(())
+}
+
+pub const MAX_HEADER_RULES: usize = 32;
+
+/// Validate an HTTP header name (an RFC 9110 token).
+pub fn header_name(value: &str) -> ValidationResult {
+    let valid = !value.is_empty()
+        && value.len() <= 128
+        && value.chars().all(|c| c.is_ascii_alphanumeric() || "!#$%&'*+-.^_`|~".contains(c));
+    if !valid {
+        return Err(err(&format!("\"{}\" is not a valid header name", value)));
+    }
+    Ok(())
+}
+
+/// Validate the proxy's header rules: valid names, values of at most 1024
+/// characters without control characters, and at most 32 rules each way.
+pub fn proxy_headers(headers: &ProxyHeaders) -> ValidationResult {
+    for rules in [&headers.request, &headers.response] {
+        header_rules(rules)?;
+    }
+    Ok(())
+}
+
+fn header_rules(rules: &HeaderRules) -> ValidationResult {
+    if rules.set.len() + rules.remove.len() > MAX_HEADER_RULES {
+        return Err(err(&format!("At most {} header rules are allowed in each direction", MAX_HEADER_RULES)));
+    }
+    for (name, value) in &rules.set {
+        header_name(name)?;
+        if value.len() > 1024 || value.chars().any(char::is_control) {
+            return Err(err(&format!("Header \"{}\" must have a value of 1024 characters or fewer without control characters", name)));
+        }
+    }
+    for name in &rules.remove {
+        header_name(name)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_domain_accepts_and_normalizes() {
+        assert_eq!(domain("app.example.com").unwrap(), "app.example.com");
+        assert_eq!(domain("UPPER.Example.COM").unwrap(), "upper.example.com");
+        assert_eq!(domain("app.example.com.").unwrap(), "app.example.com");
+    }
+
+    #[test]
+    fn test_image_repository() {
+        for good in ["web", "acme/web", "ghcr.io/acme/web", "localhost:5000/team/web-app"] {
+            assert!(image_repository(good).is_ok(), "{} should be accepted", good);
+        }
+        for bad in ["", "acme/web:latest", "acme/web@sha256:abc", "Acme/Web", "acme//web", "/web"] {
+            assert!(image_repository(bad).is_err(), "{} should be rejected", bad);
+        }
+    }
+
+    #[test]
+    fn test_platform() {
+        for good in ["linux", "linux/amd64", "linux/arm64/v8", "linux/arm/v7", "windows/amd64"] {
+            assert!(platform(good).is_ok(), "{} should be accepted", good);
+        }
+        for bad in ["", "linux/", "/amd64", "Linux/AMD64", "linux/arm/v7/extra", "linux amd64", "linux//amd64"] {
+            assert!(platform(bad).is_err(), "{} should be rejected", bad);
+        }
+    }
+
+    #[test]
+    fn test_signal() {
+        for ok in ["SIGINT", "INT", "SIGRTMIN+3", "SIGUSR1", "15"] {
+            assert!(signal(ok).is_ok(), "{}", ok);
+        }
+        for bad in ["", "SIG", "sigint", "0", "65", "SIGINT; rm", "-9"] {
+            assert!(signal(bad).is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn test_stop_grace_seconds() {
+        assert!(stop_grace_seconds(0).is_ok());
+        assert!(stop_grace_seconds(MAX_STOP_GRACE_SECONDS).is_ok());
+        assert!(stop_grace_seconds(MAX_STOP_GRACE_SECONDS + 1).is_err());
+    }
+
+    #[test]
+    fn test_stats_hours() {
+        assert!(stats_hours(1).is_ok());
+        assert!(stats_hours(24).is_ok());
+        assert!(stats_hours(0).is_err());
+        assert!(stats_hours(-5).is_err());
+    }
+
+    #[test]
+    fn test_hook_command() {
+        assert!(hook_command("./manage.py migrate --noinput", "post_deploy_cmd").is_ok());
+        assert!(hook_command("npm ci && npm run build", "pre_build_cmd").is_ok());
+        for bad in ["", "   ", "echo \0", &"x".repeat(4097)] {
+            assert!(hook_command(bad, "post_deploy_cmd").is_err());
+        }
+    }
+
+    #[test]
+    fn test_commit_sha() {
+        for ok in ["abc1", "0123456789abcdef0123456789abcdef01234567", "DEADBEEF"] {
+            assert!(commit_sha(ok).is_ok(), "{}", ok);
+        }
+        for bad in ["", "abc", "HEAD", "main", "abc1~1", &"a".repeat(65)] {
+            assert!(commit_sha(bad).is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn test_image_reference() {
+        for good in ["postgres", "postgres:16", "localhost:5000/web", "ghcr.io/acme/web:v1.2.3", "acme/web@sha256:abc"] {
+            assert!(image_reference(good).is_ok(), "{} should be accepted", good);
+        }
+        for bad in ["", "postgres:", "Postgres:16", "acme/web:bad tag", "acme//web:1"] {
+            assert!(image_reference(bad).is_err(), "{} should be rejected", bad);
+        }
+    }
+
+    #[test]
+    fn test_domain_rejects_malformed() {
+        for bad in ["bad domain", "-foo.com", "foo-.com", "localhost", "a..com", "under_score.com", "1.2.3.4", ""] {
+            assert!(domain(bad).is_err(), "{} should be rejected", bad);
+        }
+        assert!(domain(&format!("{}com", "abcdefghi.".repeat(26))).is_err());
+    }
+
+    #[test]
+    fn test_domain_pattern_allows_leading_wildcard() {
+        assert_eq!(domain_pattern("*.Example.com").unwrap(), "*.example.com");
+        assert_eq!(domain_pattern("api.example.com.").unwrap(), "api.example.com");
+        for bad in ["*.com", "*", "*example.com", "a.*.example.com", "**.example.com", "*.-bad.com"] {
+            assert!(domain_pattern(bad).is_err(), "{} should be rejected", bad);
+        }
+    }
+
+    #[test]
+    fn test_proxy_headers() {
+        for good in ["X-Request-Id", "Strict-Transport-Security", "x_custom"] {
+            assert!(header_name(good).is_ok(), "{} should be accepted", good);
+        }
+        for bad in ["", "X Bad", "X-Bad:", "Ünicode"] {
+            assert!(header_name(bad).is_err(), "{} should be rejected", bad);
+        }
+
+        let mut headers = ProxyHeaders::default();
+        headers.request.set.insert("X-Tenant".to_string(), "{http.request.host}".to_string());
+        headers.response.remove.push("Server".to_string());
+        assert!(proxy_headers(&headers).is_ok());
+
+        headers.response.set.insert("X-Bad".to_string(), "a\r\nInjected: 1".to_string());
+        assert!(proxy_headers(&headers).is_err());
+        headers.response.set.clear();
+        headers.request.remove = (0..MAX_HEADER_RULES).map(|i| format!("X-{}", i)).collect();
+        assert!(proxy_headers(&headers).is_err());
+    }
+
+    #[test]
+    fn test_tags() {
+        for good in ["region", "env", "team.owner", "cost-center", "tier_2"] {
+            assert!(tag_key(good).is_ok(), "{} should be accepted", good);
+        }
+        for bad in ["", "region:eu", "has space", "a=b", &"k".repeat(65)] {
+            assert!(tag_key(bad).is_err(), "{} should be rejected", bad);
+        }
+
+        let tag = |value: &str| BTreeMap::from([("region".to_string(), value.to_string())]);
+        assert!(tags(&tag("eu-west")).is_ok());
+        assert!(tags(&tag("")).is_ok());
+        assert!(tags(&tag(&"v".repeat(257))).is_err());
+        assert!(tags(&tag("line\nbreak")).is_err());
+        let many = (0..=MAX_TAGS).map(|i| (format!("k{}", i), String::new())).collect();
+        assert!(tags(&many).is_err());
+    }
+}
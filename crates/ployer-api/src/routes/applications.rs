@@ -1,32 +1,84 @@
 use axum::{
-    extract::{Path, State},
-    http::{HeaderMap, StatusCode},
-    routing::{get, put},
+    extract::{Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::IntoResponse,
+    routing::{delete, get, post, put},
     Json, Router,
 };
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::path::{Component, Path as FsPath, PathBuf};
+use std::time::Duration;
+use utoipa::{IntoParams, OpenApi, ToSchema};
 
 use crate::app_state::SharedState;
-use crate::auth::extract_user_id;
+use crate::auth::{extract_user_id, require_admin};
 use crate::middleware::validation;
+use crate::error::ApiError;
+use crate::services::audit::{self, ClientIp};
 use ployer_core::crypto;
-use ployer_core::models::{Application, BuildStrategy};
-use ployer_db::repositories::{ApplicationRepository, DeployKeyRepository, EnvVarRepository};
-use ployer_git::GitService;
+use ployer_core::models::{
+    AppStatus, Application, ApplicationRegistry, AuditAction, ApplicationVolume, BuildStrategy,
+    DeployKey, DeploymentStatus, EnvVarScope, HealthCheckStatus, PortMapping,
+};
+use ployer_db::repositories::{
+    ApplicationFilter, ApplicationRepository, AuditRepository, DeployKeyRepository,
+    DeploymentRepository, DomainRepository, EnvVarRepository, HealthCheckRepository,
+    NewApplication, RegistryRepository, VolumeRepository,
+};
+use ployer_docker::{ContainerFilters, DockerError, LABEL_APP_ID};
+use ployer_git::{DeployKeyType, GitService};
 
 pub fn router() -> Router<SharedState> {
     Router::new()
         .route("/", get(list_applications).post(create_application))
         .route("/:id", get(get_application).put(update_application).delete(delete_application))
+        .route("/:id/status", get(get_application_status))
+        .route("/:id/clone", post(clone_application))
         .route("/:id/envs", get(list_env_vars).post(add_env_var))
+        .route("/:id/envs/bulk", post(import_env_vars))
+        .route("/:id/envs/export", get(export_env_vars))
         .route("/:id/envs/:key", put(update_env_var).delete(delete_env_var))
+        .route("/:id/volumes", get(list_volumes).post(add_volume))
+        .route("/:id/volumes/:volume_id", delete(delete_volume))
         .route("/:id/deploy-key", get(get_deploy_key).post(generate_deploy_key))
+        .route("/:id/deploy-key/export", get(export_deploy_key))
+        .route("/:id/registry", get(get_registry).put(set_registry).delete(delete_registry))
+        .route("/:id/schedule", put(set_schedule).delete(clear_schedule))
+        .route("/:id/start", post(start_application))
+        .route("/:id/stop", post(stop_application))
+        .route("/:id/restart", post(restart_application))
+        .route("/:id/archive", post(archive_application))
+        .route("/:id/unarchive", post(unarchive_application))
+        .route("/:id/validate", post(validate_application))
 }
 
+#[derive(OpenApi)]
+#[openapi(
+    paths(
+        list_applications, create_application, get_application, update_application,
+        delete_application, get_application_status, clone_application, archive_application, unarchive_application,
+        start_application, stop_application, restart_application, list_env_vars, add_env_var,
+        update_env_var, delete_env_var, import_env_vars, export_env_vars, list_volumes, add_volume,
+        delete_volume, get_deploy_key, generate_deploy_key, export_deploy_key, get_registry, set_registry,
+        delete_registry, set_schedule, clear_schedule, validate_application,
+    ),
+    components(schemas(
+        CreateApplicationRequest, CloneApplicationRequest, ApplicationResponse,
+        ListApplicationsResponse, UpdateApplicationRequest, EnvVarRequest, EnvVarResponse,
+        ListEnvVarsResponse, ImportEnvVarsResponse, VolumeRequest, ListVolumesResponse,
+        DeployKeyResponse, DeployKeyExportResponse, RegistryRequest, ScheduleRequest, RegistryResponse, ConfigProblem,
+        ValidateApplicationResponse, ApplicationStatusResponse, Application, AppStatus,
+        BuildStrategy, PortMapping, EnvVarScope, ApplicationVolume, ApplicationRegistry,
+        HealthCheckStatus,
+    )),
+    tags((name = "applications", description = "Applications and their configuration"))
+)]
+pub struct ApiDoc;
+
 // ===== Request/Response Types =====
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 struct CreateApplicationRequest {
     name: String,
     server_id: String,
@@ -34,11 +86,26 @@ struct CreateApplicationRequest {
     #[serde(default = "default_branch")]
     git_branch: String,
     #[serde(default)]
+    git_submodules: bool,
+    #[serde(default)]
     build_strategy: BuildStrategy,
     dockerfile_path: Option<String>,
+    docker_target: Option<String>,
+    image: Option<String>,
     port: Option<u16>,
     #[serde(default)]
+    ports: Vec<PortMapping>,
+    #[serde(default)]
     auto_deploy: bool,
+    #[serde(default = "default_restart_policy")]
+    restart_policy: String,
+    /// Seconds a stopping container gets to drain before it is killed (default 10, max 600)
+    stop_grace_seconds: Option<u32>,
+    /// Shell command run before the build in a throwaway `PLOYER_PRE_BUILD_IMAGE` container
+    /// holding a copy of the checkout; admin only
+    pre_build_cmd: Option<String>,
+    /// Shell command run inside the new container once it's healthy, e.g. migrations
+    post_deploy_cmd: Option<String>,
     env_vars: Option<HashMap<String, String>>,
 }
 
@@ -46,72 +113,251 @@ fn default_branch() -> String {
     "main".to_string()
 }
 
-#[derive(Debug, Serialize)]
+fn default_restart_policy() -> String {
+    "unless-stopped".to_string()
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+struct CloneApplicationRequest {
+    /// Defaults to the source's name with a `-copy` suffix
+    name: Option<String>,
+    git_branch: Option<String>,
+    #[serde(default)]
+    copy_env_vars: bool,
+    #[serde(default)]
+    copy_health_check: bool,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
 struct ApplicationResponse {
     application: Application,
 }
 
-#[derive(Debug, Serialize)]
+/// Everything a dashboard card shows about an app's current state
+#[derive(Debug, Serialize, ToSchema)]
+struct ApplicationStatusResponse {
+    status: AppStatus,
+    /// Status of the most recent deployment, if there has been one
+    latest_deployment_status: Option<DeploymentStatus>,
+    latest_deployment_id: Option<String>,
+    /// Result of the most recent health check, if checks are configured
+    health: Option<HealthCheckStatus>,
+    /// Docker state of the running deployment's container, e.g. `running` or `exited`.
+    /// `missing` if the container is gone; null if never deployed or Docker is unavailable
+    container_state: Option<String>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct ListApplicationsQuery {
+    #[serde(default)]
+    include_archived: bool,
+    limit: Option<i64>,
+    #[serde(default)]
+    offset: i64,
+    server_id: Option<String>,
+    /// Only applications in this status, e.g. `running`
+    status: Option<String>,
+    /// Case-insensitive substring of the name
+    name: Option<String>,
+}
+
+const DEFAULT_LIST_LIMIT: i64 = 100;
+const MAX_LIST_LIMIT: i64 = 1000;
+
+/// How long creating or updating an app waits for its git remote to answer
+const GIT_REMOTE_TIMEOUT: Duration = Duration::from_secs(15);
+
+#[derive(Debug, Serialize, ToSchema)]
 struct ListApplicationsResponse {
     applications: Vec<Application>,
+    /// Matching applications across all pages
+    total: i64,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 struct UpdateApplicationRequest {
     name: Option<String>,
     git_url: Option<String>,
     git_branch: Option<String>,
+    git_submodules: Option<bool>,
     build_strategy: Option<BuildStrategy>,
     dockerfile_path: Option<String>,
+    /// Stage to build; an empty string goes back to the final stage
+    docker_target: Option<String>,
+    /// Prebuilt image to run; an empty string goes back to building from git
+    image: Option<String>,
     port: Option<u16>,
+    ports: Option<Vec<PortMapping>>,
     auto_deploy: Option<bool>,
+    restart_policy: Option<String>,
+    stop_grace_seconds: Option<u32>,
+    /// Admin only; an empty string removes it
+    pre_build_cmd: Option<String>,
+    /// An empty string removes it
+    post_deploy_cmd: Option<String>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 struct EnvVarRequest {
     key: String,
     value: String,
+    /// `build`, `runtime` or `both`; new variables default to `both`,
+    /// updates keep the current scope
+    scope: Option<EnvVarScope>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 struct EnvVarResponse {
     key: String,
     value: String, // Decrypted value
+    scope: EnvVarScope,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 struct ListEnvVarsResponse {
     env_vars: Vec<EnvVarResponse>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
+struct ImportEnvVarsResponse {
+    imported: usize,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+struct VolumeRequest {
+    volume_name: String,
+    container_path: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+struct ListVolumesResponse {
+    volumes: Vec<ApplicationVolume>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
 struct DeployKeyResponse {
     public_key: String,
     created_at: String,
 }
 
+#[derive(Debug, Serialize, ToSchema)]
+struct DeployKeyExportResponse {
+    warning: &'static str,
+    public_key: String,
+    /// OpenSSH or PEM private key, unencrypted
+    private_key: String,
+    created_at: String,
+}
+
+const DEPLOY_KEY_EXPORT_WARNING: &str = "This is the unencrypted private key. Anyone holding it can \
+    read every repository the key is registered with. Store it encrypted, never commit or share it, \
+    and generate a new key if it may have leaked.";
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct GenerateDeployKeyQuery {
+    /// `ed25519` (default) or `rsa4096`
+    key_type: Option<String>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+struct RegistryRequest {
+    image: String,
+    username: Option<String>,
+    password: Option<String>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+struct ScheduleRequest {
+    /// Five-field cron expression, evaluated in UTC
+    cron: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+struct RegistryResponse {
+    registry: ApplicationRegistry,
+}
+
+#[derive(Debug, Serialize, PartialEq, ToSchema)]
+struct ConfigProblem {
+    field: String,
+    message: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+struct ValidateApplicationResponse {
+    valid: bool,
+    problems: Vec<ConfigProblem>,
+}
+
 // ===== Handlers =====
 
+/// Applications matching the filters, newest first, one page at a time
+#[utoipa::path(
+    get,
+    path = "/applications",
+    tag = "applications",
+    params(ListApplicationsQuery),
+    responses(
+        (status = 200, body = ListApplicationsResponse),
+        (status = 400, description = "Unknown status filter", body = ErrorBody),
+    )
+)]
 async fn list_applications(
     State(state): State<SharedState>,
     headers: HeaderMap,
-) -> Result<Json<ListApplicationsResponse>, (StatusCode, String)> {
+    Query(query): Query<ListApplicationsQuery>,
+) -> Result<Json<ListApplicationsResponse>, ApiError> {
     extract_user_id(&headers, &state.config.auth.jwt_secret)?;
 
+    let filter = ApplicationFilter {
+        include_archived: query.include_archived,
+        server_id: query.server_id.as_deref(),
+        status: query.status.as_deref().map(parse_app_status).transpose()?,
+        name_query: query.name.as_deref().filter(|n| !n.is_empty()),
+    };
+    let limit = query.limit.unwrap_or(DEFAULT_LIST_LIMIT).clamp(1, MAX_LIST_LIMIT);
+
     let repo = ApplicationRepository::new(state.db.clone());
-    let applications = repo
-        .list()
+    let (applications, total) = repo
+        .list_paged(&filter, limit, query.offset.max(0))
         .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        .map_err(ApiError::internal)?;
+
+    Ok(Json(ListApplicationsResponse { applications, total }))
+}
 
-    Ok(Json(ListApplicationsResponse { applications }))
+fn parse_app_status(value: &str) -> Result<AppStatus, ApiError> {
+    AppStatus::ALL
+        .into_iter()
+        .find(|s| s.as_str() == value)
+        .ok_or_else(|| {
+            let valid: Vec<&str> = AppStatus::ALL.iter().map(|s| s.as_str()).collect();
+            ApiError::new(
+                StatusCode::BAD_REQUEST,
+                format!("Invalid status \"{}\", expected one of: {}", value, valid.join(", ")),
+            )
+        })
 }
 
+#[utoipa::path(
+    post,
+    path = "/applications",
+    tag = "applications",
+    request_body = CreateApplicationRequest,
+    responses(
+        (status = 201, body = ApplicationResponse),
+        (status = 400, description = "Invalid input", body = ErrorBody),
+        (status = 403, description = "Setting pre_build_cmd needs the admin role", body = ErrorBody),
+        (status = 422, description = "Git remote unreachable or branch missing", body = ErrorBody),
+    )
+)]
 async fn create_application(
     State(state): State<SharedState>,
     headers: HeaderMap,
     Json(req): Json<CreateApplicationRequest>,
-) -> Result<(StatusCode, Json<ApplicationResponse>), (StatusCode, String)> {
+) -> Result<(StatusCode, Json<ApplicationResponse>), ApiError> {
     extract_user_id(&headers, &state.config.auth.jwt_secret)?;
 
     validation::required(&req.name, "Name", 100)?;
@@ -122,23 +368,56 @@ async fn create_application(
     if let Some(p) = req.port {
         validation::port(p)?;
     }
+    for mapping in &req.ports {
+        validation::port_mapping(mapping)?;
+    }
+    validation::restart_policy(&req.restart_policy)?;
+    if let Some(seconds) = req.stop_grace_seconds {
+        validation::stop_grace_seconds(seconds)?;
+    }
+    if let Some(ref target) = req.docker_target {
+        validation::docker_target(target)?;
+    }
+    if let Some(ref image) = req.image {
+        validation::image_reference(image)?;
+    }
+    if let Some(ref cmd) = req.pre_build_cmd {
+        validation::hook_command(cmd, "pre_build_cmd")?;
+        // Runs any command against the checkout, in a container of the server-wide PLOYER_PRE_BUILD_IMAGE
+        require_admin(&headers, &state.config.auth.jwt_secret)?;
+    }
+    if let Some(ref cmd) = req.post_deploy_cmd {
+        validation::hook_command(cmd, "post_deploy_cmd")?;
+    }
+    // Prebuilt images are never cloned
+    if let (Some(url), None) = (&req.git_url, &req.image) {
+        check_git_remote(&state, None, url, &req.git_branch).await?;
+    }
 
     let repo = ApplicationRepository::new(state.db.clone());
 
     // Create application
     let app = repo
-        .create(
-            &req.name,
-            &req.server_id,
-            req.git_url.as_deref(),
-            &req.git_branch,
-            req.build_strategy,
-            req.dockerfile_path.as_deref(),
-            req.port,
-            req.auto_deploy,
-        )
-        .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        .create(&NewApplication {
+            name: &req.name,
+            server_id: &req.server_id,
+            git_url: req.git_url.as_deref(),
+            git_branch: &req.git_branch,
+            git_submodules: req.git_submodules,
+            build_strategy: req.build_strategy,
+            dockerfile_path: req.dockerfile_path.as_deref(),
+            docker_target: req.docker_target.as_deref(),
+            image: req.image.as_deref(),
+            port: req.port,
+            ports: &req.ports,
+            auto_deploy: req.auto_deploy,
+            restart_policy: &req.restart_policy,
+            stop_grace_seconds: req.stop_grace_seconds,
+            pre_build_cmd: req.pre_build_cmd.as_deref(),
+            post_deploy_cmd: req.post_deploy_cmd.as_deref(),
+        })
+        .await
+        .map_err(ApiError::internal)?;
 
     // Add environment variables if provided
     if let Some(env_vars) = req.env_vars {
@@ -147,44 +426,128 @@ async fn create_application(
 
         for (key, value) in env_vars {
             let encrypted = crypto::encrypt(&value, &secret_key)
-                .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, format!("Encryption failed: {}", e)))?;
+                .map_err(|e| ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, format!("Encryption failed: {}", e)))?;
 
             env_repo
-                .create(&app.id, &key, &encrypted)
+                .create(&app.id, &key, &encrypted, EnvVarScope::Both)
                 .await
-                .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+                .map_err(ApiError::internal)?;
         }
     }
 
-    // Note: Deploy key generation is now on-demand via POST /applications/:id/deploy-key
-    // This avoids blocking application creation with expensive RSA 4096 key generation
+    // Ed25519 keys are near-instant, so git apps get one straight away
+    if app.git_url.is_some() {
+        store_new_deploy_key(&state, &app.id, DeployKeyType::Ed25519).await?;
+    }
 
     Ok((StatusCode::CREATED, Json(ApplicationResponse { application: app })))
 }
 
+#[utoipa::path(
+    get,
+    path = "/applications/{id}",
+    tag = "applications",
+    params(("id" = String, Path, description = "Application ID")),
+    responses(
+        (status = 200, body = ApplicationResponse),
+        (status = 404, description = "Application not found", body = ErrorBody),
+    )
+)]
 async fn get_application(
     State(state): State<SharedState>,
     headers: HeaderMap,
     Path(id): Path<String>,
-) -> Result<Json<ApplicationResponse>, (StatusCode, String)> {
+) -> Result<Json<ApplicationResponse>, ApiError> {
     extract_user_id(&headers, &state.config.auth.jwt_secret)?;
 
     let repo = ApplicationRepository::new(state.db.clone());
     let app = repo
         .find_by_id(&id)
         .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
-        .ok_or_else(|| (StatusCode::NOT_FOUND, "Application not found".to_string()))?;
+        .map_err(ApiError::internal)?
+        .ok_or_else(|| ApiError::new(StatusCode::NOT_FOUND, "Application not found"))?;
 
     Ok(Json(ApplicationResponse { application: app }))
 }
 
+#[utoipa::path(
+    get,
+    path = "/applications/{id}/status",
+    tag = "applications",
+    params(("id" = String, Path, description = "Application ID")),
+    responses(
+        (status = 200, body = ApplicationStatusResponse),
+        (status = 404, description = "Application not found", body = ErrorBody),
+    )
+)]
+async fn get_application_status(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> Result<Json<ApplicationStatusResponse>, ApiError> {
+    extract_user_id(&headers, &state.config.auth.jwt_secret)?;
+
+    let app = ApplicationRepository::new(state.db.clone())
+        .find_by_id(&id)
+        .await
+        .map_err(ApiError::internal)?
+        .ok_or_else(|| ApiError::new(StatusCode::NOT_FOUND, "Application not found"))?;
+
+    let deployments = DeploymentRepository::new(state.db.clone());
+    let latest = deployments.get_latest(&id).await.map_err(ApiError::internal)?;
+    let running = deployments.get_latest_running(&id).await.map_err(ApiError::internal)?;
+
+    let health = HealthCheckRepository::new(state.db.clone())
+        .get_latest_status(&id)
+        .await
+        .map_err(ApiError::internal)?;
+
+    // Cached inspect keeps this cheap enough to poll for every card
+    let container_state = match (&state.docker, running.and_then(|d| d.container_id)) {
+        (Some(docker), Some(container_id)) => match docker.inspect_container_cached(&container_id).await {
+            Ok(inspect) => inspect
+                .state
+                .and_then(|s| s.status)
+                .map(|status| status.to_string()),
+            Err(DockerError::NotFound(_)) => Some("missing".to_string()),
+            Err(e) => {
+                tracing::warn!("Failed to inspect container {}: {}", container_id, e);
+                None
+            }
+        },
+        _ => None,
+    };
+
+    Ok(Json(ApplicationStatusResponse {
+        status: app.status,
+        latest_deployment_status: latest.as_ref().map(|d| d.status.clone()),
+        latest_deployment_id: latest.map(|d| d.id),
+        health,
+        container_state,
+    }))
+}
+
+/// Update the given fields, leaving the rest unchanged
+#[utoipa::path(
+    put,
+    path = "/applications/{id}",
+    tag = "applications",
+    params(("id" = String, Path, description = "Application ID")),
+    request_body = UpdateApplicationRequest,
+    responses(
+        (status = 200, body = ApplicationResponse),
+        (status = 400, description = "Invalid input", body = ErrorBody),
+        (status = 403, description = "Setting pre_build_cmd needs the admin role", body = ErrorBody),
+        (status = 404, description = "Application not found", body = ErrorBody),
+        (status = 422, description = "Git remote unreachable or branch missing", body = ErrorBody),
+    )
+)]
 async fn update_application(
     State(state): State<SharedState>,
     headers: HeaderMap,
     Path(id): Path<String>,
     Json(req): Json<UpdateApplicationRequest>,
-) -> Result<Json<ApplicationResponse>, (StatusCode, String)> {
+) -> Result<Json<ApplicationResponse>, ApiError> {
     extract_user_id(&headers, &state.config.auth.jwt_secret)?;
 
     if let Some(ref name) = req.name {
@@ -196,6 +559,30 @@ async fn update_application(
     if let Some(p) = req.port {
         validation::port(p)?;
     }
+    for mapping in req.ports.iter().flatten() {
+        validation::port_mapping(mapping)?;
+    }
+    if let Some(ref policy) = req.restart_policy {
+        validation::restart_policy(policy)?;
+    }
+    if let Some(seconds) = req.stop_grace_seconds {
+        validation::stop_grace_seconds(seconds)?;
+    }
+    if let Some(target) = req.docker_target.as_deref().filter(|t| !t.is_empty()) {
+        validation::docker_target(target)?;
+    }
+    if let Some(image) = req.image.as_deref().filter(|i| !i.is_empty()) {
+        validation::image_reference(image)?;
+    }
+    if let Some(ref cmd) = req.pre_build_cmd {
+        if !cmd.is_empty() {
+            validation::hook_command(cmd, "pre_build_cmd")?;
+        }
+        require_admin(&headers, &state.config.auth.jwt_secret)?;
+    }
+    if let Some(cmd) = req.post_deploy_cmd.as_deref().filter(|c| !c.is_empty()) {
+        validation::hook_command(cmd, "post_deploy_cmd")?;
+    }
 
     let repo = ApplicationRepository::new(state.db.clone());
 
@@ -203,8 +590,8 @@ async fn update_application(
     let existing = repo
         .find_by_id(&id)
         .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
-        .ok_or_else(|| (StatusCode::NOT_FOUND, "Application not found".to_string()))?;
+        .map_err(ApiError::internal)?
+        .ok_or_else(|| ApiError::new(StatusCode::NOT_FOUND, "Application not found"))?;
 
     // Use new values or keep existing
     let name = req.name.as_deref().unwrap_or(&existing.name);
@@ -213,45 +600,551 @@ async fn update_application(
     let build_strategy = req.build_strategy.unwrap_or(existing.build_strategy);
     let dockerfile_path = req.dockerfile_path.as_deref().or(existing.dockerfile_path.as_deref());
     let port = req.port.or(existing.port);
+    let ports = req.ports.as_deref().unwrap_or(&existing.ports);
     let auto_deploy = req.auto_deploy.unwrap_or(existing.auto_deploy);
+    let restart_policy = req.restart_policy.as_deref().unwrap_or(&existing.restart_policy);
+
+    let remote_changed = req.git_url.is_some() || req.git_branch.is_some();
+    let image = match &req.image {
+        Some(image) => Some(image.as_str()).filter(|i| !i.is_empty()),
+        None => existing.image.as_deref(),
+    };
+    if let (true, Some(url), None) = (remote_changed, git_url, image) {
+        check_git_remote(&state, Some(&id), url, git_branch).await?;
+    }
 
-    let app = repo
-        .update(&id, name, git_url, git_branch, build_strategy, dockerfile_path, port, auto_deploy)
+    let mut app = repo
+        .update(&id, name, git_url, git_branch, build_strategy, dockerfile_path, port, ports, auto_deploy, restart_policy)
         .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        .map_err(ApiError::internal)?;
+
+    if let Some(enabled) = req.git_submodules {
+        repo.set_git_submodules(&id, enabled)
+            .await
+            .map_err(ApiError::internal)?;
+        app.git_submodules = enabled;
+    }
+    if let Some(image) = req.image {
+        let image = Some(image).filter(|i| !i.is_empty());
+        repo.set_image(&id, image.as_deref())
+            .await
+            .map_err(ApiError::internal)?;
+        app.image = image;
+    }
+    if let Some(target) = req.docker_target {
+        let target = Some(target).filter(|t| !t.is_empty());
+        repo.set_docker_target(&id, target.as_deref())
+            .await
+            .map_err(ApiError::internal)?;
+        app.docker_target = target;
+    }
+    if let Some(seconds) = req.stop_grace_seconds {
+        repo.set_stop_grace_seconds(&id, seconds)
+            .await
+            .map_err(ApiError::internal)?;
+        app.stop_grace_seconds = seconds;
+    }
+    if let Some(cmd) = req.pre_build_cmd {
+        let cmd = Some(cmd).filter(|c| !c.is_empty());
+        repo.set_pre_build_cmd(&id, cmd.as_deref())
+            .await
+            .map_err(ApiError::internal)?;
+        app.pre_build_cmd = cmd;
+    }
+    if let Some(cmd) = req.post_deploy_cmd {
+        let cmd = Some(cmd).filter(|c| !c.is_empty());
+        repo.set_post_deploy_cmd(&id, cmd.as_deref())
+            .await
+            .map_err(ApiError::internal)?;
+        app.post_deploy_cmd = cmd;
+    }
 
     Ok(Json(ApplicationResponse { application: app }))
 }
 
+/// Permanently delete an application and its history (admin only; prefer archiving)
+#[utoipa::path(
+    delete,
+    path = "/applications/{id}",
+    tag = "applications",
+    params(("id" = String, Path, description = "Application ID")),
+    responses(
+        (status = 204, description = "Application deleted"),
+        (status = 403, description = "Not an admin", body = ErrorBody),
+        (status = 404, description = "Application not found", body = ErrorBody),
+    )
+)]
 async fn delete_application(
     State(state): State<SharedState>,
     headers: HeaderMap,
+    client_ip: ClientIp,
     Path(id): Path<String>,
-) -> Result<StatusCode, (StatusCode, String)> {
-    extract_user_id(&headers, &state.config.auth.jwt_secret)?;
+) -> Result<StatusCode, ApiError> {
+    let user_id = require_admin(&headers, &state.config.auth.jwt_secret)?;
 
     let repo = ApplicationRepository::new(state.db.clone());
+    let app = repo
+        .find_by_id(&id)
+        .await
+        .map_err(ApiError::internal)?
+        .ok_or_else(|| ApiError::new(StatusCode::NOT_FOUND, "Application not found"))?;
+
+    // Cleanup below is best-effort: a failure is logged and the row is still
+    // deleted, so an unreachable Docker or Caddy can't leave the app undeletable
+    if let Some(ref docker) = state.docker {
+        let filters = ContainerFilters {
+            label: Some(format!("{}={}", LABEL_APP_ID, id)),
+            ..Default::default()
+        };
+        match docker.list_containers(true, &filters).await {
+            Ok(containers) => {
+                for container in containers {
+                    if let Err(e) = docker.stop_container(&container.id, None).await {
+                        tracing::debug!("Failed to stop container {}: {}", container.name, e);
+                    }
+                    if let Err(e) = docker.remove_container(&container.id, true).await {
+                        tracing::warn!("Failed to remove container {}: {}", container.name, e);
+                    }
+                }
+            }
+            Err(e) => tracing::warn!("Failed to list containers for application {}: {}", id, e),
+        }
+    }
+
+    let domain_repo = DomainRepository::new(state.db.clone());
+    match domain_repo.list_by_application(&id).await {
+        Ok(domains) => {
+            for domain in domains {
+                if let Err(e) = state.caddy.remove_route(&domain.domain).await {
+                    tracing::warn!("Failed to remove Caddy route for {}: {}", domain.domain, e);
+                }
+                if let Err(e) = domain_repo.delete_by_domain(&domain.domain).await {
+                    tracing::warn!("Failed to delete domain {}: {}", domain.domain, e);
+                }
+            }
+        }
+        Err(e) => tracing::warn!("Failed to list domains for application {}: {}", id, e),
+    }
+
+    // Drop the app's network unless something (e.g. a sidecar) is still attached
+    if let (Some(docker), Some(network_name)) = (&state.docker, app.network_name) {
+        if let Ok(network) = docker.inspect_network(&network_name).await {
+            if network.containers.is_empty() {
+                if let Err(e) = docker.remove_network(&network.id).await {
+                    tracing::warn!("Failed to remove network {}: {}", network_name, e);
+                }
+            }
+        }
+    }
+
+    if let Some(repo_dir) = state.deployments.repo_cache_path(&id) {
+        let _ = tokio::fs::remove_dir_all(repo_dir).await;
+    }
+
     repo.delete(&id)
         .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        .map_err(ApiError::internal)?;
+
+    audit::record(&state.db, &user_id, AuditAction::ApplicationDelete, &id, &client_ip).await;
 
     Ok(StatusCode::NO_CONTENT)
 }
 
+// ===== Clone =====
+
+/// Create a new application with another's configuration, e.g. for a staging copy.
+/// Deployments, domains, volumes, webhooks and the deploy key are not copied.
+#[utoipa::path(
+    post,
+    path = "/applications/{id}/clone",
+    tag = "applications",
+    params(("id" = String, Path, description = "Application ID")),
+    request_body = CloneApplicationRequest,
+    responses(
+        (status = 201, body = ApplicationResponse),
+        (status = 400, description = "Invalid input", body = ErrorBody),
+        (status = 404, description = "Application not found", body = ErrorBody),
+        (status = 409, description = "Name already taken", body = ErrorBody),
+    )
+)]
+async fn clone_application(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+    Json(req): Json<CloneApplicationRequest>,
+) -> Result<(StatusCode, Json<ApplicationResponse>), ApiError> {
+    extract_user_id(&headers, &state.config.auth.jwt_secret)?;
+
+    let repo = ApplicationRepository::new(state.db.clone());
+    let source = repo
+        .find_by_id(&id)
+        .await
+        .map_err(ApiError::internal)?
+        .ok_or_else(|| ApiError::new(StatusCode::NOT_FOUND, "Application not found"))?;
+
+    // Containers and image tags are named after the app, so names must not collide
+    let existing = repo.list(true).await.map_err(ApiError::internal)?;
+    let taken: Vec<&str> = existing.iter().map(|a| a.name.as_str()).collect();
+    let name = match req.name {
+        Some(name) => {
+            validation::required(&name, "Name", 100)?;
+            if taken.contains(&name.as_str()) {
+                return Err(ApiError::new(
+                    StatusCode::CONFLICT,
+                    format!("An application named '{}' already exists", name),
+                ));
+            }
+            name
+        }
+        None => clone_name(&source.name, &taken),
+    };
+
+    let git_branch = match req.git_branch {
+        Some(branch) => {
+            validation::required(&branch, "Branch", 255)?;
+            if let (Some(url), None) = (&source.git_url, &source.image) {
+                check_git_remote(&state, None, url, &branch).await?;
+            }
+            branch
+        }
+        None => source.git_branch.clone(),
+    };
+
+    // Auto-deploy starts off: the copy has no webhook of its own yet
+    let app = repo
+        .create(&NewApplication {
+            name: &name,
+            server_id: &source.server_id,
+            git_url: source.git_url.as_deref(),
+            git_branch: &git_branch,
+            git_submodules: source.git_submodules,
+            build_strategy: source.build_strategy.clone(),
+            dockerfile_path: source.dockerfile_path.as_deref(),
+            docker_target: source.docker_target.as_deref(),
+            image: source.image.as_deref(),
+            port: source.port,
+            ports: &source.ports,
+            auto_deploy: false,
+            restart_policy: &source.restart_policy,
+            stop_grace_seconds: Some(source.stop_grace_seconds),
+            pre_build_cmd: source.pre_build_cmd.as_deref(),
+            post_deploy_cmd: source.post_deploy_cmd.as_deref(),
+        })
+        .await
+        .map_err(ApiError::internal)?;
+
+    if req.copy_env_vars {
+        let env_repo = EnvVarRepository::new(state.db.clone());
+        let secret_key = state.config.get_secret_key();
+        let vars = env_repo
+            .list_by_application(&source.id)
+            .await
+            .map_err(ApiError::internal)?;
+
+        // Re-encrypted so the copy doesn't share ciphertexts (and nonces) with the source
+        for var in vars {
+            let value = crypto::decrypt(&var.value_encrypted, &secret_key)
+                .map_err(|e| ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, format!("Decryption failed: {}", e)))?;
+            let encrypted = crypto::encrypt(&value, &secret_key)
+                .map_err(|e| ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, format!("Encryption failed: {}", e)))?;
+            env_repo
+                .create(&app.id, &var.key, &encrypted, var.scope)
+                .await
+                .map_err(ApiError::internal)?;
+        }
+    }
+
+    if req.copy_health_check {
+        let health_repo = HealthCheckRepository::new(state.db.clone());
+        if let Some(check) = health_repo.get(&source.id).await.map_err(ApiError::internal)? {
+            health_repo
+                .upsert(&app.id, &check.into())
+                .await
+                .map_err(ApiError::internal)?;
+        }
+    }
+
+    Ok((StatusCode::CREATED, Json(ApplicationResponse { application: app })))
+}
+
+/// `<name>-copy`, or `<name>-copy-2`, `-3`, ... when that's taken
+fn clone_name(name: &str, taken: &[&str]) -> String {
+    let base = format!("{}-copy", name);
+    std::iter::once(base.clone())
+        .chain((2..).map(|n| format!("{}-{}", base, n)))
+        .find(|candidate| !taken.contains(&candidate.as_str()))
+        .unwrap()
+}
+
+// ===== Archive =====
+
+/// Hide the application and remove its container, keeping its history
+#[utoipa::path(
+    post,
+    path = "/applications/{id}/archive",
+    tag = "applications",
+    params(("id" = String, Path, description = "Application ID")),
+    responses(
+        (status = 200, body = ApplicationResponse),
+        (status = 404, description = "Application not found", body = ErrorBody),
+        (status = 409, description = "Already archived", body = ErrorBody),
+    )
+)]
+async fn archive_application(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> Result<Json<ApplicationResponse>, ApiError> {
+    extract_user_id(&headers, &state.config.auth.jwt_secret)?;
+
+    let repo = ApplicationRepository::new(state.db.clone());
+    let app = repo
+        .find_by_id(&id)
+        .await
+        .map_err(ApiError::internal)?
+        .ok_or_else(|| ApiError::new(StatusCode::NOT_FOUND, "Application not found"))?;
+
+    if app.archived_at.is_some() {
+        return Err(ApiError::new(StatusCode::CONFLICT, "Application is already archived"));
+    }
+
+    // Tear down the running container; deployments and logs stay in the database
+    let deployment_repo = DeploymentRepository::new(state.db.clone());
+    let latest = deployment_repo
+        .get_latest_running(&id)
+        .await
+        .map_err(ApiError::internal)?;
+
+    if let Some(deployment) = latest {
+        if let (Some(docker), Some(container_id)) = (&state.docker, &deployment.container_id) {
+            let _ = docker.stop_container(container_id, Some(app.stop_grace_seconds as i64)).await;
+            match docker.remove_container(container_id, true).await {
+                Ok(()) | Err(DockerError::NotFound(_)) => {}
+                Err(e) => return Err(ApiError::internal(e)),
+            }
+        }
+
+        // Cancelled so the reconciler doesn't report the missing container
+        deployment_repo
+            .update_status(&deployment.id, DeploymentStatus::Cancelled)
+            .await
+            .map_err(ApiError::internal)?;
+    }
+
+    repo.archive(&id)
+        .await
+        .map_err(ApiError::internal)?;
+
+    let app = repo
+        .find_by_id(&id)
+        .await
+        .map_err(ApiError::internal)?
+        .ok_or_else(|| ApiError::new(StatusCode::NOT_FOUND, "Application not found"))?;
+
+    Ok(Json(ApplicationResponse { application: app }))
+}
+
+#[utoipa::path(
+    post,
+    path = "/applications/{id}/unarchive",
+    tag = "applications",
+    params(("id" = String, Path, description = "Application ID")),
+    responses(
+        (status = 200, body = ApplicationResponse),
+        (status = 404, description = "Application not found", body = ErrorBody),
+        (status = 409, description = "Not archived", body = ErrorBody),
+    )
+)]
+async fn unarchive_application(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> Result<Json<ApplicationResponse>, ApiError> {
+    extract_user_id(&headers, &state.config.auth.jwt_secret)?;
+
+    let repo = ApplicationRepository::new(state.db.clone());
+    let app = repo
+        .find_by_id(&id)
+        .await
+        .map_err(ApiError::internal)?
+        .ok_or_else(|| ApiError::new(StatusCode::NOT_FOUND, "Application not found"))?;
+
+    if app.archived_at.is_none() {
+        return Err(ApiError::new(StatusCode::CONFLICT, "Application is not archived"));
+    }
+
+    // Stays stopped until the next deploy
+    repo.unarchive(&id)
+        .await
+        .map_err(ApiError::internal)?;
+
+    let app = repo
+        .find_by_id(&id)
+        .await
+        .map_err(ApiError::internal)?
+        .ok_or_else(|| ApiError::new(StatusCode::NOT_FOUND, "Application not found"))?;
+
+    Ok(Json(ApplicationResponse { application: app }))
+}
+
+// ===== Lifecycle =====
+
+#[derive(Clone, Copy)]
+enum LifecycleAction {
+    Start,
+    Stop,
+    Restart,
+}
+
+/// Start the container of the app's latest deployment
+#[utoipa::path(
+    post,
+    path = "/applications/{id}/start",
+    tag = "applications",
+    params(("id" = String, Path, description = "Application ID")),
+    responses(
+        (status = 200, body = ApplicationResponse),
+        (status = 404, description = "Application not found or never deployed", body = ErrorBody),
+        (status = 409, description = "Already running", body = ErrorBody),
+    )
+)]
+async fn start_application(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> Result<Json<ApplicationResponse>, ApiError> {
+    extract_user_id(&headers, &state.config.auth.jwt_secret)?;
+    apply_lifecycle_action(&state, &id, LifecycleAction::Start).await
+}
+
+/// Stop the container of the app's latest deployment
+#[utoipa::path(
+    post,
+    path = "/applications/{id}/stop",
+    tag = "applications",
+    params(("id" = String, Path, description = "Application ID")),
+    responses(
+        (status = 200, body = ApplicationResponse),
+        (status = 404, description = "Application not found or never deployed", body = ErrorBody),
+        (status = 409, description = "Already stopped", body = ErrorBody),
+    )
+)]
+async fn stop_application(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> Result<Json<ApplicationResponse>, ApiError> {
+    extract_user_id(&headers, &state.config.auth.jwt_secret)?;
+    apply_lifecycle_action(&state, &id, LifecycleAction::Stop).await
+}
+
+/// Restart the container of the app's latest deployment
+#[utoipa::path(
+    post,
+    path = "/applications/{id}/restart",
+    tag = "applications",
+    params(("id" = String, Path, description = "Application ID")),
+    responses(
+        (status = 200, body = ApplicationResponse),
+        (status = 404, description = "Application not found or never deployed", body = ErrorBody),
+    )
+)]
+async fn restart_application(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> Result<Json<ApplicationResponse>, ApiError> {
+    extract_user_id(&headers, &state.config.auth.jwt_secret)?;
+    apply_lifecycle_action(&state, &id, LifecycleAction::Restart).await
+}
+
+/// Run a lifecycle action against the container of the app's latest running deployment
+async fn apply_lifecycle_action(
+    state: &SharedState,
+    id: &str,
+    action: LifecycleAction,
+) -> Result<Json<ApplicationResponse>, ApiError> {
+    let docker = state
+        .docker
+        .as_ref()
+        .ok_or_else(|| ApiError::new(StatusCode::SERVICE_UNAVAILABLE, "Docker not available"))?;
+
+    let repo = ApplicationRepository::new(state.db.clone());
+    let app = repo
+        .find_by_id(id)
+        .await
+        .map_err(ApiError::internal)?
+        .ok_or_else(|| ApiError::new(StatusCode::NOT_FOUND, "Application not found"))?;
+
+    let container_id = DeploymentRepository::new(state.db.clone())
+        .get_latest_running(id)
+        .await
+        .map_err(ApiError::internal)?
+        .and_then(|d| d.container_id)
+        .ok_or_else(|| ApiError::new(StatusCode::NOT_FOUND, "Application has not been deployed"))?;
+
+    let running = docker
+        .inspect_container(&container_id)
+        .await
+        .map_err(|e| ApiError::docker(e, "Application container"))?
+        .state
+        .and_then(|s| s.running)
+        .unwrap_or(false);
+
+    let result = match action {
+        LifecycleAction::Start if running => {
+            return Err(ApiError::new(StatusCode::CONFLICT, "Application is already running"));
+        }
+        LifecycleAction::Stop if !running => {
+            return Err(ApiError::new(StatusCode::CONFLICT, "Application is already stopped"));
+        }
+        LifecycleAction::Start => docker.start_container(&container_id).await,
+        LifecycleAction::Stop => {
+            docker.stop_container(&container_id, Some(app.stop_grace_seconds as i64)).await
+        }
+        LifecycleAction::Restart => docker.restart_container(&container_id).await,
+    };
+    result.map_err(|e| ApiError::docker(e, "Application container"))?;
+
+    let status = match action {
+        LifecycleAction::Stop => AppStatus::Stopped,
+        LifecycleAction::Start | LifecycleAction::Restart => AppStatus::Running,
+    };
+    repo.update_status(id, status)
+        .await
+        .map_err(ApiError::internal)?;
+
+    let app = repo
+        .find_by_id(id)
+        .await
+        .map_err(ApiError::internal)?
+        .ok_or_else(|| ApiError::new(StatusCode::NOT_FOUND, "Application not found"))?;
+
+    Ok(Json(ApplicationResponse { application: app }))
+}
+
 // ===== Environment Variables =====
 
+/// Environment variables with their decrypted values
+#[utoipa::path(
+    get,
+    path = "/applications/{id}/envs",
+    tag = "applications",
+    params(("id" = String, Path, description = "Application ID")),
+    responses((status = 200, body = ListEnvVarsResponse))
+)]
 async fn list_env_vars(
     State(state): State<SharedState>,
     headers: HeaderMap,
     Path(app_id): Path<String>,
-) -> Result<Json<ListEnvVarsResponse>, (StatusCode, String)> {
+) -> Result<Json<ListEnvVarsResponse>, ApiError> {
     extract_user_id(&headers, &state.config.auth.jwt_secret)?;
 
     let repo = EnvVarRepository::new(state.db.clone());
     let env_vars = repo
         .list_by_application(&app_id)
         .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        .map_err(ApiError::internal)?;
 
     // Decrypt values
     let secret_key = state.config.get_secret_key();
@@ -259,89 +1152,421 @@ async fn list_env_vars(
 
     for var in env_vars {
         let value = crypto::decrypt(&var.value_encrypted, &secret_key)
-            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, format!("Decryption failed: {}", e)))?;
+            .map_err(|e| ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, format!("Decryption failed: {}", e)))?;
 
         decrypted.push(EnvVarResponse {
             key: var.key,
             value,
+            scope: var.scope,
         });
     }
 
     Ok(Json(ListEnvVarsResponse { env_vars: decrypted }))
 }
 
+#[utoipa::path(
+    post,
+    path = "/applications/{id}/envs",
+    tag = "applications",
+    params(("id" = String, Path, description = "Application ID")),
+    request_body = EnvVarRequest,
+    responses(
+        (status = 201, description = "Variable added"),
+        (status = 400, description = "Invalid input", body = ErrorBody),
+    )
+)]
 async fn add_env_var(
     State(state): State<SharedState>,
     headers: HeaderMap,
+    client_ip: ClientIp,
     Path(app_id): Path<String>,
     Json(req): Json<EnvVarRequest>,
-) -> Result<StatusCode, (StatusCode, String)> {
-    extract_user_id(&headers, &state.config.auth.jwt_secret)?;
+) -> Result<StatusCode, ApiError> {
+    let user_id = extract_user_id(&headers, &state.config.auth.jwt_secret)?;
 
     validation::env_key(&req.key)?;
 
     let secret_key = state.config.get_secret_key();
     let encrypted = crypto::encrypt(&req.value, &secret_key)
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, format!("Encryption failed: {}", e)))?;
+        .map_err(|e| ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, format!("Encryption failed: {}", e)))?;
 
     let repo = EnvVarRepository::new(state.db.clone());
-    repo.create(&app_id, &req.key, &encrypted)
+    repo.create(&app_id, &req.key, &encrypted, req.scope.unwrap_or_default())
         .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        .map_err(ApiError::internal)?;
+
+    let target = format!("{}/{}", app_id, req.key);
+    audit::record(&state.db, &user_id, AuditAction::EnvVarCreate, &target, &client_ip).await;
 
     Ok(StatusCode::CREATED)
 }
 
+#[utoipa::path(
+    put,
+    path = "/applications/{id}/envs/{key}",
+    tag = "applications",
+    params(("id" = String, Path, description = "Application ID"), ("key" = String, Path, description = "Variable name")),
+    request_body = EnvVarRequest,
+    responses(
+        (status = 204, description = "Variable updated"),
+        (status = 404, description = "Environment variable not found", body = ErrorBody),
+    )
+)]
 async fn update_env_var(
     State(state): State<SharedState>,
     headers: HeaderMap,
+    client_ip: ClientIp,
     Path((app_id, key)): Path<(String, String)>,
     Json(req): Json<EnvVarRequest>,
-) -> Result<StatusCode, (StatusCode, String)> {
-    extract_user_id(&headers, &state.config.auth.jwt_secret)?;
+) -> Result<StatusCode, ApiError> {
+    let user_id = extract_user_id(&headers, &state.config.auth.jwt_secret)?;
 
     let secret_key = state.config.get_secret_key();
     let encrypted = crypto::encrypt(&req.value, &secret_key)
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, format!("Encryption failed: {}", e)))?;
+        .map_err(|e| ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, format!("Encryption failed: {}", e)))?;
 
     let repo = EnvVarRepository::new(state.db.clone());
-    repo.update(&app_id, &key, &encrypted)
+    let existing = repo
+        .find_by_application_and_key(&app_id, &key)
+        .await
+        .map_err(ApiError::internal)?
+        .ok_or_else(|| ApiError::new(StatusCode::NOT_FOUND, "Environment variable not found"))?;
+
+    repo.update(&app_id, &key, &encrypted, req.scope.unwrap_or(existing.scope))
         .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        .map_err(ApiError::internal)?;
+
+    let target = format!("{}/{}", app_id, key);
+    audit::record(&state.db, &user_id, AuditAction::EnvVarUpdate, &target, &client_ip).await;
 
     Ok(StatusCode::NO_CONTENT)
 }
 
+#[utoipa::path(
+    delete,
+    path = "/applications/{id}/envs/{key}",
+    tag = "applications",
+    params(("id" = String, Path, description = "Application ID"), ("key" = String, Path, description = "Variable name")),
+    responses((status = 204, description = "Variable deleted"))
+)]
 async fn delete_env_var(
     State(state): State<SharedState>,
     headers: HeaderMap,
+    client_ip: ClientIp,
     Path((app_id, key)): Path<(String, String)>,
-) -> Result<StatusCode, (StatusCode, String)> {
-    extract_user_id(&headers, &state.config.auth.jwt_secret)?;
+) -> Result<StatusCode, ApiError> {
+    let user_id = extract_user_id(&headers, &state.config.auth.jwt_secret)?;
 
     let repo = EnvVarRepository::new(state.db.clone());
     repo.delete(&app_id, &key)
         .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        .map_err(ApiError::internal)?;
+
+    let target = format!("{}/{}", app_id, key);
+    audit::record(&state.db, &user_id, AuditAction::EnvVarDelete, &target, &client_ip).await;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Upsert many variables from a JSON object or a dotenv-style body
+#[utoipa::path(
+    post,
+    path = "/applications/{id}/envs/bulk",
+    tag = "applications",
+    params(("id" = String, Path, description = "Application ID")),
+    request_body(
+        content = String,
+        content_type = "text/plain",
+        description = "`KEY=value` lines, or a JSON object of strings sent as `application/json`"
+    ),
+    responses(
+        (status = 200, body = ImportEnvVarsResponse),
+        (status = 400, description = "Invalid input", body = ErrorBody),
+        (status = 404, description = "Application not found", body = ErrorBody),
+    )
+)]
+async fn import_env_vars(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    client_ip: ClientIp,
+    Path(app_id): Path<String>,
+    body: String,
+) -> Result<Json<ImportEnvVarsResponse>, ApiError> {
+    let user_id = extract_user_id(&headers, &state.config.auth.jwt_secret)?;
+
+    let is_json = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with("application/json"));
+    let vars: Vec<(String, String)> = if is_json {
+        serde_json::from_str::<HashMap<String, String>>(&body)
+            .map_err(|e| ApiError::new(StatusCode::BAD_REQUEST, format!("Invalid JSON: {}", e)))?
+            .into_iter()
+            .collect()
+    } else {
+        parse_dotenv(&body).map_err(|e| ApiError::new(StatusCode::BAD_REQUEST, e))?
+    };
+
+    // Validate everything before writing anything
+    for (key, _) in &vars {
+        validation::env_key(key)?;
+    }
+
+    ApplicationRepository::new(state.db.clone())
+        .find_by_id(&app_id)
+        .await
+        .map_err(ApiError::internal)?
+        .ok_or_else(|| ApiError::new(StatusCode::NOT_FOUND, "Application not found"))?;
+
+    let secret_key = state.config.get_secret_key();
+    let mut encrypted = Vec::with_capacity(vars.len());
+    for (key, value) in vars {
+        let value = crypto::encrypt(&value, &secret_key)
+            .map_err(|e| ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, format!("Encryption failed: {}", e)))?;
+        encrypted.push((key, value));
+    }
+
+    let repo = EnvVarRepository::new(state.db.clone());
+    repo.upsert_many(&app_id, &encrypted)
+        .await
+        .map_err(ApiError::internal)?;
+
+    audit::record(&state.db, &user_id, AuditAction::EnvVarImport, &app_id, &client_ip).await;
+
+    Ok(Json(ImportEnvVarsResponse { imported: encrypted.len() }))
+}
+
+/// Download all variables as a `.env` file
+#[utoipa::path(
+    get,
+    path = "/applications/{id}/envs/export",
+    tag = "applications",
+    params(("id" = String, Path, description = "Application ID")),
+    responses((status = 200, description = "The variables as a `.env` file", body = String, content_type = "text/plain"))
+)]
+async fn export_env_vars(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    Path(app_id): Path<String>,
+) -> Result<impl IntoResponse, ApiError> {
+    extract_user_id(&headers, &state.config.auth.jwt_secret)?;
+
+    let repo = EnvVarRepository::new(state.db.clone());
+    let env_vars = repo
+        .list_by_application(&app_id)
+        .await
+        .map_err(ApiError::internal)?;
+
+    let secret_key = state.config.get_secret_key();
+    let mut decrypted = Vec::with_capacity(env_vars.len());
+    for var in env_vars {
+        let value = crypto::decrypt(&var.value_encrypted, &secret_key)
+            .map_err(|e| ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, format!("Decryption failed: {}", e)))?;
+        decrypted.push((var.key, value));
+    }
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "text/plain; charset=utf-8"),
+            (header::CONTENT_DISPOSITION, "attachment; filename=\".env\""),
+        ],
+        format_dotenv(&decrypted),
+    ))
+}
+
+/// Parse `KEY=value` lines, skipping blanks and `#` comments. Values may be
+/// single-quoted (literal) or double-quoted (with `\n`, `\"` and `\\` escapes).
+fn parse_dotenv(body: &str) -> Result<Vec<(String, String)>, String> {
+    let mut vars = Vec::new();
+
+    for (i, line) in body.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let line = line.strip_prefix("export ").unwrap_or(line);
+        let (key, raw) = line
+            .split_once('=')
+            .ok_or_else(|| format!("Line {}: expected KEY=value", i + 1))?;
+        let raw = raw.trim();
+
+        let value = if let Some(inner) = raw.strip_prefix('"') {
+            let inner = inner
+                .strip_suffix('"')
+                .ok_or_else(|| format!("Line {}: unterminated double quote", i + 1))?;
+            let mut value = String::with_capacity(inner.len());
+            let mut chars = inner.chars();
+            while let Some(c) = chars.next() {
+                if c != '\\' {
+                    value.push(c);
+                    continue;
+                }
+                match chars.next() {
+                    Some('n') => value.push('\n'),
+                    Some(other) => value.push(other),
+                    None => value.push('\\'),
+                }
+            }
+            value
+        } else if let Some(inner) = raw.strip_prefix('\'') {
+            inner
+                .strip_suffix('\'')
+                .ok_or_else(|| format!("Line {}: unterminated single quote", i + 1))?
+                .to_string()
+        } else {
+            // Unquoted values end at an inline comment
+            match raw.find(" #") {
+                Some(idx) => raw[..idx].trim_end().to_string(),
+                None => raw.to_string(),
+            }
+        };
+
+        vars.push((key.trim().to_string(), value));
+    }
+
+    Ok(vars)
+}
+
+/// Render variables as a `.env` file that `parse_dotenv` reads back unchanged
+fn format_dotenv(vars: &[(String, String)]) -> String {
+    let mut out = String::new();
+    for (key, value) in vars {
+        let escaped = value
+            .replace('\\', "\\\\")
+            .replace('"', "\\\"")
+            .replace('\n', "\\n");
+        out.push_str(&format!("{}=\"{}\"\n", key, escaped));
+    }
+    out
+}
+
+// ===== Volumes =====
+
+#[utoipa::path(
+    get,
+    path = "/applications/{id}/volumes",
+    tag = "applications",
+    params(("id" = String, Path, description = "Application ID")),
+    responses((status = 200, body = ListVolumesResponse))
+)]
+async fn list_volumes(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    Path(app_id): Path<String>,
+) -> Result<Json<ListVolumesResponse>, ApiError> {
+    extract_user_id(&headers, &state.config.auth.jwt_secret)?;
+
+    let repo = VolumeRepository::new(state.db.clone());
+    let volumes = repo
+        .list_by_application(&app_id)
+        .await
+        .map_err(ApiError::internal)?;
+
+    Ok(Json(ListVolumesResponse { volumes }))
+}
+
+#[utoipa::path(
+    post,
+    path = "/applications/{id}/volumes",
+    tag = "applications",
+    params(("id" = String, Path, description = "Application ID")),
+    request_body = VolumeRequest,
+    responses(
+        (status = 201, body = ApplicationVolume),
+        (status = 400, description = "Invalid input", body = ErrorBody),
+        (status = 404, description = "Application not found", body = ErrorBody),
+        (status = 409, description = "A volume is already mounted at this path", body = ErrorBody),
+    )
+)]
+async fn add_volume(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    Path(app_id): Path<String>,
+    Json(req): Json<VolumeRequest>,
+) -> Result<(StatusCode, Json<ApplicationVolume>), ApiError> {
+    extract_user_id(&headers, &state.config.auth.jwt_secret)?;
+
+    validation::volume_name(&req.volume_name)?;
+    validation::container_path(&req.container_path)?;
+
+    ApplicationRepository::new(state.db.clone())
+        .find_by_id(&app_id)
+        .await
+        .map_err(ApiError::internal)?
+        .ok_or_else(|| ApiError::new(StatusCode::NOT_FOUND, "Application not found"))?;
+
+    // Takes effect on the next deploy
+    let repo = VolumeRepository::new(state.db.clone());
+    let volume = repo
+        .create(&app_id, &req.volume_name, &req.container_path)
+        .await
+        .map_err(|e| {
+            if e.to_string().contains("UNIQUE constraint failed") {
+                ApiError::new(StatusCode::CONFLICT, "A volume is already mounted at this path")
+            } else {
+                ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
+            }
+        })?;
+
+    Ok((StatusCode::CREATED, Json(volume)))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/applications/{id}/volumes/{volume_id}",
+    tag = "applications",
+    params(("id" = String, Path, description = "Application ID"), ("volume_id" = String, Path, description = "Volume ID")),
+    responses(
+        (status = 204, description = "Volume detached"),
+        (status = 404, description = "Volume not found", body = ErrorBody),
+    )
+)]
+async fn delete_volume(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    Path((app_id, volume_id)): Path<(String, String)>,
+) -> Result<StatusCode, ApiError> {
+    extract_user_id(&headers, &state.config.auth.jwt_secret)?;
+
+    // Only the mapping is removed; the Docker volume and its data are kept
+    let repo = VolumeRepository::new(state.db.clone());
+    let deleted = repo
+        .delete(&app_id, &volume_id)
+        .await
+        .map_err(ApiError::internal)?;
+
+    if !deleted {
+        return Err(ApiError::new(StatusCode::NOT_FOUND, "Volume not found"));
+    }
 
     Ok(StatusCode::NO_CONTENT)
 }
 
 // ===== Deploy Key =====
 
+#[utoipa::path(
+    get,
+    path = "/applications/{id}/deploy-key",
+    tag = "applications",
+    params(("id" = String, Path, description = "Application ID")),
+    responses(
+        (status = 200, body = DeployKeyResponse),
+        (status = 404, description = "Deploy key not found", body = ErrorBody),
+    )
+)]
 async fn get_deploy_key(
     State(state): State<SharedState>,
     headers: HeaderMap,
     Path(app_id): Path<String>,
-) -> Result<Json<DeployKeyResponse>, (StatusCode, String)> {
+) -> Result<Json<DeployKeyResponse>, ApiError> {
     extract_user_id(&headers, &state.config.auth.jwt_secret)?;
 
     let repo = DeployKeyRepository::new(state.db.clone());
     let key = repo
         .find_by_application(&app_id)
         .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
-        .ok_or_else(|| (StatusCode::NOT_FOUND, "Deploy key not found".to_string()))?;
+        .map_err(ApiError::internal)?
+        .ok_or_else(|| ApiError::new(StatusCode::NOT_FOUND, "Deploy key not found"))?;
 
     Ok(Json(DeployKeyResponse {
         public_key: key.public_key,
@@ -349,37 +1574,587 @@ async fn get_deploy_key(
     }))
 }
 
+/// Generate a new SSH deploy key, replacing any existing one
+#[utoipa::path(
+    post,
+    path = "/applications/{id}/deploy-key",
+    tag = "applications",
+    params(("id" = String, Path, description = "Application ID"), GenerateDeployKeyQuery),
+    responses(
+        (status = 201, body = DeployKeyResponse),
+        (status = 400, description = "Unknown key type", body = ErrorBody),
+    )
+)]
 async fn generate_deploy_key(
     State(state): State<SharedState>,
     headers: HeaderMap,
     Path(app_id): Path<String>,
-) -> Result<(StatusCode, Json<DeployKeyResponse>), (StatusCode, String)> {
+    Query(query): Query<GenerateDeployKeyQuery>,
+) -> Result<(StatusCode, Json<DeployKeyResponse>), ApiError> {
     extract_user_id(&headers, &state.config.auth.jwt_secret)?;
 
-    // Delete existing key if present
-    let key_repo = DeployKeyRepository::new(state.db.clone());
-    let _ = key_repo.delete(&app_id).await; // Ignore error if no key exists
+    let key_type = match query.key_type.as_deref() {
+        None => DeployKeyType::default(),
+        Some(s) => DeployKeyType::parse(s).ok_or_else(|| {
+            ApiError::new(StatusCode::BAD_REQUEST, "key_type must be ed25519 or rsa4096")
+        })?,
+    };
 
-    // Generate new key pair
-    let (public_key, private_key) = GitService::generate_deploy_key()
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, format!("Key generation failed: {}", e)))?;
+    let key = store_new_deploy_key(&state, &app_id, key_type).await?;
 
-    // Encrypt private key
-    let secret_key = state.config.get_secret_key();
-    let encrypted_private = crypto::encrypt(&private_key, &secret_key)
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, format!("Encryption failed: {}", e)))?;
+    Ok((
+        StatusCode::CREATED,
+        Json(DeployKeyResponse {
+            public_key: key.public_key,
+            created_at: key.created_at.to_rfc3339(),
+        }),
+    ))
+}
+
+/// Decrypt the deploy key's private key for backup or disaster recovery (admin only)
+#[utoipa::path(
+    get,
+    path = "/applications/{id}/deploy-key/export",
+    tag = "applications",
+    params(("id" = String, Path, description = "Application ID")),
+    responses(
+        (status = 200, body = DeployKeyExportResponse),
+        (status = 403, description = "Not an admin", body = ErrorBody),
+        (status = 404, description = "Deploy key not found", body = ErrorBody),
+    )
+)]
+async fn export_deploy_key(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    client_ip: ClientIp,
+    Path(app_id): Path<String>,
+) -> Result<impl IntoResponse, ApiError> {
+    let user_id = require_admin(&headers, &state.config.auth.jwt_secret)?;
 
-    // Store in database
-    let key = key_repo
-        .create(&app_id, &public_key, &encrypted_private)
+    let key = DeployKeyRepository::new(state.db.clone())
+        .find_by_application(&app_id)
+        .await
+        .map_err(ApiError::internal)?
+        .ok_or_else(|| ApiError::new(StatusCode::NOT_FOUND, "Deploy key not found"))?;
+
+    let private_key = crypto::decrypt(&key.private_key_encrypted, &state.config.get_secret_key())
+        .map_err(|e| ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, format!("Decryption failed: {}", e)))?;
+
+    // Unlike other audited actions, nothing has happened yet: refuse the export
+    // rather than hand out the key without a record of it
+    AuditRepository::new(state.db.clone())
+        .record(Some(&user_id), AuditAction::DeployKeyExport, &app_id, client_ip.0.as_deref())
         .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        .map_err(ApiError::internal)?;
 
     Ok((
-        StatusCode::CREATED,
-        Json(DeployKeyResponse {
+        [(header::CACHE_CONTROL, "no-store")],
+        Json(DeployKeyExportResponse {
+            warning: DEPLOY_KEY_EXPORT_WARNING,
             public_key: key.public_key,
+            private_key,
             created_at: key.created_at.to_rfc3339(),
         }),
     ))
 }
+
+/// Generate a key pair and store it as the app's deploy key, replacing any existing one
+async fn store_new_deploy_key(
+    state: &SharedState,
+    app_id: &str,
+    key_type: DeployKeyType,
+) -> Result<DeployKey, ApiError> {
+    // Delete existing key if present
+    let key_repo = DeployKeyRepository::new(state.db.clone());
+    let _ = key_repo.delete(app_id).await; // Ignore error if no key exists
+
+    // RSA generation takes seconds; keep it off the async workers
+    let (public_key, private_key) = tokio::task::spawn_blocking(move || GitService::generate_deploy_key(key_type))
+        .await
+        .map_err(ApiError::internal)?
+        .map_err(|e| ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, format!("Key generation failed: {}", e)))?;
+
+    // Encrypt private key
+    let secret_key = state.config.get_secret_key();
+    let encrypted_private = crypto::encrypt(&private_key, &secret_key)
+        .map_err(|e| ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, format!("Encryption failed: {}", e)))?;
+
+    key_repo
+        .create(app_id, &public_key, &encrypted_private)
+        .await
+        .map_err(ApiError::internal)
+}
+
+// ===== Registry =====
+
+#[utoipa::path(
+    get,
+    path = "/applications/{id}/registry",
+    tag = "applications",
+    params(("id" = String, Path, description = "Application ID")),
+    responses(
+        (status = 200, body = RegistryResponse),
+        (status = 404, description = "No registry configured", body = ErrorBody),
+    )
+)]
+async fn get_registry(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    Path(app_id): Path<String>,
+) -> Result<Json<RegistryResponse>, ApiError> {
+    extract_user_id(&headers, &state.config.auth.jwt_secret)?;
+
+    let registry = RegistryRepository::new(state.db.clone())
+        .find_by_application(&app_id)
+        .await
+        .map_err(ApiError::internal)?
+        .ok_or_else(|| ApiError::new(StatusCode::NOT_FOUND, "No registry configured"))?;
+
+    Ok(Json(RegistryResponse { registry }))
+}
+
+#[utoipa::path(
+    put,
+    path = "/applications/{id}/registry",
+    tag = "applications",
+    params(("id" = String, Path, description = "Application ID")),
+    request_body = RegistryRequest,
+    responses(
+        (status = 200, body = RegistryResponse),
+        (status = 400, description = "Invalid input", body = ErrorBody),
+        (status = 404, description = "Application not found", body = ErrorBody),
+    )
+)]
+async fn set_registry(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    Path(app_id): Path<String>,
+    Json(req): Json<RegistryRequest>,
+) -> Result<Json<RegistryResponse>, ApiError> {
+    extract_user_id(&headers, &state.config.auth.jwt_secret)?;
+
+    validation::image_repository(&req.image)?;
+    if req.username.is_some() != req.password.is_some() {
+        return Err(ApiError::new(
+            StatusCode::BAD_REQUEST,
+            "username and password must be given together",
+        ));
+    }
+    if let Some(ref username) = req.username {
+        validation::required(username, "username", 255)?;
+    }
+
+    ApplicationRepository::new(state.db.clone())
+        .find_by_id(&app_id)
+        .await
+        .map_err(ApiError::internal)?
+        .ok_or_else(|| ApiError::new(StatusCode::NOT_FOUND, "Application not found"))?;
+
+    let password_encrypted = match req.password {
+        Some(ref password) => Some(
+            crypto::encrypt(password, &state.config.get_secret_key())
+                .map_err(|e| ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, format!("Encryption failed: {}", e)))?,
+        ),
+        None => None,
+    };
+
+    let registry = RegistryRepository::new(state.db.clone())
+        .upsert(&app_id, &req.image, req.username.as_deref(), password_encrypted.as_deref())
+        .await
+        .map_err(ApiError::internal)?;
+
+    Ok(Json(RegistryResponse { registry }))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/applications/{id}/registry",
+    tag = "applications",
+    params(("id" = String, Path, description = "Application ID")),
+    responses(
+        (status = 204, description = "Registry removed"),
+        (status = 404, description = "No registry configured", body = ErrorBody),
+    )
+)]
+async fn delete_registry(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    Path(app_id): Path<String>,
+) -> Result<StatusCode, ApiError> {
+    extract_user_id(&headers, &state.config.auth.jwt_secret)?;
+
+    let deleted = RegistryRepository::new(state.db.clone())
+        .delete(&app_id)
+        .await
+        .map_err(ApiError::internal)?;
+    if !deleted {
+        return Err(ApiError::new(StatusCode::NOT_FOUND, "No registry configured"));
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// ===== Deploy Schedule =====
+
+#[utoipa::path(
+    put,
+    path = "/applications/{id}/schedule",
+    tag = "applications",
+    params(("id" = String, Path, description = "Application ID")),
+    request_body = ScheduleRequest,
+    responses(
+        (status = 200, body = ApplicationResponse),
+        (status = 400, description = "Invalid input", body = ErrorBody),
+        (status = 404, description = "Application not found", body = ErrorBody),
+    )
+)]
+async fn set_schedule(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    Path(app_id): Path<String>,
+    Json(req): Json<ScheduleRequest>,
+) -> Result<Json<ApplicationResponse>, ApiError> {
+    extract_user_id(&headers, &state.config.auth.jwt_secret)?;
+
+    validation::cron_schedule(&req.cron)?;
+    let cron = req.cron.split_whitespace().collect::<Vec<_>>().join(" ");
+
+    let repo = ApplicationRepository::new(state.db.clone());
+    repo.find_by_id(&app_id)
+        .await
+        .map_err(ApiError::internal)?
+        .ok_or_else(|| ApiError::new(StatusCode::NOT_FOUND, "Application not found"))?;
+
+    repo.set_deploy_schedule(&app_id, Some(&cron))
+        .await
+        .map_err(ApiError::internal)?;
+
+    let application = repo
+        .find_by_id(&app_id)
+        .await
+        .map_err(ApiError::internal)?
+        .ok_or_else(|| ApiError::new(StatusCode::NOT_FOUND, "Application not found"))?;
+
+    Ok(Json(ApplicationResponse { application }))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/applications/{id}/schedule",
+    tag = "applications",
+    params(("id" = String, Path, description = "Application ID")),
+    responses(
+        (status = 204, description = "Schedule cleared"),
+        (status = 404, description = "Application not found or no schedule configured", body = ErrorBody),
+    )
+)]
+async fn clear_schedule(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    Path(app_id): Path<String>,
+) -> Result<StatusCode, ApiError> {
+    extract_user_id(&headers, &state.config.auth.jwt_secret)?;
+
+    let repo = ApplicationRepository::new(state.db.clone());
+    let application = repo
+        .find_by_id(&app_id)
+        .await
+        .map_err(ApiError::internal)?
+        .ok_or_else(|| ApiError::new(StatusCode::NOT_FOUND, "Application not found"))?;
+    if application.deploy_schedule.is_none() {
+        return Err(ApiError::new(StatusCode::NOT_FOUND, "No deploy schedule configured"));
+    }
+
+    repo.set_deploy_schedule(&app_id, None)
+        .await
+        .map_err(ApiError::internal)?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// ===== Validation =====
+
+/// Check the configuration without deploying
+#[utoipa::path(
+    post,
+    path = "/applications/{id}/validate",
+    tag = "applications",
+    params(("id" = String, Path, description = "Application ID")),
+    responses(
+        (status = 200, body = ValidateApplicationResponse),
+        (status = 404, description = "Application not found", body = ErrorBody),
+    )
+)]
+async fn validate_application(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    Path(app_id): Path<String>,
+) -> Result<Json<ValidateApplicationResponse>, ApiError> {
+    extract_user_id(&headers, &state.config.auth.jwt_secret)?;
+
+    let app = ApplicationRepository::new(state.db.clone())
+        .find_by_id(&app_id)
+        .await
+        .map_err(ApiError::internal)?
+        .ok_or_else(|| ApiError::new(StatusCode::NOT_FOUND, "Application not found"))?;
+
+    let mut problems = config_problems(&app);
+
+    // Only touch the remote once the static config is sound; prebuilt images aren't cloned
+    let git_url = app.git_url.clone().filter(|_| app.image.is_none());
+    if let (Some(git_url), true) = (git_url, problems.is_empty()) {
+        let private_key = match DeployKeyRepository::new(state.db.clone())
+            .find_by_application(&app_id)
+            .await
+            .map_err(ApiError::internal)?
+        {
+            Some(key) => Some(
+                crypto::decrypt(&key.private_key_encrypted, &state.config.get_secret_key())
+                    .map_err(|e| ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, format!("Decryption failed: {}", e)))?,
+            ),
+            None => None,
+        };
+
+        let is_ssh = git_url.starts_with("git@") || git_url.starts_with("ssh://");
+        if is_ssh && private_key.is_none() {
+            problems.push(problem("deploy_key", "SSH repositories need a deploy key; generate one first"));
+        } else {
+            let clone_dir = PathBuf::from(format!("/tmp/ployer-validate/{}", uuid::Uuid::new_v4()));
+            problems.extend(
+                repository_problems(&app, git_url, private_key, clone_dir.clone()).await,
+            );
+            let _ = tokio::fs::remove_dir_all(&clone_dir).await;
+        }
+    }
+
+    Ok(Json(ValidateApplicationResponse {
+        valid: problems.is_empty(),
+        problems,
+    }))
+}
+
+/// Reject a git_url whose remote can't be reached or lacks the branch, so a typo
+/// is caught when it's saved rather than after a slow clone at deploy time.
+/// SSH remotes are only checked once the app has a deploy key to connect with.
+async fn check_git_remote(
+    state: &SharedState,
+    app_id: Option<&str>,
+    git_url: &str,
+    git_branch: &str,
+) -> Result<(), ApiError> {
+    let key = match app_id {
+        Some(app_id) => DeployKeyRepository::new(state.db.clone())
+            .find_by_application(app_id)
+            .await
+            .map_err(ApiError::internal)?,
+        None => None,
+    };
+    let private_key = match key {
+        Some(key) => Some(
+            crypto::decrypt(&key.private_key_encrypted, &state.config.get_secret_key())
+                .map_err(|e| ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, format!("Decryption failed: {}", e)))?,
+        ),
+        None => None,
+    };
+
+    let is_ssh = git_url.starts_with("git@") || git_url.starts_with("ssh://");
+    if is_ssh && private_key.is_none() {
+        return Ok(());
+    }
+
+    let git = GitService::new();
+    let check = git.check_reachable(git_url, git_branch, private_key.as_deref());
+    match tokio::time::timeout(GIT_REMOTE_TIMEOUT, check).await {
+        Ok(Ok(())) => Ok(()),
+        Ok(Err(e)) => Err(ApiError::new(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            format!("Could not verify git_url: {}", e),
+        )),
+        Err(_) => Err(ApiError::new(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            format!(
+                "Could not verify git_url: no response from the remote within {} seconds",
+                GIT_REMOTE_TIMEOUT.as_secs()
+            ),
+        )),
+    }
+}
+
+fn problem(field: &str, message: impl Into<String>) -> ConfigProblem {
+    ConfigProblem {
+        field: field.to_string(),
+        message: message.into(),
+    }
+}
+
+/// Checks that need nothing beyond the stored application config
+fn config_problems(app: &Application) -> Vec<ConfigProblem> {
+    let mut problems = Vec::new();
+
+    match (&app.image, &app.git_url) {
+        (Some(image), _) => {
+            if let Err(e) = validation::image_reference(image) {
+                problems.push(problem("image", e.message));
+            }
+        }
+        (None, None) => problems.push(problem("git_url", "Application has no git_url configured")),
+        (None, Some(url)) => {
+            if let Err(e) = validation::git_url(url) {
+                problems.push(problem("git_url", e.message));
+            }
+        }
+    }
+
+    if app.git_branch.trim().is_empty() {
+        problems.push(problem("git_branch", "git_branch is required"));
+    }
+
+    if let Some(path) = &app.dockerfile_path {
+        let escapes_repo = FsPath::new(path)
+            .components()
+            .any(|c| !matches!(c, Component::Normal(_) | Component::CurDir));
+        if escapes_repo {
+            problems.push(problem("dockerfile_path", "dockerfile_path must be a relative path inside the repository"));
+        }
+    }
+
+    if let Err(e) = validation::restart_policy(&app.restart_policy) {
+        problems.push(problem("restart_policy", e.message));
+    }
+
+    for mapping in &app.ports {
+        if let Err(e) = validation::port_mapping(mapping) {
+            problems.push(problem("ports", e.message));
+        }
+    }
+
+    problems
+}
+
+/// Shallow-clone the configured branch and look for the Dockerfile in it
+async fn repository_problems(
+    app: &Application,
+    git_url: String,
+    private_key: Option<String>,
+    clone_dir: PathBuf,
+) -> Vec<ConfigProblem> {
+    let branch = app.git_branch.clone();
+    let dest = clone_dir.clone();
+    let cloned = tokio::task::spawn_blocking(move || {
+        std::fs::create_dir_all(&dest)?;
+        GitService::new().shallow_clone_repo(&git_url, &dest, &branch, private_key.as_deref())
+    })
+    .await;
+
+    match cloned {
+        Ok(Ok(())) => {}
+        Ok(Err(e)) => {
+            return vec![problem(
+                "git_url",
+                format!("Could not clone branch '{}': {}", app.git_branch, e),
+            )]
+        }
+        Err(e) => return vec![problem("git_url", format!("Clone task failed: {}", e))],
+    }
+
+    let dockerfile = app.dockerfile_path.as_deref().unwrap_or("Dockerfile");
+    if clone_dir.join(dockerfile).is_file() {
+        Vec::new()
+    } else {
+        vec![problem(
+            "dockerfile_path",
+            format!("{} not found on branch '{}'", dockerfile, app.git_branch),
+        )]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clone_name_skips_taken_names() {
+        assert_eq!(clone_name("web", &["web"]), "web-copy");
+        assert_eq!(clone_name("web", &["web", "web-copy", "web-copy-2"]), "web-copy-3");
+    }
+
+    #[test]
+    fn test_config_problems() {
+        let now = chrono::Utc::now();
+        let mut app = Application {
+            id: "a1".to_string(),
+            name: "web".to_string(),
+            server_id: "s1".to_string(),
+            git_url: Some("https://github.com/acme/web.git".to_string()),
+            git_branch: "main".to_string(),
+            git_submodules: false,
+            build_strategy: BuildStrategy::Dockerfile,
+            dockerfile_path: Some("docker/Dockerfile.prod".to_string()),
+            docker_target: None,
+            image: None,
+            port: Some(3000),
+            ports: Vec::new(),
+            status: AppStatus::Idle,
+            auto_deploy: false,
+            deploy_schedule: None,
+            restart_policy: "unless-stopped".to_string(),
+            stop_grace_seconds: 10,
+            pre_build_cmd: None,
+            post_deploy_cmd: None,
+            network_name: None,
+            archived_at: None,
+            created_at: now,
+            updated_at: now,
+        };
+        assert!(config_problems(&app).is_empty());
+
+        app.git_url = Some("ftp://example.com/repo".to_string());
+        app.dockerfile_path = Some("../Dockerfile".to_string());
+        app.restart_policy = "sometimes".to_string();
+        let fields: Vec<String> = config_problems(&app).into_iter().map(|p| p.field).collect();
+        assert_eq!(fields, vec!["git_url", "dockerfile_path", "restart_policy"]);
+
+        app.git_url = None;
+        app.dockerfile_path = Some("/etc/Dockerfile".to_string());
+        assert_eq!(config_problems(&app)[0], problem("git_url", "Application has no git_url configured"));
+        assert_eq!(config_problems(&app)[1].field, "dockerfile_path");
+
+        // Prebuilt images don't need a repository
+        app.dockerfile_path = None;
+        app.restart_policy = "always".to_string();
+        app.image = Some("postgres:16".to_string());
+        assert!(config_problems(&app).is_empty());
+        app.image = Some("postgres:".to_string());
+        assert_eq!(config_problems(&app)[0].field, "image");
+    }
+
+    #[test]
+    fn test_parse_dotenv() {
+        let body = "# comment\n\nexport A=1\nB = two words # note\nC=\"line\\nbreak \\\"q\\\"\"\nD='$literal \\n'\nE=\n";
+        let vars = parse_dotenv(body).unwrap();
+
+        assert_eq!(
+            vars,
+            vec![
+                ("A".to_string(), "1".to_string()),
+                ("B".to_string(), "two words".to_string()),
+                ("C".to_string(), "line\nbreak \"q\"".to_string()),
+                ("D".to_string(), "$literal \\n".to_string()),
+                ("E".to_string(), String::new()),
+            ]
+        );
+        assert!(parse_dotenv("NOVALUE").is_err());
+        assert!(parse_dotenv("A=\"open").is_err());
+    }
+
+    #[test]
+    fn test_format_dotenv_round_trips() {
+        let vars = vec![
+            ("URL".to_string(), "postgres://u:p@h/db?x=1#frag".to_string()),
+            ("MULTI".to_string(), "a\nb \"c\" \\d".to_string()),
+            ("EMPTY".to_string(), String::new()),
+        ];
+
+        assert_eq!(parse_dotenv(&format_dotenv(&vars)).unwrap(), vars);
+    }
+}
@@ -0,0 +1,74 @@
+use axum::{
+    extract::{Query, State},
+    http::{HeaderMap, StatusCode},
+    routing::get,
+    Json, Router,
+};
+use serde::{Deserialize, Serialize};
+use ployer_core::models::{AuditAction, AuditEntry};
+use ployer_db::repositories::{AuditFilter, AuditRepository};
+
+use crate::app_state::SharedState;
+use crate::auth::require_admin;
+use crate::error::ApiError;
+
+const DEFAULT_LIST_LIMIT: i64 = 100;
+const MAX_LIST_LIMIT: i64 = 1000;
+
+pub fn router() -> Router<SharedState> {
+    Router::new()
+        .route("/", get(list_audit_entries))
+}
+
+#[derive(Debug, Deserialize)]
+struct ListAuditQuery {
+    user_id: Option<String>,
+    /// Only entries for this action, e.g. `server.delete`
+    action: Option<String>,
+    limit: Option<i64>,
+    #[serde(default)]
+    offset: i64,
+}
+
+#[derive(Debug, Serialize)]
+struct ListAuditResponse {
+    entries: Vec<AuditEntry>,
+    /// Matching entries across all pages
+    total: i64,
+}
+
+/// Audit log, newest first (admin only)
+async fn list_audit_entries(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    Query(query): Query<ListAuditQuery>,
+) -> Result<Json<ListAuditResponse>, ApiError> {
+    require_admin(&headers, &state.config.auth.jwt_secret)?;
+
+    let filter = AuditFilter {
+        user_id: query.user_id.as_deref().filter(|u| !u.is_empty()),
+        action: query.action.as_deref().map(parse_action).transpose()?,
+    };
+    let limit = query.limit.unwrap_or(DEFAULT_LIST_LIMIT).clamp(1, MAX_LIST_LIMIT);
+
+    let repo = AuditRepository::new(state.db.clone());
+    let (entries, total) = repo
+        .list_paged(&filter, limit, query.offset.max(0))
+        .await
+        .map_err(ApiError::internal)?;
+
+    Ok(Json(ListAuditResponse { entries, total }))
+}
+
+fn parse_action(value: &str) -> Result<AuditAction, ApiError> {
+    AuditAction::ALL
+        .into_iter()
+        .find(|a| a.as_str() == value)
+        .ok_or_else(|| {
+            let valid: Vec<&str> = AuditAction::ALL.iter().map(|a| a.as_str()).collect();
+            ApiError::new(
+                StatusCode::BAD_REQUEST,
+                format!("Invalid action \"{}\", expected one of: {}", value, valid.join(", ")),
+            )
+        })
+}
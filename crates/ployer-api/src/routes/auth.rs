@@ -6,68 +6,105 @@ use axum::{
     Json, Router,
 };
 use serde::{Deserialize, Serialize};
-use ployer_core::models::User;
+use ployer_core::models::{User, UserRole};
+use utoipa::{OpenApi, ToSchema};
 
 use ployer_db::repositories::{SettingsRepository, UserRepository};
 
 use crate::app_state::SharedState;
 use crate::auth::{validate_token, AuthService};
 use crate::middleware::validation;
+use crate::error::ApiError;
 
 pub fn router() -> Router<SharedState> {
     Router::new()
         .route("/register", post(register))
         .route("/login", post(login))
+        .route("/refresh", post(refresh))
+        .route("/logout", post(logout))
         .route("/me", get(me))
         .route("/registration-status", get(registration_status))
 }
 
-#[derive(Debug, Serialize)]
+#[derive(OpenApi)]
+#[openapi(
+    paths(register, login, refresh, logout, me, registration_status),
+    components(schemas(
+        RegisterRequest, RegisterResponse, LoginRequest, LoginResponse, RefreshRequest,
+        MeResponse, RegistrationStatusResponse, User, UserRole,
+    )),
+    tags((name = "auth", description = "Accounts and tokens"))
+)]
+pub struct ApiDoc;
+
+#[derive(Debug, Serialize, ToSchema)]
 struct RegistrationStatusResponse {
     allow_registration: bool,
 }
 
+/// Whether new accounts can register
+#[utoipa::path(
+    get,
+    path = "/auth/registration-status",
+    tag = "auth",
+    security(()),
+    responses((status = 200, body = RegistrationStatusResponse))
+)]
 async fn registration_status(
     State(state): State<SharedState>,
-) -> Result<Json<RegistrationStatusResponse>, (StatusCode, String)> {
+) -> Result<Json<RegistrationStatusResponse>, ApiError> {
     let allow = SettingsRepository::new(state.db.clone())
         .allow_registration()
         .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        .map_err(ApiError::internal)?;
     Ok(Json(RegistrationStatusResponse { allow_registration: allow }))
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 struct RegisterRequest {
     email: String,
     password: String,
     name: String,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 struct RegisterResponse {
     user: User,
     token: String,
+    refresh_token: String,
 }
 
+/// Create an account. The first account is always allowed and becomes the admin.
+#[utoipa::path(
+    post,
+    path = "/auth/register",
+    tag = "auth",
+    security(()),
+    request_body = RegisterRequest,
+    responses(
+        (status = 200, body = RegisterResponse),
+        (status = 400, description = "Invalid input or email already registered", body = ErrorBody),
+        (status = 403, description = "Registration is disabled", body = ErrorBody),
+    )
+)]
 async fn register(
     State(state): State<SharedState>,
     Json(req): Json<RegisterRequest>,
-) -> Result<Json<RegisterResponse>, (StatusCode, String)> {
+) -> Result<Json<RegisterResponse>, ApiError> {
     let auth_service = AuthService::new(state.db.clone());
 
     // Check if registration is allowed (always allow if no users exist yet)
     let user_count = UserRepository::new(state.db.clone())
         .count()
         .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        .map_err(ApiError::internal)?;
     if user_count > 0 {
         let allow = SettingsRepository::new(state.db.clone())
             .allow_registration()
             .await
-            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+            .map_err(ApiError::internal)?;
         if !allow {
-            return Err((StatusCode::FORBIDDEN, "Registration is disabled".to_string()));
+            return Err(ApiError::new(StatusCode::FORBIDDEN, "Registration is disabled"));
         }
     }
 
@@ -79,7 +116,7 @@ async fn register(
     let user = auth_service
         .register(&req.email, &req.password, &req.name)
         .await
-        .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?;
+        .map_err(|e| ApiError::new(StatusCode::BAD_REQUEST, e.to_string()))?;
 
     // Generate token
     let token = crate::auth::jwt::generate_token(
@@ -89,27 +126,44 @@ async fn register(
         &state.config.auth.jwt_secret,
         state.config.auth.token_expiry_hours,
     )
-    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    .map_err(ApiError::internal)?;
+
+    let refresh_token = auth_service
+        .issue_refresh_token(&user.id, state.config.auth.refresh_token_expiry_days)
+        .await
+        .map_err(ApiError::internal)?;
 
-    Ok(Json(RegisterResponse { user, token }))
+    Ok(Json(RegisterResponse { user, token, refresh_token }))
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 struct LoginRequest {
     email: String,
     password: String,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 struct LoginResponse {
     user: User,
     token: String,
+    refresh_token: String,
 }
 
+#[utoipa::path(
+    post,
+    path = "/auth/login",
+    tag = "auth",
+    security(()),
+    request_body = LoginRequest,
+    responses(
+        (status = 200, body = LoginResponse),
+        (status = 401, description = "Wrong email or password", body = ErrorBody),
+    )
+)]
 async fn login(
     State(state): State<SharedState>,
     Json(req): Json<LoginRequest>,
-) -> Result<Json<LoginResponse>, (StatusCode, String)> {
+) -> Result<Json<LoginResponse>, ApiError> {
     let auth_service = AuthService::new(state.db.clone());
 
     // Login
@@ -121,40 +175,112 @@ async fn login(
             state.config.auth.token_expiry_hours,
         )
         .await
-        .map_err(|e| (StatusCode::UNAUTHORIZED, e.to_string()))?;
+        .map_err(|e| ApiError::new(StatusCode::UNAUTHORIZED, e.to_string()))?;
+
+    let refresh_token = auth_service
+        .issue_refresh_token(&user.id, state.config.auth.refresh_token_expiry_days)
+        .await
+        .map_err(ApiError::internal)?;
+
+    Ok(Json(LoginResponse { user, token, refresh_token }))
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+struct RefreshRequest {
+    refresh_token: String,
+}
+
+/// Exchange a refresh token for a new access token and refresh token
+#[utoipa::path(
+    post,
+    path = "/auth/refresh",
+    tag = "auth",
+    security(()),
+    request_body = RefreshRequest,
+    responses(
+        (status = 200, body = LoginResponse),
+        (status = 401, description = "Refresh token is invalid, expired or revoked", body = ErrorBody),
+    )
+)]
+async fn refresh(
+    State(state): State<SharedState>,
+    Json(req): Json<RefreshRequest>,
+) -> Result<Json<LoginResponse>, ApiError> {
+    let auth_service = AuthService::new(state.db.clone());
+
+    let (user, token, refresh_token) = auth_service
+        .refresh(
+            &req.refresh_token,
+            &state.config.auth.jwt_secret,
+            state.config.auth.token_expiry_hours,
+            state.config.auth.refresh_token_expiry_days,
+        )
+        .await
+        .map_err(|e| ApiError::new(StatusCode::UNAUTHORIZED, e.to_string()))?;
 
-    Ok(Json(LoginResponse { user, token }))
+    Ok(Json(LoginResponse { user, token, refresh_token }))
 }
 
-#[derive(Debug, Serialize)]
+/// Revoke a refresh token
+#[utoipa::path(
+    post,
+    path = "/auth/logout",
+    tag = "auth",
+    security(()),
+    request_body = RefreshRequest,
+    responses((status = 204, description = "Token revoked"))
+)]
+async fn logout(
+    State(state): State<SharedState>,
+    Json(req): Json<RefreshRequest>,
+) -> Result<StatusCode, ApiError> {
+    AuthService::new(state.db.clone())
+        .logout(&req.refresh_token)
+        .await
+        .map_err(ApiError::internal)?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[derive(Debug, Serialize, ToSchema)]
 struct MeResponse {
     user: User,
 }
 
+/// The signed-in user
+#[utoipa::path(
+    get,
+    path = "/auth/me",
+    tag = "auth",
+    responses(
+        (status = 200, body = MeResponse),
+        (status = 401, description = "Missing, invalid or expired token", body = ErrorBody),
+    )
+)]
 async fn me(
     State(state): State<SharedState>,
     headers: HeaderMap,
-) -> Result<Json<MeResponse>, (StatusCode, String)> {
+) -> Result<Json<MeResponse>, ApiError> {
     // Extract and validate token
     let auth_header = headers
         .get("Authorization")
         .and_then(|h| h.to_str().ok())
-        .ok_or_else(|| (StatusCode::UNAUTHORIZED, "Missing Authorization header".to_string()))?;
+        .ok_or_else(|| ApiError::new(StatusCode::UNAUTHORIZED, "Missing Authorization header"))?;
 
     let token = auth_header
         .strip_prefix("Bearer ")
-        .ok_or_else(|| (StatusCode::UNAUTHORIZED, "Invalid Authorization header format".to_string()))?;
+        .ok_or_else(|| ApiError::new(StatusCode::UNAUTHORIZED, "Invalid Authorization header format"))?;
 
     let claims = validate_token(token, &state.config.auth.jwt_secret)
-        .map_err(|_| (StatusCode::UNAUTHORIZED, "Invalid or expired token".to_string()))?;
+        .map_err(|_| ApiError::new(StatusCode::UNAUTHORIZED, "Invalid or expired token"))?;
 
     // Get user
     let auth_service = AuthService::new(state.db.clone());
     let user = auth_service
         .get_user(&claims.sub)
         .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
-        .ok_or_else(|| (StatusCode::NOT_FOUND, "User not found".to_string()))?;
+        .map_err(ApiError::internal)?
+        .ok_or_else(|| ApiError::new(StatusCode::NOT_FOUND, "User not found"))?;
 
     Ok(Json(MeResponse { user }))
 }
@@ -6,10 +6,16 @@ use axum::{
 };
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use utoipa::{IntoParams, OpenApi, ToSchema};
 
 use crate::app_state::SharedState;
-use crate::auth::extract_user_id;
-use ployer_docker::{ContainerConfig, ContainerInfo, ContainerStats, NetworkInfo, VolumeInfo};
+use crate::auth::{extract_user_id, require_admin};
+use crate::error::ApiError;
+use crate::middleware::validation;
+use ployer_docker::{
+    ContainerConfig, ContainerFilters, ContainerInfo, ContainerStats, DockerClient, FileChange,
+    FileChangeKind, LogFilter, LogOptions, MountInfo, NetworkInfo, PortInfo, VolumeInfo,
+};
 
 pub fn router() -> Router<SharedState> {
     Router::new()
@@ -17,15 +23,19 @@ pub fn router() -> Router<SharedState> {
         .route("/:id", get(get_container).delete(remove_container))
         .route("/:id/start", post(start_container))
         .route("/:id/stop", post(stop_container))
+        .route("/:id/kill", post(kill_container))
         .route("/:id/restart", post(restart_container))
         .route("/:id/logs", get(get_container_logs))
         .route("/:id/stats", get(get_container_stats))
+        .route("/:id/changes", get(get_container_changes))
 }
 
 pub fn networks_router() -> Router<SharedState> {
     Router::new()
         .route("/", get(list_networks).post(create_network))
         .route("/:id", get(get_network).delete(remove_network))
+        .route("/:id/connect", post(connect_network))
+        .route("/:id/disconnect", post(disconnect_network))
 }
 
 pub fn volumes_router() -> Router<SharedState> {
@@ -34,20 +44,48 @@ pub fn volumes_router() -> Router<SharedState> {
         .route("/:name", get(get_volume).delete(remove_volume))
 }
 
+#[derive(OpenApi)]
+#[openapi(
+    paths(
+        list_containers, create_container, get_container, start_container, stop_container,
+        kill_container, restart_container, remove_container, get_container_logs, get_container_stats,
+        get_container_changes,
+        list_networks, create_network, get_network, remove_network, connect_network,
+        disconnect_network, list_volumes, create_volume, get_volume, remove_volume,
+    ),
+    components(schemas(
+        ListContainersResponse, CreateContainerRequest, ContainerResponse, ContainerDetailsResponse, ContainerEnvVar,
+        ContainerLogsResponse, ContainerStatsResponse, ContainerChangesResponse, ListNetworksResponse, CreateNetworkRequest,
+        NetworkResponse, NetworkDetailsResponse, NetworkConnectionRequest, ListDockerVolumesResponse,
+        CreateVolumeRequest, VolumeResponse, ContainerInfo, PortInfo, ContainerStats, NetworkInfo,
+        VolumeInfo, FileChange, FileChangeKind, MountInfo,
+    )),
+    tags(
+        (name = "containers", description = "Docker containers on the local server"),
+        (name = "networks", description = "Docker networks"),
+        (name = "volumes", description = "Docker volumes"),
+    )
+)]
+pub struct ApiDoc;
+
 // ===== Request/Response Types =====
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 struct ListContainersQuery {
     #[serde(default)]
     all: bool,
+    status: Option<String>,
+    label: Option<String>,
+    name: Option<String>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 struct ListContainersResponse {
     containers: Vec<ContainerInfo>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 struct CreateContainerRequest {
     image: String,
     name: Option<String>,
@@ -55,46 +93,136 @@ struct CreateContainerRequest {
     ports: Option<HashMap<String, String>>,
     volumes: Option<HashMap<String, String>>,
     network: Option<String>,
+    restart_policy: Option<String>,
+    labels: Option<HashMap<String, String>>,
     cmd: Option<Vec<String>>,
+    /// Remove the container once it exits, for one-off jobs
+    #[serde(default)]
+    auto_remove: bool,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 struct ContainerResponse {
     container_id: String,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct StopContainerQuery {
+    /// SIGKILL without waiting for a graceful shutdown
+    #[serde(default)]
+    kill: bool,
+    /// Signal to stop with instead of the container's stop signal, e.g. `SIGINT`
+    signal: Option<String>,
+    /// Seconds to wait for the container to exit before SIGKILL; defaults to 10
+    timeout: Option<i64>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct KillContainerQuery {
+    /// Signal to send, e.g. `SIGINT` or `SIGHUP`; defaults to `SIGKILL`
+    signal: Option<String>,
+}
+
+/// Longest grace period a stop may ask for
+const MAX_STOP_TIMEOUT_SECS: i64 = 3600;
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct RemoveContainerQuery {
+    /// Kill and remove a running container instead of refusing
+    #[serde(default)]
+    force: bool,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct GetContainerQuery {
+    /// Also report `size_rw` and `size_root_fs`; slower, as Docker walks the filesystem
+    #[serde(default)]
+    size: bool,
+    /// Show the values of secret-looking env vars; admin only
+    #[serde(default)]
+    reveal: bool,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
 struct ContainerDetailsResponse {
     container: ContainerInfo,
+    /// In the order Docker reports them
+    env: Vec<ContainerEnvVar>,
+    mounts: Vec<MountInfo>,
 }
 
-#[derive(Debug, Deserialize)]
-struct GetLogsQuery {
-    #[serde(default = "default_tail")]
-    tail: usize,
+#[derive(Debug, Serialize, ToSchema)]
+struct ContainerEnvVar {
+    key: String,
+    /// `REDACTED_VALUE` when `redacted` is set
+    value: String,
+    redacted: bool,
+}
+
+const REDACTED_VALUE: &str = "********";
+
+/// Whether an env var's name suggests its value is a credential,
+/// e.g. `API_KEY`, `GITHUB_TOKEN`, `DB_PASSWORD` or `SESSION_SECRET`
+fn is_secret_env_key(key: &str) -> bool {
+    let key = key.to_ascii_uppercase();
+    ["_KEY", "_TOKEN"].iter().any(|suffix| key.ends_with(suffix))
+        || ["KEY", "TOKEN"].contains(&key.as_str())
+        || ["PASSWORD", "PASSWD", "SECRET", "CREDENTIAL"].iter().any(|word| key.contains(word))
 }
 
-fn default_tail() -> usize {
-    100
+#[derive(Debug, Serialize, ToSchema)]
+struct ContainerChangesResponse {
+    changes: Vec<FileChange>,
 }
 
-#[derive(Debug, Serialize)]
+/// `tail` defaults to 100 lines. With `since` and no `tail`, every line since
+/// that timestamp is returned; with both, the last `tail` lines after `since`.
+/// `filter` keeps only lines containing it, or matching it as a regex with
+/// `regex=true`, and is applied before `tail`.
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct GetLogsQuery {
+    tail: Option<usize>,
+    since: Option<i64>,
+    until: Option<i64>,
+    #[serde(default)]
+    timestamps: bool,
+    filter: Option<String>,
+    #[serde(default)]
+    regex: bool,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
 struct ContainerLogsResponse {
     logs: Vec<String>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 struct ContainerStatsResponse {
     stats: ContainerStats,
 }
 
 // ===== Handlers =====
 
+#[utoipa::path(
+    get,
+    path = "/containers",
+    tag = "containers",
+    params(ListContainersQuery),
+    responses(
+        (status = 200, body = ListContainersResponse),
+        (status = 503, description = "Docker not available", body = ErrorBody),
+    )
+)]
 async fn list_containers(
     State(state): State<SharedState>,
     headers: HeaderMap,
     Query(query): Query<ListContainersQuery>,
-) -> Result<Json<ListContainersResponse>, (StatusCode, String)> {
+) -> Result<Json<ListContainersResponse>, ApiError> {
     // Validate auth
     extract_user_id(&headers, &state.config.auth.jwt_secret)?;
 
@@ -102,21 +230,40 @@ async fn list_containers(
     let docker = state
         .docker
         .as_ref()
-        .ok_or_else(|| (StatusCode::SERVICE_UNAVAILABLE, "Docker not available".to_string()))?;
+        .ok_or_else(|| ApiError::new(StatusCode::SERVICE_UNAVAILABLE, "Docker not available"))?;
 
     let containers = docker
-        .list_containers(query.all)
+        .list_containers(
+            query.all,
+            &ContainerFilters {
+                status: query.status,
+                label: query.label,
+                name: query.name,
+                publish: None,
+            },
+        )
         .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        .map_err(ApiError::internal)?;
 
     Ok(Json(ListContainersResponse { containers }))
 }
 
+#[utoipa::path(
+    post,
+    path = "/containers",
+    tag = "containers",
+    request_body = CreateContainerRequest,
+    responses(
+        (status = 201, body = ContainerResponse),
+        (status = 400, description = "Image name is required", body = ErrorBody),
+        (status = 503, description = "Docker not available", body = ErrorBody),
+    )
+)]
 async fn create_container(
     State(state): State<SharedState>,
     headers: HeaderMap,
     Json(req): Json<CreateContainerRequest>,
-) -> Result<(StatusCode, Json<ContainerResponse>), (StatusCode, String)> {
+) -> Result<(StatusCode, Json<ContainerResponse>), ApiError> {
     // Validate auth
     extract_user_id(&headers, &state.config.auth.jwt_secret)?;
 
@@ -124,11 +271,18 @@ async fn create_container(
     let docker = state
         .docker
         .as_ref()
-        .ok_or_else(|| (StatusCode::SERVICE_UNAVAILABLE, "Docker not available".to_string()))?;
+        .ok_or_else(|| ApiError::new(StatusCode::SERVICE_UNAVAILABLE, "Docker not available"))?;
 
     // Validate input
     if req.image.trim().is_empty() {
-        return Err((StatusCode::BAD_REQUEST, "Image name is required".to_string()));
+        return Err(ApiError::new(StatusCode::BAD_REQUEST, "Image name is required"));
+    }
+    // Docker would restart a container it is also meant to remove on exit
+    if req.auto_remove && req.restart_policy.as_deref().is_some_and(|p| p != "no") {
+        return Err(ApiError::new(
+            StatusCode::BAD_REQUEST,
+            "auto_remove can't be combined with a restart policy",
+        ));
     }
 
     let config = ContainerConfig {
@@ -136,15 +290,21 @@ async fn create_container(
         name: req.name,
         env: req.env,
         ports: req.ports,
-        volumes: req.volumes,
+        // The request maps host path -> container path
+        volumes: req
+            .volumes
+            .map(|volumes| volumes.into_iter().map(|(host, container)| (container, host)).collect()),
         network: req.network,
         cmd: req.cmd,
+        restart_policy: req.restart_policy,
+        labels: req.labels,
+        auto_remove: req.auto_remove,
     };
 
     let container_id = docker
         .create_container(config)
         .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        .map_err(ApiError::internal)?;
 
     Ok((
         StatusCode::CREATED,
@@ -152,55 +312,73 @@ async fn create_container(
     ))
 }
 
+#[utoipa::path(
+    get,
+    path = "/containers/{id}",
+    tag = "containers",
+    params(("id" = String, Path, description = "Container ID or name"), GetContainerQuery),
+    responses(
+        (status = 200, body = ContainerDetailsResponse),
+        (status = 403, description = "`reveal=true` without the admin role", body = ErrorBody),
+        (status = 404, description = "Container not found", body = ErrorBody),
+        (status = 503, description = "Docker not available", body = ErrorBody),
+    )
+)]
 async fn get_container(
     State(state): State<SharedState>,
     headers: HeaderMap,
     Path(id): Path<String>,
-) -> Result<Json<ContainerDetailsResponse>, (StatusCode, String)> {
-    // Validate auth
-    extract_user_id(&headers, &state.config.auth.jwt_secret)?;
+    Query(query): Query<GetContainerQuery>,
+) -> Result<Json<ContainerDetailsResponse>, ApiError> {
+    // Validate auth; only admins see secret values
+    if query.reveal {
+        require_admin(&headers, &state.config.auth.jwt_secret)?;
+    } else {
+        extract_user_id(&headers, &state.config.auth.jwt_secret)?;
+    }
 
     // Check if Docker is available
     let docker = state
         .docker
         .as_ref()
-        .ok_or_else(|| (StatusCode::SERVICE_UNAVAILABLE, "Docker not available".to_string()))?;
+        .ok_or_else(|| ApiError::new(StatusCode::SERVICE_UNAVAILABLE, "Docker not available"))?;
 
     let inspect = docker
-        .inspect_container(&id)
+        .inspect_container_sized(&id, query.size)
         .await
-        .map_err(|e| {
-            if e.to_string().contains("No such container") {
-                (StatusCode::NOT_FOUND, "Container not found".to_string())
-            } else {
-                (StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
-            }
-        })?;
-
-    // Convert inspect response to ContainerInfo
-    let state = inspect.state
-        .and_then(|s| s.status)
-        .map(|s| s.to_string())
-        .unwrap_or_else(|| "unknown".to_string());
-
-    let container = ContainerInfo {
-        id: inspect.id.unwrap_or_default(),
-        name: inspect.name.unwrap_or_default().trim_start_matches('/').to_string(),
-        image: inspect.config.and_then(|c| c.image).unwrap_or_default(),
-        state,
-        status: "running".to_string(), // Simplified
-        created: 0, // Would need to parse from inspect.created
-        ports: vec![], // Would need to parse from inspect.network_settings
-    };
-
-    Ok(Json(ContainerDetailsResponse { container }))
+        .map_err(|e| ApiError::docker(e, "Container"))?;
+
+    let env = DockerClient::inspect_env(&inspect)
+        .into_iter()
+        .map(|(key, value)| {
+            let redacted = !query.reveal && is_secret_env_key(&key);
+            let value = if redacted { REDACTED_VALUE.to_string() } else { value };
+            ContainerEnvVar { key, value, redacted }
+        })
+        .collect();
+    let mounts = DockerClient::inspect_mounts(&inspect);
+    let container = DockerClient::inspect_to_info(inspect);
+
+    Ok(Json(ContainerDetailsResponse { container, env, mounts }))
 }
 
+#[utoipa::path(
+    post,
+    path = "/containers/{id}/start",
+    tag = "containers",
+    params(("id" = String, Path, description = "Container ID or name")),
+    responses(
+        (status = 204, description = "Container started"),
+        (status = 404, description = "Container not found", body = ErrorBody),
+        (status = 409, description = "Container already running", body = ErrorBody),
+        (status = 503, description = "Docker not available", body = ErrorBody),
+    )
+)]
 async fn start_container(
     State(state): State<SharedState>,
     headers: HeaderMap,
     Path(id): Path<String>,
-) -> Result<StatusCode, (StatusCode, String)> {
+) -> Result<StatusCode, ApiError> {
     // Validate auth
     extract_user_id(&headers, &state.config.auth.jwt_secret)?;
 
@@ -208,29 +386,92 @@ async fn start_container(
     let docker = state
         .docker
         .as_ref()
-        .ok_or_else(|| (StatusCode::SERVICE_UNAVAILABLE, "Docker not available".to_string()))?;
+        .ok_or_else(|| ApiError::new(StatusCode::SERVICE_UNAVAILABLE, "Docker not available"))?;
 
     docker
         .start_container(&id)
         .await
-        .map_err(|e| {
-            if e.to_string().contains("already started") {
-                (StatusCode::CONFLICT, "Container already running".to_string())
-            } else if e.to_string().contains("No such container") {
-                (StatusCode::NOT_FOUND, "Container not found".to_string())
-            } else {
-                (StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
-            }
-        })?;
+        .map_err(|e| ApiError::docker(e, "Container"))?;
 
     Ok(StatusCode::NO_CONTENT)
 }
 
+/// Stop gracefully (SIGTERM, then SIGKILL after 10 seconds), or with `?kill=true`
+/// SIGKILL straight away for a container that ignores SIGTERM. `signal` and
+/// `timeout` pick a different stop signal and grace period.
+#[utoipa::path(
+    post,
+    path = "/containers/{id}/stop",
+    tag = "containers",
+    params(("id" = String, Path, description = "Container ID or name"), StopContainerQuery),
+    responses(
+        (status = 204, description = "Container stopped"),
+        (status = 400, description = "Invalid signal or timeout", body = ErrorBody),
+        (status = 404, description = "Container not found", body = ErrorBody),
+        (status = 409, description = "Container not running", body = ErrorBody),
+        (status = 503, description = "Docker not available", body = ErrorBody),
+    )
+)]
 async fn stop_container(
     State(state): State<SharedState>,
     headers: HeaderMap,
     Path(id): Path<String>,
-) -> Result<StatusCode, (StatusCode, String)> {
+    Query(query): Query<StopContainerQuery>,
+) -> Result<StatusCode, ApiError> {
+    // Validate auth
+    extract_user_id(&headers, &state.config.auth.jwt_secret)?;
+
+    // Check if Docker is available
+    let docker = state
+        .docker
+        .as_ref()
+        .ok_or_else(|| ApiError::new(StatusCode::SERVICE_UNAVAILABLE, "Docker not available"))?;
+
+    if query.kill && (query.signal.is_some() || query.timeout.is_some()) {
+        return Err(ApiError::new(StatusCode::BAD_REQUEST, "kill can't be combined with signal or timeout"));
+    }
+    if let Some(timeout) = query.timeout {
+        if !(0..=MAX_STOP_TIMEOUT_SECS).contains(&timeout) {
+            return Err(ApiError::new(
+                StatusCode::BAD_REQUEST,
+                format!("timeout must be between 0 and {} seconds", MAX_STOP_TIMEOUT_SECS),
+            ));
+        }
+    }
+
+    let timeout = if query.kill { Some(0) } else { query.timeout };
+    let result = match query.signal.as_deref() {
+        Some(signal) => {
+            validation::signal(signal)?;
+            docker.stop_container_with_signal(&id, signal, timeout).await
+        }
+        None => docker.stop_container(&id, timeout).await,
+    };
+    result.map_err(|e| ApiError::docker(e, "Container"))?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Send a signal to the container's main process without waiting for it to exit
+#[utoipa::path(
+    post,
+    path = "/containers/{id}/kill",
+    tag = "containers",
+    params(("id" = String, Path, description = "Container ID or name"), KillContainerQuery),
+    responses(
+        (status = 204, description = "Signal sent"),
+        (status = 400, description = "Invalid signal", body = ErrorBody),
+        (status = 404, description = "Container not found", body = ErrorBody),
+        (status = 409, description = "Container not running", body = ErrorBody),
+        (status = 503, description = "Docker not available", body = ErrorBody),
+    )
+)]
+async fn kill_container(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+    Query(query): Query<KillContainerQuery>,
+) -> Result<StatusCode, ApiError> {
     // Validate auth
     extract_user_id(&headers, &state.config.auth.jwt_secret)?;
 
@@ -238,29 +479,35 @@ async fn stop_container(
     let docker = state
         .docker
         .as_ref()
-        .ok_or_else(|| (StatusCode::SERVICE_UNAVAILABLE, "Docker not available".to_string()))?;
+        .ok_or_else(|| ApiError::new(StatusCode::SERVICE_UNAVAILABLE, "Docker not available"))?;
+
+    let signal = query.signal.as_deref().unwrap_or("SIGKILL");
+    validation::signal(signal)?;
 
     docker
-        .stop_container(&id, None)
+        .kill_container(&id, signal)
         .await
-        .map_err(|e| {
-            if e.to_string().contains("not running") {
-                (StatusCode::CONFLICT, "Container not running".to_string())
-            } else if e.to_string().contains("No such container") {
-                (StatusCode::NOT_FOUND, "Container not found".to_string())
-            } else {
-                (StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
-            }
-        })?;
+        .map_err(|e| ApiError::docker(e, "Container"))?;
 
     Ok(StatusCode::NO_CONTENT)
 }
 
+#[utoipa::path(
+    post,
+    path = "/containers/{id}/restart",
+    tag = "containers",
+    params(("id" = String, Path, description = "Container ID or name")),
+    responses(
+        (status = 204, description = "Container restarted"),
+        (status = 404, description = "Container not found", body = ErrorBody),
+        (status = 503, description = "Docker not available", body = ErrorBody),
+    )
+)]
 async fn restart_container(
     State(state): State<SharedState>,
     headers: HeaderMap,
     Path(id): Path<String>,
-) -> Result<StatusCode, (StatusCode, String)> {
+) -> Result<StatusCode, ApiError> {
     // Validate auth
     extract_user_id(&headers, &state.config.auth.jwt_secret)?;
 
@@ -268,27 +515,35 @@ async fn restart_container(
     let docker = state
         .docker
         .as_ref()
-        .ok_or_else(|| (StatusCode::SERVICE_UNAVAILABLE, "Docker not available".to_string()))?;
+        .ok_or_else(|| ApiError::new(StatusCode::SERVICE_UNAVAILABLE, "Docker not available"))?;
 
     docker
         .restart_container(&id)
         .await
-        .map_err(|e| {
-            if e.to_string().contains("No such container") {
-                (StatusCode::NOT_FOUND, "Container not found".to_string())
-            } else {
-                (StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
-            }
-        })?;
+        .map_err(|e| ApiError::docker(e, "Container"))?;
 
     Ok(StatusCode::NO_CONTENT)
 }
 
+/// Remove a stopped container; a running one is refused with 409 unless `?force=true`
+#[utoipa::path(
+    delete,
+    path = "/containers/{id}",
+    tag = "containers",
+    params(("id" = String, Path, description = "Container ID or name"), RemoveContainerQuery),
+    responses(
+        (status = 204, description = "Container removed"),
+        (status = 404, description = "Container not found", body = ErrorBody),
+        (status = 409, description = "Container is running and `force` is not set", body = ErrorBody),
+        (status = 503, description = "Docker not available", body = ErrorBody),
+    )
+)]
 async fn remove_container(
     State(state): State<SharedState>,
     headers: HeaderMap,
     Path(id): Path<String>,
-) -> Result<StatusCode, (StatusCode, String)> {
+    Query(query): Query<RemoveContainerQuery>,
+) -> Result<StatusCode, ApiError> {
     // Validate auth
     extract_user_id(&headers, &state.config.auth.jwt_secret)?;
 
@@ -296,28 +551,34 @@ async fn remove_container(
     let docker = state
         .docker
         .as_ref()
-        .ok_or_else(|| (StatusCode::SERVICE_UNAVAILABLE, "Docker not available".to_string()))?;
+        .ok_or_else(|| ApiError::new(StatusCode::SERVICE_UNAVAILABLE, "Docker not available"))?;
 
     docker
-        .remove_container(&id, true)
+        .remove_container(&id, query.force)
         .await
-        .map_err(|e| {
-            if e.to_string().contains("No such container") {
-                (StatusCode::NOT_FOUND, "Container not found".to_string())
-            } else {
-                (StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
-            }
-        })?;
+        .map_err(|e| ApiError::docker(e, "Container"))?;
 
     Ok(StatusCode::NO_CONTENT)
 }
 
+#[utoipa::path(
+    get,
+    path = "/containers/{id}/logs",
+    tag = "containers",
+    params(("id" = String, Path, description = "Container ID or name"), GetLogsQuery),
+    responses(
+        (status = 200, body = ContainerLogsResponse),
+        (status = 400, description = "Invalid filter regex", body = ErrorBody),
+        (status = 404, description = "Container not found", body = ErrorBody),
+        (status = 503, description = "Docker not available", body = ErrorBody),
+    )
+)]
 async fn get_container_logs(
     State(state): State<SharedState>,
     headers: HeaderMap,
     Path(id): Path<String>,
     Query(query): Query<GetLogsQuery>,
-) -> Result<Json<ContainerLogsResponse>, (StatusCode, String)> {
+) -> Result<Json<ContainerLogsResponse>, ApiError> {
     // Validate auth
     extract_user_id(&headers, &state.config.auth.jwt_secret)?;
 
@@ -325,27 +586,50 @@ async fn get_container_logs(
     let docker = state
         .docker
         .as_ref()
-        .ok_or_else(|| (StatusCode::SERVICE_UNAVAILABLE, "Docker not available".to_string()))?;
+        .ok_or_else(|| ApiError::new(StatusCode::SERVICE_UNAVAILABLE, "Docker not available"))?;
+
+    let filter = match query.filter {
+        Some(pattern) if query.regex => Some(LogFilter::Regex(
+            regex::Regex::new(&pattern)
+                .map_err(|e| ApiError::new(StatusCode::BAD_REQUEST, format!("Invalid filter regex: {}", e)))?,
+        )),
+        Some(needle) => Some(LogFilter::Contains(needle)),
+        None => None,
+    };
 
     let logs = docker
-        .get_container_logs(&id, Some(query.tail))
+        .get_container_logs(
+            &id,
+            LogOptions {
+                tail: query.tail,
+                since: query.since,
+                until: query.until,
+                timestamps: query.timestamps,
+                filter,
+            },
+        )
         .await
-        .map_err(|e| {
-            if e.to_string().contains("No such container") {
-                (StatusCode::NOT_FOUND, "Container not found".to_string())
-            } else {
-                (StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
-            }
-        })?;
+        .map_err(|e| ApiError::docker(e, "Container"))?;
 
     Ok(Json(ContainerLogsResponse { logs }))
 }
 
+#[utoipa::path(
+    get,
+    path = "/containers/{id}/stats",
+    tag = "containers",
+    params(("id" = String, Path, description = "Container ID or name")),
+    responses(
+        (status = 200, body = ContainerStatsResponse),
+        (status = 404, description = "Container not found", body = ErrorBody),
+        (status = 503, description = "Docker not available", body = ErrorBody),
+    )
+)]
 async fn get_container_stats(
     State(state): State<SharedState>,
     headers: HeaderMap,
     Path(id): Path<String>,
-) -> Result<Json<ContainerStatsResponse>, (StatusCode, String)> {
+) -> Result<Json<ContainerStatsResponse>, ApiError> {
     // Validate auth
     extract_user_id(&headers, &state.config.auth.jwt_secret)?;
 
@@ -353,30 +637,58 @@ async fn get_container_stats(
     let docker = state
         .docker
         .as_ref()
-        .ok_or_else(|| (StatusCode::SERVICE_UNAVAILABLE, "Docker not available".to_string()))?;
+        .ok_or_else(|| ApiError::new(StatusCode::SERVICE_UNAVAILABLE, "Docker not available"))?;
 
     let stats = docker
         .get_container_stats(&id)
         .await
-        .map_err(|e| {
-            if e.to_string().contains("No such container") {
-                (StatusCode::NOT_FOUND, "Container not found".to_string())
-            } else {
-                (StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
-            }
-        })?;
+        .map_err(|e| ApiError::docker(e, "Container"))?;
 
     Ok(Json(ContainerStatsResponse { stats }))
 }
 
+/// Paths changed in the container's writable layer, like `docker diff`
+#[utoipa::path(
+    get,
+    path = "/containers/{id}/changes",
+    tag = "containers",
+    params(("id" = String, Path, description = "Container ID or name")),
+    responses(
+        (status = 200, body = ContainerChangesResponse),
+        (status = 404, description = "Container not found", body = ErrorBody),
+        (status = 503, description = "Docker not available", body = ErrorBody),
+    )
+)]
+async fn get_container_changes(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> Result<Json<ContainerChangesResponse>, ApiError> {
+    // Validate auth
+    extract_user_id(&headers, &state.config.auth.jwt_secret)?;
+
+    // Check if Docker is available
+    let docker = state
+        .docker
+        .as_ref()
+        .ok_or_else(|| ApiError::new(StatusCode::SERVICE_UNAVAILABLE, "Docker not available"))?;
+
+    let changes = docker
+        .container_changes(&id)
+        .await
+        .map_err(|e| ApiError::docker(e, "Container"))?;
+
+    Ok(Json(ContainerChangesResponse { changes }))
+}
+
 // ===== Network Handlers =====
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 struct ListNetworksResponse {
     networks: Vec<NetworkInfo>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 struct CreateNetworkRequest {
     name: String,
     #[serde(default = "default_driver")]
@@ -387,20 +699,34 @@ fn default_driver() -> String {
     "bridge".to_string()
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 struct NetworkResponse {
     network_id: String,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 struct NetworkDetailsResponse {
     network: NetworkInfo,
 }
 
+#[derive(Debug, Deserialize, ToSchema)]
+struct NetworkConnectionRequest {
+    container_id: String,
+}
+
+#[utoipa::path(
+    get,
+    path = "/networks",
+    tag = "networks",
+    responses(
+        (status = 200, body = ListNetworksResponse),
+        (status = 503, description = "Docker not available", body = ErrorBody),
+    )
+)]
 async fn list_networks(
     State(state): State<SharedState>,
     headers: HeaderMap,
-) -> Result<Json<ListNetworksResponse>, (StatusCode, String)> {
+) -> Result<Json<ListNetworksResponse>, ApiError> {
     // Validate auth
     extract_user_id(&headers, &state.config.auth.jwt_secret)?;
 
@@ -408,21 +734,32 @@ async fn list_networks(
     let docker = state
         .docker
         .as_ref()
-        .ok_or_else(|| (StatusCode::SERVICE_UNAVAILABLE, "Docker not available".to_string()))?;
+        .ok_or_else(|| ApiError::new(StatusCode::SERVICE_UNAVAILABLE, "Docker not available"))?;
 
     let networks = docker
         .list_networks()
         .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        .map_err(ApiError::internal)?;
 
     Ok(Json(ListNetworksResponse { networks }))
 }
 
+#[utoipa::path(
+    post,
+    path = "/networks",
+    tag = "networks",
+    request_body = CreateNetworkRequest,
+    responses(
+        (status = 201, body = NetworkResponse),
+        (status = 400, description = "Network name is required", body = ErrorBody),
+        (status = 503, description = "Docker not available", body = ErrorBody),
+    )
+)]
 async fn create_network(
     State(state): State<SharedState>,
     headers: HeaderMap,
     Json(req): Json<CreateNetworkRequest>,
-) -> Result<(StatusCode, Json<NetworkResponse>), (StatusCode, String)> {
+) -> Result<(StatusCode, Json<NetworkResponse>), ApiError> {
     // Validate auth
     extract_user_id(&headers, &state.config.auth.jwt_secret)?;
 
@@ -430,17 +767,17 @@ async fn create_network(
     let docker = state
         .docker
         .as_ref()
-        .ok_or_else(|| (StatusCode::SERVICE_UNAVAILABLE, "Docker not available".to_string()))?;
+        .ok_or_else(|| ApiError::new(StatusCode::SERVICE_UNAVAILABLE, "Docker not available"))?;
 
     // Validate input
     if req.name.trim().is_empty() {
-        return Err((StatusCode::BAD_REQUEST, "Network name is required".to_string()));
+        return Err(ApiError::new(StatusCode::BAD_REQUEST, "Network name is required"));
     }
 
     let network_id = docker
         .create_network(&req.name, &req.driver)
         .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        .map_err(ApiError::internal)?;
 
     Ok((
         StatusCode::CREATED,
@@ -448,11 +785,22 @@ async fn create_network(
     ))
 }
 
+#[utoipa::path(
+    get,
+    path = "/networks/{id}",
+    tag = "networks",
+    params(("id" = String, Path, description = "Network ID or name")),
+    responses(
+        (status = 200, body = NetworkDetailsResponse),
+        (status = 404, description = "Network not found", body = ErrorBody),
+        (status = 503, description = "Docker not available", body = ErrorBody),
+    )
+)]
 async fn get_network(
     State(state): State<SharedState>,
     headers: HeaderMap,
     Path(id): Path<String>,
-) -> Result<Json<NetworkDetailsResponse>, (StatusCode, String)> {
+) -> Result<Json<NetworkDetailsResponse>, ApiError> {
     // Validate auth
     extract_user_id(&headers, &state.config.auth.jwt_secret)?;
 
@@ -460,27 +808,33 @@ async fn get_network(
     let docker = state
         .docker
         .as_ref()
-        .ok_or_else(|| (StatusCode::SERVICE_UNAVAILABLE, "Docker not available".to_string()))?;
+        .ok_or_else(|| ApiError::new(StatusCode::SERVICE_UNAVAILABLE, "Docker not available"))?;
 
     let network = docker
         .inspect_network(&id)
         .await
-        .map_err(|e| {
-            if e.to_string().contains("not found") {
-                (StatusCode::NOT_FOUND, "Network not found".to_string())
-            } else {
-                (StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
-            }
-        })?;
+        .map_err(|e| ApiError::docker(e, "Network"))?;
 
     Ok(Json(NetworkDetailsResponse { network }))
 }
 
+#[utoipa::path(
+    delete,
+    path = "/networks/{id}",
+    tag = "networks",
+    params(("id" = String, Path, description = "Network ID or name")),
+    responses(
+        (status = 204, description = "Network removed"),
+        (status = 404, description = "Network not found", body = ErrorBody),
+        (status = 409, description = "Network is in use by containers", body = ErrorBody),
+        (status = 503, description = "Docker not available", body = ErrorBody),
+    )
+)]
 async fn remove_network(
     State(state): State<SharedState>,
     headers: HeaderMap,
     Path(id): Path<String>,
-) -> Result<StatusCode, (StatusCode, String)> {
+) -> Result<StatusCode, ApiError> {
     // Validate auth
     extract_user_id(&headers, &state.config.auth.jwt_secret)?;
 
@@ -488,45 +842,155 @@ async fn remove_network(
     let docker = state
         .docker
         .as_ref()
-        .ok_or_else(|| (StatusCode::SERVICE_UNAVAILABLE, "Docker not available".to_string()))?;
+        .ok_or_else(|| ApiError::new(StatusCode::SERVICE_UNAVAILABLE, "Docker not available"))?;
 
     docker
         .remove_network(&id)
         .await
-        .map_err(|e| {
-            if e.to_string().contains("not found") {
-                (StatusCode::NOT_FOUND, "Network not found".to_string())
-            } else if e.to_string().contains("in use") {
-                (StatusCode::CONFLICT, "Network is in use by containers".to_string())
-            } else {
-                (StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
-            }
-        })?;
+        .map_err(|e| ApiError::docker(e, "Network"))?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    post,
+    path = "/networks/{id}/connect",
+    tag = "networks",
+    params(("id" = String, Path, description = "Network ID or name")),
+    request_body = NetworkConnectionRequest,
+    responses(
+        (status = 204, description = "Container connected"),
+        (status = 404, description = "Network or container not found", body = ErrorBody),
+        (status = 409, description = "Container is already connected to this network", body = ErrorBody),
+        (status = 503, description = "Docker not available", body = ErrorBody),
+    )
+)]
+async fn connect_network(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+    Json(req): Json<NetworkConnectionRequest>,
+) -> Result<StatusCode, ApiError> {
+    // Validate auth
+    extract_user_id(&headers, &state.config.auth.jwt_secret)?;
+
+    // Check if Docker is available
+    let docker = state
+        .docker
+        .as_ref()
+        .ok_or_else(|| ApiError::new(StatusCode::SERVICE_UNAVAILABLE, "Docker not available"))?;
+
+    let (network_id, connected) = network_membership(docker, &id, &req.container_id).await?;
+    if connected {
+        return Err(ApiError::new(StatusCode::CONFLICT, "Container is already connected to this network"));
+    }
+
+    docker
+        .connect_container_to_network(&network_id, &req.container_id)
+        .await
+        .map_err(ApiError::internal)?;
 
     Ok(StatusCode::NO_CONTENT)
 }
 
+#[utoipa::path(
+    post,
+    path = "/networks/{id}/disconnect",
+    tag = "networks",
+    params(("id" = String, Path, description = "Network ID or name")),
+    request_body = NetworkConnectionRequest,
+    responses(
+        (status = 204, description = "Container disconnected"),
+        (status = 404, description = "Network or container not found", body = ErrorBody),
+        (status = 409, description = "Container is not connected to this network", body = ErrorBody),
+        (status = 503, description = "Docker not available", body = ErrorBody),
+    )
+)]
+async fn disconnect_network(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+    Json(req): Json<NetworkConnectionRequest>,
+) -> Result<StatusCode, ApiError> {
+    // Validate auth
+    extract_user_id(&headers, &state.config.auth.jwt_secret)?;
+
+    // Check if Docker is available
+    let docker = state
+        .docker
+        .as_ref()
+        .ok_or_else(|| ApiError::new(StatusCode::SERVICE_UNAVAILABLE, "Docker not available"))?;
+
+    let (network_id, connected) = network_membership(docker, &id, &req.container_id).await?;
+    if !connected {
+        return Err(ApiError::new(StatusCode::CONFLICT, "Container is not connected to this network"));
+    }
+
+    docker
+        .disconnect_container_from_network(&network_id, &req.container_id)
+        .await
+        .map_err(ApiError::internal)?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Resolve the network's full id and whether the container is attached to it,
+/// returning 404 if either doesn't exist
+async fn network_membership(
+    docker: &DockerClient,
+    network: &str,
+    container_id: &str,
+) -> Result<(String, bool), ApiError> {
+    let network = docker
+        .inspect_network(network)
+        .await
+        .map_err(|e| ApiError::docker(e, "Network"))?;
+
+    let container = docker
+        .inspect_container(container_id)
+        .await
+        .map_err(|e| ApiError::docker(e, "Container"))?;
+
+    let connected = container
+        .network_settings
+        .and_then(|ns| ns.networks)
+        .unwrap_or_default()
+        .values()
+        .any(|endpoint| endpoint.network_id.as_deref() == Some(network.id.as_str()));
+
+    Ok((network.id, connected))
+}
+
 // ===== Volume Handlers =====
 
-#[derive(Debug, Serialize)]
-struct ListVolumesResponse {
+#[derive(Debug, Serialize, ToSchema)]
+struct ListDockerVolumesResponse {
     volumes: Vec<VolumeInfo>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 struct CreateVolumeRequest {
     name: String,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 struct VolumeResponse {
     volume: VolumeInfo,
 }
 
+#[utoipa::path(
+    get,
+    path = "/volumes",
+    tag = "volumes",
+    responses(
+        (status = 200, body = ListDockerVolumesResponse),
+        (status = 503, description = "Docker not available", body = ErrorBody),
+    )
+)]
 async fn list_volumes(
     State(state): State<SharedState>,
     headers: HeaderMap,
-) -> Result<Json<ListVolumesResponse>, (StatusCode, String)> {
+) -> Result<Json<ListDockerVolumesResponse>, ApiError> {
     // Validate auth
     extract_user_id(&headers, &state.config.auth.jwt_secret)?;
 
@@ -534,21 +998,32 @@ async fn list_volumes(
     let docker = state
         .docker
         .as_ref()
-        .ok_or_else(|| (StatusCode::SERVICE_UNAVAILABLE, "Docker not available".to_string()))?;
+        .ok_or_else(|| ApiError::new(StatusCode::SERVICE_UNAVAILABLE, "Docker not available"))?;
 
     let volumes = docker
         .list_volumes()
         .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        .map_err(ApiError::internal)?;
 
-    Ok(Json(ListVolumesResponse { volumes }))
+    Ok(Json(ListDockerVolumesResponse { volumes }))
 }
 
+#[utoipa::path(
+    post,
+    path = "/volumes",
+    tag = "volumes",
+    request_body = CreateVolumeRequest,
+    responses(
+        (status = 201, body = VolumeResponse),
+        (status = 400, description = "Volume name is required", body = ErrorBody),
+        (status = 503, description = "Docker not available", body = ErrorBody),
+    )
+)]
 async fn create_volume(
     State(state): State<SharedState>,
     headers: HeaderMap,
     Json(req): Json<CreateVolumeRequest>,
-) -> Result<(StatusCode, Json<VolumeResponse>), (StatusCode, String)> {
+) -> Result<(StatusCode, Json<VolumeResponse>), ApiError> {
     // Validate auth
     extract_user_id(&headers, &state.config.auth.jwt_secret)?;
 
@@ -556,17 +1031,17 @@ async fn create_volume(
     let docker = state
         .docker
         .as_ref()
-        .ok_or_else(|| (StatusCode::SERVICE_UNAVAILABLE, "Docker not available".to_string()))?;
+        .ok_or_else(|| ApiError::new(StatusCode::SERVICE_UNAVAILABLE, "Docker not available"))?;
 
     // Validate input
     if req.name.trim().is_empty() {
-        return Err((StatusCode::BAD_REQUEST, "Volume name is required".to_string()));
+        return Err(ApiError::new(StatusCode::BAD_REQUEST, "Volume name is required"));
     }
 
     let volume = docker
         .create_volume(&req.name)
         .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        .map_err(ApiError::internal)?;
 
     Ok((
         StatusCode::CREATED,
@@ -574,11 +1049,22 @@ async fn create_volume(
     ))
 }
 
+#[utoipa::path(
+    get,
+    path = "/volumes/{name}",
+    tag = "volumes",
+    params(("name" = String, Path, description = "Volume name")),
+    responses(
+        (status = 200, body = VolumeResponse),
+        (status = 404, description = "Volume not found", body = ErrorBody),
+        (status = 503, description = "Docker not available", body = ErrorBody),
+    )
+)]
 async fn get_volume(
     State(state): State<SharedState>,
     headers: HeaderMap,
     Path(name): Path<String>,
-) -> Result<Json<VolumeResponse>, (StatusCode, String)> {
+) -> Result<Json<VolumeResponse>, ApiError> {
     // Validate auth
     extract_user_id(&headers, &state.config.auth.jwt_secret)?;
 
@@ -586,27 +1072,33 @@ async fn get_volume(
     let docker = state
         .docker
         .as_ref()
-        .ok_or_else(|| (StatusCode::SERVICE_UNAVAILABLE, "Docker not available".to_string()))?;
+        .ok_or_else(|| ApiError::new(StatusCode::SERVICE_UNAVAILABLE, "Docker not available"))?;
 
     let volume = docker
         .inspect_volume(&name)
         .await
-        .map_err(|e| {
-            if e.to_string().contains("not found") {
-                (StatusCode::NOT_FOUND, "Volume not found".to_string())
-            } else {
-                (StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
-            }
-        })?;
+        .map_err(|e| ApiError::docker(e, "Volume"))?;
 
     Ok(Json(VolumeResponse { volume }))
 }
 
+#[utoipa::path(
+    delete,
+    path = "/volumes/{name}",
+    tag = "volumes",
+    params(("name" = String, Path, description = "Volume name")),
+    responses(
+        (status = 204, description = "Volume removed"),
+        (status = 404, description = "Volume not found", body = ErrorBody),
+        (status = 409, description = "Volume is in use by containers", body = ErrorBody),
+        (status = 503, description = "Docker not available", body = ErrorBody),
+    )
+)]
 async fn remove_volume(
     State(state): State<SharedState>,
     headers: HeaderMap,
     Path(name): Path<String>,
-) -> Result<StatusCode, (StatusCode, String)> {
+) -> Result<StatusCode, ApiError> {
     // Validate auth
     extract_user_id(&headers, &state.config.auth.jwt_secret)?;
 
@@ -614,20 +1106,27 @@ async fn remove_volume(
     let docker = state
         .docker
         .as_ref()
-        .ok_or_else(|| (StatusCode::SERVICE_UNAVAILABLE, "Docker not available".to_string()))?;
+        .ok_or_else(|| ApiError::new(StatusCode::SERVICE_UNAVAILABLE, "Docker not available"))?;
 
     docker
         .remove_volume(&name, false)
         .await
-        .map_err(|e| {
-            if e.to_string().contains("not found") {
-                (StatusCode::NOT_FOUND, "Volume not found".to_string())
-            } else if e.to_string().contains("in use") {
-                (StatusCode::CONFLICT, "Volume is in use by containers".to_string())
-            } else {
-                (StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
-            }
-        })?;
+        .map_err(|e| ApiError::docker(e, "Volume"))?;
 
     Ok(StatusCode::NO_CONTENT)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_secret_env_key() {
+        for secret in ["API_KEY", "stripe_secret_key", "GITHUB_TOKEN", "TOKEN", "DB_PASSWORD", "SESSION_SECRET", "AWS_CREDENTIALS"] {
+            assert!(is_secret_env_key(secret), "{} should be redacted", secret);
+        }
+        for plain in ["PATH", "NODE_ENV", "PORT", "KEYBOARD_LAYOUT", "TOKENIZER_MODEL", "MONKEY"] {
+            assert!(!is_secret_env_key(plain), "{} should be shown", plain);
+        }
+    }
+}
@@ -1,167 +1,606 @@
 use axum::{
     extract::{Path, Query, State},
-    http::{HeaderMap, StatusCode},
+    http::{header, HeaderMap, StatusCode},
+    response::IntoResponse,
     routing::{get, post},
     Json, Router,
 };
+use chrono::Utc;
 use serde::{Deserialize, Serialize};
-use std::sync::Arc;
+use utoipa::{IntoParams, OpenApi, ToSchema};
 
 use crate::app_state::SharedState;
 use crate::auth::extract_user_id;
-use crate::services::DeploymentService;
-use ployer_core::models::Deployment;
-use ployer_core::crypto;
-use ployer_db::repositories::{ApplicationRepository, DeployKeyRepository, DeploymentRepository};
+use crate::services::audit::{self, ClientIp};
+use crate::services::{start_deployment, DeployOptions, DeploymentService};
+use crate::error::ApiError;
+use crate::middleware::validation;
+use ployer_core::models::{AuditAction, Deployment, DeploymentStatus};
+use ployer_db::repositories::{ApplicationRepository, DeploymentRepository};
+use ployer_git::{GitService, UnknownRevision};
+
+/// How long a deploy trigger's `Idempotency-Key` maps to the deployment it created
+const IDEMPOTENCY_WINDOW_HOURS: i64 = 24;
 
 pub fn router() -> Router<SharedState> {
     Router::new()
         .route("/", get(list_deployments))
         .route("/:id", get(get_deployment))
+        .route("/:id/logs", get(get_deployment_logs))
         .route("/:id/cancel", post(cancel_deployment))
 }
 
 /// Add deployment routes to application router
 pub fn app_deploy_router() -> Router<SharedState> {
-    Router::new().route("/applications/:id/deploy", post(trigger_deployment))
+    Router::new()
+        .route("/applications/:id/deploy", post(trigger_deployment))
+        .route("/applications/:id/latest-deployment", get(get_latest_deployment))
+        .route("/applications/:id/deployments/compare", get(compare_deployments))
 }
 
+#[derive(OpenApi)]
+#[openapi(
+    paths(
+        trigger_deployment, list_deployments, get_deployment, get_latest_deployment,
+        get_deployment_logs, cancel_deployment, compare_deployments,
+    ),
+    components(schemas(
+        DeploymentResponse, LatestDeploymentResponse, ListDeploymentsResponse, Deployment,
+        DeploymentStatus, CompareDeploymentsResponse, CommitSummary,
+    )),
+    tags((name = "deployments", description = "Builds and deploys"))
+)]
+pub struct ApiDoc;
+
 // ===== Request/Response Types =====
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct TriggerDeploymentQuery {
+    /// Rebuild every layer instead of reusing Docker's build cache
+    #[serde(default)]
+    no_cache: bool,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 struct ListDeploymentsQuery {
     application_id: Option<String>,
+    /// Only deployments in this status, e.g. `failed`
+    status: Option<String>,
+    /// Cap on results when filtering by status
+    limit: Option<i64>,
+}
+
+const DEFAULT_STATUS_LIMIT: i64 = 100;
+const MAX_STATUS_LIMIT: i64 = 1000;
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct DeploymentLogsQuery {
+    tail: Option<usize>,
+    #[serde(default)]
+    download: bool,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct CompareDeploymentsQuery {
+    /// Commit SHA of the deployment to compare from, e.g. the one running now
+    from: String,
+    /// Commit SHA of the candidate deployment
+    to: String,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 struct DeploymentResponse {
     deployment: Deployment,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
+struct LatestDeploymentResponse {
+    /// None when the application has never been deployed
+    deployment: Option<Deployment>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
 struct ListDeploymentsResponse {
     deployments: Vec<Deployment>,
 }
 
+#[derive(Debug, Serialize, ToSchema)]
+struct CommitSummary {
+    sha: String,
+    message: String,
+    author: String,
+    /// Unix seconds
+    timestamp: i64,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+struct CompareDeploymentsResponse {
+    from: String,
+    to: String,
+    /// Commits in `to` that `from` doesn't have, newest first
+    commits: Vec<CommitSummary>,
+    /// Paths that differ between the two commits
+    files: Vec<String>,
+}
+
 // ===== Handlers =====
 
+/// Start a deployment. Retrying with the same `Idempotency-Key` returns the
+/// deployment the first request created.
+#[utoipa::path(
+    post,
+    path = "/applications/{id}/deploy",
+    tag = "deployments",
+    params(
+        ("id" = String, Path, description = "Application ID"),
+        ("Idempotency-Key" = Option<String>, Header, description = "Client-chosen key, honored for 24 hours"),
+        TriggerDeploymentQuery,
+    ),
+    responses(
+        (status = 201, description = "Deployment started", body = DeploymentResponse),
+        (status = 200, description = "Deployment already created for this Idempotency-Key", body = DeploymentResponse),
+        (status = 400, description = "Invalid Idempotency-Key", body = ErrorBody),
+        (status = 404, description = "Application not found", body = ErrorBody),
+        (status = 409, description = "Application is archived", body = ErrorBody),
+        (status = 503, description = "Docker not available", body = ErrorBody),
+    )
+)]
 async fn trigger_deployment(
     State(state): State<SharedState>,
     headers: HeaderMap,
+    client_ip: ClientIp,
     Path(app_id): Path<String>,
-) -> Result<(StatusCode, Json<DeploymentResponse>), (StatusCode, String)> {
-    extract_user_id(&headers, &state.config.auth.jwt_secret)?;
+    Query(query): Query<TriggerDeploymentQuery>,
+) -> Result<(StatusCode, Json<DeploymentResponse>), ApiError> {
+    let user_id = extract_user_id(&headers, &state.config.auth.jwt_secret)?;
 
     // Get application
     let app_repo = ApplicationRepository::new(state.db.clone());
     let application = app_repo
         .find_by_id(&app_id)
         .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
-        .ok_or_else(|| (StatusCode::NOT_FOUND, "Application not found".to_string()))?;
-
-    // Get deploy key (private key) if application has git_url
-    let private_key = if application.git_url.is_some() {
-        let key_repo = DeployKeyRepository::new(state.db.clone());
-        if let Some(key) = key_repo
-            .find_by_application(&app_id)
-            .await
-            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
-        {
-            // Decrypt private key
-            let secret_key = state.config.get_secret_key();
-            let decrypted = crypto::decrypt(&key.private_key_encrypted, &secret_key)
-                .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, format!("Decryption failed: {}", e)))?;
-            Some(decrypted)
-        } else {
-            None
+        .map_err(ApiError::internal)?
+        .ok_or_else(|| ApiError::new(StatusCode::NOT_FOUND, "Application not found"))?;
+
+    if application.archived_at.is_some() {
+        return Err(ApiError::new(StatusCode::CONFLICT, "Application is archived"));
+    }
+
+    // A retried trigger with the same key gets the deployment it already started
+    let idempotency_key = idempotency_key(&headers)?;
+    if let Some(key) = idempotency_key {
+        if let Some(deployment) = existing_deployment(&state, &app_id, key).await? {
+            return Ok((StatusCode::OK, Json(DeploymentResponse { deployment })));
         }
-    } else {
-        None
+    }
+
+    if state.docker.is_none() {
+        return Err(ApiError::new(StatusCode::SERVICE_UNAVAILABLE, "Docker not available"));
+    }
+
+    let options = DeployOptions {
+        no_cache: query.no_cache,
+        idempotency_key: idempotency_key.map(str::to_string),
+        ..Default::default()
     };
+    let result = start_deployment(&state, application, options).await;
 
-    // Create deployment service
-    let docker = state.docker.as_ref()
-        .ok_or_else(|| (StatusCode::SERVICE_UNAVAILABLE, "Docker not available".to_string()))?
-        .clone();
+    match (result, idempotency_key) {
+        (Ok(deployment), _) => {
+            audit::record(&state.db, &user_id, AuditAction::DeploymentTrigger, &deployment.id, &client_ip).await;
+            Ok((StatusCode::CREATED, Json(DeploymentResponse { deployment })))
+        }
+        // Lost a race with a concurrent trigger using the same key
+        (Err(e), Some(key)) if is_unique_violation(&e) => {
+            let deployment = existing_deployment(&state, &app_id, key)
+                .await?
+                .ok_or_else(|| ApiError::internal(e))?;
+            Ok((StatusCode::OK, Json(DeploymentResponse { deployment })))
+        }
+        (Err(e), _) => Err(ApiError::internal(e)),
+    }
+}
+
+/// The request's `Idempotency-Key` header, if it sent one
+fn idempotency_key(headers: &HeaderMap) -> Result<Option<&str>, ApiError> {
+    let Some(value) = headers.get("Idempotency-Key") else {
+        return Ok(None);
+    };
+
+    let key = value.to_str().unwrap_or_default().trim();
+    if key.is_empty() || key.len() > 255 {
+        return Err(ApiError::new(
+            StatusCode::BAD_REQUEST,
+            "Idempotency-Key must be 1-255 printable ASCII characters",
+        ));
+    }
+    Ok(Some(key))
+}
+
+/// Deployment created for this key within the idempotency window. A key
+/// whose deployment is older than the window is released for reuse.
+async fn existing_deployment(
+    state: &SharedState,
+    app_id: &str,
+    key: &str,
+) -> Result<Option<Deployment>, ApiError> {
+    let repo = DeploymentRepository::new(state.db.clone());
+    let Some(deployment) = repo
+        .find_by_idempotency_key(app_id, key)
+        .await
+        .map_err(ApiError::internal)?
+    else {
+        return Ok(None);
+    };
+
+    if Utc::now() - deployment.started_at < chrono::Duration::hours(IDEMPOTENCY_WINDOW_HOURS) {
+        return Ok(Some(deployment));
+    }
 
-    let deployment_service = DeploymentService::new(
-        state.db.clone(),
-        docker,
-        Some(Arc::new(state.caddy.clone())),
-        state.config.server.base_domain.clone(),
-        state.ws_broadcast.clone(),
-    );
-
-    // Trigger deployment
-    let deployment = deployment_service
-        .deploy(application, private_key)
+    repo.release_idempotency_key(app_id, key)
         .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        .map_err(ApiError::internal)?;
+    Ok(None)
+}
 
-    Ok((StatusCode::CREATED, Json(DeploymentResponse { deployment })))
+fn is_unique_violation(err: &anyhow::Error) -> bool {
+    err.downcast_ref::<sqlx::Error>()
+        .and_then(|e| e.as_database_error())
+        .is_some_and(|e| e.is_unique_violation())
 }
 
+#[utoipa::path(
+    get,
+    path = "/deployments",
+    tag = "deployments",
+    params(ListDeploymentsQuery),
+    responses(
+        (status = 200, body = ListDeploymentsResponse),
+        (status = 400, description = "Unknown status filter", body = ErrorBody),
+    )
+)]
 async fn list_deployments(
     State(state): State<SharedState>,
     headers: HeaderMap,
     Query(query): Query<ListDeploymentsQuery>,
-) -> Result<Json<ListDeploymentsResponse>, (StatusCode, String)> {
+) -> Result<Json<ListDeploymentsResponse>, ApiError> {
     extract_user_id(&headers, &state.config.auth.jwt_secret)?;
 
     let repo = DeploymentRepository::new(state.db.clone());
-    let deployments = repo
-        .list(query.application_id.as_deref())
-        .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    let deployments = match query.status.as_deref() {
+        Some(status) => {
+            let status = parse_status(status)?;
+            let limit = query.limit.unwrap_or(DEFAULT_STATUS_LIMIT).clamp(1, MAX_STATUS_LIMIT);
+            repo.list_by_status(&status, query.application_id.as_deref(), limit)
+                .await
+                .map_err(ApiError::internal)?
+        }
+        None => repo
+            .list(query.application_id.as_deref())
+            .await
+            .map_err(ApiError::internal)?,
+    };
 
     Ok(Json(ListDeploymentsResponse { deployments }))
 }
 
+fn parse_status(value: &str) -> Result<DeploymentStatus, ApiError> {
+    DeploymentStatus::ALL
+        .into_iter()
+        .find(|s| s.as_str() == value)
+        .ok_or_else(|| {
+            let valid: Vec<&str> = DeploymentStatus::ALL.iter().map(|s| s.as_str()).collect();
+            ApiError::new(
+                StatusCode::BAD_REQUEST,
+                format!("Invalid status \"{}\", expected one of: {}", value, valid.join(", ")),
+            )
+        })
+}
+
+#[utoipa::path(
+    get,
+    path = "/deployments/{id}",
+    tag = "deployments",
+    params(("id" = String, Path, description = "Deployment ID")),
+    responses(
+        (status = 200, body = DeploymentResponse),
+        (status = 404, description = "Deployment not found", body = ErrorBody),
+    )
+)]
 async fn get_deployment(
     State(state): State<SharedState>,
     headers: HeaderMap,
     Path(id): Path<String>,
-) -> Result<Json<DeploymentResponse>, (StatusCode, String)> {
+) -> Result<Json<DeploymentResponse>, ApiError> {
     extract_user_id(&headers, &state.config.auth.jwt_secret)?;
 
     let repo = DeploymentRepository::new(state.db.clone());
     let deployment = repo
         .find_by_id(&id)
         .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
-        .ok_or_else(|| (StatusCode::NOT_FOUND, "Deployment not found".to_string()))?;
+        .map_err(ApiError::internal)?
+        .ok_or_else(|| ApiError::new(StatusCode::NOT_FOUND, "Deployment not found"))?;
 
     Ok(Json(DeploymentResponse { deployment }))
 }
 
-async fn cancel_deployment(
+/// The application's most recent deployment, so a failed build shows up even
+/// while an older deployment is still serving traffic
+#[utoipa::path(
+    get,
+    path = "/applications/{id}/latest-deployment",
+    tag = "deployments",
+    params(("id" = String, Path, description = "Application ID")),
+    responses(
+        (status = 200, body = LatestDeploymentResponse),
+        (status = 404, description = "Application not found", body = ErrorBody),
+    )
+)]
+async fn get_latest_deployment(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    Path(app_id): Path<String>,
+) -> Result<Json<LatestDeploymentResponse>, ApiError> {
+    extract_user_id(&headers, &state.config.auth.jwt_secret)?;
+
+    ApplicationRepository::new(state.db.clone())
+        .find_by_id(&app_id)
+        .await
+        .map_err(ApiError::internal)?
+        .ok_or_else(|| ApiError::new(StatusCode::NOT_FOUND, "Application not found"))?;
+
+    let deployment = DeploymentRepository::new(state.db.clone())
+        .get_latest(&app_id)
+        .await
+        .map_err(ApiError::internal)?;
+
+    Ok(Json(LatestDeploymentResponse { deployment }))
+}
+
+/// Commits and changed files between two deployments' SHAs, read from the
+/// application's cached clone
+#[utoipa::path(
+    get,
+    path = "/applications/{id}/deployments/compare",
+    tag = "deployments",
+    params(("id" = String, Path, description = "Application ID"), CompareDeploymentsQuery),
+    responses(
+        (status = 200, body = CompareDeploymentsResponse),
+        (status = 400, description = "Invalid commit SHA", body = ErrorBody),
+        (status = 404, description = "Application not found, or a SHA is not in the cached clone", body = ErrorBody),
+        (status = 409, description = "Application has no cached clone", body = ErrorBody),
+    )
+)]
+async fn compare_deployments(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    Path(app_id): Path<String>,
+    Query(query): Query<CompareDeploymentsQuery>,
+) -> Result<Json<CompareDeploymentsResponse>, ApiError> {
+    extract_user_id(&headers, &state.config.auth.jwt_secret)?;
+    validation::commit_sha(&query.from)?;
+    validation::commit_sha(&query.to)?;
+
+    ApplicationRepository::new(state.db.clone())
+        .find_by_id(&app_id)
+        .await
+        .map_err(ApiError::internal)?
+        .ok_or_else(|| ApiError::new(StatusCode::NOT_FOUND, "Application not found"))?;
+
+    let clone_dir = state
+        .deployments
+        .repo_cache_path(&app_id)
+        .filter(|dir| dir.join(".git").exists())
+        .ok_or_else(|| {
+            ApiError::new(
+                StatusCode::CONFLICT,
+                "Application has no cached clone to compare; deploy it with the repo cache enabled",
+            )
+        })?;
+
+    let (from, to) = (query.from.clone(), query.to.clone());
+    let comparison = tokio::task::spawn_blocking(move || GitService::new().compare(&clone_dir, &from, &to))
+        .await
+        .map_err(ApiError::internal)?
+        .map_err(|e| match e.downcast_ref::<UnknownRevision>() {
+            Some(unknown) => ApiError::new(
+                StatusCode::NOT_FOUND,
+                format!("{}; deploy again to fetch the latest commits", unknown),
+            ),
+            None => ApiError::internal(e),
+        })?;
+
+    let commits = comparison
+        .commits
+        .into_iter()
+        .map(|c| CommitSummary {
+            sha: c.sha,
+            message: c.message,
+            author: c.author,
+            timestamp: c.timestamp,
+        })
+        .collect();
+
+    Ok(Json(CompareDeploymentsResponse {
+        from: query.from,
+        to: query.to,
+        commits,
+        files: comparison.files,
+    }))
+}
+
+/// Return the build log as plain text, optionally only the last `tail` lines
+#[utoipa::path(
+    get,
+    path = "/deployments/{id}/logs",
+    tag = "deployments",
+    params(("id" = String, Path, description = "Deployment ID"), DeploymentLogsQuery),
+    responses(
+        (status = 200, description = "Build log", body = String, content_type = "text/plain"),
+        (status = 404, description = "Deployment not found", body = ErrorBody),
+    )
+)]
+async fn get_deployment_logs(
     State(state): State<SharedState>,
     headers: HeaderMap,
     Path(id): Path<String>,
-) -> Result<StatusCode, (StatusCode, String)> {
+    Query(query): Query<DeploymentLogsQuery>,
+) -> Result<impl IntoResponse, ApiError> {
     extract_user_id(&headers, &state.config.auth.jwt_secret)?;
 
+    let repo = DeploymentRepository::new(state.db.clone());
+    let deployment = repo
+        .find_by_id(&id)
+        .await
+        .map_err(ApiError::internal)?
+        .ok_or_else(|| ApiError::new(StatusCode::NOT_FOUND, "Deployment not found"))?;
+
+    let log = deployment.build_log.unwrap_or_default();
+    let body = match query.tail {
+        Some(n) => tail_lines(&log, n),
+        None => log,
+    };
+
+    let mut response_headers = HeaderMap::new();
+    response_headers.insert(header::CONTENT_TYPE, "text/plain; charset=utf-8".parse().unwrap());
+    if query.download {
+        let disposition = format!("attachment; filename=\"deployment-{}.log\"", deployment.id);
+        response_headers.insert(
+            header::CONTENT_DISPOSITION,
+            disposition
+                .parse()
+                .map_err(|_| ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, "Invalid deployment id"))?,
+        );
+    }
+
+    Ok((response_headers, body))
+}
+
+fn tail_lines(log: &str, n: usize) -> String {
+    let lines: Vec<&str> = log.lines().collect();
+    let mut tail = lines[lines.len().saturating_sub(n)..].join("\n");
+    if !tail.is_empty() {
+        tail.push('\n');
+    }
+    tail
+}
+
+#[utoipa::path(
+    post,
+    path = "/deployments/{id}/cancel",
+    tag = "deployments",
+    params(("id" = String, Path, description = "Deployment ID")),
+    responses(
+        (status = 204, description = "Deployment cancelled"),
+        (status = 400, description = "Deployment cannot be cancelled", body = ErrorBody),
+        (status = 503, description = "Docker not available", body = ErrorBody),
+    )
+)]
+async fn cancel_deployment(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    client_ip: ClientIp,
+    Path(id): Path<String>,
+) -> Result<StatusCode, ApiError> {
+    let user_id = extract_user_id(&headers, &state.config.auth.jwt_secret)?;
+
     let docker = state.docker.as_ref()
-        .ok_or_else(|| (StatusCode::SERVICE_UNAVAILABLE, "Docker not available".to_string()))?
+        .ok_or_else(|| ApiError::new(StatusCode::SERVICE_UNAVAILABLE, "Docker not available"))?
         .clone();
 
-    let deployment_service = DeploymentService::new(
-        state.db.clone(),
-        docker,
-        Some(Arc::new(state.caddy.clone())),
-        state.config.server.base_domain.clone(),
-        state.ws_broadcast.clone(),
-    );
+    let deployment_service = DeploymentService::from_state(&state, docker);
 
     let cancelled = deployment_service
         .cancel_deployment(&id)
         .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        .map_err(ApiError::internal)?;
 
     if cancelled {
+        audit::record(&state.db, &user_id, AuditAction::DeploymentCancel, &id, &client_ip).await;
         Ok(StatusCode::NO_CONTENT)
     } else {
-        Err((StatusCode::BAD_REQUEST, "Deployment cannot be cancelled".to_string()))
+        Err(ApiError::new(StatusCode::BAD_REQUEST, "Deployment cannot be cancelled"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app_state::AppState;
+    use crate::auth::jwt::generate_token;
+    use ployer_core::config::AppConfig;
+    use ployer_docker::DockerClient;
+    use ployer_proxy::CaddyClient;
+
+    #[test]
+    fn test_parse_status() {
+        assert_eq!(parse_status("failed").unwrap(), DeploymentStatus::Failed);
+        assert_eq!(parse_status("rolled_back").unwrap(), DeploymentStatus::RolledBack);
+
+        let err = parse_status("broken").unwrap_err();
+        assert_eq!(err.status, StatusCode::BAD_REQUEST);
+        assert!(err.message.contains("queued"));
+    }
+
+    #[tokio::test]
+    async fn test_idempotency_key_starts_a_single_deployment() {
+        let db = ployer_db::memory_pool().await.unwrap();
+        for statement in [
+            "INSERT INTO servers (id, name, host, is_local, created_at, updated_at) VALUES ('local', 'local', 'localhost', 1, '2024-01-01T00:00:00Z', '2024-01-01T00:00:00Z')",
+            "INSERT INTO applications (id, name, server_id, created_at, updated_at) VALUES ('a1', 'web', 'local', '2024-01-01T00:00:00Z', '2024-01-01T00:00:00Z')",
+        ] {
+            sqlx::query(statement).execute(&db).await.unwrap();
+        }
+
+        let config = AppConfig::default();
+        let token = generate_token("u1", "admin@example.com", "admin", &config.auth.jwt_secret, 1).unwrap();
+        let mut headers = HeaderMap::new();
+        headers.insert("Authorization", format!("Bearer {}", token).parse().unwrap());
+        headers.insert("Idempotency-Key", "ci-run-42".parse().unwrap());
+
+        // Nothing listens on this socket, so the background pipeline just fails
+        let docker = DockerClient::new("/tmp/ployer-test-no-docker.sock").unwrap();
+        let state = AppState::new(
+            db.clone(),
+            Some(docker),
+            CaddyClient::new("http://127.0.0.1:1", "/tmp/Caddyfile", &Default::default()).unwrap(),
+            config,
+        );
+
+        let trigger = |headers: HeaderMap| {
+            trigger_deployment(
+                State(state.clone()),
+                headers,
+                ClientIp(Some("203.0.113.7".to_string())),
+                Path("a1".to_string()),
+                Query(TriggerDeploymentQuery { no_cache: false }),
+            )
+        };
+        let (first_status, Json(first)) = trigger(headers.clone()).await.unwrap();
+        let (second_status, Json(second)) = trigger(headers.clone()).await.unwrap();
+
+        assert_eq!(first_status, StatusCode::CREATED);
+        assert_eq!(second_status, StatusCode::OK);
+        assert_eq!(first.deployment.id, second.deployment.id);
+        let deployments: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM deployments")
+            .fetch_one(&db)
+            .await
+            .unwrap();
+        assert_eq!(deployments, 1);
+
+        // Only the deployment that was actually started is audited
+        let audited: Vec<(String, String, String)> =
+            sqlx::query_as("SELECT user_id, target_id, ip FROM audit_log WHERE action = 'deployment.trigger'")
+                .fetch_all(&db)
+                .await
+                .unwrap();
+        assert_eq!(audited, vec![("u1".to_string(), first.deployment.id.clone(), "203.0.113.7".to_string())]);
+
+        headers.insert("Idempotency-Key", "".parse().unwrap());
+        assert_eq!(trigger(headers).await.unwrap_err().status, StatusCode::BAD_REQUEST);
     }
 }
@@ -1,15 +1,23 @@
 use axum::{
     extract::{Path, State},
     http::{HeaderMap, StatusCode},
-    routing::{delete, get, post},
+    routing::{delete, get, post, put},
     Json, Router,
 };
 use serde::{Deserialize, Serialize};
+use utoipa::{OpenApi, ToSchema};
 
 use crate::app_state::SharedState;
 use crate::auth::extract_user_id;
-use ployer_core::models::Domain;
-use ployer_db::repositories::DomainRepository;
+use crate::middleware::validation;
+use crate::error::ApiError;
+use crate::services::audit::{self, ClientIp};
+use ployer_core::models::{AuditAction, Domain, HeaderRules, HealthCheckType, ProxyHeaders, RateLimit};
+use ployer_db::repositories::{DeploymentRepository, DomainRepository, HealthCheckRepository};
+use ployer_proxy::{ReverseProxyConfig, UpstreamHealthCheck};
+
+/// Most extra hostnames one domain's route may carry
+const MAX_DOMAIN_ALIASES: usize = 32;
 
 pub fn router() -> Router<SharedState> {
     Router::new()
@@ -17,28 +25,76 @@ pub fn router() -> Router<SharedState> {
         .route("/applications/:app_id/domains/:domain", delete(remove_domain))
         .route("/applications/:app_id/domains/:domain/verify", post(verify_domain))
         .route("/applications/:app_id/domains/:domain/primary", post(set_primary_domain))
+        .route(
+            "/applications/:app_id/domains/:domain/basic-auth",
+            put(set_basic_auth).delete(clear_basic_auth),
+        )
+        .route(
+            "/applications/:app_id/domains/:domain/rate-limit",
+            put(set_rate_limit).delete(clear_rate_limit),
+        )
+        .route(
+            "/applications/:app_id/domains/:domain/proxy-health-check",
+            put(enable_proxy_health_check).delete(disable_proxy_health_check),
+        )
+        .route(
+            "/applications/:app_id/domains/:domain/headers",
+            put(set_proxy_headers).delete(clear_proxy_headers),
+        )
+        .route("/applications/:app_id/domains/:domain/aliases", put(set_domain_aliases))
 }
 
+#[derive(OpenApi)]
+#[openapi(
+    paths(
+        list_domains, add_domain, remove_domain, verify_domain, set_primary_domain, set_basic_auth,
+        clear_basic_auth, set_rate_limit, clear_rate_limit, enable_proxy_health_check,
+        disable_proxy_health_check, set_proxy_headers, clear_proxy_headers, set_domain_aliases,
+    ),
+    components(schemas(
+        AddDomainRequest, BasicAuthRequest, DomainAliasesRequest, DomainResponse, ListDomainsResponse,
+        VerifyDomainResponse, Domain, RateLimit, ProxyHeaders, HeaderRules,
+    )),
+    tags((name = "domains", description = "Custom domains and their proxy routes"))
+)]
+pub struct ApiDoc;
+
 // ===== Request/Response Types =====
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 struct AddDomainRequest {
     domain: String,
     #[serde(default)]
     is_primary: bool,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Deserialize, ToSchema)]
+struct BasicAuthRequest {
+    username: String,
+    password: String,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+struct DomainAliasesRequest {
+    /// Extra hostnames served by the same route, e.g. `["*.example.com"]`; replaces the current list
+    #[serde(default)]
+    aliases: Vec<String>,
+    /// Redirect `www.<domain>` to the domain
+    #[serde(default)]
+    www_redirect: bool,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
 struct DomainResponse {
     domain: Domain,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 struct ListDomainsResponse {
     domains: Vec<Domain>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 struct VerifyDomainResponse {
     success: bool,
     message: String,
@@ -46,47 +102,63 @@ struct VerifyDomainResponse {
 
 // ===== Handlers =====
 
+#[utoipa::path(
+    get,
+    path = "/applications/{app_id}/domains",
+    tag = "domains",
+    params(("app_id" = String, Path, description = "Application ID")),
+    responses((status = 200, body = ListDomainsResponse))
+)]
 async fn list_domains(
     State(state): State<SharedState>,
     headers: HeaderMap,
     Path(app_id): Path<String>,
-) -> Result<Json<ListDomainsResponse>, (StatusCode, String)> {
+) -> Result<Json<ListDomainsResponse>, ApiError> {
     extract_user_id(&headers, &state.config.auth.jwt_secret)?;
 
     let repo = DomainRepository::new(state.db.clone());
     let domains = repo
         .list_by_application(&app_id)
         .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        .map_err(ApiError::internal)?;
 
     Ok(Json(ListDomainsResponse { domains }))
 }
 
+#[utoipa::path(
+    post,
+    path = "/applications/{app_id}/domains",
+    tag = "domains",
+    params(("app_id" = String, Path, description = "Application ID")),
+    request_body = AddDomainRequest,
+    responses(
+        (status = 201, body = DomainResponse),
+        (status = 400, description = "Invalid domain", body = ErrorBody),
+        (status = 409, description = "Domain already exists", body = ErrorBody),
+    )
+)]
 async fn add_domain(
     State(state): State<SharedState>,
     headers: HeaderMap,
     Path(app_id): Path<String>,
     Json(req): Json<AddDomainRequest>,
-) -> Result<(StatusCode, Json<DomainResponse>), (StatusCode, String)> {
+) -> Result<(StatusCode, Json<DomainResponse>), ApiError> {
     extract_user_id(&headers, &state.config.auth.jwt_secret)?;
 
-    // Validate domain name
-    if req.domain.trim().is_empty() {
-        return Err((StatusCode::BAD_REQUEST, "Domain name is required".to_string()));
-    }
+    let domain_name = validation::domain(&req.domain)?;
 
     let repo = DomainRepository::new(state.db.clone());
 
     // Check if domain already exists
-    if let Some(_) = repo.find_by_domain(&req.domain).await.map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))? {
-        return Err((StatusCode::CONFLICT, "Domain already exists".to_string()));
+    if let Some(_) = repo.find_by_domain(&domain_name).await.map_err(ApiError::internal)? {
+        return Err(ApiError::new(StatusCode::CONFLICT, "Domain already exists"));
     }
 
     // Create domain
     let domain = repo
-        .create(&app_id, &req.domain, req.is_primary)
+        .create(&app_id, &domain_name, req.is_primary)
         .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        .map_err(ApiError::internal)?;
 
     // TODO: Configure Caddy reverse proxy
     // For now, we'll skip Caddy configuration until we have container info
@@ -95,12 +167,25 @@ async fn add_domain(
     Ok((StatusCode::CREATED, Json(DomainResponse { domain })))
 }
 
+#[utoipa::path(
+    delete,
+    path = "/applications/{app_id}/domains/{domain}",
+    tag = "domains",
+    params(("app_id" = String, Path, description = "Application ID"), ("domain" = String, Path, description = "Domain name, e.g. `app.example.com`")),
+    responses(
+        (status = 204, description = "Domain removed"),
+        (status = 403, description = "Domain does not belong to this application", body = ErrorBody),
+        (status = 404, description = "Domain not found", body = ErrorBody),
+        (status = 502, description = "Caddy could not remove the route", body = ErrorBody),
+    )
+)]
 async fn remove_domain(
     State(state): State<SharedState>,
     headers: HeaderMap,
+    client_ip: ClientIp,
     Path((app_id, domain)): Path<(String, String)>,
-) -> Result<StatusCode, (StatusCode, String)> {
-    extract_user_id(&headers, &state.config.auth.jwt_secret)?;
+) -> Result<StatusCode, ApiError> {
+    let user_id = extract_user_id(&headers, &state.config.auth.jwt_secret)?;
 
     let repo = DomainRepository::new(state.db.clone());
 
@@ -108,31 +193,47 @@ async fn remove_domain(
     let domain_record = repo
         .find_by_domain(&domain)
         .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
-        .ok_or_else(|| (StatusCode::NOT_FOUND, "Domain not found".to_string()))?;
+        .map_err(ApiError::internal)?
+        .ok_or_else(|| ApiError::new(StatusCode::NOT_FOUND, "Domain not found"))?;
 
     if domain_record.application_id != app_id {
-        return Err((StatusCode::FORBIDDEN, "Domain does not belong to this application".to_string()));
+        return Err(ApiError::new(StatusCode::FORBIDDEN, "Domain does not belong to this application"));
     }
 
+    // Stop serving it first, so a Caddy failure doesn't leave an untracked route behind
+    state
+        .caddy
+        .remove_route(&domain_record.domain)
+        .await
+        .map_err(|e| ApiError::new(StatusCode::BAD_GATEWAY, e.to_string()))?;
+
     // Delete domain
     repo.delete_by_domain(&domain)
         .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        .map_err(ApiError::internal)?;
 
-    // TODO: Remove Caddy route
-    // if let Some(ref caddy) = state.caddy {
-    //     let _ = caddy.remove_route(&domain).await;
-    // }
+    audit::record(&state.db, &user_id, AuditAction::DomainDelete, &domain, &client_ip).await;
 
     Ok(StatusCode::NO_CONTENT)
 }
 
+/// Check that the domain resolves to this server and refresh its SSL status
+#[utoipa::path(
+    post,
+    path = "/applications/{app_id}/domains/{domain}/verify",
+    tag = "domains",
+    params(("app_id" = String, Path, description = "Application ID"), ("domain" = String, Path, description = "Domain name, e.g. `app.example.com`")),
+    responses(
+        (status = 200, body = VerifyDomainResponse),
+        (status = 403, description = "Domain does not belong to this application", body = ErrorBody),
+        (status = 404, description = "Domain not found", body = ErrorBody),
+    )
+)]
 async fn verify_domain(
     State(state): State<SharedState>,
     headers: HeaderMap,
     Path((app_id, domain)): Path<(String, String)>,
-) -> Result<Json<VerifyDomainResponse>, (StatusCode, String)> {
+) -> Result<Json<VerifyDomainResponse>, ApiError> {
     extract_user_id(&headers, &state.config.auth.jwt_secret)?;
 
     let repo = DomainRepository::new(state.db.clone());
@@ -141,11 +242,11 @@ async fn verify_domain(
     let domain_record = repo
         .find_by_domain(&domain)
         .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
-        .ok_or_else(|| (StatusCode::NOT_FOUND, "Domain not found".to_string()))?;
+        .map_err(ApiError::internal)?
+        .ok_or_else(|| ApiError::new(StatusCode::NOT_FOUND, "Domain not found"))?;
 
     if domain_record.application_id != app_id {
-        return Err((StatusCode::FORBIDDEN, "Domain does not belong to this application".to_string()));
+        return Err(ApiError::new(StatusCode::FORBIDDEN, "Domain does not belong to this application"));
     }
 
     // TODO: Implement DNS verification
@@ -162,17 +263,28 @@ async fn verify_domain(
     if success {
         repo.update_ssl_status(&domain_record.id, true)
             .await
-            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+            .map_err(ApiError::internal)?;
     }
 
     Ok(Json(VerifyDomainResponse { success, message }))
 }
 
+#[utoipa::path(
+    post,
+    path = "/applications/{app_id}/domains/{domain}/primary",
+    tag = "domains",
+    params(("app_id" = String, Path, description = "Application ID"), ("domain" = String, Path, description = "Domain name, e.g. `app.example.com`")),
+    responses(
+        (status = 204, description = "Domain is now primary"),
+        (status = 403, description = "Domain does not belong to this application", body = ErrorBody),
+        (status = 404, description = "Domain not found", body = ErrorBody),
+    )
+)]
 async fn set_primary_domain(
     State(state): State<SharedState>,
     headers: HeaderMap,
     Path((app_id, domain)): Path<(String, String)>,
-) -> Result<StatusCode, (StatusCode, String)> {
+) -> Result<StatusCode, ApiError> {
     extract_user_id(&headers, &state.config.auth.jwt_secret)?;
 
     let repo = DomainRepository::new(state.db.clone());
@@ -181,17 +293,557 @@ async fn set_primary_domain(
     let domain_record = repo
         .find_by_domain(&domain)
         .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
-        .ok_or_else(|| (StatusCode::NOT_FOUND, "Domain not found".to_string()))?;
+        .map_err(ApiError::internal)?
+        .ok_or_else(|| ApiError::new(StatusCode::NOT_FOUND, "Domain not found"))?;
 
     if domain_record.application_id != app_id {
-        return Err((StatusCode::FORBIDDEN, "Domain does not belong to this application".to_string()));
+        return Err(ApiError::new(StatusCode::FORBIDDEN, "Domain does not belong to this application"));
     }
 
     // Set as primary
     repo.set_primary(&domain_record.id)
         .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        .map_err(ApiError::internal)?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    put,
+    path = "/applications/{app_id}/domains/{domain}/basic-auth",
+    tag = "domains",
+    params(("app_id" = String, Path, description = "Application ID"), ("domain" = String, Path, description = "Domain name, e.g. `app.example.com`")),
+    request_body = BasicAuthRequest,
+    responses(
+        (status = 200, body = DomainResponse),
+        (status = 400, description = "Invalid username or password", body = ErrorBody),
+        (status = 403, description = "Domain does not belong to this application", body = ErrorBody),
+        (status = 404, description = "Domain not found", body = ErrorBody),
+        (status = 409, description = "Application has no running deployment to route to", body = ErrorBody),
+        (status = 502, description = "Caddy rejected the route", body = ErrorBody),
+    )
+)]
+async fn set_basic_auth(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    Path((app_id, domain)): Path<(String, String)>,
+    Json(req): Json<BasicAuthRequest>,
+) -> Result<Json<DomainResponse>, ApiError> {
+    extract_user_id(&headers, &state.config.auth.jwt_secret)?;
+
+    validation::required(&req.username, "username", 128)?;
+    if req.username.contains(|c: char| c.is_whitespace() || c == ':') {
+        return Err(ApiError::new(StatusCode::BAD_REQUEST, "username may not contain whitespace or ':'"));
+    }
+    validation::password(&req.password)?;
+
+    let repo = DomainRepository::new(state.db.clone());
+    let domain_record = owned_domain(&repo, &app_id, &domain).await?;
+
+    // bcrypt is deliberately slow; keep it off the async workers
+    let password = req.password;
+    let hash = tokio::task::spawn_blocking(move || bcrypt::hash(password, bcrypt::DEFAULT_COST))
+        .await
+        .map_err(ApiError::internal)?
+        .map_err(ApiError::internal)?;
+
+    apply_basic_auth(&state, &repo, &domain_record, Some((&req.username, &hash))).await?;
+
+    Ok(Json(DomainResponse {
+        domain: Domain {
+            basic_auth_user: Some(req.username),
+            ..domain_record
+        },
+    }))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/applications/{app_id}/domains/{domain}/basic-auth",
+    tag = "domains",
+    params(("app_id" = String, Path, description = "Application ID"), ("domain" = String, Path, description = "Domain name, e.g. `app.example.com`")),
+    responses(
+        (status = 204, description = "Basic auth removed"),
+        (status = 403, description = "Domain does not belong to this application", body = ErrorBody),
+        (status = 404, description = "Domain not found", body = ErrorBody),
+        (status = 409, description = "Application has no running deployment to route to", body = ErrorBody),
+        (status = 502, description = "Caddy rejected the route", body = ErrorBody),
+    )
+)]
+async fn clear_basic_auth(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    Path((app_id, domain)): Path<(String, String)>,
+) -> Result<StatusCode, ApiError> {
+    extract_user_id(&headers, &state.config.auth.jwt_secret)?;
+
+    let repo = DomainRepository::new(state.db.clone());
+    let domain_record = owned_domain(&repo, &app_id, &domain).await?;
+
+    apply_basic_auth(&state, &repo, &domain_record, None).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    put,
+    path = "/applications/{app_id}/domains/{domain}/rate-limit",
+    tag = "domains",
+    params(("app_id" = String, Path, description = "Application ID"), ("domain" = String, Path, description = "Domain name, e.g. `app.example.com`")),
+    request_body = RateLimit,
+    responses(
+        (status = 200, body = DomainResponse),
+        (status = 400, description = "Limit out of range", body = ErrorBody),
+        (status = 403, description = "Domain does not belong to this application", body = ErrorBody),
+        (status = 404, description = "Domain not found", body = ErrorBody),
+        (status = 409, description = "Application has no running deployment to route to", body = ErrorBody),
+        (status = 502, description = "Caddy rejected the route", body = ErrorBody),
+    )
+)]
+async fn set_rate_limit(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    Path((app_id, domain)): Path<(String, String)>,
+    Json(limit): Json<RateLimit>,
+) -> Result<Json<DomainResponse>, ApiError> {
+    extract_user_id(&headers, &state.config.auth.jwt_secret)?;
+
+    if !(1..=1_000_000).contains(&limit.requests) {
+        return Err(ApiError::new(StatusCode::BAD_REQUEST, "requests must be between 1 and 1000000"));
+    }
+    if !(1..=86_400).contains(&limit.window_secs) {
+        return Err(ApiError::new(StatusCode::BAD_REQUEST, "window_secs must be between 1 and 86400"));
+    }
+
+    let repo = DomainRepository::new(state.db.clone());
+    let domain_record = owned_domain(&repo, &app_id, &domain).await?;
+
+    let domain_record = Domain {
+        rate_limit: Some(limit),
+        ..domain_record
+    };
+    apply_proxy_route(&state, &repo, &domain_record).await?;
+    repo.set_rate_limit(&domain_record.id, Some(limit))
+        .await
+        .map_err(ApiError::internal)?;
+
+    Ok(Json(DomainResponse { domain: domain_record }))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/applications/{app_id}/domains/{domain}/rate-limit",
+    tag = "domains",
+    params(("app_id" = String, Path, description = "Application ID"), ("domain" = String, Path, description = "Domain name, e.g. `app.example.com`")),
+    responses(
+        (status = 204, description = "Rate limit removed"),
+        (status = 403, description = "Domain does not belong to this application", body = ErrorBody),
+        (status = 404, description = "Domain not found", body = ErrorBody),
+        (status = 409, description = "Application has no running deployment to route to", body = ErrorBody),
+        (status = 502, description = "Caddy rejected the route", body = ErrorBody),
+    )
+)]
+async fn clear_rate_limit(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    Path((app_id, domain)): Path<(String, String)>,
+) -> Result<StatusCode, ApiError> {
+    extract_user_id(&headers, &state.config.auth.jwt_secret)?;
+
+    let repo = DomainRepository::new(state.db.clone());
+    let domain_record = owned_domain(&repo, &app_id, &domain).await?;
+
+    let domain_record = Domain {
+        rate_limit: None,
+        ..domain_record
+    };
+    apply_proxy_route(&state, &repo, &domain_record).await?;
+    repo.set_rate_limit(&domain_record.id, None)
+        .await
+        .map_err(ApiError::internal)?;
 
     Ok(StatusCode::NO_CONTENT)
 }
+
+/// Have Caddy health check the upstream itself, using the app's HTTP health check
+#[utoipa::path(
+    put,
+    path = "/applications/{app_id}/domains/{domain}/proxy-health-check",
+    tag = "domains",
+    params(("app_id" = String, Path, description = "Application ID"), ("domain" = String, Path, description = "Domain name, e.g. `app.example.com`")),
+    responses(
+        (status = 200, body = DomainResponse),
+        (status = 403, description = "Domain does not belong to this application", body = ErrorBody),
+        (status = 404, description = "Domain not found", body = ErrorBody),
+        (status = 409, description = "No HTTP health check configured, or no running deployment", body = ErrorBody),
+        (status = 502, description = "Caddy rejected the route", body = ErrorBody),
+    )
+)]
+async fn enable_proxy_health_check(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    Path((app_id, domain)): Path<(String, String)>,
+) -> Result<Json<DomainResponse>, ApiError> {
+    extract_user_id(&headers, &state.config.auth.jwt_secret)?;
+
+    let repo = DomainRepository::new(state.db.clone());
+    let domain_record = owned_domain(&repo, &app_id, &domain).await?;
+
+    let health_check = HealthCheckRepository::new(state.db.clone())
+        .get(&app_id)
+        .await
+        .map_err(ApiError::internal)?;
+    if !health_check.is_some_and(|c| c.check_type == HealthCheckType::Http) {
+        return Err(ApiError::new(
+            StatusCode::CONFLICT,
+            "Proxy health checks need the application to have an HTTP health check configured",
+        ));
+    }
+
+    let domain_record = Domain {
+        proxy_health_check: true,
+        ..domain_record
+    };
+    apply_proxy_route(&state, &repo, &domain_record).await?;
+    repo.set_proxy_health_check(&domain_record.id, true)
+        .await
+        .map_err(ApiError::internal)?;
+
+    Ok(Json(DomainResponse { domain: domain_record }))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/applications/{app_id}/domains/{domain}/proxy-health-check",
+    tag = "domains",
+    params(("app_id" = String, Path, description = "Application ID"), ("domain" = String, Path, description = "Domain name, e.g. `app.example.com`")),
+    responses(
+        (status = 204, description = "Proxy health checks turned off"),
+        (status = 403, description = "Domain does not belong to this application", body = ErrorBody),
+        (status = 404, description = "Domain not found", body = ErrorBody),
+        (status = 409, description = "Application has no running deployment to route to", body = ErrorBody),
+        (status = 502, description = "Caddy rejected the route", body = ErrorBody),
+    )
+)]
+async fn disable_proxy_health_check(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    Path((app_id, domain)): Path<(String, String)>,
+) -> Result<StatusCode, ApiError> {
+    extract_user_id(&headers, &state.config.auth.jwt_secret)?;
+
+    let repo = DomainRepository::new(state.db.clone());
+    let domain_record = owned_domain(&repo, &app_id, &domain).await?;
+
+    let domain_record = Domain {
+        proxy_health_check: false,
+        ..domain_record
+    };
+    apply_proxy_route(&state, &repo, &domain_record).await?;
+    repo.set_proxy_health_check(&domain_record.id, false)
+        .await
+        .map_err(ApiError::internal)?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    put,
+    path = "/applications/{app_id}/domains/{domain}/headers",
+    tag = "domains",
+    params(("app_id" = String, Path, description = "Application ID"), ("domain" = String, Path, description = "Domain name, e.g. `app.example.com`")),
+    request_body = ProxyHeaders,
+    responses(
+        (status = 200, body = DomainResponse),
+        (status = 400, description = "Invalid header name or value", body = ErrorBody),
+        (status = 403, description = "Domain does not belong to this application", body = ErrorBody),
+        (status = 404, description = "Domain not found", body = ErrorBody),
+        (status = 409, description = "Application has no running deployment to route to", body = ErrorBody),
+        (status = 502, description = "Caddy rejected the route", body = ErrorBody),
+    )
+)]
+async fn set_proxy_headers(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    Path((app_id, domain)): Path<(String, String)>,
+    Json(proxy_headers): Json<ProxyHeaders>,
+) -> Result<Json<DomainResponse>, ApiError> {
+    extract_user_id(&headers, &state.config.auth.jwt_secret)?;
+
+    validation::proxy_headers(&proxy_headers)?;
+
+    let repo = DomainRepository::new(state.db.clone());
+    let domain_record = owned_domain(&repo, &app_id, &domain).await?;
+
+    let domain_record = Domain {
+        proxy_headers,
+        ..domain_record
+    };
+    apply_proxy_route(&state, &repo, &domain_record).await?;
+    repo.set_proxy_headers(&domain_record.id, &domain_record.proxy_headers)
+        .await
+        .map_err(ApiError::internal)?;
+
+    Ok(Json(DomainResponse { domain: domain_record }))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/applications/{app_id}/domains/{domain}/headers",
+    tag = "domains",
+    params(("app_id" = String, Path, description = "Application ID"), ("domain" = String, Path, description = "Domain name, e.g. `app.example.com`")),
+    responses(
+        (status = 204, description = "Header rules removed"),
+        (status = 403, description = "Domain does not belong to this application", body = ErrorBody),
+        (status = 404, description = "Domain not found", body = ErrorBody),
+        (status = 409, description = "Application has no running deployment to route to", body = ErrorBody),
+        (status = 502, description = "Caddy rejected the route", body = ErrorBody),
+    )
+)]
+async fn clear_proxy_headers(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    Path((app_id, domain)): Path<(String, String)>,
+) -> Result<StatusCode, ApiError> {
+    extract_user_id(&headers, &state.config.auth.jwt_secret)?;
+
+    let repo = DomainRepository::new(state.db.clone());
+    let domain_record = owned_domain(&repo, &app_id, &domain).await?;
+
+    let domain_record = Domain {
+        proxy_headers: ProxyHeaders::default(),
+        ..domain_record
+    };
+    apply_proxy_route(&state, &repo, &domain_record).await?;
+    repo.set_proxy_headers(&domain_record.id, &domain_record.proxy_headers)
+        .await
+        .map_err(ApiError::internal)?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Rebuild the domain's admin API route around the app's running container,
+/// applying the record's rate limit, proxy health check and header settings
+async fn apply_proxy_route(
+    state: &SharedState,
+    repo: &DomainRepository,
+    domain_record: &Domain,
+) -> Result<(), ApiError> {
+    let host_port = DeploymentRepository::new(state.db.clone())
+        .get_latest_running(&domain_record.application_id)
+        .await
+        .map_err(ApiError::internal)?
+        .and_then(|d| d.host_port)
+        .ok_or_else(|| ApiError::new(StatusCode::CONFLICT, "Application has no running deployment to route to"))?;
+
+    let mut config = ReverseProxyConfig::new(
+        &domain_record.domain,
+        &format!("localhost:{}", host_port),
+        true,
+    );
+    config.basic_auth = repo
+        .get_basic_auth(&domain_record.id)
+        .await
+        .map_err(ApiError::internal)?;
+    config.domains = domain_record.aliases.clone();
+    config.www_redirect = domain_record.www_redirect;
+    config.rate_limit = domain_record.rate_limit;
+    config.headers = domain_record.proxy_headers.clone();
+    if domain_record.proxy_health_check {
+        config.health_check = HealthCheckRepository::new(state.db.clone())
+            .get(&domain_record.application_id)
+            .await
+            .map_err(ApiError::internal)?
+            .filter(|c| c.check_type == HealthCheckType::Http)
+            .map(|c| UpstreamHealthCheck {
+                path: c.path,
+                interval_secs: c.interval_seconds.max(1) as u32,
+                timeout_secs: c.timeout_seconds.max(1) as u32,
+            });
+    }
+
+    state
+        .caddy
+        .replace_route(config.clone())
+        .await
+        .map_err(|e| ApiError::new(StatusCode::BAD_GATEWAY, e.to_string()))?;
+
+    // Caddy accepted it; keep it in apps.caddy so a reload doesn't drop the settings
+    state.caddy.persist_config(&config).map_err(ApiError::internal)
+}
+
+#[utoipa::path(
+    put,
+    path = "/applications/{app_id}/domains/{domain}/aliases",
+    tag = "domains",
+    params(("app_id" = String, Path, description = "Application ID"), ("domain" = String, Path, description = "Domain name, e.g. `app.example.com`")),
+    request_body = DomainAliasesRequest,
+    responses(
+        (status = 200, body = DomainResponse),
+        (status = 400, description = "Invalid alias", body = ErrorBody),
+        (status = 403, description = "Domain does not belong to this application", body = ErrorBody),
+        (status = 404, description = "Domain not found", body = ErrorBody),
+        (status = 409, description = "Alias is already a domain, or the application has no running deployment to route to", body = ErrorBody),
+        (status = 502, description = "Caddy rejected the route", body = ErrorBody),
+    )
+)]
+async fn set_domain_aliases(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    Path((app_id, domain)): Path<(String, String)>,
+    Json(req): Json<DomainAliasesRequest>,
+) -> Result<Json<DomainResponse>, ApiError> {
+    extract_user_id(&headers, &state.config.auth.jwt_secret)?;
+
+    if req.aliases.len() > MAX_DOMAIN_ALIASES {
+        return Err(ApiError::new(
+            StatusCode::BAD_REQUEST,
+            format!("At most {} aliases are allowed per domain", MAX_DOMAIN_ALIASES),
+        ));
+    }
+
+    let repo = DomainRepository::new(state.db.clone());
+    let domain_record = owned_domain(&repo, &app_id, &domain).await?;
+
+    let www = format!("www.{}", domain_record.domain);
+    let mut aliases: Vec<String> = Vec::new();
+    for alias in &req.aliases {
+        let alias = validation::domain_pattern(alias)?;
+        if alias == domain_record.domain || aliases.contains(&alias) {
+            continue;
+        }
+        if req.www_redirect && alias == www {
+            return Err(ApiError::new(
+                StatusCode::BAD_REQUEST,
+                format!("{} can't be an alias while it redirects to {}", www, domain_record.domain),
+            ));
+        }
+        // A registered domain has its own route, which the alias would shadow
+        if repo.find_by_domain(&alias).await.map_err(ApiError::internal)?.is_some() {
+            return Err(ApiError::new(StatusCode::CONFLICT, format!("{} is already added as a domain", alias)));
+        }
+        aliases.push(alias);
+    }
+
+    let domain_record = Domain {
+        aliases,
+        www_redirect: req.www_redirect,
+        ..domain_record
+    };
+    apply_proxy_route(&state, &repo, &domain_record).await?;
+    repo.set_aliases(&domain_record.id, &domain_record.aliases, domain_record.www_redirect)
+        .await
+        .map_err(ApiError::internal)?;
+
+    Ok(Json(DomainResponse { domain: domain_record }))
+}
+
+/// Store basic auth credentials and rebuild the route with them.
+/// The route reads the credentials back from the database, so the previous
+/// ones are restored if Caddy can't be updated.
+async fn apply_basic_auth(
+    state: &SharedState,
+    repo: &DomainRepository,
+    domain_record: &Domain,
+    auth: Option<(&str, &str)>,
+) -> Result<(), ApiError> {
+    let previous = repo
+        .get_basic_auth(&domain_record.id)
+        .await
+        .map_err(ApiError::internal)?;
+    repo.set_basic_auth(&domain_record.id, auth)
+        .await
+        .map_err(ApiError::internal)?;
+
+    if let Err(e) = apply_proxy_route(state, repo, domain_record).await {
+        let previous = previous.as_ref().map(|(user, hash)| (user.as_str(), hash.as_str()));
+        repo.set_basic_auth(&domain_record.id, previous)
+            .await
+            .map_err(ApiError::internal)?;
+        return Err(e);
+    }
+    Ok(())
+}
+
+/// Look up a domain, rejecting it unless it belongs to the application
+async fn owned_domain(repo: &DomainRepository, app_id: &str, domain: &str) -> Result<Domain, ApiError> {
+    let domain_record = repo
+        .find_by_domain(domain)
+        .await
+        .map_err(ApiError::internal)?
+        .ok_or_else(|| ApiError::new(StatusCode::NOT_FOUND, "Domain not found"))?;
+
+    if domain_record.application_id != app_id {
+        return Err(ApiError::new(StatusCode::FORBIDDEN, "Domain does not belong to this application"));
+    }
+
+    Ok(domain_record)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app_state::AppState;
+    use crate::auth::jwt::generate_token;
+    use ployer_core::config::AppConfig;
+    use ployer_proxy::CaddyClient;
+
+    #[tokio::test]
+    async fn test_set_domain_aliases_validates_before_routing() {
+        let db = ployer_db::memory_pool().await.unwrap();
+        for statement in [
+            "INSERT INTO servers (id, name, host, is_local, created_at, updated_at) VALUES ('local', 'local', 'localhost', 1, '2024-01-01T00:00:00Z', '2024-01-01T00:00:00Z')",
+            "INSERT INTO applications (id, name, server_id, created_at, updated_at) VALUES ('a1', 'web', 'local', '2024-01-01T00:00:00Z', '2024-01-01T00:00:00Z')",
+            "INSERT INTO domains (id, application_id, domain, is_primary, ssl_active, created_at) VALUES ('d1', 'a1', 'example.com', 1, 0, '2024-01-01T00:00:00Z')",
+            "INSERT INTO domains (id, application_id, domain, is_primary, ssl_active, created_at) VALUES ('d2', 'a1', 'api.example.com', 0, 0, '2024-01-01T00:00:00Z')",
+        ] {
+            sqlx::query(statement).execute(&db).await.unwrap();
+        }
+
+        let config = AppConfig::default();
+        let token = generate_token("u1", "admin@example.com", "admin", &config.auth.jwt_secret, 1).unwrap();
+        let mut headers = HeaderMap::new();
+        headers.insert("Authorization", format!("Bearer {}", token).parse().unwrap());
+
+        // Nothing listens here, so any route that reaches Caddy fails with 502
+        let state = AppState::new(
+            db.clone(),
+            None,
+            CaddyClient::new("http://127.0.0.1:1", "/tmp/Caddyfile", &Default::default()).unwrap(),
+            config,
+        );
+
+        let set = |aliases: &[&str], www_redirect: bool| {
+            set_domain_aliases(
+                State(state.clone()),
+                headers.clone(),
+                Path(("a1".to_string(), "example.com".to_string())),
+                Json(DomainAliasesRequest {
+                    aliases: aliases.iter().map(|a| a.to_string()).collect(),
+                    www_redirect,
+                }),
+            )
+        };
+        let status = |result: Result<Json<DomainResponse>, ApiError>| result.unwrap_err().status;
+
+        assert_eq!(status(set(&["a.*.example.com"], false).await), StatusCode::BAD_REQUEST);
+        assert_eq!(status(set(&["www.example.com"], true).await), StatusCode::BAD_REQUEST);
+        assert_eq!(status(set(&["API.example.com"], false).await), StatusCode::CONFLICT);
+        assert_eq!(
+            set(&["*.example.com"], true).await.unwrap_err().message,
+            "Application has no running deployment to route to"
+        );
+
+        // The wildcard passes validation and only fails once Caddy is called
+        sqlx::query("INSERT INTO deployments (id, application_id, server_id, status, image_tag, started_at, host_port) VALUES ('dep1', 'a1', 'local', 'running', 'img', '2024-01-01T00:00:00Z', 49153)")
+            .execute(&db)
+            .await
+            .unwrap();
+        assert_eq!(status(set(&["*.Example.com", "example.com"], true).await), StatusCode::BAD_GATEWAY);
+
+        // Nothing is stored unless Caddy took the route
+        let stored = DomainRepository::new(db).find_by_domain("example.com").await.unwrap().unwrap();
+        assert!(stored.aliases.is_empty());
+        assert!(!stored.www_redirect);
+    }
+}
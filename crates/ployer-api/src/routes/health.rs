@@ -1,28 +1,68 @@
-use axum::{extract::State, routing::get, Json, Router};
+use axum::{extract::State, http::StatusCode, routing::get, Json, Router};
 use serde_json::{json, Value};
+use std::time::Duration;
 use crate::app_state::SharedState;
 
+/// Upper bound for each dependency probe so a hung service can't stall the endpoint
+const PROBE_TIMEOUT: Duration = Duration::from_secs(2);
+
 pub fn router() -> Router<SharedState> {
     Router::new().route("/", get(health_check))
 }
 
-async fn health_check(State(state): State<SharedState>) -> Json<Value> {
-    let docker_ok = match &state.docker {
-        Some(docker) => docker.ping().await.unwrap_or(false),
-        None => false,
+fn probe_status(ok: bool) -> &'static str {
+    if ok { "ok" } else { "unavailable" }
+}
+
+/// Readiness probe: 200 when the database is reachable, 503 otherwise.
+/// Docker and Caddy failures are reported as degraded but don't fail the probe.
+async fn health_check(State(state): State<SharedState>) -> (StatusCode, Json<Value>) {
+    let db_probe = async {
+        tokio::time::timeout(PROBE_TIMEOUT, sqlx::query("SELECT 1").execute(&state.db))
+            .await
+            .map(|r| r.is_ok())
+            .unwrap_or(false)
     };
 
-    let db_ok = sqlx::query("SELECT 1")
-        .execute(&state.db)
-        .await
-        .is_ok();
-
-    Json(json!({
-        "status": if db_ok { "ok" } else { "degraded" },
-        "version": env!("CARGO_PKG_VERSION"),
-        "services": {
-            "database": db_ok,
-            "docker": docker_ok,
+    let docker_probe = async {
+        match &state.docker {
+            Some(docker) => tokio::time::timeout(PROBE_TIMEOUT, docker.ping())
+                .await
+                .ok()
+                .and_then(|r| r.ok())
+                .unwrap_or(false),
+            None => false,
         }
-    }))
+    };
+
+    let caddy_probe = async {
+        tokio::time::timeout(PROBE_TIMEOUT, state.caddy.ping())
+            .await
+            .ok()
+            .and_then(|r| r.ok())
+            .unwrap_or(false)
+    };
+
+    let (db_ok, docker_ok, caddy_ok) = tokio::join!(db_probe, docker_probe, caddy_probe);
+
+    let (code, status) = if !db_ok {
+        (StatusCode::SERVICE_UNAVAILABLE, "unavailable")
+    } else if !docker_ok || !caddy_ok {
+        (StatusCode::OK, "degraded")
+    } else {
+        (StatusCode::OK, "ok")
+    };
+
+    (
+        code,
+        Json(json!({
+            "status": status,
+            "version": env!("CARGO_PKG_VERSION"),
+            "services": {
+                "database": probe_status(db_ok),
+                "docker": probe_status(docker_ok),
+                "caddy": probe_status(caddy_ok),
+            }
+        })),
+    )
 }
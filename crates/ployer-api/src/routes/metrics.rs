@@ -0,0 +1,178 @@
+use axum::{
+    extract::State,
+    http::{header, HeaderMap, StatusCode},
+    response::IntoResponse,
+    routing::get,
+    Router,
+};
+use ployer_db::repositories::{ApplicationRepository, ContainerStatsRepository, DeploymentRepository};
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use crate::app_state::SharedState;
+use crate::error::ApiError;
+
+pub fn router() -> Router<SharedState> {
+    Router::new().route("/metrics", get(metrics))
+}
+
+/// Point-in-time values rendered by the metrics endpoint
+struct MetricsSnapshot {
+    applications: usize,
+    deployments_by_status: Vec<(String, i64)>,
+    containers_running: Option<usize>,
+    /// (app_id, app_name, cpu_percent, memory_mb)
+    app_usage: Vec<(String, String, f64, f64)>,
+}
+
+async fn metrics(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+) -> Result<impl IntoResponse, ApiError> {
+    if let Some(ref expected) = state.config.auth.metrics_token {
+        let provided = headers
+            .get("Authorization")
+            .and_then(|h| h.to_str().ok())
+            .and_then(|h| h.strip_prefix("Bearer "));
+        if provided != Some(expected.as_str()) {
+            return Err(ApiError::new(StatusCode::UNAUTHORIZED, "Invalid metrics token"));
+        }
+    }
+
+    let applications = ApplicationRepository::new(state.db.clone())
+        .list(false)
+        .await
+        .map_err(ApiError::internal)?;
+
+    let deployments_by_status = DeploymentRepository::new(state.db.clone())
+        .count_by_status()
+        .await
+        .map_err(ApiError::internal)?
+        .into_iter()
+        .map(|(status, count)| (status.as_str().to_string(), count))
+        .collect();
+
+    let latest_stats = ContainerStatsRepository::new(state.db.clone())
+        .get_latest_per_app()
+        .await
+        .map_err(ApiError::internal)?;
+
+    let containers_running = match &state.docker {
+        Some(docker) => docker.list_containers(false, &Default::default()).await.ok().map(|c| c.len()),
+        None => None,
+    };
+
+    let names: HashMap<&str, &str> = applications
+        .iter()
+        .map(|a| (a.id.as_str(), a.name.as_str()))
+        .collect();
+    let app_usage = latest_stats
+        .into_iter()
+        .filter_map(|s| {
+            let app_id = s.application_id?;
+            let name = names.get(app_id.as_str())?.to_string();
+            Some((app_id, name, s.cpu_percent, s.memory_mb))
+        })
+        .collect();
+
+    let body = render_metrics(&MetricsSnapshot {
+        applications: applications.len(),
+        deployments_by_status,
+        containers_running,
+        app_usage,
+    });
+
+    Ok(([(header::CONTENT_TYPE, "text/plain; version=0.0.4")], body))
+}
+
+/// Render a snapshot in the Prometheus text exposition format
+fn render_metrics(snapshot: &MetricsSnapshot) -> String {
+    let mut out = String::new();
+
+    let _ = writeln!(out, "# HELP ployer_applications Number of configured applications");
+    let _ = writeln!(out, "# TYPE ployer_applications gauge");
+    let _ = writeln!(out, "ployer_applications {}", snapshot.applications);
+
+    let _ = writeln!(out, "# HELP ployer_deployments_total Number of deployments by status");
+    let _ = writeln!(out, "# TYPE ployer_deployments_total gauge");
+    for (status, count) in &snapshot.deployments_by_status {
+        let _ = writeln!(out, "ployer_deployments_total{{status=\"{}\"}} {}", escape_label(status), count);
+    }
+
+    if let Some(running) = snapshot.containers_running {
+        let _ = writeln!(out, "# HELP ployer_containers_running Number of running Docker containers");
+        let _ = writeln!(out, "# TYPE ployer_containers_running gauge");
+        let _ = writeln!(out, "ployer_containers_running {}", running);
+    }
+
+    let _ = writeln!(out, "# HELP ployer_app_cpu_percent Latest CPU usage per application");
+    let _ = writeln!(out, "# TYPE ployer_app_cpu_percent gauge");
+    for (app_id, name, cpu, _) in &snapshot.app_usage {
+        let _ = writeln!(
+            out,
+            "ployer_app_cpu_percent{{app_id=\"{}\",app=\"{}\"}} {}",
+            escape_label(app_id),
+            escape_label(name),
+            cpu
+        );
+    }
+
+    let _ = writeln!(out, "# HELP ployer_app_memory_mb Latest memory usage per application in MB");
+    let _ = writeln!(out, "# TYPE ployer_app_memory_mb gauge");
+    for (app_id, name, _, memory) in &snapshot.app_usage {
+        let _ = writeln!(
+            out,
+            "ployer_app_memory_mb{{app_id=\"{}\",app=\"{}\"}} {}",
+            escape_label(app_id),
+            escape_label(name),
+            memory
+        );
+    }
+
+    out
+}
+
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Minimal exposition-format check: `name{labels} value` or a comment per line
+    fn parse_sample(line: &str) -> Option<(&str, f64)> {
+        let (series, value) = line.rsplit_once(' ')?;
+        let name = series.split('{').next()?;
+        let valid_name = !name.is_empty()
+            && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == ':');
+        if !valid_name || (series.contains('{') && !series.ends_with('}')) {
+            return None;
+        }
+        Some((name, value.parse().ok()?))
+    }
+
+    #[test]
+    fn test_render_metrics_is_parseable() {
+        let body = render_metrics(&MetricsSnapshot {
+            applications: 2,
+            deployments_by_status: vec![("running".to_string(), 3), ("failed".to_string(), 1)],
+            containers_running: Some(4),
+            app_usage: vec![("a1".to_string(), "web \"prod\"".to_string(), 12.5, 256.0)],
+        });
+
+        let mut names = Vec::new();
+        for line in body.lines().filter(|l| !l.starts_with('#')) {
+            let (name, _) = parse_sample(line).unwrap_or_else(|| panic!("unparseable line: {}", line));
+            names.push(name);
+        }
+
+        assert!(names.contains(&"ployer_deployments_total"));
+        assert!(body.contains("ployer_deployments_total{status=\"running\"} 3"));
+        assert!(body.contains("ployer_containers_running 4"));
+        assert!(body.contains("app=\"web \\\"prod\\\"\""));
+    }
+}
@@ -8,6 +8,9 @@ pub mod domains;
 pub mod webhooks;
 pub mod monitoring;
 pub mod settings;
+pub mod metrics;
+pub mod audit;
+pub mod openapi;
 
 use axum::{routing::get, Router};
 use crate::app_state::SharedState;
@@ -28,5 +31,7 @@ pub fn api_router() -> Router<SharedState> {
         .merge(monitoring::router())
         .nest("/deployments", deployments::router())
         .nest("/settings", settings::router())
+        .nest("/audit", audit::router())
+        .merge(openapi::router())
         .route("/ws", get(websocket::websocket_handler))
 }
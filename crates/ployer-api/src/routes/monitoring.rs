@@ -5,11 +5,14 @@ use axum::{
     routing::{get, post},
     Json, Router,
 };
-use ployer_core::models::HealthCheckStatus;
+use ployer_core::models::{parse_status_range, HealthCheckConfig, HealthCheckStatus, HealthCheckType};
 use serde::{Deserialize, Serialize};
 
 use crate::app_state::SharedState;
-use crate::auth::extract_user_id;
+use crate::auth::{extract_user_id, require_admin};
+use crate::error::ApiError;
+use crate::middleware::validation;
+use crate::websocket::ConnectionInfo;
 
 pub fn router() -> Router<SharedState> {
     Router::new()
@@ -25,11 +28,23 @@ pub fn router() -> Router<SharedState> {
             "/applications/:app_id/stats",
             get(get_application_stats),
         )
+        .route(
+            "/applications/:app_id/stats/summary",
+            get(get_application_stats_summary),
+        )
+        .route("/monitoring/connections", get(list_connections))
 }
 
 #[derive(Debug, Deserialize)]
 struct ConfigureHealthCheckRequest {
+    #[serde(default)]
+    check_type: HealthCheckType,
+    #[serde(default = "default_path")]
     path: String,
+    #[serde(default = "default_method")]
+    method: String,
+    #[serde(default = "default_expected_status")]
+    expected_status: String,
     interval_seconds: i32,
     timeout_seconds: i32,
     healthy_threshold: i32,
@@ -40,7 +55,10 @@ struct ConfigureHealthCheckRequest {
 struct HealthCheckResponse {
     id: String,
     application_id: String,
+    check_type: HealthCheckType,
     path: String,
+    method: String,
+    expected_status: String,
     interval_seconds: i32,
     timeout_seconds: i32,
     healthy_threshold: i32,
@@ -59,6 +77,18 @@ struct HealthCheckResultResponse {
     checked_at: String,
 }
 
+fn default_path() -> String {
+    "/".to_string()
+}
+
+fn default_method() -> String {
+    "GET".to_string()
+}
+
+fn default_expected_status() -> String {
+    "2xx".to_string()
+}
+
 #[derive(Debug, Deserialize)]
 struct StatsQuery {
     hours: Option<i64>,
@@ -70,9 +100,20 @@ async fn configure_health_check(
     State(state): State<SharedState>,
     Path(app_id): Path<String>,
     Json(req): Json<ConfigureHealthCheckRequest>,
-) -> Result<impl IntoResponse, (StatusCode, String)> {
+) -> Result<impl IntoResponse, ApiError> {
     extract_user_id(&headers, &state.config.auth.jwt_secret)?;
 
+    let method = req.method.to_ascii_uppercase();
+    if !matches!(method.as_str(), "GET" | "HEAD" | "POST") {
+        return Err(ApiError::new(StatusCode::BAD_REQUEST, "method must be GET, HEAD, or POST"));
+    }
+    if parse_status_range(&req.expected_status).is_none() {
+        return Err(ApiError::new(
+            StatusCode::BAD_REQUEST,
+            "expected_status must be a status code, a range like 200-299, or a class like 2xx",
+        ));
+    }
+
     let health_repo = ployer_db::repositories::HealthCheckRepository::new(state.db.clone());
     let app_repo = ployer_db::repositories::ApplicationRepository::new(state.db.clone());
 
@@ -80,26 +121,34 @@ async fn configure_health_check(
     app_repo
         .find_by_id(&app_id)
         .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
-        .ok_or_else(|| (StatusCode::NOT_FOUND, "Application not found".to_string()))?;
+        .map_err(ApiError::internal)?
+        .ok_or_else(|| ApiError::new(StatusCode::NOT_FOUND, "Application not found"))?;
 
     // Upsert health check configuration
     let health_check = health_repo
         .upsert(
             &app_id,
-            &req.path,
-            req.interval_seconds,
-            req.timeout_seconds,
-            req.healthy_threshold,
-            req.unhealthy_threshold,
+            &HealthCheckConfig {
+                check_type: req.check_type,
+                path: req.path,
+                method,
+                expected_status: req.expected_status,
+                interval_seconds: req.interval_seconds,
+                timeout_seconds: req.timeout_seconds,
+                healthy_threshold: req.healthy_threshold,
+                unhealthy_threshold: req.unhealthy_threshold,
+            },
         )
         .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        .map_err(ApiError::internal)?;
 
     Ok(Json(HealthCheckResponse {
         id: health_check.id,
         application_id: health_check.application_id,
+        check_type: health_check.check_type,
         path: health_check.path,
+        method: health_check.method,
+        expected_status: health_check.expected_status,
         interval_seconds: health_check.interval_seconds,
         timeout_seconds: health_check.timeout_seconds,
         healthy_threshold: health_check.healthy_threshold,
@@ -113,7 +162,7 @@ async fn get_health_check(
     headers: HeaderMap,
     State(state): State<SharedState>,
     Path(app_id): Path<String>,
-) -> Result<impl IntoResponse, (StatusCode, String)> {
+) -> Result<impl IntoResponse, ApiError> {
     extract_user_id(&headers, &state.config.auth.jwt_secret)?;
 
     let health_repo = ployer_db::repositories::HealthCheckRepository::new(state.db.clone());
@@ -121,13 +170,16 @@ async fn get_health_check(
     let health_check = health_repo
         .get(&app_id)
         .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
-        .ok_or_else(|| (StatusCode::NOT_FOUND, "Health check not configured".to_string()))?;
+        .map_err(ApiError::internal)?
+        .ok_or_else(|| ApiError::new(StatusCode::NOT_FOUND, "Health check not configured"))?;
 
     Ok(Json(HealthCheckResponse {
         id: health_check.id,
         application_id: health_check.application_id,
+        check_type: health_check.check_type,
         path: health_check.path,
+        method: health_check.method,
+        expected_status: health_check.expected_status,
         interval_seconds: health_check.interval_seconds,
         timeout_seconds: health_check.timeout_seconds,
         healthy_threshold: health_check.healthy_threshold,
@@ -141,7 +193,7 @@ async fn get_health_check_results(
     headers: HeaderMap,
     State(state): State<SharedState>,
     Path(app_id): Path<String>,
-) -> Result<impl IntoResponse, (StatusCode, String)> {
+) -> Result<impl IntoResponse, ApiError> {
     extract_user_id(&headers, &state.config.auth.jwt_secret)?;
 
     let health_repo = ployer_db::repositories::HealthCheckRepository::new(state.db.clone());
@@ -149,7 +201,7 @@ async fn get_health_check_results(
     let results = health_repo
         .get_recent_results(&app_id, 50)
         .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        .map_err(ApiError::internal)?;
 
     let response: Vec<HealthCheckResultResponse> = results
         .into_iter()
@@ -173,16 +225,17 @@ async fn get_application_stats(
     State(state): State<SharedState>,
     Path(app_id): Path<String>,
     Query(query): Query<StatsQuery>,
-) -> Result<impl IntoResponse, (StatusCode, String)> {
+) -> Result<impl IntoResponse, ApiError> {
     extract_user_id(&headers, &state.config.auth.jwt_secret)?;
     let stats_repo = ployer_db::repositories::ContainerStatsRepository::new(state.db.clone());
 
     let hours = query.hours.unwrap_or(1); // Default to last 1 hour
+    validation::stats_hours(hours)?;
 
     let stats = stats_repo
         .get_app_stats(&app_id, hours)
         .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        .map_err(ApiError::internal)?;
 
     // Convert to response format
     let response: Vec<serde_json::Value> = stats
@@ -202,3 +255,44 @@ async fn get_application_stats(
 
     Ok(Json(response))
 }
+
+/// Get aggregated container stats for an application
+async fn get_application_stats_summary(
+    headers: HeaderMap,
+    State(state): State<SharedState>,
+    Path(app_id): Path<String>,
+    Query(query): Query<StatsQuery>,
+) -> Result<impl IntoResponse, ApiError> {
+    extract_user_id(&headers, &state.config.auth.jwt_secret)?;
+    let stats_repo = ployer_db::repositories::ContainerStatsRepository::new(state.db.clone());
+
+    let hours = query.hours.unwrap_or(1); // Default to last 1 hour
+    validation::stats_hours(hours)?;
+
+    let summary = stats_repo
+        .get_app_summary(&app_id, hours)
+        .await
+        .map_err(ApiError::internal)?;
+
+    Ok(Json(summary))
+}
+
+#[derive(Debug, Serialize)]
+struct ConnectionsResponse {
+    count: usize,
+    connections: Vec<ConnectionInfo>,
+}
+
+/// List open WebSocket connections (admin only)
+async fn list_connections(
+    headers: HeaderMap,
+    State(state): State<SharedState>,
+) -> Result<Json<ConnectionsResponse>, ApiError> {
+    require_admin(&headers, &state.config.auth.jwt_secret)?;
+
+    let connections = state.ws_connections.list().await;
+    Ok(Json(ConnectionsResponse {
+        count: connections.len(),
+        connections,
+    }))
+}
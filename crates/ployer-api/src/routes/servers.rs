@@ -1,22 +1,32 @@
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::{HeaderMap, StatusCode},
     routing::{get, post},
     Json, Router,
 };
 use serde::{Deserialize, Serialize};
-use ployer_core::models::{Server, ServerStatus};
-use ployer_db::repositories::ServerRepository;
+use ployer_core::crypto;
+use ployer_core::models::{Application, AuditAction, Server, ServerStatus, WsEvent};
+use ployer_db::repositories::{ApplicationRepository, ServerRepository};
+use ployer_docker::{ContainerFilters, ContainerInfo, DockerDiskUsage, LABEL_APP_ID};
 use ployer_server::ServerManager;
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
 
 use crate::app_state::SharedState;
 use crate::auth::extract_user_id;
+use crate::error::ApiError;
+use crate::middleware::validation;
+use crate::services::audit::{self, ClientIp};
+use crate::services::health_monitor;
 
 pub fn router() -> Router<SharedState> {
     Router::new()
         .route("/", get(list_servers).post(create_server))
         .route("/:id", get(get_server).put(update_server).delete(delete_server))
         .route("/:id/resources", get(get_server_resources))
+        .route("/:id/containers", get(list_server_containers))
+        .route("/:id/applications", get(list_server_applications))
         .route("/:id/validate", post(validate_server))
 }
 
@@ -25,16 +35,33 @@ struct ListServersResponse {
     servers: Vec<Server>,
 }
 
+#[derive(Debug, Deserialize)]
+struct ListServersQuery {
+    /// `key:value`, or just `key` to match any value
+    tag: Option<String>,
+}
+
 async fn list_servers(
     State(state): State<SharedState>,
     headers: HeaderMap,
-) -> Result<Json<ListServersResponse>, (StatusCode, String)> {
+    Query(query): Query<ListServersQuery>,
+) -> Result<Json<ListServersResponse>, ApiError> {
     // Validate auth
     extract_user_id(&headers, &state.config.auth.jwt_secret)?;
 
     let repo = ServerRepository::new(state.db.clone());
-    let servers = repo.list().await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    let servers = match query.tag.as_deref() {
+        Some(tag) => {
+            let (key, value) = match tag.split_once(':') {
+                Some((key, value)) => (key, Some(value)),
+                None => (tag, None),
+            };
+            validation::tag_key(key)?;
+            repo.list_by_tag(key, value).await
+        }
+        None => repo.list().await,
+    }
+    .map_err(ApiError::internal)?;
 
     Ok(Json(ListServersResponse { servers }))
 }
@@ -50,11 +77,28 @@ struct CreateServerRequest {
     ssh_key: Option<String>,
     #[serde(default)]
     is_local: bool,
+    /// e.g. `linux/arm64`
+    platform: Option<String>,
+    /// e.g. `{"region": "eu", "env": "prod"}`
+    tags: Option<BTreeMap<String, String>>,
 }
 
 fn default_port() -> u16 { 22 }
 fn default_username() -> String { "root".to_string() }
 
+/// Encrypt an SSH key for storage; blank keys are treated as absent
+fn encrypt_ssh_key(
+    state: &SharedState,
+    ssh_key: Option<&str>,
+) -> Result<Option<String>, ApiError> {
+    match ssh_key.map(str::trim).filter(|k| !k.is_empty()) {
+        Some(key) => crypto::encrypt(key, &state.config.get_secret_key())
+            .map(Some)
+            .map_err(|e| ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, format!("Encryption failed: {}", e))),
+        None => Ok(None),
+    }
+}
+
 #[derive(Debug, Serialize)]
 struct ServerResponse {
     server: Server,
@@ -64,26 +108,47 @@ async fn create_server(
     State(state): State<SharedState>,
     headers: HeaderMap,
     Json(req): Json<CreateServerRequest>,
-) -> Result<(StatusCode, Json<ServerResponse>), (StatusCode, String)> {
+) -> Result<(StatusCode, Json<ServerResponse>), ApiError> {
     // Validate auth
     extract_user_id(&headers, &state.config.auth.jwt_secret)?;
 
     // Validate input
     if req.name.trim().is_empty() || req.host.trim().is_empty() {
-        return Err((StatusCode::BAD_REQUEST, "Name and host are required".to_string()));
+        return Err(ApiError::new(StatusCode::BAD_REQUEST, "Name and host are required"));
+    }
+    if let Some(ref platform) = req.platform {
+        validation::platform(platform)?;
     }
+    if let Some(ref tags) = req.tags {
+        validation::tags(tags)?;
+    }
+
+    let ssh_key_encrypted = encrypt_ssh_key(&state, req.ssh_key.as_deref())?;
 
     let repo = ServerRepository::new(state.db.clone());
-    let server = repo.create(
+    let mut server = repo.create(
         &req.name,
         &req.host,
         req.port,
         &req.username,
-        req.ssh_key.as_deref(),
+        ssh_key_encrypted.as_deref(),
         req.is_local,
     )
     .await
-    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    .map_err(ApiError::internal)?;
+
+    if req.platform.is_some() {
+        repo.set_platform(&server.id, req.platform.as_deref())
+            .await
+            .map_err(ApiError::internal)?;
+        server.platform = req.platform;
+    }
+    if let Some(tags) = req.tags {
+        repo.set_tags(&server.id, &tags)
+            .await
+            .map_err(ApiError::internal)?;
+        server.tags = tags;
+    }
 
     Ok((StatusCode::CREATED, Json(ServerResponse { server })))
 }
@@ -92,14 +157,14 @@ async fn get_server(
     State(state): State<SharedState>,
     headers: HeaderMap,
     Path(id): Path<String>,
-) -> Result<Json<ServerResponse>, (StatusCode, String)> {
+) -> Result<Json<ServerResponse>, ApiError> {
     // Validate auth
     extract_user_id(&headers, &state.config.auth.jwt_secret)?;
 
     let repo = ServerRepository::new(state.db.clone());
     let server = repo.find_by_id(&id).await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
-        .ok_or_else(|| (StatusCode::NOT_FOUND, "Server not found".to_string()))?;
+        .map_err(ApiError::internal)?
+        .ok_or_else(|| ApiError::new(StatusCode::NOT_FOUND, "Server not found"))?;
 
     Ok(Json(ServerResponse { server }))
 }
@@ -112,6 +177,10 @@ struct UpdateServerRequest {
     username: Option<String>,
     ssh_key: Option<String>,
     is_local: Option<bool>,
+    /// `""` goes back to the daemon's own platform
+    platform: Option<String>,
+    /// Replaces the server's tags; `{}` clears them
+    tags: Option<BTreeMap<String, String>>,
 }
 
 async fn update_server(
@@ -119,28 +188,54 @@ async fn update_server(
     headers: HeaderMap,
     Path(id): Path<String>,
     Json(req): Json<UpdateServerRequest>,
-) -> Result<Json<ServerResponse>, (StatusCode, String)> {
+) -> Result<Json<ServerResponse>, ApiError> {
     // Validate auth
     extract_user_id(&headers, &state.config.auth.jwt_secret)?;
 
+    if let Some(platform) = req.platform.as_deref().filter(|p| !p.is_empty()) {
+        validation::platform(platform)?;
+    }
+    if let Some(ref tags) = req.tags {
+        validation::tags(tags)?;
+    }
+
     let repo = ServerRepository::new(state.db.clone());
 
     // Get existing server
     let existing = repo.find_by_id(&id).await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
-        .ok_or_else(|| (StatusCode::NOT_FOUND, "Server not found".to_string()))?;
+        .map_err(ApiError::internal)?
+        .ok_or_else(|| ApiError::new(StatusCode::NOT_FOUND, "Server not found"))?;
 
     // Use new values or keep existing
     let name = req.name.as_deref().unwrap_or(&existing.name);
     let host = req.host.as_deref().unwrap_or(&existing.host);
     let port = req.port.unwrap_or(existing.port);
     let username = req.username.as_deref().unwrap_or(&existing.username);
-    let ssh_key = req.ssh_key.as_deref().or(existing.ssh_key_encrypted.as_deref());
     let is_local = req.is_local.unwrap_or(existing.is_local);
 
-    let server = repo.update(&id, name, host, port, username, ssh_key, is_local)
+    // Only encrypt a newly supplied key; the stored one is already encrypted
+    let ssh_key = match encrypt_ssh_key(&state, req.ssh_key.as_deref())? {
+        Some(encrypted) => Some(encrypted),
+        None => existing.ssh_key_encrypted.clone(),
+    };
+
+    let mut server = repo.update(&id, name, host, port, username, ssh_key.as_deref(), is_local)
         .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        .map_err(ApiError::internal)?;
+
+    if let Some(platform) = req.platform {
+        let platform = Some(platform).filter(|p| !p.is_empty());
+        repo.set_platform(&id, platform.as_deref())
+            .await
+            .map_err(ApiError::internal)?;
+        server.platform = platform;
+    }
+    if let Some(tags) = req.tags {
+        repo.set_tags(&id, &tags)
+            .await
+            .map_err(ApiError::internal)?;
+        server.tags = tags;
+    }
 
     Ok(Json(ServerResponse { server }))
 }
@@ -148,20 +243,23 @@ async fn update_server(
 async fn delete_server(
     State(state): State<SharedState>,
     headers: HeaderMap,
+    client_ip: ClientIp,
     Path(id): Path<String>,
-) -> Result<StatusCode, (StatusCode, String)> {
+) -> Result<StatusCode, ApiError> {
     // Validate auth
-    extract_user_id(&headers, &state.config.auth.jwt_secret)?;
+    let user_id = extract_user_id(&headers, &state.config.auth.jwt_secret)?;
 
     let repo = ServerRepository::new(state.db.clone());
 
     // Check if server exists
     repo.find_by_id(&id).await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
-        .ok_or_else(|| (StatusCode::NOT_FOUND, "Server not found".to_string()))?;
+        .map_err(ApiError::internal)?
+        .ok_or_else(|| ApiError::new(StatusCode::NOT_FOUND, "Server not found"))?;
 
     repo.delete(&id).await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        .map_err(ApiError::internal)?;
+
+    audit::record(&state.db, &user_id, AuditAction::ServerDelete, &id, &client_ip).await;
 
     Ok(StatusCode::NO_CONTENT)
 }
@@ -169,30 +267,156 @@ async fn delete_server(
 #[derive(Debug, Serialize)]
 struct ServerResourcesResponse {
     stats: ployer_server::LocalStats,
+    /// None when Docker isn't available
+    docker_disk: Option<DockerDiskUsage>,
 }
 
 async fn get_server_resources(
     State(state): State<SharedState>,
     headers: HeaderMap,
     Path(id): Path<String>,
-) -> Result<Json<ServerResourcesResponse>, (StatusCode, String)> {
+) -> Result<Json<ServerResourcesResponse>, ApiError> {
     // Validate auth
     extract_user_id(&headers, &state.config.auth.jwt_secret)?;
 
     let repo = ServerRepository::new(state.db.clone());
     let server = repo.find_by_id(&id).await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
-        .ok_or_else(|| (StatusCode::NOT_FOUND, "Server not found".to_string()))?;
+        .map_err(ApiError::internal)?
+        .ok_or_else(|| ApiError::new(StatusCode::NOT_FOUND, "Server not found"))?;
 
     // Only local servers supported for now
     if !server.is_local {
-        return Err((StatusCode::NOT_IMPLEMENTED, "Resource stats only available for local servers".to_string()));
+        return Err(ApiError::new(StatusCode::NOT_IMPLEMENTED, "Resource stats only available for local servers"));
     }
 
+    // Disk stats come from the filesystem Docker stores images and volumes on,
+    // or the build directory when Docker's root isn't visible from here
+    let (docker_root, docker_disk) = match &state.docker {
+        Some(docker) => (
+            docker.root_dir().await.ok().flatten(),
+            docker.disk_usage().await.ok(),
+        ),
+        None => (None, None),
+    };
+    let data_dir = docker_root
+        .map(PathBuf::from)
+        .filter(|p| p.exists())
+        .unwrap_or_else(|| PathBuf::from("/tmp/ployer-builds"));
+
     let mut manager = ServerManager::new();
-    let stats = manager.local_stats();
+    let stats = manager.local_stats(&data_dir);
+
+    Ok(Json(ServerResourcesResponse { stats, docker_disk }))
+}
+
+#[derive(Debug, Serialize)]
+struct ServerContainer {
+    #[serde(flatten)]
+    container: ContainerInfo,
+    application_id: String,
+    application_name: String,
+}
+
+#[derive(Debug, Serialize)]
+struct ServerContainersResponse {
+    containers: Vec<ServerContainer>,
+}
+
+/// Keep the containers owned by an application on `server_id`, paired with that application
+fn server_containers(
+    containers: Vec<ContainerInfo>,
+    applications: &[Application],
+    server_id: &str,
+) -> Vec<ServerContainer> {
+    let names: HashMap<&str, &str> = applications
+        .iter()
+        .filter(|app| app.server_id == server_id)
+        .map(|app| (app.id.as_str(), app.name.as_str()))
+        .collect();
+
+    containers
+        .into_iter()
+        .filter_map(|container| {
+            let app_id = container.labels.get(LABEL_APP_ID)?.clone();
+            let name = names.get(app_id.as_str())?.to_string();
+            Some(ServerContainer {
+                container,
+                application_id: app_id,
+                application_name: name,
+            })
+        })
+        .collect()
+}
+
+async fn list_server_containers(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> Result<Json<ServerContainersResponse>, ApiError> {
+    // Validate auth
+    extract_user_id(&headers, &state.config.auth.jwt_secret)?;
 
-    Ok(Json(ServerResourcesResponse { stats }))
+    let repo = ServerRepository::new(state.db.clone());
+    let server = repo.find_by_id(&id).await
+        .map_err(ApiError::internal)?
+        .ok_or_else(|| ApiError::new(StatusCode::NOT_FOUND, "Server not found"))?;
+
+    // Only local servers supported for now
+    if !server.is_local {
+        return Err(ApiError::new(StatusCode::NOT_IMPLEMENTED, "Container listing only available for local servers"));
+    }
+
+    let docker = state
+        .docker
+        .as_ref()
+        .ok_or_else(|| ApiError::new(StatusCode::SERVICE_UNAVAILABLE, "Docker not available"))?;
+
+    let filters = ContainerFilters {
+        label: Some(LABEL_APP_ID.to_string()),
+        ..Default::default()
+    };
+    let containers = docker
+        .list_containers(true, &filters)
+        .await
+        .map_err(ApiError::internal)?;
+
+    // Archived apps are included so their stopped containers still get a name
+    let applications = ApplicationRepository::new(state.db.clone())
+        .list(true)
+        .await
+        .map_err(ApiError::internal)?;
+
+    Ok(Json(ServerContainersResponse {
+        containers: server_containers(containers, &applications, &server.id),
+    }))
+}
+
+#[derive(Debug, Serialize)]
+struct ServerApplicationsResponse {
+    applications: Vec<Application>,
+}
+
+/// Applications assigned to the server, newest first; archived ones are left out
+async fn list_server_applications(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> Result<Json<ServerApplicationsResponse>, ApiError> {
+    // Validate auth
+    extract_user_id(&headers, &state.config.auth.jwt_secret)?;
+
+    ServerRepository::new(state.db.clone())
+        .find_by_id(&id)
+        .await
+        .map_err(ApiError::internal)?
+        .ok_or_else(|| ApiError::new(StatusCode::NOT_FOUND, "Server not found"))?;
+
+    let applications = ApplicationRepository::new(state.db.clone())
+        .list_by_server(&id)
+        .await
+        .map_err(ApiError::internal)?;
+
+    Ok(Json(ServerApplicationsResponse { applications }))
 }
 
 #[derive(Debug, Serialize)]
@@ -205,38 +429,126 @@ async fn validate_server(
     State(state): State<SharedState>,
     headers: HeaderMap,
     Path(id): Path<String>,
-) -> Result<Json<ValidateServerResponse>, (StatusCode, String)> {
+) -> Result<Json<ValidateServerResponse>, ApiError> {
     // Validate auth
     extract_user_id(&headers, &state.config.auth.jwt_secret)?;
 
     let repo = ServerRepository::new(state.db.clone());
     let server = repo.find_by_id(&id).await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
-        .ok_or_else(|| (StatusCode::NOT_FOUND, "Server not found".to_string()))?;
+        .map_err(ApiError::internal)?
+        .ok_or_else(|| ApiError::new(StatusCode::NOT_FOUND, "Server not found"))?;
+
+    let ssh_key = match &server.ssh_key_encrypted {
+        Some(encrypted) => Some(
+            crypto::decrypt(encrypted, &state.config.get_secret_key())
+                .map_err(|e| ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, format!("Decryption failed: {}", e)))?,
+        ),
+        None => None,
+    };
 
     // Test connection
     let reachable = ServerManager::test_ssh_connection(
         &server.host,
         server.port,
         &server.username,
-        server.ssh_key_encrypted.as_deref(),
+        ssh_key.as_deref(),
     )
     .await
-    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    .map_err(ApiError::internal)?;
 
     // Update server status
-    let new_status = if reachable {
-        ServerStatus::Online
-    } else {
-        ServerStatus::Offline
-    };
+    let docker_up = health_monitor::docker_up(&server, state.docker.as_deref()).await;
+    let new_status = ServerStatus::from_checks(reachable, docker_up);
 
     repo.update_status(&id, new_status.clone(), chrono::Utc::now())
         .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        .map_err(ApiError::internal)?;
+
+    if server.status != new_status {
+        let _ = state.ws_broadcast.send(WsEvent::ServerHealth {
+            server_id: id.clone(),
+            status: new_status.clone(),
+        });
+    }
 
     Ok(Json(ValidateServerResponse {
         reachable,
         status: new_status.as_str().to_string(),
     }))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ployer_core::models::{AppStatus, BuildStrategy};
+
+    fn app(id: &str, name: &str, server_id: &str) -> Application {
+        let now = chrono::Utc::now();
+        Application {
+            id: id.to_string(),
+            name: name.to_string(),
+            server_id: server_id.to_string(),
+            git_url: None,
+            git_branch: "main".to_string(),
+            git_submodules: false,
+            build_strategy: BuildStrategy::Dockerfile,
+            dockerfile_path: None,
+            docker_target: None,
+            image: None,
+            port: None,
+            ports: Vec::new(),
+            status: AppStatus::Running,
+            auto_deploy: false,
+            deploy_schedule: None,
+            restart_policy: "unless-stopped".to_string(),
+            stop_grace_seconds: 10,
+            pre_build_cmd: None,
+            post_deploy_cmd: None,
+            network_name: None,
+            archived_at: None,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    fn container(id: &str, app_id: Option<&str>) -> ContainerInfo {
+        ContainerInfo {
+            id: id.to_string(),
+            name: format!("ployer-{}", id),
+            image: "web:latest".to_string(),
+            state: "running".to_string(),
+            status: "Up 5 minutes".to_string(),
+            created: 0,
+            ports: Vec::new(),
+            labels: app_id
+                .map(|app_id| HashMap::from([(LABEL_APP_ID.to_string(), app_id.to_string())]))
+                .unwrap_or_default(),
+            restart_count: None,
+            exit_code: None,
+            oom_killed: None,
+            size_rw: None,
+            size_root_fs: None,
+        }
+    }
+
+    #[test]
+    fn test_server_containers() {
+        let applications = vec![app("a1", "web", "s1"), app("a2", "api", "s2")];
+        let containers = vec![
+            container("c1", Some("a1")),
+            container("c2", Some("a2")),
+            container("c3", Some("deleted")),
+            container("c4", None),
+        ];
+
+        let listed = server_containers(containers, &applications, "s1");
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].container.id, "c1");
+        assert_eq!(listed[0].application_id, "a1");
+        assert_eq!(listed[0].application_name, "web");
+
+        let json = serde_json::to_value(&listed[0]).unwrap();
+        assert_eq!(json["name"], "ployer-c1");
+        assert_eq!(json["application_name"], "web");
+    }
+}
@@ -1,6 +1,6 @@
 use axum::{
     extract::State,
-    http::{HeaderMap, StatusCode},
+    http::HeaderMap,
     routing::get,
     Json, Router,
 };
@@ -9,6 +9,7 @@ use ployer_db::repositories::SettingsRepository;
 
 use crate::app_state::SharedState;
 use crate::auth::extract_user_id;
+use crate::error::ApiError;
 
 pub fn router() -> Router<SharedState> {
     Router::new()
@@ -28,14 +29,14 @@ struct UpdateSettingsRequest {
 async fn get_settings(
     State(state): State<SharedState>,
     headers: HeaderMap,
-) -> Result<Json<SettingsResponse>, (StatusCode, String)> {
+) -> Result<Json<SettingsResponse>, ApiError> {
     extract_user_id(&headers, &state.config.auth.jwt_secret)?;
 
     let repo = SettingsRepository::new(state.db.clone());
     let allow_registration = repo
         .allow_registration()
         .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        .map_err(ApiError::internal)?;
 
     Ok(Json(SettingsResponse { allow_registration }))
 }
@@ -44,14 +45,14 @@ async fn update_settings(
     State(state): State<SharedState>,
     headers: HeaderMap,
     Json(req): Json<UpdateSettingsRequest>,
-) -> Result<Json<SettingsResponse>, (StatusCode, String)> {
+) -> Result<Json<SettingsResponse>, ApiError> {
     extract_user_id(&headers, &state.config.auth.jwt_secret)?;
 
     let repo = SettingsRepository::new(state.db.clone());
     let value = if req.allow_registration { "true" } else { "false" };
     repo.set("allow_registration", value)
         .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        .map_err(ApiError::internal)?;
 
     Ok(Json(SettingsResponse {
         allow_registration: req.allow_registration,
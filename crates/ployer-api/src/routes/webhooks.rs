@@ -5,9 +5,8 @@ use axum::{
     routing::{get, post},
     Json, Router,
 };
-use ployer_core::models::{WebhookProvider, WebhookDeliveryStatus};
+use ployer_core::models::{Webhook, WebhookProvider, WebhookDeliveryStatus};
 use serde::{Deserialize, Serialize};
-use std::sync::Arc;
 use uuid::Uuid;
 
 use crate::app_state::SharedState;
@@ -15,19 +14,27 @@ use crate::auth::extract_user_id;
 use crate::services::webhook::{
     parse_github_push, parse_gitlab_push, verify_github_signature, verify_gitlab_signature,
 };
-use crate::services::DeploymentService;
-use ployer_core::crypto;
+use crate::services::{start_deployment, DeployOptions};
+use crate::error::ApiError;
+use ployer_db::repositories::DeliveryCursor;
 
 pub fn router() -> Router<SharedState> {
     Router::new()
         .route(
             "/applications/:app_id/webhooks",
-            get(get_webhook).post(create_webhook).delete(delete_webhook),
+            get(get_webhook)
+                .post(create_webhook)
+                .patch(update_webhook)
+                .delete(delete_webhook),
         )
         .route(
             "/applications/:app_id/webhooks/deliveries",
             get(list_deliveries),
         )
+        .route(
+            "/applications/:app_id/webhooks/rotate-secret",
+            post(rotate_webhook_secret),
+        )
         .route("/webhooks/github", post(handle_github_webhook))
         .route("/webhooks/gitlab", post(handle_gitlab_webhook))
 }
@@ -37,6 +44,11 @@ struct CreateWebhookRequest {
     provider: WebhookProvider,
 }
 
+#[derive(Debug, Deserialize)]
+struct UpdateWebhookRequest {
+    enabled: bool,
+}
+
 #[derive(Debug, Deserialize)]
 struct WebhookQuery {
     app_id: String,
@@ -61,18 +73,38 @@ struct DeliveryResponse {
     commit_sha: Option<String>,
     commit_message: Option<String>,
     author: Option<String>,
+    author_email: Option<String>,
+    committer_name: Option<String>,
+    committer_email: Option<String>,
+    avatar_url: Option<String>,
     status: WebhookDeliveryStatus,
     deployment_id: Option<String>,
     delivered_at: String,
 }
 
-/// Create or update webhook for an application
+#[derive(Debug, Deserialize)]
+struct ListDeliveriesQuery {
+    /// `next_cursor` from the previous page
+    before: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct ListDeliveriesResponse {
+    deliveries: Vec<DeliveryResponse>,
+    /// Pass as `before` for the next page; null on the last page
+    next_cursor: Option<String>,
+}
+
+const DELIVERIES_PAGE_SIZE: usize = 50;
+
+/// Create the webhook for an application. Re-posting returns the existing
+/// webhook unchanged; the secret only changes through `rotate-secret`.
 async fn create_webhook(
     headers: HeaderMap,
     State(state): State<SharedState>,
     Path(app_id): Path<String>,
     Json(req): Json<CreateWebhookRequest>,
-) -> Result<impl IntoResponse, (StatusCode, String)> {
+) -> Result<impl IntoResponse, ApiError> {
     extract_user_id(&headers, &state.config.auth.jwt_secret)?;
 
     let webhook_repo = ployer_db::repositories::WebhookRepository::new(state.db.clone());
@@ -82,54 +114,78 @@ async fn create_webhook(
     app_repo
         .find_by_id(&app_id)
         .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
-        .ok_or_else(|| (StatusCode::NOT_FOUND, "Application not found".to_string()))?;
-
-    // Generate webhook secret
-    let secret = Uuid::new_v4().to_string();
+        .map_err(ApiError::internal)?
+        .ok_or_else(|| ApiError::new(StatusCode::NOT_FOUND, "Application not found"))?;
 
-    // Check if webhook already exists
     let existing = webhook_repo
         .find_by_application(&app_id)
         .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        .map_err(ApiError::internal)?;
+
+    let webhook = match existing {
+        Some(existing) if existing.provider != req.provider => {
+            return Err(ApiError::new(
+                StatusCode::CONFLICT,
+                "Application already has a webhook for another provider; delete it first",
+            ));
+        }
+        Some(existing) => existing,
+        None => {
+            let secret = Uuid::new_v4().to_string();
+            webhook_repo
+                .create(&app_id, req.provider.clone(), &secret)
+                .await
+                .map_err(ApiError::internal)?
+        }
+    };
 
-    let webhook = if let Some(_existing) = existing {
-        // Update existing webhook
-        webhook_repo
-            .update_secret(&app_id, &secret)
-            .await
-            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    Ok(Json(webhook_response(&state, webhook)))
+}
 
-        webhook_repo
-            .find_by_application(&app_id)
-            .await
-            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
-            .ok_or_else(|| (StatusCode::INTERNAL_SERVER_ERROR, "Failed to fetch webhook".to_string()))?
-    } else {
-        // Create new webhook
-        webhook_repo
-            .create(&app_id, req.provider.clone(), &secret)
-            .await
-            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
-    };
+/// Replace the webhook's secret, keeping its URL and settings
+async fn rotate_webhook_secret(
+    headers: HeaderMap,
+    State(state): State<SharedState>,
+    Path(app_id): Path<String>,
+) -> Result<impl IntoResponse, ApiError> {
+    extract_user_id(&headers, &state.config.auth.jwt_secret)?;
+
+    let webhook_repo = ployer_db::repositories::WebhookRepository::new(state.db.clone());
+
+    let webhook = webhook_repo
+        .find_by_application(&app_id)
+        .await
+        .map_err(ApiError::internal)?
+        .ok_or_else(|| ApiError::new(StatusCode::NOT_FOUND, "Webhook not found"))?;
+
+    let secret = Uuid::new_v4().to_string();
+    webhook_repo
+        .update_secret(&app_id, &secret)
+        .await
+        .map_err(ApiError::internal)?;
+
+    Ok(Json(webhook_response(&state, Webhook { secret, ..webhook })))
+}
 
-    // Construct webhook URL based on provider
-    let webhook_url = match req.provider {
-        WebhookProvider::GitHub => format!("{}/api/v1/webhooks/github?app_id={}",
-            state.config.server.public_url, app_id),
-        WebhookProvider::GitLab => format!("{}/api/v1/webhooks/gitlab?app_id={}",
-            state.config.server.public_url, app_id),
+/// Webhook config as returned to the client, with its delivery URL
+fn webhook_response(state: &SharedState, webhook: Webhook) -> WebhookResponse {
+    let path = match webhook.provider {
+        WebhookProvider::GitHub => "github",
+        WebhookProvider::GitLab => "gitlab",
     };
+    let webhook_url = format!(
+        "{}/api/v1/webhooks/{}?app_id={}",
+        state.config.server.public_url, path, webhook.application_id
+    );
 
-    Ok(Json(WebhookResponse {
+    WebhookResponse {
         id: webhook.id,
         application_id: webhook.application_id,
         provider: webhook.provider,
         webhook_url,
         secret: webhook.secret,
         enabled: webhook.enabled,
-    }))
+    }
 }
 
 /// Get webhook details for an application
@@ -137,7 +193,7 @@ async fn get_webhook(
     headers: HeaderMap,
     State(state): State<SharedState>,
     Path(app_id): Path<String>,
-) -> Result<impl IntoResponse, (StatusCode, String)> {
+) -> Result<impl IntoResponse, ApiError> {
     extract_user_id(&headers, &state.config.auth.jwt_secret)?;
 
     let webhook_repo = ployer_db::repositories::WebhookRepository::new(state.db.clone());
@@ -145,24 +201,41 @@ async fn get_webhook(
     let webhook = webhook_repo
         .find_by_application(&app_id)
         .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
-        .ok_or_else(|| (StatusCode::NOT_FOUND, "Webhook not found".to_string()))?;
-
-    let webhook_url = match webhook.provider {
-        WebhookProvider::GitHub => format!("{}/api/v1/webhooks/github?app_id={}",
-            state.config.server.public_url, app_id),
-        WebhookProvider::GitLab => format!("{}/api/v1/webhooks/gitlab?app_id={}",
-            state.config.server.public_url, app_id),
-    };
+        .map_err(ApiError::internal)?
+        .ok_or_else(|| ApiError::new(StatusCode::NOT_FOUND, "Webhook not found"))?;
 
-    Ok(Json(WebhookResponse {
-        id: webhook.id,
-        application_id: webhook.application_id,
-        provider: webhook.provider,
-        webhook_url,
-        secret: webhook.secret,
-        enabled: webhook.enabled,
-    }))
+    Ok(Json(webhook_response(&state, webhook)))
+}
+
+/// Pause or resume auto-deploys without discarding the webhook's URL and secret
+async fn update_webhook(
+    headers: HeaderMap,
+    State(state): State<SharedState>,
+    Path(app_id): Path<String>,
+    Json(req): Json<UpdateWebhookRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    extract_user_id(&headers, &state.config.auth.jwt_secret)?;
+
+    let webhook_repo = ployer_db::repositories::WebhookRepository::new(state.db.clone());
+
+    webhook_repo
+        .find_by_application(&app_id)
+        .await
+        .map_err(ApiError::internal)?
+        .ok_or_else(|| ApiError::new(StatusCode::NOT_FOUND, "Webhook not found"))?;
+
+    webhook_repo
+        .toggle_enabled(&app_id, req.enabled)
+        .await
+        .map_err(ApiError::internal)?;
+
+    let webhook = webhook_repo
+        .find_by_application(&app_id)
+        .await
+        .map_err(ApiError::internal)?
+        .ok_or_else(|| ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, "Failed to fetch webhook"))?;
+
+    Ok(Json(webhook_response(&state, webhook)))
 }
 
 /// Delete webhook for an application
@@ -170,7 +243,7 @@ async fn delete_webhook(
     headers: HeaderMap,
     State(state): State<SharedState>,
     Path(app_id): Path<String>,
-) -> Result<StatusCode, (StatusCode, String)> {
+) -> Result<StatusCode, ApiError> {
     extract_user_id(&headers, &state.config.auth.jwt_secret)?;
 
     let webhook_repo = ployer_db::repositories::WebhookRepository::new(state.db.clone());
@@ -178,27 +251,45 @@ async fn delete_webhook(
     webhook_repo
         .delete(&app_id)
         .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        .map_err(ApiError::internal)?;
 
     Ok(StatusCode::NO_CONTENT)
 }
 
-/// List webhook deliveries for an application
+/// List webhook deliveries for an application, a page at a time, newest first
 async fn list_deliveries(
     headers: HeaderMap,
     State(state): State<SharedState>,
     Path(app_id): Path<String>,
-) -> Result<impl IntoResponse, (StatusCode, String)> {
+    Query(query): Query<ListDeliveriesQuery>,
+) -> Result<impl IntoResponse, ApiError> {
     extract_user_id(&headers, &state.config.auth.jwt_secret)?;
 
+    let before = query
+        .before
+        .as_deref()
+        .map(|cursor| {
+            DeliveryCursor::decode(cursor)
+                .ok_or_else(|| ApiError::new(StatusCode::BAD_REQUEST, "Invalid cursor"))
+        })
+        .transpose()?;
+
     let webhook_repo = ployer_db::repositories::WebhookRepository::new(state.db.clone());
 
-    let deliveries = webhook_repo
-        .list_deliveries(&app_id, 50)
+    // One extra row tells whether there is another page
+    let mut deliveries = webhook_repo
+        .list_deliveries(&app_id, DELIVERIES_PAGE_SIZE as i64 + 1, before.as_ref())
         .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        .map_err(ApiError::internal)?;
+
+    let next_cursor = if deliveries.len() > DELIVERIES_PAGE_SIZE {
+        deliveries.truncate(DELIVERIES_PAGE_SIZE);
+        deliveries.last().map(|d| DeliveryCursor::after(d).encode())
+    } else {
+        None
+    };
 
-    let response: Vec<DeliveryResponse> = deliveries
+    let deliveries: Vec<DeliveryResponse> = deliveries
         .into_iter()
         .map(|d| DeliveryResponse {
             id: d.id,
@@ -208,13 +299,17 @@ async fn list_deliveries(
             commit_sha: d.commit_sha,
             commit_message: d.commit_message,
             author: d.author,
+            author_email: d.author_email,
+            committer_name: d.committer_name,
+            committer_email: d.committer_email,
+            avatar_url: d.avatar_url,
             status: d.status,
             deployment_id: d.deployment_id,
             delivered_at: d.delivered_at.to_rfc3339(),
         })
         .collect();
 
-    Ok(Json(response))
+    Ok(Json(ListDeliveriesResponse { deliveries, next_cursor }))
 }
 
 /// Handle GitHub webhook
@@ -223,7 +318,7 @@ async fn handle_github_webhook(
     Query(query): Query<WebhookQuery>,
     headers: HeaderMap,
     body: axum::body::Bytes,
-) -> Result<StatusCode, (StatusCode, String)> {
+) -> Result<StatusCode, ApiError> {
     let app_id = &query.app_id;
 
     let webhook_repo = ployer_db::repositories::WebhookRepository::new(state.db.clone());
@@ -233,68 +328,51 @@ async fn handle_github_webhook(
     let webhook = webhook_repo
         .find_by_application(app_id)
         .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
-        .ok_or_else(|| (StatusCode::NOT_FOUND, "Webhook not configured".to_string()))?;
+        .map_err(ApiError::internal)?
+        .ok_or_else(|| ApiError::new(StatusCode::NOT_FOUND, "Webhook not configured"))?;
 
     if !webhook.enabled {
-        return Err((StatusCode::FORBIDDEN, "Webhook is disabled".to_string()));
+        return Err(ApiError::new(StatusCode::FORBIDDEN, "Webhook is disabled"));
     }
 
     // Verify signature
     let signature = headers
         .get("x-hub-signature-256")
         .and_then(|v| v.to_str().ok())
-        .ok_or_else(|| (StatusCode::BAD_REQUEST, "Missing signature header".to_string()))?;
+        .ok_or_else(|| ApiError::new(StatusCode::BAD_REQUEST, "Missing signature header"))?;
 
     verify_github_signature(&webhook.secret, &body, signature)
-        .map_err(|e| (StatusCode::UNAUTHORIZED, e.to_string()))?;
+        .map_err(|e| ApiError::new(StatusCode::UNAUTHORIZED, e.to_string()))?;
 
     // Parse payload
     let payload = parse_github_push(&body)
-        .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?;
+        .map_err(|e| ApiError::new(StatusCode::BAD_REQUEST, e.to_string()))?;
 
     // Get application to check auto-deploy branch
     let application = app_repo
         .find_by_id(app_id)
         .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
-        .ok_or_else(|| (StatusCode::NOT_FOUND, "Application not found".to_string()))?;
+        .map_err(ApiError::internal)?
+        .ok_or_else(|| ApiError::new(StatusCode::NOT_FOUND, "Application not found"))?;
 
     // Check if this is the branch we should auto-deploy
-    let should_deploy = application.git_branch == payload.branch;
+    let should_deploy = application.git_branch == payload.branch && application.archived_at.is_none();
 
     let (status, deployment_id) = if should_deploy {
-        // Ensure Docker client is available
-        let docker = match &state.docker {
-            Some(docker) => docker.clone(),
-            None => {
-                tracing::error!("Docker client not available for auto-deploy");
-                return Err((StatusCode::INTERNAL_SERVER_ERROR, "Docker not available".to_string()));
-            }
-        };
+        if state.docker.is_none() {
+            tracing::error!("Docker client not available for auto-deploy");
+            return Err(ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, "Docker not available"));
+        }
 
-        // Get deploy key if exists
-        let deploy_key_repo = ployer_db::repositories::DeployKeyRepository::new(state.db.clone());
-        let private_key = if let Ok(Some(key)) = deploy_key_repo.find_by_application(&application.id).await {
-            let secret_key = state.config.get_secret_key();
-            crypto::decrypt(&key.private_key_encrypted, &secret_key).ok()
-        } else {
-            None
+        let options = DeployOptions {
+            commit_sha: Some(payload.commit_sha.clone()),
+            commit_message: Some(payload.commit_message.clone()),
+            ..Default::default()
         };
-
-        // Trigger deployment
-        let deploy_service = DeploymentService::new(
-            state.db.clone(),
-            docker,
-            Some(Arc::new(state.caddy.clone())),
-            state.config.server.base_domain.clone(),
-            state.ws_broadcast.clone(),
-        );
-
-        match deploy_service.deploy(application.clone(), private_key).await {
+        match start_deployment(&state, application.clone(), options).await {
             Ok(deployment) => {
                 tracing::info!("Auto-deploy triggered for app {} via GitHub webhook", app_id);
-                (WebhookDeliveryStatus::Success, Some(deployment.id))
+                (WebhookDeliveryStatus::Pending, Some(deployment.id))
             }
             Err(e) => {
                 tracing::error!("Auto-deploy failed for app {}: {}", app_id, e);
@@ -315,14 +393,14 @@ async fn handle_github_webhook(
             Some(&payload.branch),
             Some(&payload.commit_sha),
             Some(&payload.commit_message),
-            Some(&payload.author),
+            Some(&payload.commit_author()),
             status,
             Some(200),
             None,
             deployment_id.as_deref(),
         )
         .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        .map_err(ApiError::internal)?;
 
     Ok(StatusCode::OK)
 }
@@ -333,7 +411,7 @@ async fn handle_gitlab_webhook(
     Query(query): Query<WebhookQuery>,
     headers: HeaderMap,
     body: axum::body::Bytes,
-) -> Result<StatusCode, (StatusCode, String)> {
+) -> Result<StatusCode, ApiError> {
     let app_id = &query.app_id;
 
     let webhook_repo = ployer_db::repositories::WebhookRepository::new(state.db.clone());
@@ -343,68 +421,51 @@ async fn handle_gitlab_webhook(
     let webhook = webhook_repo
         .find_by_application(app_id)
         .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
-        .ok_or_else(|| (StatusCode::NOT_FOUND, "Webhook not configured".to_string()))?;
+        .map_err(ApiError::internal)?
+        .ok_or_else(|| ApiError::new(StatusCode::NOT_FOUND, "Webhook not configured"))?;
 
     if !webhook.enabled {
-        return Err((StatusCode::FORBIDDEN, "Webhook is disabled".to_string()));
+        return Err(ApiError::new(StatusCode::FORBIDDEN, "Webhook is disabled"));
     }
 
     // Verify token
     let token = headers
         .get("x-gitlab-token")
         .and_then(|v| v.to_str().ok())
-        .ok_or_else(|| (StatusCode::BAD_REQUEST, "Missing GitLab token header".to_string()))?;
+        .ok_or_else(|| ApiError::new(StatusCode::BAD_REQUEST, "Missing GitLab token header"))?;
 
     verify_gitlab_signature(&webhook.secret, token)
-        .map_err(|e| (StatusCode::UNAUTHORIZED, e.to_string()))?;
+        .map_err(|e| ApiError::new(StatusCode::UNAUTHORIZED, e.to_string()))?;
 
     // Parse payload
     let payload = parse_gitlab_push(&body)
-        .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?;
+        .map_err(|e| ApiError::new(StatusCode::BAD_REQUEST, e.to_string()))?;
 
     // Get application to check auto-deploy branch
     let application = app_repo
         .find_by_id(app_id)
         .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
-        .ok_or_else(|| (StatusCode::NOT_FOUND, "Application not found".to_string()))?;
+        .map_err(ApiError::internal)?
+        .ok_or_else(|| ApiError::new(StatusCode::NOT_FOUND, "Application not found"))?;
 
     // Check if this is the branch we should auto-deploy
-    let should_deploy = application.git_branch == payload.branch;
+    let should_deploy = application.git_branch == payload.branch && application.archived_at.is_none();
 
     let (status, deployment_id) = if should_deploy {
-        // Ensure Docker client is available
-        let docker = match &state.docker {
-            Some(docker) => docker.clone(),
-            None => {
-                tracing::error!("Docker client not available for auto-deploy");
-                return Err((StatusCode::INTERNAL_SERVER_ERROR, "Docker not available".to_string()));
-            }
-        };
+        if state.docker.is_none() {
+            tracing::error!("Docker client not available for auto-deploy");
+            return Err(ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, "Docker not available"));
+        }
 
-        // Get deploy key if exists
-        let deploy_key_repo = ployer_db::repositories::DeployKeyRepository::new(state.db.clone());
-        let private_key = if let Ok(Some(key)) = deploy_key_repo.find_by_application(&application.id).await {
-            let secret_key = state.config.get_secret_key();
-            crypto::decrypt(&key.private_key_encrypted, &secret_key).ok()
-        } else {
-            None
+        let options = DeployOptions {
+            commit_sha: Some(payload.commit_sha.clone()),
+            commit_message: Some(payload.commit_message.clone()),
+            ..Default::default()
         };
-
-        // Trigger deployment
-        let deploy_service = DeploymentService::new(
-            state.db.clone(),
-            docker,
-            Some(Arc::new(state.caddy.clone())),
-            state.config.server.base_domain.clone(),
-            state.ws_broadcast.clone(),
-        );
-
-        match deploy_service.deploy(application.clone(), private_key).await {
+        match start_deployment(&state, application.clone(), options).await {
             Ok(deployment) => {
                 tracing::info!("Auto-deploy triggered for app {} via GitLab webhook", app_id);
-                (WebhookDeliveryStatus::Success, Some(deployment.id))
+                (WebhookDeliveryStatus::Pending, Some(deployment.id))
             }
             Err(e) => {
                 tracing::error!("Auto-deploy failed for app {}: {}", app_id, e);
@@ -425,14 +486,59 @@ async fn handle_gitlab_webhook(
             Some(&payload.branch),
             Some(&payload.commit_sha),
             Some(&payload.commit_message),
-            Some(&payload.author),
+            Some(&payload.commit_author()),
             status,
             Some(200),
             None,
             deployment_id.as_deref(),
         )
         .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        .map_err(ApiError::internal)?;
 
     Ok(StatusCode::OK)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app_state::AppState;
+    use ployer_core::config::AppConfig;
+    use ployer_db::repositories::WebhookRepository;
+    use ployer_proxy::CaddyClient;
+
+    #[tokio::test]
+    async fn test_disabled_webhook_is_rejected_without_deploying() {
+        let db = ployer_db::memory_pool().await.unwrap();
+        for statement in [
+            "INSERT INTO servers (id, name, host, created_at, updated_at) VALUES ('local', 'local', 'localhost', '2024-01-01T00:00:00Z', '2024-01-01T00:00:00Z')",
+            "INSERT INTO applications (id, name, server_id, created_at, updated_at) VALUES ('a1', 'web', 'local', '2024-01-01T00:00:00Z', '2024-01-01T00:00:00Z')",
+        ] {
+            sqlx::query(statement).execute(&db).await.unwrap();
+        }
+
+        let repo = WebhookRepository::new(db.clone());
+        repo.create("a1", WebhookProvider::GitHub, "secret").await.unwrap();
+        repo.toggle_enabled("a1", false).await.unwrap();
+
+        let state = AppState::new(
+            db.clone(),
+            None,
+            CaddyClient::new("http://127.0.0.1:1", "/tmp/Caddyfile", &Default::default()).unwrap(),
+            AppConfig::default(),
+        );
+        let result = handle_github_webhook(
+            State(state),
+            Query(WebhookQuery { app_id: "a1".to_string() }),
+            HeaderMap::new(),
+            axum::body::Bytes::from_static(b"{}"),
+        )
+        .await;
+
+        assert_eq!(result.unwrap_err().status, StatusCode::FORBIDDEN);
+        let deliveries: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM webhook_deliveries")
+            .fetch_one(&db)
+            .await
+            .unwrap();
+        assert_eq!(deliveries, 0);
+    }
+}
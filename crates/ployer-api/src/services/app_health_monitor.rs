@@ -1,4 +1,6 @@
-use ployer_core::models::{HealthCheckStatus, WsEvent};
+use futures_util::stream::{self, StreamExt};
+use ployer_core::http::build_http_client;
+use ployer_core::models::{AppAlertKind, HealthCheck, HealthCheckStatus, HealthCheckType, WsEvent};
 use ployer_db::repositories::{ApplicationRepository, DeploymentRepository, HealthCheckRepository};
 use ployer_docker::DockerClient;
 use sqlx::SqlitePool;
@@ -7,6 +9,9 @@ use std::time::Duration;
 use tokio::sync::broadcast;
 use tracing::{debug, info, warn};
 
+/// Apps probed at once, so one hanging check can't stall the whole cycle
+const MAX_CONCURRENT_CHECKS: usize = 10;
+
 pub fn spawn_app_health_monitor(
     db: SqlitePool,
     docker: Option<Arc<DockerClient>>,
@@ -39,118 +44,163 @@ async fn check_application_health(
     let app_repo = ApplicationRepository::new(db.clone());
     let deployment_repo = DeploymentRepository::new(db.clone());
 
-    let health_checks = health_repo.list().await?;
+    // Archived apps have no container left to probe
+    let health_checks = health_repo.list_active().await?;
 
-    for health_check in health_checks {
-        // Get the application
-        let app = match app_repo.find_by_id(&health_check.application_id).await? {
-            Some(app) => app,
-            None => continue,
-        };
+    stream::iter(health_checks)
+        .map(|health_check| {
+            check_app(docker, ws_broadcast, &health_repo, &app_repo, &deployment_repo, health_check)
+        })
+        .buffer_unordered(MAX_CONCURRENT_CHECKS)
+        .for_each(|result| async move {
+            if let Err(e) = result {
+                warn!("Application health check error: {}", e);
+            }
+        })
+        .await;
+
+    Ok(())
+}
+
+/// Check one application, record the result and restart its container if needed
+async fn check_app(
+    docker: &DockerClient,
+    ws_broadcast: &broadcast::Sender<WsEvent>,
+    health_repo: &HealthCheckRepository,
+    app_repo: &ApplicationRepository,
+    deployment_repo: &DeploymentRepository,
+    health_check: HealthCheck,
+) -> anyhow::Result<()> {
+    // Get the application
+    let app = match app_repo.find_by_id(&health_check.application_id).await? {
+        Some(app) => app,
+        None => return Ok(()),
+    };
+
+    // Get the latest running deployment
+    let deployment = match deployment_repo.get_latest_running(&app.id).await? {
+        Some(deployment) => deployment,
+        None => {
+            debug!("No running deployment for app {}", app.name);
+            return Ok(());
+        }
+    };
+
+    let container_id = match &deployment.container_id {
+        Some(id) => id,
+        None => return Ok(()),
+    };
 
-        // Get the latest running deployment
-        let deployment = match deployment_repo.get_latest_running(&app.id).await? {
-            Some(deployment) => deployment,
-            None => {
-                debug!("No running deployment for app {}", app.name);
-                continue;
+    // Get the old status
+    let old_status = health_repo
+        .get_latest_status(&app.id)
+        .await?
+        .unwrap_or(HealthCheckStatus::Unknown);
+
+    // Perform health check
+    let (new_status, response_time_ms, status_code, mut error_message) =
+        perform_health_check(docker, container_id, &health_check).await;
+
+    // A failed check may be explained by the container itself having died
+    if new_status != HealthCheckStatus::Healthy {
+        let state = docker.inspect_container_cached(container_id).await.ok().and_then(|c| c.state);
+        let alert = state.and_then(|s| container_alert(s.oom_killed, s.running, s.exit_code));
+
+        if let Some((kind, detail)) = alert {
+            // Only alert when the reason changes, not on every failed check
+            let previous = health_repo.get_recent_results(&app.id, 1).await?;
+            let already_reported = previous
+                .first()
+                .is_some_and(|r| r.error_message.as_deref() == Some(detail.as_str()));
+
+            if !already_reported {
+                warn!("App {} container {}: {}", app.name, container_id, detail);
+                let _ = ws_broadcast.send(WsEvent::AppAlert {
+                    app_id: app.id.clone(),
+                    kind,
+                    detail: detail.clone(),
+                });
             }
-        };
+            error_message = Some(detail);
+        }
+    }
 
-        let container_id = match &deployment.container_id {
-            Some(id) => id,
-            None => continue,
-        };
+    // Record the result
+    health_repo
+        .record_result(
+            &app.id,
+            container_id,
+            new_status.clone(),
+            response_time_ms,
+            status_code,
+            error_message.as_deref(),
+        )
+        .await?;
 
-        // Get the old status
-        let old_status = health_repo
-            .get_latest_status(&app.id)
-            .await?
-            .unwrap_or(HealthCheckStatus::Unknown);
-
-        // Perform health check
-        let (new_status, response_time_ms, status_code, error_message) =
-            perform_health_check(docker, container_id, &health_check.path, health_check.timeout_seconds).await;
-
-        // Record the result
-        health_repo
-            .record_result(
-                &app.id,
-                container_id,
-                new_status.clone(),
-                response_time_ms,
-                status_code,
-                error_message.as_deref(),
-            )
+    // Broadcast WebSocket event if status changed
+    if old_status != new_status {
+        info!(
+            "App {} health: {} -> {}",
+            app.name,
+            old_status.as_str(),
+            new_status.as_str()
+        );
+
+        let _ = ws_broadcast.send(WsEvent::AppHealth {
+            app_id: app.id.clone(),
+            status: new_status.clone(),
+        });
+    }
+
+    // Auto-restart logic: check if we need to restart the container
+    if new_status == HealthCheckStatus::Unhealthy {
+        // Get recent results to count consecutive failures
+        let recent_results = health_repo
+            .get_recent_results(&app.id, health_check.unhealthy_threshold as i64)
             .await?;
 
-        // Broadcast WebSocket event if status changed
-        if old_status != new_status {
-            info!(
-                "App {} health: {} -> {}",
-                app.name,
-                old_status.as_str(),
-                new_status.as_str()
+        // Count consecutive unhealthy checks
+        let consecutive_unhealthy = recent_results
+            .iter()
+            .take_while(|r| r.status == HealthCheckStatus::Unhealthy)
+            .count();
+
+        // If threshold exceeded, restart container
+        if consecutive_unhealthy >= health_check.unhealthy_threshold as usize {
+            warn!(
+                "App {} has {} consecutive unhealthy checks, restarting container {}",
+                app.name, consecutive_unhealthy, container_id
             );
 
-            let _ = ws_broadcast.send(WsEvent::AppHealth {
-                app_id: app.id.clone(),
-                status: new_status.clone(),
-            });
-        }
+            match docker.restart_container(container_id).await {
+                Ok(_) => {
+                    info!("Successfully restarted container {} for app {}", container_id, app.name);
 
-        // Auto-restart logic: check if we need to restart the container
-        if new_status == HealthCheckStatus::Unhealthy {
-            // Get recent results to count consecutive failures
-            let recent_results = health_repo
-                .get_recent_results(&app.id, health_check.unhealthy_threshold as i64)
-                .await?;
-
-            // Count consecutive unhealthy checks
-            let consecutive_unhealthy = recent_results
-                .iter()
-                .take_while(|r| r.status == HealthCheckStatus::Unhealthy)
-                .count();
-
-            // If threshold exceeded, restart container
-            if consecutive_unhealthy >= health_check.unhealthy_threshold as usize {
-                warn!(
-                    "App {} has {} consecutive unhealthy checks, restarting container {}",
-                    app.name, consecutive_unhealthy, container_id
-                );
-
-                match docker.restart_container(container_id).await {
-                    Ok(_) => {
-                        info!("Successfully restarted container {} for app {}", container_id, app.name);
-
-                        // Broadcast restart event
-                        let _ = ws_broadcast.send(WsEvent::AppHealth {
-                            app_id: app.id.clone(),
-                            status: HealthCheckStatus::Unknown,
-                        });
-                    }
-                    Err(e) => {
-                        warn!(
-                            "Failed to restart container {} for app {}: {}",
-                            container_id, app.name, e
-                        );
-                    }
+                    // Broadcast restart event
+                    let _ = ws_broadcast.send(WsEvent::AppHealth {
+                        app_id: app.id.clone(),
+                        status: HealthCheckStatus::Unknown,
+                    });
+                }
+                Err(e) => {
+                    warn!(
+                        "Failed to restart container {} for app {}: {}",
+                        container_id, app.name, e
+                    );
                 }
             }
         }
     }
-
     Ok(())
 }
 
 async fn perform_health_check(
     docker: &DockerClient,
     container_id: &str,
-    path: &str,
-    timeout_seconds: i32,
+    health_check: &HealthCheck,
 ) -> (HealthCheckStatus, Option<i32>, Option<i32>, Option<String>) {
     // Get container info to find the port
-    let container = match docker.inspect_container(container_id).await {
+    let container = match docker.inspect_container_cached(container_id).await {
         Ok(container) => container,
         Err(e) => {
             return (
@@ -174,7 +224,7 @@ async fn perform_health_check(
             })
         });
 
-    let port = match port {
+    let port = match port.and_then(|p| p.parse::<u16>().ok()) {
         Some(p) => p,
         None => {
             return (
@@ -186,21 +236,66 @@ async fn perform_health_check(
         }
     };
 
-    // Make HTTP request to health check endpoint
-    let url = format!("http://localhost:{}{}", port, path);
-    let client = reqwest::Client::builder()
-        .timeout(Duration::from_secs(timeout_seconds as u64))
-        .build()
-        .unwrap();
+    probe_port(health_check, port).await
+}
+
+/// Classify a container's last exit from its inspected state
+fn container_alert(
+    oom_killed: Option<bool>,
+    running: Option<bool>,
+    exit_code: Option<i64>,
+) -> Option<(AppAlertKind, String)> {
+    if oom_killed == Some(true) {
+        return Some((
+            AppAlertKind::OutOfMemory,
+            "Container was killed after running out of memory".to_string(),
+        ));
+    }
 
+    match (running, exit_code) {
+        (Some(false), Some(code)) if code != 0 => Some((
+            AppAlertKind::Crashed,
+            format!("Container exited with code {}", code),
+        )),
+        _ => None,
+    }
+}
+
+/// Probe a host port according to the health check's type
+pub(crate) async fn probe_port(
+    health_check: &HealthCheck,
+    port: u16,
+) -> (HealthCheckStatus, Option<i32>, Option<i32>, Option<String>) {
+    let timeout = Duration::from_secs(health_check.timeout_seconds.max(1) as u64);
     let start = std::time::Instant::now();
 
-    match client.get(&url).send().await {
+    if health_check.check_type == HealthCheckType::Tcp {
+        let result = tokio::time::timeout(timeout, tokio::net::TcpStream::connect(("127.0.0.1", port))).await;
+        let response_time = start.elapsed().as_millis() as i32;
+        return match result {
+            Ok(Ok(_)) => (HealthCheckStatus::Healthy, Some(response_time), None, None),
+            Ok(Err(e)) => (HealthCheckStatus::Unhealthy, Some(response_time), None, Some(e.to_string())),
+            Err(_) => (
+                HealthCheckStatus::Unhealthy,
+                Some(response_time),
+                None,
+                Some("Connection timed out".to_string()),
+            ),
+        };
+    }
+
+    // Make HTTP request to health check endpoint
+    let url = format!("http://localhost:{}{}", port, health_check.path);
+    let client = build_http_client(None, timeout).unwrap();
+
+    let method = reqwest::Method::from_bytes(health_check.method.as_bytes()).unwrap_or(reqwest::Method::GET);
+
+    match client.request(method, &url).send().await {
         Ok(response) => {
             let response_time = start.elapsed().as_millis() as i32;
             let status_code = response.status().as_u16() as i32;
 
-            let status = if response.status().is_success() {
+            let status = if health_check.accepts_status(response.status().as_u16()) {
                 HealthCheckStatus::Healthy
             } else {
                 HealthCheckStatus::Unhealthy
@@ -219,3 +314,63 @@ async fn perform_health_check(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tcp_check() -> HealthCheck {
+        HealthCheck {
+            id: "hc1".to_string(),
+            application_id: "a1".to_string(),
+            check_type: HealthCheckType::Tcp,
+            path: "/".to_string(),
+            method: "GET".to_string(),
+            expected_status: "2xx".to_string(),
+            interval_seconds: 15,
+            timeout_seconds: 1,
+            healthy_threshold: 1,
+            unhealthy_threshold: 3,
+            created_at: chrono::Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_container_alert_classifies_exit() {
+        let (kind, detail) = container_alert(Some(true), Some(false), Some(137)).unwrap();
+        assert_eq!(kind, AppAlertKind::OutOfMemory);
+        assert!(detail.contains("out of memory"));
+
+        let (kind, detail) = container_alert(Some(false), Some(false), Some(1)).unwrap();
+        assert_eq!(kind, AppAlertKind::Crashed);
+        assert_eq!(detail, "Container exited with code 1");
+
+        // Clean exits and running containers aren't crashes
+        assert!(container_alert(Some(false), Some(false), Some(0)).is_none());
+        assert!(container_alert(Some(false), Some(true), Some(0)).is_none());
+        assert!(container_alert(None, None, None).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_tcp_probe_healthy_when_port_accepts() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        let (status, response_time, status_code, error) = probe_port(&tcp_check(), port).await;
+        assert_eq!(status, HealthCheckStatus::Healthy);
+        assert!(response_time.is_some());
+        assert_eq!(status_code, None);
+        assert_eq!(error, None);
+    }
+
+    #[tokio::test]
+    async fn test_tcp_probe_unhealthy_when_port_closed() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        drop(listener);
+
+        let (status, _, _, error) = probe_port(&tcp_check(), port).await;
+        assert_eq!(status, HealthCheckStatus::Unhealthy);
+        assert!(error.is_some());
+    }
+}
@@ -0,0 +1,79 @@
+use axum::{
+    async_trait,
+    extract::{ConnectInfo, FromRequestParts},
+    http::{request::Parts, HeaderMap},
+};
+use ployer_core::models::AuditAction;
+use ployer_db::repositories::AuditRepository;
+use sqlx::SqlitePool;
+use std::convert::Infallible;
+use std::net::{IpAddr, SocketAddr};
+use tracing::warn;
+
+/// Record a sensitive action in the audit log. A failure is logged rather than
+/// returned, since the action itself has already happened.
+pub async fn record(db: &SqlitePool, user_id: &str, action: AuditAction, target_id: &str, ip: &ClientIp) {
+    if let Err(e) = AuditRepository::new(db.clone())
+        .record(Some(user_id), action, target_id, ip.0.as_deref())
+        .await
+    {
+        warn!("Failed to record {} of {} by {}: {}", action.as_str(), target_id, user_id, e);
+    }
+}
+
+/// Address of the client that sent the request, if known
+#[derive(Debug, Clone, Default)]
+pub struct ClientIp(pub Option<String>);
+
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for ClientIp {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let peer = parts
+            .extensions
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|ConnectInfo(addr)| addr.ip());
+        Ok(ClientIp(client_ip(&parts.headers, peer)))
+    }
+}
+
+/// The peer address, or the proxy's `X-Forwarded-For` / `X-Real-IP` when the
+/// peer is a proxy on this host (Caddy). Headers from remote peers are ignored
+/// since anyone can set them.
+fn client_ip(headers: &HeaderMap, peer: Option<IpAddr>) -> Option<String> {
+    if let Some(ip) = peer.filter(|ip| !ip.is_loopback()) {
+        return Some(ip.to_string());
+    }
+
+    let forwarded = headers
+        .get("X-Forwarded-For")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.split(',').next())
+        .or_else(|| headers.get("X-Real-IP").and_then(|v| v.to_str().ok()))
+        .and_then(|v| v.trim().parse::<IpAddr>().ok());
+
+    forwarded.or(peer).map(|ip| ip.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_client_ip_trusts_forwarded_headers_from_local_proxy_only() {
+        let mut headers = HeaderMap::new();
+        headers.insert("X-Forwarded-For", "203.0.113.7, 10.0.0.2".parse().unwrap());
+        let local = Some(IpAddr::from([127, 0, 0, 1]));
+        let remote = Some(IpAddr::from([198, 51, 100, 4]));
+
+        assert_eq!(client_ip(&headers, local).as_deref(), Some("203.0.113.7"));
+        assert_eq!(client_ip(&headers, remote).as_deref(), Some("198.51.100.4"));
+        assert_eq!(client_ip(&HeaderMap::new(), local).as_deref(), Some("127.0.0.1"));
+        assert_eq!(client_ip(&HeaderMap::new(), None), None);
+
+        let mut headers = HeaderMap::new();
+        headers.insert("X-Real-IP", "2001:db8::1".parse().unwrap());
+        assert_eq!(client_ip(&headers, local).as_deref(), Some("2001:db8::1"));
+    }
+}
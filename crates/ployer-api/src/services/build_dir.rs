@@ -0,0 +1,159 @@
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use tracing::{info, warn};
+
+/// Parent of the per-deployment clone directories
+pub const BUILD_ROOT: &str = "/tmp/ployer-builds";
+
+/// Build directories older than this are left over from an earlier run
+pub const STALE_BUILD_DIR_AGE: Duration = Duration::from_secs(60 * 60);
+
+/// Clone directory for a deployment that doesn't use the repo cache
+pub fn build_dir_path(deployment_id: &str) -> PathBuf {
+    Path::new(BUILD_ROOT).join(deployment_id)
+}
+
+/// Removes its directory when dropped, so a build directory is cleaned up
+/// whether the pipeline succeeds, fails, is cancelled or panics
+pub struct BuildDir {
+    path: PathBuf,
+}
+
+impl BuildDir {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+}
+
+impl Drop for BuildDir {
+    fn drop(&mut self) {
+        match std::fs::remove_dir_all(&self.path) {
+            Ok(()) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => warn!("Failed to remove build directory {:?}: {}", self.path, e),
+        }
+    }
+}
+
+/// Remove build directories under `root` last modified more than `max_age` ago.
+/// Returns how many were removed and how many bytes they held.
+pub fn sweep_stale_build_dirs(root: &Path, max_age: Duration) -> std::io::Result<(usize, u64)> {
+    let entries = match std::fs::read_dir(root) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok((0, 0)),
+        Err(e) => return Err(e),
+    };
+
+    let now = SystemTime::now();
+    let mut removed = 0;
+    let mut reclaimed = 0;
+    for entry in entries {
+        let entry = entry?;
+        let metadata = entry.metadata()?;
+        if !metadata.is_dir() {
+            continue;
+        }
+
+        let age = metadata
+            .modified()
+            .ok()
+            .and_then(|modified| now.duration_since(modified).ok())
+            .unwrap_or_default();
+        if age < max_age {
+            continue;
+        }
+
+        let size = dir_size(&entry.path());
+        match std::fs::remove_dir_all(entry.path()) {
+            Ok(()) => {
+                removed += 1;
+                reclaimed += size;
+            }
+            Err(e) => warn!("Failed to remove stale build directory {:?}: {}", entry.path(), e),
+        }
+    }
+
+    Ok((removed, reclaimed))
+}
+
+/// Remove build directories left behind by a previous run and log the space reclaimed
+pub async fn sweep_on_startup() {
+    let result = tokio::task::spawn_blocking(|| {
+        sweep_stale_build_dirs(Path::new(BUILD_ROOT), STALE_BUILD_DIR_AGE)
+    })
+    .await;
+
+    match result {
+        Ok(Ok((0, _))) => {}
+        Ok(Ok((removed, bytes))) => info!(
+            "Removed {} stale build directories, reclaimed {:.1} MB",
+            removed,
+            bytes as f64 / 1024.0 / 1024.0
+        ),
+        Ok(Err(e)) => warn!("Build directory sweep failed: {}", e),
+        Err(e) => warn!("Build directory sweep panicked: {}", e),
+    }
+}
+
+/// Total size of the files under a directory, not following symlinks
+fn dir_size(path: &Path) -> u64 {
+    let Ok(entries) = std::fs::read_dir(path) else {
+        return 0;
+    };
+
+    entries
+        .flatten()
+        .map(|entry| match entry.path().symlink_metadata() {
+            Ok(m) if m.is_dir() => dir_size(&entry.path()),
+            Ok(m) => m.len(),
+            Err(_) => 0,
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scratch_root(name: &str) -> PathBuf {
+        let root = std::env::temp_dir().join(format!("ployer-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(&root).unwrap();
+        root
+    }
+
+    #[test]
+    fn test_build_dir_removed_on_drop() {
+        let root = scratch_root("build-dir-drop");
+        let path = root.join("d1");
+        std::fs::create_dir_all(path.join("src")).unwrap();
+        std::fs::write(path.join("src/main.rs"), "fn main() {}").unwrap();
+
+        drop(BuildDir::new(path.clone()));
+        assert!(!path.exists());
+
+        // Dropping a guard whose directory was never created is fine
+        drop(BuildDir::new(root.join("never-created")));
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_sweep_removes_only_old_dirs() {
+        let root = scratch_root("build-dir-sweep");
+        std::fs::create_dir_all(root.join("d1/nested")).unwrap();
+        std::fs::write(root.join("d1/a"), vec![0u8; 1000]).unwrap();
+        std::fs::write(root.join("d1/nested/b"), vec![0u8; 24]).unwrap();
+        std::fs::write(root.join("stray-file"), "x").unwrap();
+
+        // Fresh directories survive a sweep with a real threshold
+        assert_eq!(sweep_stale_build_dirs(&root, STALE_BUILD_DIR_AGE).unwrap(), (0, 0));
+        assert!(root.join("d1").exists());
+
+        assert_eq!(sweep_stale_build_dirs(&root, Duration::ZERO).unwrap(), (1, 1024));
+        assert!(!root.join("d1").exists());
+        assert!(root.join("stray-file").exists());
+
+        std::fs::remove_dir_all(&root).unwrap();
+        assert_eq!(sweep_stale_build_dirs(&root, Duration::ZERO).unwrap(), (0, 0));
+    }
+}
@@ -0,0 +1,74 @@
+use chrono::{DateTime, Utc};
+use ployer_core::cron::CronSchedule;
+use ployer_db::repositories::{ApplicationRepository, DeploymentRepository};
+use std::time::Duration;
+use tracing::{info, warn};
+
+use crate::app_state::SharedState;
+use crate::services::{start_deployment, DeployOptions};
+
+pub fn spawn_deploy_scheduler(state: SharedState) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(30));
+        // Schedules are evaluated for every minute since the previous check, so a
+        // late tick doesn't skip a run and an early one doesn't repeat it
+        let mut last_check = Utc::now();
+
+        loop {
+            interval.tick().await;
+
+            let now = Utc::now();
+            if let Err(e) = run_due_deployments(&state, last_check, now).await {
+                warn!("Deploy scheduler error: {}", e);
+            }
+            last_check = now;
+        }
+    });
+
+    info!("Deploy scheduler started (30s interval)");
+}
+
+async fn run_due_deployments(
+    state: &SharedState,
+    after: DateTime<Utc>,
+    until: DateTime<Utc>,
+) -> anyhow::Result<()> {
+    if state.docker.is_none() {
+        return Ok(());
+    }
+
+    let deployment_repo = DeploymentRepository::new(state.db.clone());
+    let applications = ApplicationRepository::new(state.db.clone()).list_scheduled().await?;
+
+    for application in applications {
+        let Some(expr) = &application.deploy_schedule else {
+            continue;
+        };
+        let schedule = match CronSchedule::parse(expr) {
+            Ok(schedule) => schedule,
+            Err(e) => {
+                warn!("Invalid deploy schedule for app {}: {}", application.id, e);
+                continue;
+            }
+        };
+        if !schedule.fires_between(after, until) {
+            continue;
+        }
+
+        if deployment_repo.has_in_progress(&application.id).await? {
+            info!(
+                "Skipping scheduled deploy of app {}: a deployment is already in progress",
+                application.id
+            );
+            continue;
+        }
+
+        let app_id = application.id.clone();
+        match start_deployment(state, application, DeployOptions::default()).await {
+            Ok(deployment) => info!("Scheduled deploy of app {} started: {}", app_id, deployment.id),
+            Err(e) => warn!("Scheduled deploy of app {} failed to start: {}", app_id, e),
+        }
+    }
+
+    Ok(())
+}
@@ -1,15 +1,495 @@
 use anyhow::{anyhow, Result};
-use ployer_core::models::{AppStatus, Application, Deployment, DeploymentStatus, WsEvent};
-use ployer_db::repositories::{ApplicationRepository, DeploymentRepository, DomainRepository};
-use ployer_docker::{DockerClient, ContainerConfig};
+use futures_util::FutureExt;
+use ployer_core::crypto;
+use ployer_core::http::build_http_client;
+use ployer_core::models::{
+    AppStatus, Application, Deployment, DeploymentPhase, DeploymentStatus, EnvVarScope,
+    HealthCheck, HealthCheckStatus, WsEvent,
+};
+use ployer_db::repositories::{
+    ApplicationRepository, DeployKeyRepository, DeploymentRepository, DomainRepository,
+    EnvVarRepository, HealthCheckRepository, RegistryRepository, ServerRepository,
+    VolumeRepository,
+};
+use ployer_docker::{
+    describe_push_error, image_reference, registry_host, ContainerConfig, ContainerFilters,
+    DockerClient, DockerError, RegistryCredentials, LABEL_APP_ID, LABEL_DEPLOYMENT_ID,
+};
 use ployer_git::GitService;
-use ployer_proxy::{CaddyClient, ReverseProxyConfig};
+use ployer_proxy::CaddyClient;
 use sqlx::SqlitePool;
 use std::collections::HashMap;
+use std::future::Future;
+use std::panic::AssertUnwindSafe;
 use std::path::PathBuf;
-use std::sync::Arc;
-use tokio::sync::broadcast;
-use tracing::{error, warn};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::{broadcast, mpsc, OwnedMutexGuard, OwnedSemaphorePermit, Semaphore};
+use tokio_util::sync::CancellationToken;
+use tracing::{error, info, warn};
+
+use crate::app_state::SharedState;
+use crate::services::app_health_monitor::probe_port;
+use crate::services::build_dir::{build_dir_path, BuildDir};
+
+/// `dir` if it exists or can be created and is writable. Otherwise the cache is
+/// turned off so deploys clone into a temporary directory instead of failing.
+fn writable_cache_dir(dir: PathBuf) -> Option<PathBuf> {
+    let probe = dir.join(".ployer-write-test");
+    let result = std::fs::create_dir_all(&dir)
+        .and_then(|_| std::fs::write(&probe, b""))
+        .and_then(|_| std::fs::remove_file(&probe));
+    match result {
+        Ok(()) => Some(dir),
+        Err(e) => {
+            warn!(
+                "Repository cache {} is not writable ({}); cloning fresh for every deploy",
+                dir.display(),
+                e
+            );
+            None
+        }
+    }
+}
+
+/// Cancellation tokens for in-flight deployment pipelines, keyed by deployment id,
+/// plus the global limit on how many pipelines run at once, the per-app clone cache,
+/// the cap on each deployment's stored build log and the pre-build command image
+#[derive(Clone)]
+pub struct DeploymentTracker {
+    tokens: Arc<Mutex<HashMap<String, CancellationToken>>>,
+    build_slots: Arc<Semaphore>,
+    repo_cache_dir: Option<PathBuf>,
+    repo_locks: Arc<Mutex<HashMap<String, Arc<tokio::sync::Mutex<()>>>>>,
+    max_log_bytes: usize,
+    pre_build_image: String,
+}
+
+impl DeploymentTracker {
+    pub fn new(max_concurrent_builds: usize, repo_cache_dir: Option<PathBuf>) -> Self {
+        Self {
+            tokens: Arc::default(),
+            build_slots: Arc::new(Semaphore::new(max_concurrent_builds.max(1))),
+            repo_cache_dir: repo_cache_dir.and_then(writable_cache_dir),
+            repo_locks: Arc::default(),
+            max_log_bytes: 0,
+            pre_build_image: "alpine:latest".to_string(),
+        }
+    }
+
+    /// Run pre-build commands in containers of `image`
+    pub fn with_pre_build_image(mut self, image: String) -> Self {
+        self.pre_build_image = image;
+        self
+    }
+
+    /// Cap each deployment's stored build log at about `max_bytes`; 0 means no cap
+    pub fn with_log_limit(mut self, max_bytes: usize) -> Self {
+        self.max_log_bytes = max_bytes;
+        self
+    }
+
+    /// Cached clone location for an app, when the cache is enabled
+    pub fn repo_cache_path(&self, app_id: &str) -> Option<PathBuf> {
+        self.repo_cache_dir.as_ref().map(|dir| dir.join(app_id))
+    }
+
+    /// Exclusive use of an app's cached clone; released when the guard is dropped
+    async fn lock_repo(&self, app_id: &str) -> OwnedMutexGuard<()> {
+        let lock = self
+            .repo_locks
+            .lock()
+            .unwrap()
+            .entry(app_id.to_string())
+            .or_default()
+            .clone();
+        lock.lock_owned().await
+    }
+
+    /// Wait for a free build slot; it is released when the permit is dropped
+    async fn acquire_build_slot(&self) -> OwnedSemaphorePermit {
+        self.build_slots
+            .clone()
+            .acquire_owned()
+            .await
+            .expect("build slot semaphore is never closed")
+    }
+
+    fn register(&self, deployment_id: &str) -> CancellationToken {
+        let token = CancellationToken::new();
+        self.tokens
+            .lock()
+            .unwrap()
+            .insert(deployment_id.to_string(), token.clone());
+        token
+    }
+
+    fn remove(&self, deployment_id: &str) {
+        self.tokens.lock().unwrap().remove(deployment_id);
+    }
+
+    /// Signal a running pipeline to stop. Returns false if none is in flight.
+    pub fn cancel(&self, deployment_id: &str) -> bool {
+        match self.tokens.lock().unwrap().get(deployment_id) {
+            Some(token) => {
+                token.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// How long a new container gets to start serving before the deployment fails
+const HEALTH_CHECK_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Longest a pre-build or post-deploy command may run
+const HOOK_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// How a deployment pipeline ended
+enum PipelineOutcome {
+    Completed(Result<()>),
+    Cancelled,
+    TimedOut,
+}
+
+/// Registry a built image is pushed to, with its credentials decrypted
+#[derive(Debug, Clone)]
+pub struct RegistryPush {
+    pub image: String,
+    pub credentials: Option<RegistryCredentials>,
+}
+
+impl RegistryPush {
+    /// The application's configured registry, if it has one
+    pub async fn load(db: &SqlitePool, secret_key: &[u8; 32], application_id: &str) -> Result<Option<Self>> {
+        let Some(registry) = RegistryRepository::new(db.clone())
+            .find_by_application(application_id)
+            .await?
+        else {
+            return Ok(None);
+        };
+
+        let credentials = match (registry.username, registry.password_encrypted) {
+            (Some(username), Some(encrypted)) => Some(RegistryCredentials {
+                username,
+                password: crypto::decrypt(&encrypted, secret_key)?,
+            }),
+            _ => None,
+        };
+
+        Ok(Some(Self {
+            image: registry.image,
+            credentials,
+        }))
+    }
+}
+
+/// An application's environment variables, decrypted and split by scope
+#[derive(Debug, Clone, Default)]
+pub struct DeployEnv {
+    /// Passed to the image build as build args
+    pub build_args: HashMap<String, String>,
+    /// `(key, value)` pairs set on the container
+    pub runtime: Vec<(String, String)>,
+}
+
+impl DeployEnv {
+    /// The application's variables, each placed according to its scope
+    pub async fn load(db: &SqlitePool, secret_key: &[u8; 32], application_id: &str) -> Result<Self> {
+        let vars = EnvVarRepository::new(db.clone())
+            .list_by_application(application_id)
+            .await?;
+
+        let mut decrypted = Vec::with_capacity(vars.len());
+        for var in vars {
+            let value = crypto::decrypt(&var.value_encrypted, secret_key)?;
+            decrypted.push((var.key, value, var.scope));
+        }
+        Ok(Self::from_vars(decrypted))
+    }
+
+    fn from_vars(vars: impl IntoIterator<Item = (String, String, EnvVarScope)>) -> Self {
+        let mut env = Self::default();
+        for (key, value, scope) in vars {
+            if scope.at_runtime() {
+                env.runtime.push((key.clone(), value.clone()));
+            }
+            if scope.at_build() {
+                env.build_args.insert(key, value);
+            }
+        }
+        env
+    }
+
+    /// Expand `${VAR}` references against the app's other variables and the
+    /// built-ins `APP_NAME`, `APP_ID` and `PORT`, which its own variables shadow.
+    /// `$$` is a literal `$`. Undefined references and cycles are errors.
+    pub fn interpolate(self, application: &Application) -> Result<Self> {
+        let mut builtins = HashMap::from([
+            ("APP_NAME".to_string(), application.name.clone()),
+            ("APP_ID".to_string(), application.id.clone()),
+        ]);
+        if let Some(primary) = application.port_mappings().first() {
+            builtins.insert("PORT".to_string(), primary.container_port.to_string());
+        }
+        self.expand(builtins)
+    }
+
+    fn expand(self, builtins: HashMap<String, String>) -> Result<Self> {
+        let mut raw = builtins;
+        raw.extend(self.build_args.clone());
+        raw.extend(self.runtime.iter().cloned());
+
+        let mut resolved = HashMap::new();
+        let mut resolve = |key: String| -> Result<(String, String)> {
+            let value = resolve_var(&key, &raw, &mut resolved, &mut Vec::new())?;
+            Ok((key, value))
+        };
+
+        Ok(Self {
+            build_args: self.build_args.into_keys().map(&mut resolve).collect::<Result<_>>()?,
+            runtime: self
+                .runtime
+                .into_iter()
+                .map(|(key, _)| resolve(key))
+                .collect::<Result<_>>()?,
+        })
+    }
+}
+
+/// Value of `key` with its `${VAR}` references expanded; `stack` holds the
+/// variables being expanded, to report cycles
+fn resolve_var(
+    key: &str,
+    raw: &HashMap<String, String>,
+    resolved: &mut HashMap<String, String>,
+    stack: &mut Vec<String>,
+) -> Result<String> {
+    if let Some(value) = resolved.get(key) {
+        return Ok(value.clone());
+    }
+    if let Some(start) = stack.iter().position(|k| k == key) {
+        return Err(anyhow!(
+            "Environment variable reference cycle: {} -> {}",
+            stack[start..].join(" -> "),
+            key
+        ));
+    }
+    stack.push(key.to_string());
+
+    let mut value = String::new();
+    let mut rest = raw[key].as_str();
+    while let Some(i) = rest.find('$') {
+        value.push_str(&rest[..i]);
+        let after = &rest[i + 1..];
+        if let Some(after) = after.strip_prefix('$') {
+            value.push('$');
+            rest = after;
+        } else if let Some(body) = after.strip_prefix('{') {
+            let end = body
+                .find('}')
+                .ok_or_else(|| anyhow!("Environment variable {} has an unclosed ${{ reference", key))?;
+            let name = &body[..end];
+            if !raw.contains_key(name) {
+                return Err(anyhow!(
+                    "Environment variable {} references undefined variable {}",
+                    key,
+                    name
+                ));
+            }
+            value.push_str(&resolve_var(name, raw, resolved, stack)?);
+            rest = &body[end + 1..];
+        } else {
+            value.push('$');
+            rest = after;
+        }
+    }
+    value.push_str(rest);
+
+    stack.pop();
+    resolved.insert(key.to_string(), value.clone());
+    Ok(value)
+}
+
+/// Drive a deploy hook while passing the lines it sends on `rx` to `send_log`.
+/// None if the hook is still running after HOOK_TIMEOUT.
+async fn log_hook_output<T, L, Fut>(
+    hook: impl Future<Output = T>,
+    mut rx: mpsc::Receiver<String>,
+    send_log: &L,
+) -> Option<T>
+where
+    L: Fn(String) -> Fut,
+    Fut: Future<Output = ()>,
+{
+    // The hook owns the sender, so forwarding ends when the hook does
+    let forward = async {
+        while let Some(line) = rx.recv().await {
+            send_log(line).await;
+        }
+    };
+    let (result, ()) = tokio::join!(tokio::time::timeout(HOOK_TIMEOUT, hook), forward);
+    result.ok()
+}
+
+/// Error for a hook that didn't exit cleanly
+fn hook_failure(name: &str, exit_code: i64) -> anyhow::Error {
+    anyhow!("{} exited with code {}", name, exit_code)
+}
+
+/// Point every routed domain of an application at `upstream`, returning the
+/// domains switched. Domains with no route in apps.caddy are skipped. If one
+/// fails, those already switched go back to `previous` when it's given.
+pub(crate) async fn repoint_domains(
+    caddy: &CaddyClient,
+    db: &SqlitePool,
+    application_id: &str,
+    upstream: &str,
+    previous: Option<&str>,
+) -> Result<Vec<String>> {
+    let domains = DomainRepository::new(db.clone())
+        .list_by_application(application_id)
+        .await?;
+
+    let mut switched: Vec<String> = Vec::new();
+    for domain in domains.into_iter().map(|d| d.domain) {
+        if !caddy.is_persisted(&domain) {
+            continue;
+        }
+        if let Err(e) = caddy.update_upstream(&domain, upstream).await {
+            if let Some(previous) = previous {
+                for done in &switched {
+                    if let Err(e) = caddy.update_upstream(done, previous).await {
+                        warn!("Failed to point {} back at {}: {}", done, previous, e);
+                    }
+                }
+            }
+            return Err(anyhow!("{}: {}", domain, e));
+        }
+        switched.push(domain);
+    }
+    Ok(switched)
+}
+
+/// Build log line for the outcome of `DeploymentService::restore_previous`
+fn restore_message(prev: &Deployment, restored: Result<()>) -> String {
+    match restored {
+        Ok(()) => format!("Previous deployment restored from {}", prev.image_tag),
+        Err(e) => format!("Warning: could not restore previous deployment from {}: {:#}", prev.image_tag, e),
+    }
+}
+
+/// The message a panic was raised with, when it carried one
+fn panic_message(panic: &(dyn std::any::Any + Send)) -> &str {
+    panic
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| panic.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("no message")
+}
+
+/// Force-removes a container the pipeline created when dropped, unless it was
+/// kept, so an early return, a cancellation or a panic between creating the
+/// container and it taking over doesn't leave it behind
+struct ContainerGuard {
+    docker: Arc<DockerClient>,
+    id: Option<String>,
+}
+
+impl ContainerGuard {
+    fn new(docker: Arc<DockerClient>, id: &str) -> Self {
+        Self {
+            docker,
+            id: Some(id.to_string()),
+        }
+    }
+
+    /// Leave the container in place from here on
+    fn keep(&mut self) {
+        self.id = None;
+    }
+
+    /// Remove the container now, e.g. to free its name and ports, rather than
+    /// in the background once the guard is dropped
+    async fn remove(mut self) {
+        let Some(id) = self.id.take() else {
+            return;
+        };
+        match self.docker.remove_container(&id, true).await {
+            Ok(()) | Err(DockerError::NotFound(_)) => {}
+            Err(e) => warn!("Failed to remove container {} of a failed deployment: {}", id, e),
+        }
+    }
+}
+
+impl Drop for ContainerGuard {
+    fn drop(&mut self) {
+        let Some(id) = self.id.take() else {
+            return;
+        };
+        // Drop can't await, so the removal finishes in the background
+        let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+            warn!("Container {} of a failed deployment was left behind", id);
+            return;
+        };
+        let docker = self.docker.clone();
+        runtime.spawn(async move {
+            match docker.remove_container(&id, true).await {
+                Ok(()) => info!("Removed container {} of a failed deployment", id),
+                Err(DockerError::NotFound(_)) => {}
+                Err(e) => warn!("Failed to remove container {} of a failed deployment: {}", id, e),
+            }
+        });
+    }
+}
+
+/// Per-deployment settings passed to `DeploymentService::deploy`
+#[derive(Debug, Clone, Default)]
+pub struct DeployOptions {
+    /// Pre-known commit (e.g. from a webhook); the clone overwrites it
+    pub commit_sha: Option<String>,
+    pub commit_message: Option<String>,
+    /// Build without the layer cache
+    pub no_cache: bool,
+    /// Stored on the row; reusing one for the same app fails the insert
+    pub idempotency_key: Option<String>,
+}
+
+/// Start a deployment of `application` with its deploy key, registry and
+/// environment variables loaded and decrypted. Returns once the pipeline is
+/// spawned.
+pub async fn start_deployment(
+    state: &SharedState,
+    application: Application,
+    options: DeployOptions,
+) -> Result<Deployment> {
+    let docker = state
+        .docker
+        .clone()
+        .ok_or_else(|| anyhow!("Docker not available"))?;
+    let secret_key = state.config.get_secret_key();
+
+    let private_key = match &application.git_url {
+        Some(_) => DeployKeyRepository::new(state.db.clone())
+            .find_by_application(&application.id)
+            .await?
+            .map(|key| crypto::decrypt(&key.private_key_encrypted, &secret_key))
+            .transpose()
+            .map_err(|e| anyhow!("Failed to decrypt deploy key: {}", e))?,
+        None => None,
+    };
+    let registry = RegistryPush::load(&state.db, &secret_key, &application.id)
+        .await
+        .map_err(|e| anyhow!("Failed to load registry credentials: {}", e))?;
+    let env = DeployEnv::load(&state.db, &secret_key, &application.id)
+        .await
+        .map_err(|e| anyhow!("Failed to load environment variables: {}", e))?;
+
+    DeploymentService::from_state(state, docker)
+        .deploy(application, private_key, registry, env, options)
+        .await
+}
 
 pub struct DeploymentService {
     db: SqlitePool,
@@ -19,6 +499,8 @@ pub struct DeploymentService {
     caddy: Option<Arc<CaddyClient>>,
     base_domain: String,
     ws_broadcast: broadcast::Sender<WsEvent>,
+    tracker: DeploymentTracker,
+    build_timeout: Duration,
 }
 
 impl DeploymentService {
@@ -28,6 +510,8 @@ impl DeploymentService {
         caddy: Option<Arc<CaddyClient>>,
         base_domain: String,
         ws_broadcast: broadcast::Sender<WsEvent>,
+        tracker: DeploymentTracker,
+        build_timeout: Duration,
     ) -> Self {
         Self {
             db,
@@ -36,26 +520,49 @@ impl DeploymentService {
             caddy,
             base_domain,
             ws_broadcast,
+            tracker,
+            build_timeout,
         }
     }
 
+    /// The service as configured for this Ployer instance
+    pub fn from_state(state: &SharedState, docker: Arc<DockerClient>) -> Self {
+        Self::new(
+            state.db.clone(),
+            docker,
+            Some(Arc::new(state.caddy.clone())),
+            state.config.server.base_domain.clone(),
+            state.ws_broadcast.clone(),
+            state.deployments.clone(),
+            Duration::from_secs(state.config.docker.build_timeout_secs),
+        )
+    }
+
     /// Trigger a new deployment for an application
     pub async fn deploy(
         &self,
         application: Application,
         private_key: Option<String>,
+        registry: Option<RegistryPush>,
+        env: DeployEnv,
+        options: DeployOptions,
     ) -> Result<Deployment> {
+        let DeployOptions { commit_sha, commit_message, no_cache, idempotency_key } = options;
         let deployment_repo = DeploymentRepository::new(self.db.clone());
 
-        // Fixed image tag per app — always overwrite :latest
-        let image_tag = format!("ployer-{}:latest", application.name);
+        // Fixed image tag per app — always overwrite :latest — unless it runs a prebuilt image
+        let image_tag = match &application.image {
+            Some(image) => image_reference(image),
+            None => format!("ployer-{}:latest", application.name),
+        };
         let deployment = deployment_repo
             .create(
                 &application.id,
                 &application.server_id,
-                None, // commit_sha - will be updated after clone
-                None, // commit_message - will be updated after clone
+                commit_sha.as_deref(),
+                commit_message.as_deref(),
                 &image_tag,
+                idempotency_key.as_deref(),
             )
             .await?;
 
@@ -67,39 +574,112 @@ impl DeploymentService {
         let caddy = self.caddy.clone();
         let base_domain = self.base_domain.clone();
         let ws_broadcast = self.ws_broadcast.clone();
+        let tracker = self.tracker.clone();
+        let build_timeout = self.build_timeout;
+        let max_log_bytes = tracker.max_log_bytes;
+        let pre_build_image = tracker.pre_build_image.clone();
+        let cancel_token = tracker.register(&deployment_id);
 
         tokio::spawn(async move {
-            if let Err(e) = Self::execute_deployment(
-                db.clone(),
-                docker,
-                caddy,
-                base_domain,
-                ws_broadcast.clone(),
-                deployment_id.clone(),
-                application.clone(),
-                private_key,
-                image_tag,
-            )
-            .await
-            {
-                error!("Deployment failed: {}", e);
-                let repo = DeploymentRepository::new(db.clone());
-                let _ = repo.update_status(&deployment_id, DeploymentStatus::Failed).await;
-                let _ = repo.append_log(&deployment_id, &format!("ERROR: {}", e)).await;
-                let _ = ApplicationRepository::new(db)
-                    .update_status(&application.id, AppStatus::Failed).await;
-                let _ = ws_broadcast.send(WsEvent::DeploymentStatus {
-                    deployment_id,
-                    app_id: application.id,
-                    status: DeploymentStatus::Failed,
-                });
+            // The deployment stays Queued until a build slot frees up; the timeout
+            // only starts once the pipeline actually runs
+            let pipeline = async {
+                // Taken before the build slot so a deploy waiting on its app's clone
+                // doesn't sit on a slot another app could use
+                let repo_cache = tracker.repo_cache_path(&application.id);
+                let _repo = match repo_cache {
+                    Some(_) => Some(tracker.lock_repo(&application.id).await),
+                    None => None,
+                };
+
+                if tracker.build_slots.available_permits() == 0 {
+                    let _ = DeploymentRepository::new(db.clone())
+                        .with_log_limit(max_log_bytes)
+                        .append_log(&deployment_id, "Waiting for a free build slot...")
+                        .await;
+                }
+                let _slot = tracker.acquire_build_slot().await;
+
+                // A panic fails the deployment like any other error instead of
+                // leaving it stuck in whichever status it had reached
+                tokio::time::timeout(
+                    build_timeout,
+                    AssertUnwindSafe(Self::execute_deployment(
+                        db.clone(),
+                        docker.clone(),
+                        caddy,
+                        base_domain,
+                        ws_broadcast.clone(),
+                        deployment_id.clone(),
+                        application.clone(),
+                        private_key,
+                        registry,
+                        env,
+                        image_tag,
+                        no_cache,
+                        repo_cache,
+                        max_log_bytes,
+                        pre_build_image,
+                    ))
+                    .catch_unwind(),
+                )
+                .await
+            };
+
+            // Dropping the pipeline future stops it at its current await point,
+            // including mid-way through the build log stream
+            let outcome = tokio::select! {
+                _ = cancel_token.cancelled() => PipelineOutcome::Cancelled,
+                result = pipeline => match result {
+                    Ok(Ok(result)) => PipelineOutcome::Completed(result),
+                    Ok(Err(panic)) => PipelineOutcome::Completed(Err(anyhow!(
+                        "Deployment task panicked: {}",
+                        panic_message(panic.as_ref())
+                    ))),
+                    Err(_) => PipelineOutcome::TimedOut,
+                },
+            };
+            tracker.remove(&deployment_id);
+
+            let error = match outcome {
+                PipelineOutcome::Completed(Ok(())) => return,
+                PipelineOutcome::Completed(Err(e)) => e,
+                PipelineOutcome::Cancelled => {
+                    info!("Deployment {} cancelled", deployment_id);
+                    Self::cleanup_aborted(&db, &docker, &deployment_id).await;
+                    let _ = DeploymentRepository::new(db.clone())
+                        .with_log_limit(max_log_bytes)
+                        .append_log(&deployment_id, "Deployment cancelled")
+                        .await;
+                    // The previous container may already have been removed
+                    let status = Self::status_after_abort(&db, &docker, &application.id).await;
+                    let _ = ApplicationRepository::new(db)
+                        .update_status(&application.id, status).await;
+                    return;
+                }
+                PipelineOutcome::TimedOut => {
+                    Self::cleanup_aborted(&db, &docker, &deployment_id).await;
+                    anyhow!("Deployment timed out after {} seconds", build_timeout.as_secs())
+                }
+            };
+
+            // `{:#}` keeps the cause chain, e.g. the git or Docker error behind a context
+            error!("Deployment {} failed: {:#}", deployment_id, error);
+            let repo = DeploymentRepository::new(db.clone()).with_log_limit(max_log_bytes);
+            if let Err(e) = repo.update_status(&deployment_id, DeploymentStatus::Failed).await {
+                error!("Failed to mark deployment {} as failed: {}", deployment_id, e);
             }
+            let _ = repo.append_log(&deployment_id, &format!("ERROR: {:#}", error)).await;
+            let _ = ApplicationRepository::new(db.clone())
+                .update_status(&application.id, AppStatus::Failed).await;
+            Self::broadcast_status(&db, &ws_broadcast, &deployment_id).await;
         });
 
         Ok(deployment)
     }
 
     /// Execute the full deployment pipeline
+    #[allow(clippy::too_many_arguments)]
     async fn execute_deployment(
         db: SqlitePool,
         docker: Arc<DockerClient>,
@@ -109,7 +689,13 @@ impl DeploymentService {
         deployment_id: String,
         application: Application,
         private_key: Option<String>,
+        registry: Option<RegistryPush>,
+        env: DeployEnv,
         image_tag: String,
+        no_cache: bool,
+        repo_cache: Option<PathBuf>,
+        max_log_bytes: usize,
+        pre_build_image: String,
     ) -> Result<()> {
         let git = GitService::new();
         let deployment_repo = DeploymentRepository::new(db.clone());
@@ -121,7 +707,7 @@ impl DeploymentService {
             let ws_broadcast = ws_broadcast.clone();
             async move {
                 // Save to database
-                let deployment_repo = DeploymentRepository::new(db);
+                let deployment_repo = DeploymentRepository::new(db).with_log_limit(max_log_bytes);
                 let _ = deployment_repo.append_log(&deployment_id, &msg).await;
                 // Broadcast via WebSocket
                 let _ = ws_broadcast.send(WsEvent::DeploymentLog {
@@ -131,135 +717,519 @@ impl DeploymentService {
             }
         };
 
-        // Step 1: Clone git repository (if git_url is configured)
-        let context_path = if let Some(git_url) = &application.git_url {
-            deployment_repo.update_status(&deployment_id, DeploymentStatus::Cloning).await?;
-            send_log(format!("Cloning repository: {}", git_url)).await;
-
-            let clone_dir = PathBuf::from(format!("/tmp/ployer-builds/{}", deployment_id));
-            tokio::fs::create_dir_all(&clone_dir).await?;
-
-            git.clone_repo(
-                git_url,
-                &clone_dir,
-                &application.git_branch,
-                private_key.as_deref(),
-            )?;
-
-            // Get commit information
-            let commit_info = git.get_latest_commit(&clone_dir)?;
-            send_log(format!("Commit: {} - {}", commit_info.sha, commit_info.message)).await;
-
-            // Update deployment with commit info
-            sqlx::query!(
-                "UPDATE deployments SET commit_sha = ?, commit_message = ? WHERE id = ?",
-                commit_info.sha,
-                commit_info.message,
-                deployment_id
-            )
-            .execute(&db)
-            .await?;
+        // Structured progress for clients, alongside the free-text log lines
+        let send_phase = |phase: DeploymentPhase| {
+            let _ = ws_broadcast.send(WsEvent::DeploymentPhase {
+                deployment_id: deployment_id.clone(),
+                phase,
+            });
+        };
+
+        // Removed on every exit, including failures, cancellation and panics;
+        // a cached clone is kept for the next deploy
+        let _build_dir = repo_cache
+            .is_none()
+            .then(|| BuildDir::new(build_dir_path(&deployment_id)));
 
-            clone_dir
+        // Images are built and pulled for the target server's platform, or the daemon's own when unset
+        let platform = ServerRepository::new(db.clone())
+            .find_by_id(&application.server_id)
+            .await?
+            .and_then(|server| server.platform);
+        if let Some(platform) = &platform {
+            send_log(format!("Target platform: {}", platform)).await;
+        }
+
+        let env = env.interpolate(&application)?;
+
+        if application.image.is_some() {
+            // Prebuilt image: pull it instead of cloning and building
+            deployment_repo.update_status(&deployment_id, DeploymentStatus::Building).await?;
+            send_phase(DeploymentPhase::Building);
+            send_log(format!("Pulling image: {}", image_tag)).await;
+
+            // The app's registry credentials apply when the image lives on the same registry
+            let credentials = registry
+                .as_ref()
+                .filter(|r| registry_host(&r.image) == registry_host(&image_tag))
+                .and_then(|r| r.credentials.as_ref());
+            let mut pull_logs = docker.pull_image(&image_tag, credentials, platform.as_deref()).await?;
+            while let Some(log_line) = pull_logs.recv().await {
+                if let Some(error) = log_line.strip_prefix("ERROR: ") {
+                    return Err(anyhow!("Failed to pull {}: {}", image_tag, error.trim()));
+                }
+                send_log(log_line.trim().to_string()).await;
+            }
+
+            send_log("Pull completed successfully".to_string()).await;
         } else {
-            return Err(anyhow!("Application has no git_url configured"));
-        };
+            // Step 1: Clone git repository (if git_url is configured)
+            let (context_path, commit_sha) = if let Some(git_url) = &application.git_url {
+                deployment_repo.update_status(&deployment_id, DeploymentStatus::Cloning).await?;
+                send_phase(DeploymentPhase::Cloning);
+                send_log(format!("Cloning repository: {}", git_url)).await;
+
+                let clone_dir = repo_cache
+                    .clone()
+                    .unwrap_or_else(|| build_dir_path(&deployment_id));
+
+                // Fast path: bring the cached clone up to date instead of cloning again
+                let mut reused = false;
+                if repo_cache.is_some() && clone_dir.join(".git").exists() {
+                    let refreshed = git
+                        .origin_url(&clone_dir)
+                        .and_then(|url| if url == *git_url { Ok(()) } else { Err(anyhow!("origin changed")) })
+                        .and_then(|_| git.pull_latest(&clone_dir, &application.git_branch, private_key.as_deref(), application.git_submodules))
+                        .and_then(|_| git.checkout_branch(&clone_dir, &application.git_branch));
+                    match refreshed {
+                        Ok(()) => {
+                            send_log("Updated cached clone".to_string()).await;
+                            reused = true;
+                        }
+                        Err(e) => send_log(format!("Cached clone unusable ({}), cloning fresh", e)).await,
+                    }
+                }
 
-        // Step 2: Build Docker image
-        deployment_repo.update_status(&deployment_id, DeploymentStatus::Building).await?;
-        send_log("Building Docker image...".to_string()).await;
+                if !reused {
+                    let _ = tokio::fs::remove_dir_all(&clone_dir).await;
+                    tokio::fs::create_dir_all(&clone_dir).await?;
 
-        let dockerfile_path = application.dockerfile_path.as_deref();
-        let mut build_logs = docker.build_image(&context_path, dockerfile_path, &image_tag).await?;
+                    git.clone_repo(
+                        git_url,
+                        &clone_dir,
+                        &application.git_branch,
+                        private_key.as_deref(),
+                        application.git_submodules,
+                    )?;
+                }
 
-        // Stream build logs
-        while let Some(log_line) = build_logs.recv().await {
-            send_log(log_line.trim().to_string()).await;
-        }
+                // Get commit information
+                let commit_info = git.get_latest_commit(&clone_dir)?;
+                send_log(format!("Commit: {} - {}", commit_info.sha, commit_info.message)).await;
+
+                // Update deployment with commit info
+                sqlx::query!(
+                    "UPDATE deployments SET commit_sha = ?, commit_message = ? WHERE id = ?",
+                    commit_info.sha,
+                    commit_info.message,
+                    deployment_id
+                )
+                .execute(&db)
+                .await?;
+
+                (clone_dir, commit_info.sha)
+            } else {
+                return Err(anyhow!("Application has no git_url configured"));
+            };
+
+            // Step 2: Build Docker image
+            deployment_repo.update_status(&deployment_id, DeploymentStatus::Building).await?;
+            send_phase(DeploymentPhase::Building);
+
+            // The command itself isn't logged since it may carry inline secrets
+            if let Some(cmd) = &application.pre_build_cmd {
+                send_log(format!("Running pre-build command in {}...", pre_build_image)).await;
+                let (tx, rx) = mpsc::channel(100);
+                let name = format!("ployer-prebuild-{}", deployment_id);
+                let run = docker.run_in_workspace(&name, &pre_build_image, &context_path, cmd, &env.build_args, tx);
+                let result = log_hook_output(run, rx, &send_log).await;
+                // Gone already unless the command timed out
+                let _ = docker.remove_container(&name, true).await;
+                let exit_code = result
+                    .ok_or_else(|| anyhow!("Pre-build command did not finish within {} seconds", HOOK_TIMEOUT.as_secs()))?
+                    .map_err(|e| anyhow!("Pre-build command could not run: {}", e))?;
+                if exit_code != 0 {
+                    return Err(hook_failure("Pre-build command", exit_code));
+                }
+                send_log("Pre-build command finished".to_string()).await;
+            }
+
+            if no_cache {
+                send_log("Building Docker image (no cache)...".to_string()).await;
+            } else {
+                send_log("Building Docker image...".to_string()).await;
+            }
 
-        send_log("Build completed successfully".to_string()).await;
+            let dockerfile_path = application.dockerfile_path.as_deref();
+            let target = application.docker_target.as_deref();
+            if let Some(target) = target {
+                send_log(format!("Building stage: {}", target)).await;
+            }
+
+            // The previous build primes the layer cache. A server that hasn't built
+            // the app yet can still reuse layers from the registry copy.
+            let mut cache_from = Vec::new();
+            if !no_cache {
+                cache_from.push(image_tag.clone());
+                if let Some(registry) = &registry {
+                    let cached = format!("{}:latest", registry.image);
+                    let mut pull_logs = docker
+                        .pull_image(&cached, registry.credentials.as_ref(), platform.as_deref())
+                        .await?;
+                    let mut pulled = true;
+                    while let Some(log_line) = pull_logs.recv().await {
+                        pulled &= !log_line.starts_with("ERROR: ");
+                    }
+                    if pulled {
+                        cache_from.push(cached);
+                    }
+                }
+                send_log(format!("Build cache sources: {}", cache_from.join(", "))).await;
+            }
+
+            if !env.build_args.is_empty() {
+                let mut keys: Vec<&str> = env.build_args.keys().map(String::as_str).collect();
+                keys.sort_unstable();
+                send_log(format!("Build args: {}", keys.join(", "))).await;
+            }
+
+            let mut build_logs = docker
+                .build_image(
+                    &context_path,
+                    dockerfile_path,
+                    target,
+                    &image_tag,
+                    no_cache,
+                    &cache_from,
+                    &env.build_args,
+                    platform.as_deref(),
+                )
+                .await?;
+
+            // Stream build logs
+            let mut cached_steps = 0;
+            while let Some(log_line) = build_logs.recv().await {
+                if log_line.contains("Using cache") {
+                    cached_steps += 1;
+                }
+                send_log(log_line.trim().to_string()).await;
+            }
+
+            send_log("Build completed successfully".to_string()).await;
+            if cached_steps > 0 {
+                send_log(format!("Build cache used for {} step(s)", cached_steps)).await;
+            } else {
+                send_log("Build cache not used".to_string()).await;
+            }
+
+            // Publish the image so other servers can pull it instead of rebuilding
+            if let Some(registry) = &registry {
+                let short_sha: String = commit_sha.chars().take(12).collect();
+                for tag in ["latest", short_sha.as_str()] {
+                    send_log(format!("Pushing {}:{}...", registry.image, tag)).await;
+                    docker.tag_image(&image_tag, &registry.image, tag).await?;
+
+                    let mut push_logs = docker
+                        .push_image(&registry.image, tag, registry.credentials.as_ref())
+                        .await?;
+                    while let Some(log_line) = push_logs.recv().await {
+                        if let Some(error) = log_line.strip_prefix("ERROR: ") {
+                            return Err(anyhow!(describe_push_error(error)));
+                        }
+                        send_log(log_line.trim().to_string()).await;
+                    }
+                }
+                send_log(format!("Pushed {}", registry.image)).await;
+            }
+        }
 
-        // Step 3: Remove any existing containers for this app (avoids port conflicts)
         deployment_repo.update_status(&deployment_id, DeploymentStatus::Deploying).await?;
+        send_phase(DeploymentPhase::Deploying);
 
         let container_name = format!("ployer-{}", application.name);
+        let subdomain = application.subdomain(&base_domain);
+        if subdomain.is_none() {
+            send_log(format!("Warning: skipping subdomain setup: '{}' has no letters or digits", application.name)).await;
+        }
+        let domain_repo = DomainRepository::new(db.clone());
+        let route_exists = match &subdomain {
+            Some(subdomain) => domain_repo.find_by_domain(subdomain).await.ok().flatten().is_some(),
+            None => false,
+        };
 
-        // Force-remove by DB-tracked container ID (covers any naming scheme)
-        if let Ok(Some(prev)) = deployment_repo.get_latest_running(&application.id).await {
-            if let Some(prev_container_id) = &prev.container_id {
-                send_log(format!("Removing previous container ({})...", &prev_container_id[..12])).await;
-                match docker.remove_container(prev_container_id, true).await {
-                    Ok(_) => send_log("Previous container removed".to_string()).await,
-                    Err(e) => send_log(format!("Warning: could not remove previous container: {}", e)).await,
+        // With a live container already behind a Caddy route, bring the new one up
+        // alongside it and only retire the old one once traffic has been swapped over
+        let previous = deployment_repo
+            .get_latest_running(&application.id)
+            .await
+            .ok()
+            .flatten()
+            .filter(|prev| prev.container_id.is_some());
+        let mut mappings = application.port_mappings();
+        // Extra ports pinned to a host port can't be bound by two containers at once
+        let pinned_extra_ports = application.ports.iter().any(|p| p.host_port.is_some());
+        let cutover = match (&previous, &caddy, application.port) {
+            (Some(prev), Some(caddy_client), Some(_)) if route_exists && !pinned_extra_ports => {
+                Some((prev.clone(), caddy_client.clone()))
+            }
+            _ => None,
+        };
+
+        // Without a cutover the previous container is removed up front. It's brought
+        // back from its image if the new one fails before it would go live.
+        let replaced = if cutover.is_none() { previous.clone() } else { None };
+
+        if cutover.is_none() {
+            // Step 3: Remove any existing containers for this app (avoids port conflicts)
+            // Force-remove by DB-tracked container ID (covers any naming scheme)
+            if let Some(prev) = &previous {
+                if let Some(prev_container_id) = &prev.container_id {
+                    send_log(format!("Removing previous container ({})...", &prev_container_id[..12])).await;
+                    match docker.remove_container(prev_container_id, true).await {
+                        Ok(_) => send_log("Previous container removed".to_string()).await,
+                        Err(e) => send_log(format!("Warning: could not remove previous container: {}", e)).await,
+                    }
                 }
+                let _ = deployment_repo.update_status(&prev.id, DeploymentStatus::RolledBack).await;
+                Self::broadcast_status(&db, &ws_broadcast, &prev.id).await;
+            }
+
+            // Also force-remove by fixed name (catches untracked containers with the same name)
+            match docker.remove_container(&container_name, true).await {
+                Ok(_) => send_log(format!("Removed existing container '{}'", container_name)).await,
+                Err(_) => {} // doesn't exist — that's fine
             }
-            let _ = deployment_repo.update_status(&prev.id, DeploymentStatus::RolledBack).await;
-        }
 
-        // Also force-remove by fixed name (catches untracked containers with the same name)
-        match docker.remove_container(&container_name, true).await {
-            Ok(_) => send_log(format!("Removed existing container '{}'", container_name)).await,
-            Err(_) => {} // doesn't exist — that's fine
+            // Free host ports held by stale containers of this app. A port held by
+            // anything else is rejected, except the primary port: its host side only
+            // defaults to the container port, so it falls back to a free one.
+            for (i, mapping) in mappings.iter_mut().enumerate() {
+                let Some(port) = mapping.host_port else { continue };
+                let filters = ContainerFilters { publish: Some(port), ..Default::default() };
+                let mut in_use_by = None;
+                for holder in docker.list_containers(true, &filters).await? {
+                    if holder.labels.get(LABEL_APP_ID) == Some(&application.id) {
+                        if docker.remove_container(&holder.id, true).await.is_ok() {
+                            send_log(format!("Freed port {} (removed: {})", port, holder.name)).await;
+                        }
+                    } else {
+                        in_use_by = Some(holder.name);
+                    }
+                }
+
+                if let Some(holder) = in_use_by {
+                    if i == 0 && application.port.is_some() {
+                        send_log(format!(
+                            "Port {} is in use by '{}'; publishing on a free host port instead",
+                            port, holder
+                        ))
+                        .await;
+                        mapping.host_port = None;
+                    } else {
+                        return Err(anyhow!("Port {} is already in use by container '{}'", port, holder));
+                    }
+                }
+            }
         }
 
-        // Kill any remaining containers still bound to the app's port
-        if let Some(port) = application.port {
-            match docker.remove_containers_by_port(port).await {
-                Ok(removed) if !removed.is_empty() => {
-                    send_log(format!("Freed port {} (removed: {})", port, removed.join(", "))).await;
+        // Named volumes are reused across deploys so data survives the container swap.
+        // Keyed by mount point, so one volume can be mounted at several paths
+        let mut volumes = HashMap::new();
+        for volume in VolumeRepository::new(db.clone()).list_by_application(&application.id).await? {
+            let name = volume.docker_volume_name();
+            if docker.inspect_volume(&name).await.is_err() {
+                docker.create_volume(&name).await?;
+                send_log(format!("Created volume '{}'", name)).await;
+                // Volumes used to be created without the app id prefix
+                if docker.inspect_volume(&volume.volume_name).await.is_ok() {
+                    send_log(format!(
+                        "Warning: existing volume '{}' is no longer mounted; copy its data into '{}' to keep it",
+                        volume.volume_name, name
+                    ))
+                    .await;
                 }
-                _ => {}
             }
+            volumes.insert(volume.container_path, name);
         }
 
-        // Step 4: Create and start new container with fixed name
+        // Each app gets its own network so sidecars can reach it by name without
+        // sharing the default bridge with every other app
+        let network_name = application
+            .network_name
+            .clone()
+            .unwrap_or_else(|| format!("ployer-{}", application.id));
+        if docker.inspect_network(&network_name).await.is_err() {
+            docker.create_network(&network_name, "bridge").await?;
+            send_log(format!("Created network '{}'", network_name)).await;
+        }
+        if application.network_name.is_none() {
+            ApplicationRepository::new(db.clone())
+                .set_network_name(&application.id, &network_name)
+                .await?;
+        }
+
+        // Step 4: Create and start the new container. During a cutover the old
+        // container still holds the fixed name and ports, so the new one runs under
+        // a temporary name on Docker-assigned host ports.
         send_log("Creating container...".to_string()).await;
 
+        let new_name = if cutover.is_some() {
+            format!("{}-{}", container_name, &deployment_id[..8])
+        } else {
+            container_name.clone()
+        };
+        let ports: HashMap<String, String> = mappings
+            .iter()
+            .map(|m| {
+                let host_port = match m.host_port {
+                    Some(p) if cutover.is_none() => p.to_string(),
+                    _ => String::new(),
+                };
+                (format!("{}/{}", m.container_port, m.protocol), host_port)
+            })
+            .collect();
+
         let container_config = ContainerConfig {
             image: image_tag.clone(),
-            name: Some(container_name.clone()),
-            env: None, // TODO: Load from environment variables
-            ports: application.port.map(|p| {
-                let mut ports = HashMap::new();
-                ports.insert(format!("{}/tcp", p), p.to_string());
-                ports
-            }),
-            volumes: None,
-            network: Some("bridge".to_string()),
+            name: Some(new_name.clone()),
+            env: if env.runtime.is_empty() {
+                None
+            } else {
+                Some(env.runtime.iter().map(|(key, value)| format!("{}={}", key, value)).collect())
+            },
+            ports: if ports.is_empty() { None } else { Some(ports) },
+            volumes: if volumes.is_empty() { None } else { Some(volumes) },
+            network: Some(network_name),
             cmd: None,
+            restart_policy: Some(application.restart_policy.clone()),
+            // Stopped app containers are kept so they can be started again
+            auto_remove: false,
+            labels: Some(HashMap::from([
+                (LABEL_APP_ID.to_string(), application.id.clone()),
+                (LABEL_DEPLOYMENT_ID.to_string(), deployment_id.clone()),
+            ])),
         };
 
+        let restore_config = container_config.clone();
         let container_id = docker.create_container(container_config).await?;
+        // Removed on any failure until it takes traffic. A first deploy keeps its
+        // container once started, so a failed health check can be inspected.
+        let mut container = ContainerGuard::new(docker.clone(), &container_id);
         deployment_repo.set_container_id(&deployment_id, &container_id).await?;
-        send_log(format!("Container '{}' created", container_name)).await;
+        send_log(format!("Container '{}' created", new_name)).await;
 
-        docker.start_container(&container_id).await?;
-        send_log(format!("Container '{}' started", container_name)).await;
+        match docker.start_container(&container_id).await {
+            Ok(()) => {}
+            // Ports bound outside Docker (or by a container created meanwhile)
+            Err(DockerError::PortInUse(msg)) => return Err(anyhow!("Host port already in use: {}", msg)),
+            Err(e) => return Err(e.into()),
+        }
+        send_log(format!("Container '{}' started", new_name)).await;
+        if cutover.is_none() && replaced.is_none() {
+            container.keep();
+        }
 
-        // Step 5: Health check (simple wait)
+        // Step 5: Health check — the old container keeps serving until this passes
+        send_phase(DeploymentPhase::HealthCheck);
         send_log("Waiting for health check...".to_string()).await;
-        tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
+        let bound_port = match application.port {
+            Some(port) => docker.host_port(&container_id, port).await?,
+            None => None,
+        };
+        deployment_repo.set_host_port(&deployment_id, bound_port).await?;
+        if let Some(port) = bound_port {
+            send_log(format!("Listening on host port {}", port)).await;
+        }
+        let health_check = HealthCheckRepository::new(db.clone())
+            .get(&application.id)
+            .await
+            .ok()
+            .flatten();
+        if let Err(e) = Self::wait_until_healthy(&docker, &container_id, bound_port, health_check.as_ref()).await {
+            if cutover.is_some() {
+                send_log("New container failed its health check; previous container left serving".to_string()).await;
+            } else if let Some(prev) = &replaced {
+                container.remove().await;
+                let restored = Self::restore_previous(&db, &docker, caddy.as_deref(), &application, prev, restore_config).await;
+                send_log(restore_message(prev, restored)).await;
+                Self::broadcast_status(&db, &ws_broadcast, &prev.id).await;
+            }
+            return Err(e);
+        }
+        send_log("Container is healthy".to_string()).await;
 
-        // Step 5.5: Create subdomain and configure Caddy
-        // For MVP, skip actual Caddy configuration (would need Caddy running)
-        // Just create the domain record
-        send_log("Configuring domain...".to_string()).await;
-        let subdomain = format!("{}.{}", application.name, base_domain);
+        // Step 5.2: Post-deploy command (e.g. migrations) before the container takes traffic
+        if let Some(cmd) = &application.post_deploy_cmd {
+            send_log("Running post-deploy command...".to_string()).await;
+            let (tx, rx) = mpsc::channel(100);
+            let exec = docker.exec(&container_id, vec!["sh".to_string(), "-c".to_string(), cmd.clone()], tx);
+            let result = match log_hook_output(exec, rx, &send_log).await {
+                Some(Ok(0)) => Ok(()),
+                Some(Ok(code)) => Err(hook_failure("Post-deploy command", code)),
+                Some(Err(e)) => Err(anyhow!("Post-deploy command could not run: {}", e)),
+                None => Err(anyhow!("Post-deploy command did not finish within {} seconds", HOOK_TIMEOUT.as_secs())),
+            };
+            if let Err(e) = result {
+                if cutover.is_some() {
+                    send_log("Post-deploy command failed; previous container left serving".to_string()).await;
+                } else if let Some(prev) = &replaced {
+                    container.remove().await;
+                    let restored = Self::restore_previous(&db, &docker, caddy.as_deref(), &application, prev, restore_config).await;
+                    send_log(restore_message(prev, restored)).await;
+                    Self::broadcast_status(&db, &ws_broadcast, &prev.id).await;
+                }
+                return Err(e);
+            }
+            send_log("Post-deploy command finished".to_string()).await;
+        }
+        if cutover.is_none() {
+            container.keep();
+        }
 
-        let domain_repo = DomainRepository::new(db.clone());
-        // Check if subdomain already exists
-        if domain_repo.find_by_domain(&subdomain).await.ok().flatten().is_none() {
-            match domain_repo.create(&application.id, &subdomain, true).await {
-                Ok(_) => {
-                    send_log(format!("Subdomain created: {}", subdomain)).await;
+        if let Some((prev, caddy_client)) = cutover {
+            // Step 5.5: Swap the proxy upstream of every domain before retiring the old container
+            let upstream = format!("localhost:{}", bound_port.ok_or_else(|| anyhow!("New container has no host port"))?);
+            let previous_upstream = prev.host_port.map(|p| format!("localhost:{}", p));
+            match repoint_domains(&caddy_client, &db, &application.id, &upstream, previous_upstream.as_deref()).await {
+                Ok(domains) => {
+                    container.keep();
+                    send_log(format!("Traffic switched to new container ({}) for {}", upstream, domains.join(", "))).await;
+                }
+                Err(e) => {
+                    send_log(format!("Failed to switch Caddy upstream for {}; previous container left serving", e)).await;
+                    return Err(e);
+                }
+            }
 
-                    // Configure Caddy if available
-                    if let Some(ref caddy_client) = caddy {
-                        if let Some(port) = application.port {
-                            let upstream = format!("localhost:{}", port);
+            if let Some(prev_container_id) = &prev.container_id {
+                send_log(format!("Stopping previous container ({})...", &prev_container_id[..12])).await;
+                let _ = docker
+                    .stop_container(prev_container_id, Some(application.stop_grace_seconds as i64))
+                    .await;
+                match docker.remove_container(prev_container_id, true).await {
+                    Ok(_) => send_log("Previous container removed".to_string()).await,
+                    Err(e) => send_log(format!("Warning: could not remove previous container: {}", e)).await,
+                }
+            }
+            let _ = deployment_repo.update_status(&prev.id, DeploymentStatus::RolledBack).await;
+            Self::broadcast_status(&db, &ws_broadcast, &prev.id).await;
+
+            // Take over the fixed name now that it's free
+            if docker.rename_container(&container_id, &container_name).await.is_ok() {
+                send_log(format!("Container renamed to '{}'", container_name)).await;
+            }
+        } else {
+            // Step 5.5: Create subdomain and configure Caddy
+            let upstream = bound_port.map(|p| format!("localhost:{}", p));
 
+            // The previous container is gone, so re-point every route that was serving it
+            if let (Some(caddy_client), Some(upstream)) = (&caddy, &upstream) {
+                if let Err(e) = repoint_domains(caddy_client, &db, &application.id, upstream, None).await {
+                    warn!("Failed to update Caddy upstream for {}", e);
+                    send_log(format!("Warning: Caddy upstream update failed for {}", e)).await;
+                }
+            }
+
+            if let Some(subdomain) = subdomain.filter(|_| !route_exists) {
+                send_log("Configuring domain...".to_string()).await;
+                match domain_repo.create(&application.id, &subdomain, true).await {
+                    Ok(_) => {
+                        send_log(format!("Subdomain created: {}", subdomain)).await;
+
+                        // Configure Caddy if available
+                        if let (Some(caddy_client), Some(upstream)) = (&caddy, &upstream) {
                             // Persist route to apps.caddy so it survives Caddy restarts
-                            if let Err(e) = caddy_client.persist_route(&subdomain, &upstream) {
+                            if let Err(e) = caddy_client.persist_route(&subdomain, upstream) {
                                 warn!("Failed to persist Caddy route: {}", e);
                                 send_log(format!("Warning: Caddy route persistence failed: {}", e)).await;
                             } else {
@@ -267,48 +1237,330 @@ impl DeploymentService {
                             }
                         }
                     }
-                }
-                Err(e) => {
-                    warn!("Failed to create subdomain: {}", e);
+                    Err(e) => {
+                        warn!("Failed to create subdomain: {}", e);
+                    }
                 }
             }
         }
 
         // Step 6: Mark deployment as running and update application status
         deployment_repo.update_status(&deployment_id, DeploymentStatus::Running).await?;
+        // Catch any older deployment still marked running, e.g. one whose container
+        // was never recorded, so only the live deployment stays running
+        if let Err(e) = deployment_repo.retire_superseded(&application.id, &deployment_id).await {
+            warn!("Failed to mark superseded deployments rolled back: {}", e);
+        }
         ApplicationRepository::new(db.clone())
             .update_status(&application.id, AppStatus::Running).await?;
         send_log("Deployment completed successfully!".to_string()).await;
+        send_phase(DeploymentPhase::Done);
 
         // Broadcast deployment status change
-        let _ = ws_broadcast.send(WsEvent::DeploymentStatus {
-            deployment_id: deployment_id.clone(),
-            app_id: application.id.clone(),
-            status: DeploymentStatus::Running,
-        });
+        Self::broadcast_status(&db, &ws_broadcast, &deployment_id).await;
 
-        // Clean up build directory
-        let _ = tokio::fs::remove_dir_all(context_path).await;
+        Ok(())
+    }
 
+    /// Re-create the container of `prev`, the deployment a failed deploy had already
+    /// removed, from its image and the failed deploy's container settings. `prev` is
+    /// marked running again and the app's routes are pointed at it.
+    async fn restore_previous(
+        db: &SqlitePool,
+        docker: &DockerClient,
+        caddy: Option<&CaddyClient>,
+        application: &Application,
+        prev: &Deployment,
+        mut config: ContainerConfig,
+    ) -> Result<()> {
+        config.image = prev.image_tag.clone();
+        if let Some(labels) = config.labels.as_mut() {
+            labels.insert(LABEL_DEPLOYMENT_ID.to_string(), prev.id.clone());
+        }
+        let container_id = docker.create_container(config).await?;
+        docker.start_container(&container_id).await?;
+        let host_port = match application.port {
+            Some(port) => docker.host_port(&container_id, port).await?,
+            None => None,
+        };
+
+        let deployment_repo = DeploymentRepository::new(db.clone());
+        deployment_repo.set_container_id(&prev.id, &container_id).await?;
+        deployment_repo.set_host_port(&prev.id, host_port).await?;
+        deployment_repo.update_status(&prev.id, DeploymentStatus::Running).await?;
+
+        if let (Some(caddy), Some(port)) = (caddy, host_port) {
+            repoint_domains(caddy, db, &application.id, &format!("localhost:{}", port), None).await?;
+        }
         Ok(())
     }
 
+    /// Wait for a freshly started container to serve traffic: it must stay running,
+    /// pass its Docker HEALTHCHECK if it defines one, and pass the app's configured
+    /// health check on its host port (or answer any HTTP request when none is set)
+    async fn wait_until_healthy(
+        docker: &DockerClient,
+        container_id: &str,
+        host_port: Option<u16>,
+        health_check: Option<&HealthCheck>,
+    ) -> Result<()> {
+        let deadline = tokio::time::Instant::now() + HEALTH_CHECK_TIMEOUT;
+        let client = build_http_client(None, Duration::from_secs(2))?;
+
+        loop {
+            let state = docker.inspect_container(container_id).await?.state.unwrap_or_default();
+            if !state.running.unwrap_or(false) {
+                return Err(anyhow!(
+                    "Container exited with code {} before becoming healthy",
+                    state.exit_code.unwrap_or_default()
+                ));
+            }
+
+            let docker_health = state
+                .health
+                .and_then(|h| h.status)
+                .map(|s| s.to_string())
+                .filter(|s| !s.is_empty() && s != "none");
+
+            let healthy = match docker_health.as_deref() {
+                Some("healthy") => true,
+                Some("unhealthy") => return Err(anyhow!("Container reported unhealthy")),
+                Some(_) => false,
+                None => match (host_port, health_check) {
+                    (Some(port), Some(hc)) => {
+                        probe_port(hc, port).await.0 == HealthCheckStatus::Healthy
+                    }
+                    // Without a configured check, any HTTP answer means the app is up
+                    (Some(port), None) => {
+                        client.get(format!("http://localhost:{}/", port)).send().await.is_ok()
+                    }
+                    (None, _) => true,
+                },
+            };
+
+            if healthy {
+                return Ok(());
+            }
+            if tokio::time::Instant::now() >= deadline {
+                return Err(anyhow!(
+                    "Container did not become healthy within {} seconds",
+                    HEALTH_CHECK_TIMEOUT.as_secs()
+                ));
+            }
+            tokio::time::sleep(Duration::from_secs(1)).await;
+        }
+    }
+
+    /// Tell clients a deployment's stored status, with when it finished and how
+    /// long it took once it has
+    async fn broadcast_status(db: &SqlitePool, ws_broadcast: &broadcast::Sender<WsEvent>, deployment_id: &str) {
+        if let Ok(Some(deployment)) = DeploymentRepository::new(db.clone()).find_by_id(deployment_id).await {
+            let _ = ws_broadcast.send(WsEvent::deployment_status(&deployment));
+        }
+    }
+
+    /// Remove the half-built container of an aborted pipeline
+    async fn cleanup_aborted(db: &SqlitePool, docker: &DockerClient, deployment_id: &str) {
+        let deployment_repo = DeploymentRepository::new(db.clone());
+        if let Ok(Some(deployment)) = deployment_repo.find_by_id(deployment_id).await {
+            if let Some(container_id) = &deployment.container_id {
+                // The pipeline's own guard may have removed it already
+                match docker.remove_container(container_id, true).await {
+                    Ok(()) | Err(DockerError::NotFound(_)) => {}
+                    Err(e) => warn!("Failed to remove container {} of aborted deployment: {}", container_id, e),
+                }
+            }
+        }
+    }
+
+    /// Application status once an aborted deployment is cleaned up: `Running` while
+    /// an earlier deployment's container still serves, `Stopped` otherwise
+    async fn status_after_abort(db: &SqlitePool, docker: &DockerClient, application_id: &str) -> AppStatus {
+        let live = DeploymentRepository::new(db.clone())
+            .get_latest_running(application_id)
+            .await
+            .ok()
+            .flatten()
+            .and_then(|d| d.container_id);
+        let Some(container_id) = live else {
+            return AppStatus::Stopped;
+        };
+        match docker.inspect_container(&container_id).await {
+            Ok(inspect) if inspect.state.as_ref().and_then(|s| s.running).unwrap_or(false) => AppStatus::Running,
+            _ => AppStatus::Stopped,
+        }
+    }
+
     /// Cancel a running deployment
     pub async fn cancel_deployment(&self, deployment_id: &str) -> Result<bool> {
         let deployment_repo = DeploymentRepository::new(self.db.clone());
         let cancelled = deployment_repo.cancel(deployment_id).await?;
 
         if cancelled {
-            // Get deployment to find app_id
-            if let Some(deployment) = deployment_repo.find_by_id(deployment_id).await? {
-                let _ = self.ws_broadcast.send(WsEvent::DeploymentStatus {
-                    deployment_id: deployment_id.to_string(),
-                    app_id: deployment.application_id,
-                    status: DeploymentStatus::Cancelled,
-                });
-            }
+            // Stop the in-flight pipeline; cleanup happens in the deployment task
+            self.tracker.cancel(deployment_id);
+
+            Self::broadcast_status(&self.db, &self.ws_broadcast, deployment_id).await;
         }
 
         Ok(cancelled)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn test_unwritable_repo_cache_falls_back_to_fresh_clones() {
+        let dir = std::env::temp_dir().join(format!("ployer-repo-cache-{}", uuid::Uuid::new_v4()));
+        let tracker = DeploymentTracker::new(1, Some(dir.clone()));
+        assert_eq!(tracker.repo_cache_path("app"), Some(dir.join("app")));
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        // A directory can't be created under a regular file
+        let file = std::env::temp_dir().join(format!("ployer-repo-cache-{}", uuid::Uuid::new_v4()));
+        std::fs::write(&file, b"").unwrap();
+        let tracker = DeploymentTracker::new(1, Some(file.join("repos")));
+        assert_eq!(tracker.repo_cache_path("app"), None);
+        std::fs::remove_file(&file).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_build_slots_serialize_pipelines() {
+        let tracker = DeploymentTracker::new(1, None);
+        let running = Arc::new(AtomicUsize::new(0));
+        let max_running = Arc::new(AtomicUsize::new(0));
+
+        let tasks: Vec<_> = (0..3)
+            .map(|_| {
+                let tracker = tracker.clone();
+                let running = running.clone();
+                let max_running = max_running.clone();
+                tokio::spawn(async move {
+                    let _slot = tracker.acquire_build_slot().await;
+                    let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                    max_running.fetch_max(now, Ordering::SeqCst);
+                    tokio::time::sleep(Duration::from_millis(20)).await;
+                    running.fetch_sub(1, Ordering::SeqCst);
+                })
+            })
+            .collect();
+
+        for task in tasks {
+            task.await.unwrap();
+        }
+
+        assert_eq!(max_running.load(Ordering::SeqCst), 1);
+        assert_eq!(tracker.build_slots.available_permits(), 1);
+    }
+
+    #[test]
+    fn test_deploy_env_split_by_scope() {
+        let var = |key: &str, scope| (key.to_string(), format!("{}-value", key), scope);
+        let env = DeployEnv::from_vars([
+            var("NPM_TOKEN", EnvVarScope::Build),
+            var("DATABASE_URL", EnvVarScope::Runtime),
+            var("NODE_ENV", EnvVarScope::Both),
+        ]);
+
+        assert_eq!(
+            env.runtime,
+            vec![
+                ("DATABASE_URL".to_string(), "DATABASE_URL-value".to_string()),
+                ("NODE_ENV".to_string(), "NODE_ENV-value".to_string()),
+            ]
+        );
+        let mut build_keys: Vec<&str> = env.build_args.keys().map(String::as_str).collect();
+        build_keys.sort_unstable();
+        assert_eq!(build_keys, vec!["NODE_ENV", "NPM_TOKEN"]);
+        assert_eq!(env.build_args["NPM_TOKEN"], "NPM_TOKEN-value");
+    }
+
+    #[test]
+    fn test_deploy_env_interpolation() {
+        let var = |key: &str, value: &str| (key.to_string(), value.to_string(), EnvVarScope::Both);
+        let builtins = || HashMap::from([("APP_NAME".to_string(), "shop".to_string())]);
+
+        let env = DeployEnv::from_vars([
+            var("DB_PASSWORD", "s3cret"),
+            var("DATABASE_URL", "postgres://${APP_NAME}:${DB_PASSWORD}@db/${DB_NAME}"),
+            var("DB_NAME", "${APP_NAME}_prod"),
+            var("PRICE", "$$5 or $5"),
+        ])
+        .expand(builtins())
+        .unwrap();
+        assert_eq!(env.build_args["DATABASE_URL"], "postgres://shop:s3cret@db/shop_prod");
+        assert_eq!(env.build_args["PRICE"], "$5 or $5");
+        assert!(env.runtime.contains(&("DB_NAME".to_string(), "shop_prod".to_string())));
+
+        let undefined = DeployEnv::from_vars([var("URL", "http://${HOST}/")]).expand(builtins());
+        assert!(undefined.unwrap_err().to_string().contains("undefined variable HOST"));
+
+        let cycle = DeployEnv::from_vars([var("A", "${B}"), var("B", "x${A}")]).expand(builtins());
+        assert!(cycle.unwrap_err().to_string().contains("cycle"));
+    }
+
+    #[tokio::test]
+    async fn test_failed_clone_marks_deployment_failed() {
+        let db = ployer_db::memory_pool().await.unwrap();
+        for statement in [
+            "INSERT INTO servers (id, name, host, is_local, created_at, updated_at) VALUES ('local', 'local', 'localhost', 1, '2024-01-01T00:00:00Z', '2024-01-01T00:00:00Z')",
+            "INSERT INTO applications (id, name, server_id, git_url, created_at, updated_at) VALUES ('a1', 'web', 'local', 'file:///nonexistent/ployer-test.git', '2024-01-01T00:00:00Z', '2024-01-01T00:00:00Z')",
+        ] {
+            sqlx::query(statement).execute(&db).await.unwrap();
+        }
+        let application = ApplicationRepository::new(db.clone())
+            .find_by_id("a1")
+            .await
+            .unwrap()
+            .unwrap();
+
+        let (ws_broadcast, mut events) = broadcast::channel(64);
+        let service = DeploymentService::new(
+            db.clone(),
+            // The clone fails before Docker is ever reached
+            Arc::new(DockerClient::new("/tmp/ployer-test-no-docker.sock").unwrap()),
+            None,
+            "localhost".to_string(),
+            ws_broadcast,
+            DeploymentTracker::new(1, None),
+            Duration::from_secs(60),
+        );
+        let deployment = service
+            .deploy(application, None, None, DeployEnv::default(), DeployOptions::default())
+            .await
+            .unwrap();
+
+        // The Failed status event is the last thing the pipeline task sends
+        let status = loop {
+            let event = tokio::time::timeout(Duration::from_secs(30), events.recv())
+                .await
+                .unwrap()
+                .unwrap();
+            if let WsEvent::DeploymentStatus { deployment_id, status, finished_at, .. } = event {
+                assert_eq!(deployment_id, deployment.id);
+                assert!(finished_at.is_some());
+                break status;
+            }
+        };
+        assert_eq!(status, DeploymentStatus::Failed);
+
+        let stored = DeploymentRepository::new(db.clone())
+            .find_by_id(&deployment.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.status, DeploymentStatus::Failed);
+        assert!(stored.finished_at.is_some());
+        let log = stored.build_log.unwrap();
+        assert!(log.contains("Cloning repository: file:///nonexistent/ployer-test.git"));
+        assert!(log.contains("ERROR: "), "{}", log);
+        // The partial clone went with the pipeline
+        assert!(!build_dir_path(&deployment.id).exists());
+
+        let application = ApplicationRepository::new(db).find_by_id("a1").await.unwrap().unwrap();
+        assert_eq!(application.status, AppStatus::Failed);
+    }
+}
@@ -1,19 +1,27 @@
-use ployer_core::models::{ServerStatus, WsEvent};
+use ployer_core::crypto;
+use ployer_core::models::{Server, ServerStatus, WsEvent};
 use ployer_db::repositories::ServerRepository;
+use ployer_docker::DockerClient;
 use ployer_server::ServerManager;
 use sqlx::SqlitePool;
+use std::sync::Arc;
 use std::time::Duration;
 use tokio::sync::broadcast;
 use tracing::{info, warn};
 
-pub fn spawn_health_monitor(db: SqlitePool, ws_broadcast: broadcast::Sender<WsEvent>) {
+pub fn spawn_health_monitor(
+    db: SqlitePool,
+    docker: Option<Arc<DockerClient>>,
+    ws_broadcast: broadcast::Sender<WsEvent>,
+    secret_key: [u8; 32],
+) {
     tokio::spawn(async move {
         let mut interval = tokio::time::interval(Duration::from_secs(30));
 
         loop {
             interval.tick().await;
 
-            if let Err(e) = check_servers(&db, &ws_broadcast).await {
+            if let Err(e) = check_servers(&db, docker.as_deref(), &ws_broadcast, &secret_key).await {
                 warn!("Health check error: {}", e);
             }
         }
@@ -22,36 +30,38 @@ pub fn spawn_health_monitor(db: SqlitePool, ws_broadcast: broadcast::Sender<WsEv
     info!("Health monitor started (30s interval)");
 }
 
-async fn check_servers(db: &SqlitePool, ws_broadcast: &broadcast::Sender<WsEvent>) -> anyhow::Result<()> {
+async fn check_servers(
+    db: &SqlitePool,
+    docker: Option<&DockerClient>,
+    ws_broadcast: &broadcast::Sender<WsEvent>,
+    secret_key: &[u8; 32],
+) -> anyhow::Result<()> {
     let repo = ServerRepository::new(db.clone());
     let servers = repo.list().await?;
 
     for server in servers {
         let old_status = server.status.clone();
 
-        let new_status = if server.is_local {
-            // Local server is always online if Ployer is running
-            ServerStatus::Online
+        let reachable = if server.is_local {
+            // Local server is always reachable if Ployer is running
+            true
         } else {
+            let ssh_key = server
+                .ssh_key_encrypted
+                .as_deref()
+                .and_then(|encrypted| crypto::decrypt(encrypted, secret_key).ok());
+
             // Test remote server connectivity
-            match ServerManager::test_ssh_connection(
+            ServerManager::test_ssh_connection(
                 &server.host,
                 server.port,
                 &server.username,
-                server.ssh_key_encrypted.as_deref(),
+                ssh_key.as_deref(),
             )
             .await
-            {
-                Ok(reachable) => {
-                    if reachable {
-                        ServerStatus::Online
-                    } else {
-                        ServerStatus::Offline
-                    }
-                }
-                Err(_) => ServerStatus::Offline,
-            }
+            .unwrap_or(false)
         };
+        let new_status = ServerStatus::from_checks(reachable, docker_up(&server, docker).await);
 
         // Update if status changed
         if old_status != new_status {
@@ -76,3 +86,15 @@ async fn check_servers(db: &SqlitePool, ws_broadcast: &broadcast::Sender<WsEvent
 
     Ok(())
 }
+
+/// Whether the server's Docker daemon answers a ping. Only the local daemon has
+/// a client here, so remote servers are judged on reachability alone
+pub(crate) async fn docker_up(server: &Server, docker: Option<&DockerClient>) -> bool {
+    if !server.is_local {
+        return true;
+    }
+    match docker {
+        Some(docker) => docker.ping().await.unwrap_or(false),
+        None => false,
+    }
+}
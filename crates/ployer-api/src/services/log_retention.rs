@@ -0,0 +1,28 @@
+use ployer_db::repositories::DeploymentRepository;
+use sqlx::SqlitePool;
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// Clear the build logs of deployments older than `retention_days` once an hour
+pub fn spawn_log_retention(db: SqlitePool, retention_days: u64) {
+    if retention_days == 0 {
+        info!("Build log retention disabled");
+        return;
+    }
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(3600));
+
+        loop {
+            interval.tick().await;
+
+            match DeploymentRepository::new(db.clone()).clear_old_logs(retention_days).await {
+                Ok(0) => {}
+                Ok(cleared) => info!("Cleared build logs of {} old deployments", cleared),
+                Err(e) => warn!("Build log cleanup error: {}", e),
+            }
+        }
+    });
+
+    info!("Build log retention started ({} days)", retention_days);
+}
@@ -1,7 +1,18 @@
 pub mod health_monitor;
 pub mod app_health_monitor;
 pub mod stats_aggregator;
+pub mod log_retention;
+pub mod ssl_monitor;
 pub mod deployment;
+pub mod build_dir;
 pub mod webhook;
+pub mod webhook_retrier;
+pub mod deploy_scheduler;
+pub mod server_stats;
+pub mod reconciler;
+pub mod audit;
+pub mod key_encryption;
 
-pub use deployment::DeploymentService;
+pub use deployment::{
+    start_deployment, DeployOptions, DeploymentService, DeploymentTracker,
+};
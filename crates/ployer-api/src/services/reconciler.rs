@@ -0,0 +1,145 @@
+use ployer_core::models::{Deployment, DeploymentStatus};
+use ployer_db::repositories::{ApplicationRepository, DeploymentRepository};
+use ployer_docker::{ContainerFilters, DockerClient, LABEL_DEPLOYMENT_ID};
+use ployer_proxy::CaddyClient;
+use sqlx::SqlitePool;
+use std::collections::{HashMap, HashSet};
+use tracing::{info, warn};
+
+use crate::services::deployment::repoint_domains;
+
+/// Reconcile Docker containers with deployment records after a restart.
+///
+/// Deployments that were mid-pipeline or whose container no longer exists are
+/// marked failed, and Ployer-labeled containers without a deployment record are stopped.
+/// Only an app's latest running deployment is checked; older ones still marked
+/// running were superseded and are marked rolled back. Docker may give a restarted
+/// container a new host port, so a live deployment's port and the app's proxy
+/// routes are brought up to date with it.
+pub async fn reconcile_containers(
+    db: &SqlitePool,
+    docker: &DockerClient,
+    caddy: &CaddyClient,
+) -> anyhow::Result<()> {
+    let deployment_repo = DeploymentRepository::new(db.clone());
+
+    let containers = docker.list_containers(true, &ContainerFilters::default()).await?;
+    let existing: HashSet<&str> = containers.iter().map(|c| c.id.as_str()).collect();
+
+    let deployments = deployment_repo.list(None).await?;
+    let known: HashSet<&str> = deployments.iter().map(|d| d.id.as_str()).collect();
+
+    // Deployments are listed newest first, so the first running one per app is live
+    let mut live: HashMap<&str, &str> = HashMap::new();
+    for deployment in deployments.iter().filter(|d| d.status == DeploymentStatus::Running) {
+        live.entry(deployment.application_id.as_str()).or_insert(deployment.id.as_str());
+    }
+
+    let mut failed = 0;
+    let mut superseded = 0;
+    let mut repointed = 0;
+    for deployment in &deployments {
+        if deployment.status == DeploymentStatus::Running
+            && live.get(deployment.application_id.as_str()) != Some(&deployment.id.as_str())
+        {
+            deployment_repo
+                .update_status(&deployment.id, DeploymentStatus::RolledBack)
+                .await?;
+            superseded += 1;
+            continue;
+        }
+
+        let interrupted = matches!(
+            deployment.status,
+            DeploymentStatus::Queued
+                | DeploymentStatus::Cloning
+                | DeploymentStatus::Building
+                | DeploymentStatus::Deploying
+        );
+        let container_gone = deployment.status == DeploymentStatus::Running
+            && !deployment
+                .container_id
+                .as_deref()
+                .is_some_and(|id| existing.contains(id));
+
+        if interrupted || container_gone {
+            deployment_repo
+                .update_status(&deployment.id, DeploymentStatus::Failed)
+                .await?;
+            let reason = if interrupted {
+                "Deployment interrupted by Ployer restart"
+            } else {
+                "Container no longer exists"
+            };
+            let _ = deployment_repo.append_log(&deployment.id, &format!("ERROR: {}", reason)).await;
+            failed += 1;
+        } else if deployment.status == DeploymentStatus::Running {
+            match resync_host_port(db, docker, caddy, deployment).await {
+                Ok(true) => repointed += 1,
+                Ok(false) => {}
+                Err(e) => warn!("Failed to refresh host port of deployment {}: {}", deployment.id, e),
+            }
+        }
+    }
+
+    let mut stopped = 0;
+    for container in &containers {
+        let Some(deployment_id) = container.labels.get(LABEL_DEPLOYMENT_ID) else {
+            continue;
+        };
+        if known.contains(deployment_id.as_str()) || container.state != "running" {
+            continue;
+        }
+
+        match docker.stop_container(&container.id, None).await {
+            Ok(_) => stopped += 1,
+            Err(e) => warn!("Failed to stop orphaned container {}: {}", container.name, e),
+        }
+    }
+
+    info!(
+        "Container reconciliation: {} deployments marked failed, {} superseded deployments marked rolled back, {} moved to a new host port, {} orphaned containers stopped",
+        failed, superseded, repointed, stopped
+    );
+    Ok(())
+}
+
+/// Record the host port a live deployment's container is bound to now and point the
+/// app's routes at it. Returns whether the port had changed.
+async fn resync_host_port(
+    db: &SqlitePool,
+    docker: &DockerClient,
+    caddy: &CaddyClient,
+    deployment: &Deployment,
+) -> anyhow::Result<bool> {
+    let Some(container_id) = &deployment.container_id else {
+        return Ok(false);
+    };
+    let Some(port) = ApplicationRepository::new(db.clone())
+        .find_by_id(&deployment.application_id)
+        .await?
+        .and_then(|app| app.port)
+    else {
+        return Ok(false);
+    };
+    // A stopped container has no binding; keep the last known port until it runs again
+    let Some(host_port) = docker.host_port(container_id, port).await? else {
+        return Ok(false);
+    };
+    if deployment.host_port == Some(host_port) {
+        return Ok(false);
+    }
+
+    DeploymentRepository::new(db.clone())
+        .set_host_port(&deployment.id, Some(host_port))
+        .await?;
+    let upstream = format!("localhost:{}", host_port);
+    let domains = repoint_domains(caddy, db, &deployment.application_id, &upstream, None).await?;
+    info!(
+        "Deployment {} moved to host port {}; routes updated for {}",
+        deployment.id,
+        host_port,
+        domains.join(", ")
+    );
+    Ok(true)
+}
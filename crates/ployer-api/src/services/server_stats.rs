@@ -0,0 +1,71 @@
+use ployer_core::models::WsEvent;
+use ployer_db::repositories::ServerRepository;
+use ployer_server::ServerManager;
+use sqlx::SqlitePool;
+use std::path::Path;
+use std::time::Duration;
+use tokio::sync::broadcast;
+use tracing::{info, warn};
+
+/// Push the host's CPU and memory usage to WebSocket clients as `ServerStats`
+/// for every local server, so dashboards don't have to poll the resources endpoint
+pub fn spawn_server_stats(db: SqlitePool, ws_broadcast: broadcast::Sender<WsEvent>, interval_secs: u64) {
+    if interval_secs == 0 {
+        info!("Server stats broadcast disabled");
+        return;
+    }
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(interval_secs));
+        // Kept across ticks: CPU usage is measured between two consecutive refreshes
+        let mut manager = Some(ServerManager::new());
+
+        loop {
+            interval.tick().await;
+
+            // Sampling refreshes every process, so skip it while nobody is listening
+            if ws_broadcast.receiver_count() == 0 {
+                continue;
+            }
+
+            let server_ids = match ServerRepository::new(db.clone()).list().await {
+                Ok(servers) => servers.into_iter().filter(|s| s.is_local).map(|s| s.id).collect::<Vec<_>>(),
+                Err(e) => {
+                    warn!("Server stats: failed to list servers: {}", e);
+                    continue;
+                }
+            };
+            if server_ids.is_empty() {
+                continue;
+            }
+
+            let mut sampler = manager.take().unwrap_or_else(ServerManager::new);
+            let sampled = tokio::task::spawn_blocking(move || {
+                let stats = sampler.local_stats(Path::new("/"));
+                (sampler, stats)
+            })
+            .await;
+            let stats = match sampled {
+                Ok((sampler, stats)) => {
+                    manager = Some(sampler);
+                    stats
+                }
+                Err(e) => {
+                    warn!("Server stats sampling panicked: {}", e);
+                    continue;
+                }
+            };
+
+            for server_id in server_ids {
+                let _ = ws_broadcast.send(WsEvent::ServerStats {
+                    server_id,
+                    cpu: f64::from(stats.cpu_usage),
+                    mem_used: stats.used_memory_mb,
+                    mem_total: stats.total_memory_mb,
+                });
+            }
+        }
+    });
+
+    info!("Server stats broadcast started ({}s interval)", interval_secs);
+}
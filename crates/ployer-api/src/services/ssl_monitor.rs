@@ -0,0 +1,59 @@
+use ployer_core::models::WsEvent;
+use ployer_db::repositories::DomainRepository;
+use ployer_proxy::{CaddyClient, SslStatus};
+use sqlx::SqlitePool;
+use std::time::Duration;
+use tokio::sync::broadcast;
+use tracing::{info, warn};
+
+pub fn spawn_ssl_monitor(
+    db: SqlitePool,
+    caddy: CaddyClient,
+    ws_broadcast: broadcast::Sender<WsEvent>,
+) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(300));
+
+        loop {
+            interval.tick().await;
+
+            if let Err(e) = check_domains(&db, &caddy, &ws_broadcast).await {
+                warn!("SSL status check error: {}", e);
+            }
+        }
+    });
+
+    info!("SSL monitor started (300s interval)");
+}
+
+/// Sync each domain's `ssl_active` flag with the certificate Caddy actually serves
+async fn check_domains(
+    db: &SqlitePool,
+    caddy: &CaddyClient,
+    ws_broadcast: &broadcast::Sender<WsEvent>,
+) -> anyhow::Result<()> {
+    let repo = DomainRepository::new(db.clone());
+
+    // Wildcard hosts can't be probed directly
+    for domain in repo.list().await?.into_iter().filter(|d| !d.domain.starts_with("*.")) {
+        // An unreachable Caddy says nothing about the certificates; try again next round
+        let active = caddy.get_ssl_status(&domain.domain).await? == SslStatus::Active;
+
+        if active != domain.ssl_active {
+            info!(
+                "Domain {}: ssl_active {} -> {}",
+                domain.domain, domain.ssl_active, active
+            );
+            repo.update_ssl_status(&domain.id, active).await?;
+
+            if active {
+                let _ = ws_broadcast.send(WsEvent::DomainSslActive {
+                    app_id: domain.application_id.clone(),
+                    domain: domain.domain.clone(),
+                });
+            }
+        }
+    }
+
+    Ok(())
+}
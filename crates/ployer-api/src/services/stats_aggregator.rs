@@ -38,7 +38,7 @@ async fn collect_container_stats(db: &SqlitePool, docker: &DockerClient) -> anyh
     let deployment_repo = DeploymentRepository::new(db.clone());
     let app_repo = ApplicationRepository::new(db.clone());
 
-    let applications = app_repo.list().await?;
+    let applications = app_repo.list(false).await?;
 
     for app in applications {
         // Get the latest running deployment
@@ -2,6 +2,7 @@ use anyhow::{Result, anyhow};
 use hmac::{Hmac, Mac};
 use sha2::Sha256;
 use serde::{Deserialize, Serialize};
+use ployer_core::models::CommitAuthor;
 
 type HmacSha256 = Hmac<Sha256>;
 
@@ -12,9 +13,26 @@ pub struct WebhookPayload {
     pub commit_sha: String,
     pub commit_message: String,
     pub author: String,
+    pub author_email: Option<String>,
+    pub committer_name: Option<String>,
+    pub committer_email: Option<String>,
+    /// Avatar of the account that pushed
+    pub avatar_url: Option<String>,
     pub repository_url: String,
 }
 
+impl WebhookPayload {
+    pub fn commit_author(&self) -> CommitAuthor {
+        CommitAuthor {
+            name: self.author.clone(),
+            email: self.author_email.clone(),
+            committer_name: self.committer_name.clone(),
+            committer_email: self.committer_email.clone(),
+            avatar_url: self.avatar_url.clone(),
+        }
+    }
+}
+
 /// GitHub push event payload (subset of fields we care about)
 #[derive(Debug, Deserialize)]
 struct GitHubPushEvent {
@@ -22,6 +40,7 @@ struct GitHubPushEvent {
     git_ref: String,
     head_commit: GitHubCommit,
     repository: GitHubRepository,
+    sender: Option<GitHubSender>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -29,11 +48,18 @@ struct GitHubCommit {
     id: String,
     message: String,
     author: GitHubAuthor,
+    committer: Option<GitHubAuthor>,
 }
 
 #[derive(Debug, Deserialize)]
 struct GitHubAuthor {
     name: String,
+    email: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GitHubSender {
+    avatar_url: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -49,6 +75,7 @@ struct GitLabPushEvent {
     checkout_sha: String,
     commits: Vec<GitLabCommit>,
     repository: GitLabRepository,
+    user_avatar: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -60,6 +87,7 @@ struct GitLabCommit {
 #[derive(Debug, Deserialize)]
 struct GitLabAuthor {
     name: String,
+    email: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -107,11 +135,17 @@ pub fn parse_github_push(payload: &[u8]) -> Result<WebhookPayload> {
         .unwrap_or(&event.git_ref)
         .to_string();
 
+    let committer = event.head_commit.committer;
+
     Ok(WebhookPayload {
         branch,
         commit_sha: event.head_commit.id,
         commit_message: event.head_commit.message,
         author: event.head_commit.author.name,
+        author_email: event.head_commit.author.email,
+        committer_name: committer.as_ref().map(|c| c.name.clone()),
+        committer_email: committer.and_then(|c| c.email),
+        avatar_url: event.sender.and_then(|s| s.avatar_url),
         repository_url: event.repository.clone_url,
     })
 }
@@ -137,6 +171,11 @@ pub fn parse_gitlab_push(payload: &[u8]) -> Result<WebhookPayload> {
         commit_sha: event.checkout_sha,
         commit_message: latest_commit.message.clone(),
         author: latest_commit.author.name.clone(),
+        author_email: latest_commit.author.email.clone(),
+        // GitLab push events don't report the committer separately
+        committer_name: None,
+        committer_email: None,
+        avatar_url: event.user_avatar,
         repository_url: event.repository.git_ssh_url,
     })
 }
@@ -186,6 +225,29 @@ mod tests {
         assert_eq!(result.commit_sha, "abc123");
         assert_eq!(result.commit_message, "Fix bug");
         assert_eq!(result.author, "John Doe");
+        assert_eq!(result.author_email, None);
+        assert_eq!(result.avatar_url, None);
+    }
+
+    #[test]
+    fn test_parse_github_push_author_details() {
+        let payload = r#"{
+            "ref": "refs/heads/main",
+            "head_commit": {
+                "id": "abc123",
+                "message": "Fix bug",
+                "author": {"name": "John Doe", "email": "john@example.com", "username": "jdoe"},
+                "committer": {"name": "GitHub", "email": "noreply@github.com"}
+            },
+            "repository": {"clone_url": "https://github.com/user/repo.git"},
+            "sender": {"login": "jdoe", "avatar_url": "https://avatars.githubusercontent.com/u/1"}
+        }"#;
+
+        let author = parse_github_push(payload.as_bytes()).unwrap().commit_author();
+        assert_eq!(author.email.as_deref(), Some("john@example.com"));
+        assert_eq!(author.committer_name.as_deref(), Some("GitHub"));
+        assert_eq!(author.committer_email.as_deref(), Some("noreply@github.com"));
+        assert_eq!(author.avatar_url.as_deref(), Some("https://avatars.githubusercontent.com/u/1"));
     }
 
     #[test]
@@ -209,5 +271,13 @@ mod tests {
         assert_eq!(result.commit_sha, "def456");
         assert_eq!(result.commit_message, "Add feature");
         assert_eq!(result.author, "Jane Smith");
+        assert_eq!(result.author_email, None);
+
+        let with_email = payload
+            .replace(r#"{"name": "Jane Smith"}"#, r#"{"name": "Jane Smith", "email": "jane@example.com"}"#)
+            .replace(r#""checkout_sha""#, r#""user_avatar": "https://gitlab.com/avatar.png", "checkout_sha""#);
+        let result = parse_gitlab_push(with_email.as_bytes()).unwrap();
+        assert_eq!(result.author_email.as_deref(), Some("jane@example.com"));
+        assert_eq!(result.avatar_url.as_deref(), Some("https://gitlab.com/avatar.png"));
     }
 }
@@ -0,0 +1,153 @@
+use chrono::{DateTime, Utc};
+use ployer_core::models::{WebhookDelivery, WebhookDeliveryStatus};
+use ployer_db::repositories::{ApplicationRepository, DeploymentRepository, WebhookRepository};
+use std::time::Duration;
+use tracing::{info, warn};
+
+use crate::app_state::SharedState;
+use crate::services::{start_deployment, DeployOptions};
+
+/// Maximum number of attempts (including the original delivery)
+const MAX_ATTEMPTS: i32 = 5;
+/// Only retry deliveries received within this window
+const MAX_AGE_HOURS: i64 = 24;
+/// Delay before the first retry; doubles with each further attempt
+const BASE_DELAY_SECS: i64 = 60;
+
+pub fn spawn_webhook_retrier(state: SharedState) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(30));
+
+        loop {
+            interval.tick().await;
+
+            if let Err(e) = retry_failed_deliveries(&state).await {
+                warn!("Webhook retry error: {}", e);
+            }
+        }
+    });
+
+    info!("Webhook retrier started (30s interval, max {} attempts)", MAX_ATTEMPTS);
+}
+
+/// Backoff delay before the next attempt, given how many attempts were already made
+fn retry_delay(attempts: i32) -> chrono::Duration {
+    let exponent = (attempts - 1).clamp(0, 16) as u32;
+    chrono::Duration::seconds(BASE_DELAY_SECS * 2i64.pow(exponent))
+}
+
+fn is_due(delivery: &WebhookDelivery, now: DateTime<Utc>) -> bool {
+    let last_attempt = delivery.last_attempt_at.unwrap_or(delivery.delivered_at);
+    now >= last_attempt + retry_delay(delivery.attempts)
+}
+
+async fn retry_failed_deliveries(state: &SharedState) -> anyhow::Result<()> {
+    if state.docker.is_none() {
+        return Ok(());
+    }
+
+    let webhook_repo = WebhookRepository::new(state.db.clone());
+    let app_repo = ApplicationRepository::new(state.db.clone());
+    let deployment_repo = DeploymentRepository::new(state.db.clone());
+
+    // A delivery stays pending until its deployment finishes; one whose build or
+    // health check failed becomes eligible for another attempt below
+    let settled = webhook_repo.resolve_pending().await?;
+    if settled > 0 {
+        info!("Settled {} webhook deliveries from their deployments", settled);
+    }
+
+    let deliveries = webhook_repo
+        .list_failed_for_retry(chrono::Duration::hours(MAX_AGE_HOURS), MAX_ATTEMPTS)
+        .await?;
+
+    let now = Utc::now();
+
+    for delivery in deliveries.into_iter().filter(|d| is_due(d, now)) {
+        let application = match app_repo.find_by_id(&delivery.application_id).await? {
+            Some(app) => app,
+            None => continue,
+        };
+
+        // The app or its webhook may have changed since the push arrived
+        let webhook = webhook_repo.find_by_application(&application.id).await?;
+        let skip_reason = if application.archived_at.is_some() {
+            Some("Application is archived")
+        } else {
+            match webhook {
+                Some(webhook) if webhook.id != delivery.webhook_id => Some("Webhook was replaced"),
+                Some(webhook) if !webhook.enabled => Some("Webhook is disabled"),
+                Some(_) => None,
+                None => Some("Webhook was removed"),
+            }
+        };
+        if let Some(reason) = skip_reason {
+            info!("Not retrying webhook delivery {}: {}", delivery.id, reason);
+            webhook_repo
+                .record_retry(&delivery.id, WebhookDeliveryStatus::Skipped, Some(reason), None)
+                .await?;
+            continue;
+        }
+
+        // Skip redeploying if this commit is already deployed or deploying
+        if let Some(commit_sha) = &delivery.commit_sha {
+            if let Some(existing) = deployment_repo
+                .find_active_for_commit(&application.id, commit_sha)
+                .await?
+            {
+                info!(
+                    "Webhook delivery {} already satisfied by deployment {}",
+                    delivery.id, existing.id
+                );
+                webhook_repo
+                    .record_retry(&delivery.id, WebhookDeliveryStatus::Pending, None, Some(&existing.id))
+                    .await?;
+                continue;
+            }
+        }
+
+        let options = DeployOptions {
+            commit_sha: delivery.commit_sha.clone(),
+            commit_message: delivery.commit_message.clone(),
+            ..Default::default()
+        };
+        match start_deployment(state, application, options).await {
+            Ok(deployment) => {
+                info!(
+                    "Webhook delivery {} retried (attempt {}), deployment {}",
+                    delivery.id,
+                    delivery.attempts + 1,
+                    deployment.id
+                );
+                webhook_repo
+                    .record_retry(&delivery.id, WebhookDeliveryStatus::Pending, None, Some(&deployment.id))
+                    .await?;
+            }
+            Err(e) => {
+                warn!(
+                    "Webhook delivery {} retry failed (attempt {}): {}",
+                    delivery.id,
+                    delivery.attempts + 1,
+                    e
+                );
+                webhook_repo
+                    .record_retry(&delivery.id, WebhookDeliveryStatus::Failed, Some(&e.to_string()), None)
+                    .await?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_retry_delay_doubles() {
+        assert_eq!(retry_delay(1), chrono::Duration::seconds(60));
+        assert_eq!(retry_delay(2), chrono::Duration::seconds(120));
+        assert_eq!(retry_delay(4), chrono::Duration::seconds(480));
+    }
+}
@@ -3,17 +3,22 @@ use axum::{
         ws::{Message, WebSocket},
         Query, State, WebSocketUpgrade,
     },
+    http::{header, HeaderMap, StatusCode},
     response::Response,
 };
+use chrono::{DateTime, Utc};
 use futures_util::{SinkExt, StreamExt};
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
-use tokio::sync::Mutex;
+use std::time::Duration;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::{mpsc, Mutex};
 use tracing::{info, warn};
 
 use crate::app_state::SharedState;
 use crate::auth::validate_token;
+use crate::error::ApiError;
 use ployer_core::models::WsEvent;
 
 // Client message types (from browser to server)
@@ -38,6 +43,14 @@ enum WsServerMessage {
         status: String,
         timestamp: String,
     },
+    #[serde(rename = "server_stats")]
+    ServerStats {
+        server_id: String,
+        cpu: f64,
+        mem_used: u64,
+        mem_total: u64,
+        timestamp: String,
+    },
     #[serde(rename = "container_logs")]
     #[allow(dead_code)]
     ContainerLogs {
@@ -57,6 +70,15 @@ enum WsServerMessage {
         deployment_id: String,
         status: String,
         message: Option<String>,
+        /// Set once the deployment has finished, as is `duration_seconds`
+        finished_at: Option<String>,
+        duration_seconds: Option<i64>,
+    },
+    #[serde(rename = "deployment_phase")]
+    DeploymentPhase {
+        deployment_id: String,
+        phase: String,
+        timestamp: String,
     },
     #[serde(rename = "deployment_logs")]
     DeploymentLogs {
@@ -70,8 +92,28 @@ enum WsServerMessage {
         status: String,
         timestamp: String,
     },
+    #[serde(rename = "app_alert")]
+    AppAlert {
+        app_id: String,
+        kind: String,
+        detail: String,
+        timestamp: String,
+    },
+    #[serde(rename = "domain_ssl_active")]
+    DomainSslActive {
+        app_id: String,
+        domain: String,
+        timestamp: String,
+    },
+    /// The client fell behind the broadcast and `missed` events were dropped for it
+    #[serde(rename = "events_missed")]
+    EventsMissed {
+        missed: u64,
+        timestamp: String,
+    },
     #[serde(rename = "pong")]
     Pong,
+    /// A client message the server couldn't act on
     #[serde(rename = "error")]
     Error { message: String },
 }
@@ -120,38 +162,133 @@ impl ConnectionManager {
     }
 }
 
+/// How often the server pings each connection
+const PING_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Connections silent for this long (no pong or any other frame) are closed
+const IDLE_TIMEOUT: Duration = Duration::from_secs(90);
+
+/// An open WebSocket connection, as listed by `GET /monitoring/connections`
+#[derive(Debug, Clone, Serialize)]
+pub struct ConnectionInfo {
+    pub id: String,
+    pub user_id: String,
+    pub connected_at: DateTime<Utc>,
+    pub last_seen: DateTime<Utc>,
+}
+
+/// Open WebSocket connections across the server, shared through `AppState`
+#[derive(Clone, Default)]
+pub struct ConnectionRegistry {
+    connections: Arc<Mutex<HashMap<String, ConnectionInfo>>>,
+}
+
+impl ConnectionRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    async fn register(&self, conn_id: &str, user_id: &str) {
+        let now = Utc::now();
+        self.connections.lock().await.insert(
+            conn_id.to_string(),
+            ConnectionInfo {
+                id: conn_id.to_string(),
+                user_id: user_id.to_string(),
+                connected_at: now,
+                last_seen: now,
+            },
+        );
+    }
+
+    /// Record that the client was heard from
+    async fn touch(&self, conn_id: &str) {
+        if let Some(conn) = self.connections.lock().await.get_mut(conn_id) {
+            conn.last_seen = Utc::now();
+        }
+    }
+
+    /// Whether the client has been silent for longer than `timeout`
+    async fn is_idle(&self, conn_id: &str, timeout: Duration) -> bool {
+        let timeout = chrono::Duration::from_std(timeout).unwrap_or(chrono::Duration::MAX);
+        self.connections
+            .lock()
+            .await
+            .get(conn_id)
+            .is_none_or(|conn| Utc::now() - conn.last_seen > timeout)
+    }
+
+    async fn remove(&self, conn_id: &str) {
+        self.connections.lock().await.remove(conn_id);
+    }
+
+    /// Open connections, oldest first
+    pub async fn list(&self) -> Vec<ConnectionInfo> {
+        let mut connections: Vec<_> = self.connections.lock().await.values().cloned().collect();
+        connections.sort_by_key(|c| c.connected_at);
+        connections
+    }
+}
+
 // Query parameters for WebSocket connection
 #[derive(Debug, Deserialize)]
 pub struct WsQuery {
-    token: String,
+    /// Deprecated: query strings end up in proxy and access logs. Send the token
+    /// as the `bearer, <token>` subprotocol or an `Authorization` header instead.
+    token: Option<String>,
+}
+
+/// Subprotocol echoed back to clients that authenticate via `Sec-WebSocket-Protocol`
+const BEARER_PROTOCOL: &str = "bearer";
+
+/// Pick the upgrade's JWT: subprotocol first, then `Authorization`, then the legacy query param
+fn request_token(headers: &HeaderMap, query: &WsQuery) -> Option<String> {
+    let from_protocol = headers
+        .get(header::SEC_WEBSOCKET_PROTOCOL)
+        .and_then(|h| h.to_str().ok())
+        .and_then(|protocols| {
+            let protocols: Vec<&str> = protocols.split(',').map(str::trim).collect();
+            if !protocols.contains(&BEARER_PROTOCOL) {
+                return None;
+            }
+            protocols.into_iter().find(|p| *p != BEARER_PROTOCOL && !p.is_empty())
+        });
+
+    let from_header = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|h| h.to_str().ok())
+        .and_then(|h| h.strip_prefix("Bearer "));
+
+    from_protocol
+        .or(from_header)
+        .map(str::to_string)
+        .or_else(|| query.token.clone())
 }
 
 // WebSocket handler
 pub async fn websocket_handler(
     ws: WebSocketUpgrade,
+    headers: HeaderMap,
     Query(query): Query<WsQuery>,
     State(state): State<SharedState>,
-) -> Response {
-    // Validate JWT token
-    let user_id = match validate_token(&query.token, &state.config.auth.jwt_secret) {
-        Ok(claims) => claims.sub,
-        Err(_) => {
+) -> Result<Response, ApiError> {
+    // Validate JWT token before upgrading
+    let token = request_token(&headers, &query).ok_or_else(|| {
+        warn!("WebSocket connection denied: missing token");
+        ApiError::new(StatusCode::UNAUTHORIZED, "Missing authentication token")
+    })?;
+    let user_id = validate_token(&token, &state.config.auth.jwt_secret)
+        .map_err(|_| {
             warn!("WebSocket connection denied: invalid token");
-            return ws.on_upgrade(|mut socket| async move {
-                let error_msg = WsServerMessage::Error {
-                    message: "Invalid authentication token".to_string(),
-                };
-                if let Ok(json) = serde_json::to_string(&error_msg) {
-                    let _ = socket.send(Message::Text(json)).await;
-                }
-                let _ = socket.close().await;
-            });
-        }
-    };
+            ApiError::new(StatusCode::UNAUTHORIZED, "Invalid authentication token")
+        })?
+        .sub;
 
     info!("WebSocket connection established for user: {}", user_id);
 
-    ws.on_upgrade(move |socket| handle_socket(socket, user_id, state))
+    Ok(ws
+        .protocols([BEARER_PROTOCOL])
+        .on_upgrade(move |socket| handle_socket(socket, user_id, state)))
 }
 
 async fn handle_socket(socket: WebSocket, user_id: String, state: SharedState) {
@@ -159,15 +296,63 @@ async fn handle_socket(socket: WebSocket, user_id: String, state: SharedState) {
     let conn_id = uuid::Uuid::new_v4().to_string();
 
     let manager = ConnectionManager::new();
+    let registry = state.ws_connections.clone();
+    registry.register(&conn_id, &user_id).await;
 
     // Subscribe to broadcast channel
     let mut broadcast_rx = state.ws_broadcast.subscribe();
+    // Direct replies to this client (pongs, errors) go out through the send task
+    let (reply_tx, mut reply_rx) = mpsc::channel::<WsServerMessage>(16);
 
-    // Task to forward broadcast messages to this client
+    // Task to forward broadcast messages to this client and keep it alive
     let manager_clone = manager.clone();
+    let registry_clone = registry.clone();
     let conn_id_clone = conn_id.clone();
     let mut send_task = tokio::spawn(async move {
-        while let Ok(event) = broadcast_rx.recv().await {
+        let mut ping = tokio::time::interval(PING_INTERVAL);
+        ping.tick().await;
+
+        loop {
+            let event = tokio::select! {
+                Some(reply) = reply_rx.recv() => {
+                    if let Ok(json) = serde_json::to_string(&reply) {
+                        if sender.send(Message::Text(json)).await.is_err() {
+                            break;
+                        }
+                    }
+                    continue;
+                }
+                event = broadcast_rx.recv() => match event {
+                    Ok(event) => event,
+                    // Too slow to keep up: skip ahead and tell the client, rather than disconnect
+                    Err(RecvError::Lagged(missed)) => {
+                        warn!("WebSocket client {} lagged behind, {} events dropped", conn_id_clone, missed);
+                        let notice = WsServerMessage::EventsMissed {
+                            missed,
+                            timestamp: chrono::Utc::now().to_rfc3339(),
+                        };
+                        if let Ok(json) = serde_json::to_string(&notice) {
+                            if sender.send(Message::Text(json)).await.is_err() {
+                                break;
+                            }
+                        }
+                        continue;
+                    }
+                    Err(RecvError::Closed) => break,
+                },
+                _ = ping.tick() => {
+                    if registry_clone.is_idle(&conn_id_clone, IDLE_TIMEOUT).await {
+                        info!("Closing idle WebSocket connection: {}", conn_id_clone);
+                        let _ = sender.send(Message::Close(None)).await;
+                        break;
+                    }
+                    if sender.send(Message::Ping(Vec::new())).await.is_err() {
+                        break;
+                    }
+                    continue;
+                }
+            };
+
             // Convert ployer_core::models::WsEvent to our WsServerMessage
             let message = match event {
                 WsEvent::ServerHealth { server_id, status } => {
@@ -177,6 +362,15 @@ async fn handle_socket(socket: WebSocket, user_id: String, state: SharedState) {
                         timestamp: chrono::Utc::now().to_rfc3339(),
                     })
                 }
+                WsEvent::ServerStats { server_id, cpu, mem_used, mem_total } => {
+                    Some(WsServerMessage::ServerStats {
+                        server_id,
+                        cpu,
+                        mem_used,
+                        mem_total,
+                        timestamp: chrono::Utc::now().to_rfc3339(),
+                    })
+                }
                 WsEvent::DeploymentLog { deployment_id, line } => {
                     Some(WsServerMessage::DeploymentLogs {
                         deployment_id,
@@ -184,11 +378,20 @@ async fn handle_socket(socket: WebSocket, user_id: String, state: SharedState) {
                         timestamp: chrono::Utc::now().to_rfc3339(),
                     })
                 }
-                WsEvent::DeploymentStatus { deployment_id, status, .. } => {
+                WsEvent::DeploymentPhase { deployment_id, phase } => {
+                    Some(WsServerMessage::DeploymentPhase {
+                        deployment_id,
+                        phase: phase.as_str().to_string(),
+                        timestamp: chrono::Utc::now().to_rfc3339(),
+                    })
+                }
+                WsEvent::DeploymentStatus { deployment_id, status, finished_at, duration_seconds, .. } => {
                     Some(WsServerMessage::DeploymentStatus {
                         deployment_id,
                         status: status.as_str().to_string(),
                         message: None,
+                        finished_at: finished_at.map(|t| t.to_rfc3339()),
+                        duration_seconds,
                     })
                 }
                 WsEvent::ContainerStats { container_id, cpu_percent, memory_mb } => {
@@ -206,6 +409,21 @@ async fn handle_socket(socket: WebSocket, user_id: String, state: SharedState) {
                         timestamp: chrono::Utc::now().to_rfc3339(),
                     })
                 }
+                WsEvent::AppAlert { app_id, kind, detail } => {
+                    Some(WsServerMessage::AppAlert {
+                        app_id,
+                        kind: kind.as_str().to_string(),
+                        detail,
+                        timestamp: chrono::Utc::now().to_rfc3339(),
+                    })
+                }
+                WsEvent::DomainSslActive { app_id, domain } => {
+                    Some(WsServerMessage::DomainSslActive {
+                        app_id,
+                        domain,
+                        timestamp: chrono::Utc::now().to_rfc3339(),
+                    })
+                }
             };
 
             if let Some(msg) = message {
@@ -222,9 +440,13 @@ async fn handle_socket(socket: WebSocket, user_id: String, state: SharedState) {
 
     // Task to handle incoming messages from client
     let manager_clone = manager.clone();
+    let registry_clone = registry.clone();
     let conn_id_clone = conn_id.clone();
     let mut recv_task = tokio::spawn(async move {
         while let Some(Ok(msg)) = receiver.next().await {
+            // Pongs (and anything else) show the client is still there
+            registry_clone.touch(&conn_id_clone).await;
+
             if let Message::Text(text) = msg {
                 match serde_json::from_str::<WsClientMessage>(&text) {
                     Ok(WsClientMessage::Subscribe { channel }) => {
@@ -234,15 +456,14 @@ async fn handle_socket(socket: WebSocket, user_id: String, state: SharedState) {
                         manager_clone.unsubscribe(&conn_id_clone, &channel).await;
                     }
                     Ok(WsClientMessage::Ping) => {
-                        // Send pong back
-                        let pong = WsServerMessage::Pong;
-                        if let Ok(_json) = serde_json::to_string(&pong) {
-                            // Note: Can't send here directly, would need a channel
-                            // For now, ping/pong is mostly for keepalive
-                        }
+                        let _ = reply_tx.send(WsServerMessage::Pong).await;
                     }
                     Err(e) => {
                         warn!("Failed to parse WebSocket message: {}", e);
+                        let error = WsServerMessage::Error {
+                            message: format!("Invalid message: {}", e),
+                        };
+                        let _ = reply_tx.send(error).await;
                     }
                 }
             } else if let Message::Close(_) = msg {
@@ -258,6 +479,82 @@ async fn handle_socket(socket: WebSocket, user_id: String, state: SharedState) {
         _ = &mut send_task => recv_task.abort(),
         _ = &mut recv_task => send_task.abort(),
     }
+    registry.remove(&conn_id).await;
 
     info!("WebSocket connection closed for user: {}", user_id);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_connection_registry() {
+        let registry = ConnectionRegistry::new();
+        registry.register("c1", "u1").await;
+        registry.register("c2", "u2").await;
+
+        let connections = registry.list().await;
+        assert_eq!(connections.len(), 2);
+        assert_eq!(connections[0].id, "c1");
+        assert_eq!(connections[1].user_id, "u2");
+
+        assert!(!registry.is_idle("c1", IDLE_TIMEOUT).await);
+        tokio::time::sleep(Duration::from_millis(5)).await;
+        assert!(registry.is_idle("c1", Duration::from_millis(1)).await);
+        registry.touch("c1").await;
+        assert!(!registry.is_idle("c1", Duration::from_secs(1)).await);
+
+        registry.remove("c1").await;
+        assert_eq!(registry.list().await.len(), 1);
+        // A connection that's gone counts as idle so its task stops
+        assert!(registry.is_idle("c1", IDLE_TIMEOUT).await);
+    }
+
+    #[tokio::test]
+    async fn test_lagged_receiver_recovers_with_missed_count() {
+        let (tx, mut rx) = tokio::sync::broadcast::channel(2);
+        for i in 0..5 {
+            tx.send(i).unwrap();
+        }
+
+        let missed = match rx.recv().await {
+            Err(RecvError::Lagged(missed)) => missed,
+            other => panic!("expected lag, got {:?}", other),
+        };
+        assert_eq!(missed, 3);
+        // The receiver keeps going from the oldest event still buffered
+        assert_eq!(rx.recv().await.unwrap(), 3);
+
+        let notice = WsServerMessage::EventsMissed { missed, timestamp: "t".to_string() };
+        let json = serde_json::to_value(&notice).unwrap();
+        assert_eq!(json, serde_json::json!({ "type": "events_missed", "missed": 3, "timestamp": "t" }));
+    }
+
+    #[test]
+    fn test_invalid_client_message_gets_an_error_reply() {
+        let err = serde_json::from_str::<WsClientMessage>(r#"{"type": "shout"}"#).unwrap_err();
+        let reply = WsServerMessage::Error { message: format!("Invalid message: {}", err) };
+        let json = serde_json::to_value(&reply).unwrap();
+        assert_eq!(json["type"], "error");
+        assert!(json["message"].as_str().unwrap().contains("shout"));
+    }
+
+    #[test]
+    fn test_request_token_precedence() {
+        let query = WsQuery { token: Some("from-query".to_string()) };
+        let mut headers = HeaderMap::new();
+        assert_eq!(request_token(&headers, &query).as_deref(), Some("from-query"));
+
+        headers.insert(header::AUTHORIZATION, "Bearer from-header".parse().unwrap());
+        assert_eq!(request_token(&headers, &query).as_deref(), Some("from-header"));
+
+        headers.insert(header::SEC_WEBSOCKET_PROTOCOL, "bearer, from-protocol".parse().unwrap());
+        assert_eq!(request_token(&headers, &query).as_deref(), Some("from-protocol"));
+
+        // Unrelated subprotocols are not mistaken for a token
+        let mut headers = HeaderMap::new();
+        headers.insert(header::SEC_WEBSOCKET_PROTOCOL, "graphql-ws".parse().unwrap());
+        assert_eq!(request_token(&headers, &WsQuery { token: None }), None);
+    }
+}
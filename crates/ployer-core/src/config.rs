@@ -1,25 +1,29 @@
+use anyhow::Context;
 use serde::{Deserialize, Serialize};
-use sha2::{Sha256, Digest};
+use std::path::PathBuf;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+use crate::crypto;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
 pub struct AppConfig {
     pub server: ServerConfig,
     pub database: DatabaseConfig,
     pub auth: AuthConfig,
     pub docker: DockerConfig,
     pub caddy: CaddyConfig,
+    pub proxy: ProxyConfig,
 }
 
 impl AppConfig {
     /// Derive a 32-byte encryption key from the JWT secret using SHA-256
     pub fn get_secret_key(&self) -> [u8; 32] {
-        let mut hasher = Sha256::new();
-        hasher.update(self.auth.jwt_secret.as_bytes());
-        hasher.finalize().into()
+        crypto::derive_key(&self.auth.jwt_secret)
     }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct ServerConfig {
     pub host: String,
     pub port: u16,
@@ -28,80 +32,292 @@ pub struct ServerConfig {
     /// Comma-separated list of allowed CORS origins, e.g. "http://localhost:5173,https://app.example.com"
     /// Use "*" to allow all origins (default, suitable for development).
     pub allowed_origins: String,
+    /// How often host CPU and memory usage is pushed to WebSocket clients; 0 turns it off
+    pub stats_interval_secs: u64,
+    /// Requests per minute across the whole API; 0 turns the limit off
+    pub rate_limit_per_minute: u32,
+    /// Separate bucket for signature-authenticated webhook receivers; 0 exempts them entirely
+    pub webhook_rate_limit_per_minute: u32,
+    /// Comma-separated path prefixes that use the webhook bucket instead of the global one
+    pub webhook_rate_limit_paths: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct DatabaseConfig {
     pub url: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct AuthConfig {
     pub jwt_secret: String,
     pub token_expiry_hours: u64,
+    pub refresh_token_expiry_days: u64,
+    /// Bearer token required by GET /metrics; the endpoint is open when unset
+    pub metrics_token: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct DockerConfig {
     pub socket_path: String,
+    /// Maximum duration of a whole deployment pipeline before it is aborted
+    pub build_timeout_secs: u64,
+    /// Deployment pipelines allowed to run at once; the rest wait as queued
+    pub max_concurrent_builds: usize,
+    /// Where each app's git clone is kept between deploys; unset clones fresh every time
+    pub repo_cache_dir: Option<String>,
+    /// Stored build log size per deployment; older lines are dropped first. 0 keeps everything
+    pub max_build_log_kb: usize,
+    /// Build logs of deployments finished longer ago are cleared; 0 keeps them forever
+    pub build_log_retention_days: u64,
+    /// Image of the throwaway container an app's pre-build command runs in
+    pub pre_build_image: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct CaddyConfig {
     pub admin_url: String,
     pub caddyfile_path: String,
 }
 
-impl Default for AppConfig {
+/// Egress proxy for outbound HTTP, e.g. to a remote Caddy admin API.
+/// Loopback hosts are always reached directly.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProxyConfig {
+    pub http_proxy: Option<String>,
+    pub https_proxy: Option<String>,
+    /// Comma-separated hosts, domains (`.example.com`) or CIDRs reached directly
+    pub no_proxy: Option<String>,
+}
+
+impl Default for ServerConfig {
     fn default() -> Self {
         Self {
-            server: ServerConfig {
-                host: "0.0.0.0".to_string(),
-                port: 3001,
-                base_domain: "localhost".to_string(),
-                public_url: "http://localhost:3001".to_string(),
-                allowed_origins: "*".to_string(),
-            },
-            database: DatabaseConfig {
-                url: "sqlite://ployer.db?mode=rwc".to_string(),
-            },
-            auth: AuthConfig {
-                jwt_secret: "change-me-in-production".to_string(),
-                token_expiry_hours: 24,
-            },
-            docker: DockerConfig {
-                socket_path: "/var/run/docker.sock".to_string(),
-            },
-            caddy: CaddyConfig {
-                admin_url: "http://localhost:2019".to_string(),
-                caddyfile_path: "/opt/ployer/Caddyfile".to_string(),
-            },
+            host: "0.0.0.0".to_string(),
+            port: 3001,
+            base_domain: "localhost".to_string(),
+            public_url: "http://localhost:3001".to_string(),
+            allowed_origins: "*".to_string(),
+            stats_interval_secs: 5,
+            rate_limit_per_minute: 300,
+            webhook_rate_limit_per_minute: 3000,
+            webhook_rate_limit_paths: "/api/v1/webhooks/".to_string(),
+        }
+    }
+}
+
+impl Default for DatabaseConfig {
+    fn default() -> Self {
+        Self {
+            url: "sqlite://ployer.db?mode=rwc".to_string(),
+        }
+    }
+}
+
+impl Default for AuthConfig {
+    fn default() -> Self {
+        Self {
+            jwt_secret: "change-me-in-production".to_string(),
+            token_expiry_hours: 24,
+            refresh_token_expiry_days: 30,
+            metrics_token: None,
+        }
+    }
+}
+
+impl Default for DockerConfig {
+    fn default() -> Self {
+        Self {
+            socket_path: "/var/run/docker.sock".to_string(),
+            build_timeout_secs: 1800,
+            max_concurrent_builds: 2,
+            repo_cache_dir: Some("/var/lib/ployer/repos".to_string()),
+            max_build_log_kb: 1024,
+            build_log_retention_days: 30,
+            pre_build_image: "alpine:latest".to_string(),
+        }
+    }
+}
+
+impl Default for CaddyConfig {
+    fn default() -> Self {
+        Self {
+            admin_url: "http://localhost:2019".to_string(),
+            caddyfile_path: "/opt/ployer/Caddyfile".to_string(),
         }
     }
 }
 
 impl AppConfig {
+    /// Load config with precedence: env vars > config file > defaults.
+    ///
+    /// The config file path comes from PLOYER_CONFIG, falling back to `ployer.toml`
+    /// in the working directory. A missing default file is not an error; a missing
+    /// file named by PLOYER_CONFIG or malformed TOML is.
+    pub fn load() -> anyhow::Result<Self> {
+        let (path, explicit) = match std::env::var("PLOYER_CONFIG") {
+            Ok(p) => (PathBuf::from(p), true),
+            Err(_) => (PathBuf::from("ployer.toml"), false),
+        };
+
+        let mut cfg = if path.exists() || explicit {
+            let contents = std::fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read config file {}", path.display()))?;
+            Self::from_toml_str(&contents)
+                .with_context(|| format!("Invalid config file {}", path.display()))?
+        } else {
+            Self::default()
+        };
+
+        cfg.apply_env(|key| std::env::var(key).ok());
+        Ok(cfg)
+    }
+
+    /// Parse a TOML config; omitted fields keep their defaults.
+    pub fn from_toml_str(contents: &str) -> anyhow::Result<Self> {
+        Ok(toml::from_str(contents)?)
+    }
+
     /// Load config from environment variables, falling back to defaults.
     ///
     /// Supported env vars:
     ///   PLOYER_HOST, PLOYER_PORT, PLOYER_BASE_DOMAIN, PLOYER_PUBLIC_URL,
     ///   PLOYER_ALLOWED_ORIGINS, PLOYER_DATABASE_URL, PLOYER_JWT_SECRET,
-    ///   PLOYER_TOKEN_EXPIRY_HOURS, PLOYER_DOCKER_SOCKET, PLOYER_CADDY_URL
+    ///   PLOYER_TOKEN_EXPIRY_HOURS, PLOYER_REFRESH_TOKEN_EXPIRY_DAYS, PLOYER_METRICS_TOKEN,
+    ///   PLOYER_DOCKER_SOCKET, PLOYER_BUILD_TIMEOUT_SECS, PLOYER_CADDY_URL,
+    ///   PLOYER_STATS_INTERVAL_SECS, PLOYER_MAX_BUILD_LOG_KB, PLOYER_BUILD_LOG_RETENTION_DAYS,
+    ///   PLOYER_RATE_LIMIT_PER_MINUTE, PLOYER_WEBHOOK_RATE_LIMIT_PER_MINUTE, PLOYER_WEBHOOK_RATE_LIMIT_PATHS,
+    ///   PLOYER_HTTP_PROXY, PLOYER_HTTPS_PROXY, PLOYER_NO_PROXY; the standard HTTP_PROXY,
+    ///   HTTPS_PROXY and NO_PROXY fill in whichever of those is still unset
     pub fn from_env() -> Self {
         let mut cfg = Self::default();
+        cfg.apply_env(|key| std::env::var(key).ok());
+        cfg
+    }
 
-        if let Ok(v) = std::env::var("PLOYER_HOST")            { cfg.server.host = v; }
-        if let Ok(v) = std::env::var("PLOYER_PORT")            { if let Ok(p) = v.parse() { cfg.server.port = p; } }
-        if let Ok(v) = std::env::var("PLOYER_BASE_DOMAIN")     { cfg.server.base_domain = v; }
-        if let Ok(v) = std::env::var("PLOYER_PUBLIC_URL")      { cfg.server.public_url = v; }
-        if let Ok(v) = std::env::var("PLOYER_ALLOWED_ORIGINS") { cfg.server.allowed_origins = v; }
-        if let Ok(v) = std::env::var("PLOYER_DATABASE_URL")    { cfg.database.url = v; }
-        if let Ok(v) = std::env::var("PLOYER_JWT_SECRET")      { cfg.auth.jwt_secret = v; }
-        if let Ok(v) = std::env::var("PLOYER_TOKEN_EXPIRY_HOURS") { if let Ok(h) = v.parse() { cfg.auth.token_expiry_hours = h; } }
-        if let Ok(v) = std::env::var("PLOYER_DOCKER_SOCKET")   { cfg.docker.socket_path = v; }
-        if let Ok(v) = std::env::var("PLOYER_CADDY_URL")        { cfg.caddy.admin_url = v; }
-        if let Ok(v) = std::env::var("PLOYER_CADDYFILE")        { cfg.caddy.caddyfile_path = v; }
+    /// Overlay values from an env var lookup onto this config
+    fn apply_env(&mut self, var: impl Fn(&str) -> Option<String>) {
+        let cfg = self;
 
-        cfg
+        if let Some(v) = var("PLOYER_HOST")            { cfg.server.host = v; }
+        if let Some(v) = var("PLOYER_PORT")            { if let Ok(p) = v.parse() { cfg.server.port = p; } }
+        if let Some(v) = var("PLOYER_BASE_DOMAIN")     { cfg.server.base_domain = v; }
+        if let Some(v) = var("PLOYER_PUBLIC_URL")      { cfg.server.public_url = v; }
+        if let Some(v) = var("PLOYER_ALLOWED_ORIGINS") { cfg.server.allowed_origins = v; }
+        if let Some(v) = var("PLOYER_STATS_INTERVAL_SECS") { if let Ok(s) = v.parse() { cfg.server.stats_interval_secs = s; } }
+        if let Some(v) = var("PLOYER_RATE_LIMIT_PER_MINUTE") { if let Ok(n) = v.parse() { cfg.server.rate_limit_per_minute = n; } }
+        if let Some(v) = var("PLOYER_WEBHOOK_RATE_LIMIT_PER_MINUTE") { if let Ok(n) = v.parse() { cfg.server.webhook_rate_limit_per_minute = n; } }
+        if let Some(v) = var("PLOYER_WEBHOOK_RATE_LIMIT_PATHS") { cfg.server.webhook_rate_limit_paths = v; }
+        if let Some(v) = var("PLOYER_DATABASE_URL")    { cfg.database.url = v; }
+        if let Some(v) = var("PLOYER_JWT_SECRET")      { cfg.auth.jwt_secret = v; }
+        if let Some(v) = var("PLOYER_TOKEN_EXPIRY_HOURS") { if let Ok(h) = v.parse() { cfg.auth.token_expiry_hours = h; } }
+        if let Some(v) = var("PLOYER_REFRESH_TOKEN_EXPIRY_DAYS") { if let Ok(d) = v.parse() { cfg.auth.refresh_token_expiry_days = d; } }
+        if let Some(v) = var("PLOYER_METRICS_TOKEN")   { cfg.auth.metrics_token = Some(v); }
+        if let Some(v) = var("PLOYER_DOCKER_SOCKET")   { cfg.docker.socket_path = v; }
+        if let Some(v) = var("PLOYER_BUILD_TIMEOUT_SECS") { if let Ok(s) = v.parse() { cfg.docker.build_timeout_secs = s; } }
+        if let Some(v) = var("PLOYER_MAX_CONCURRENT_BUILDS") { if let Ok(n) = v.parse() { cfg.docker.max_concurrent_builds = n; } }
+        if let Some(v) = var("PLOYER_REPO_CACHE_DIR")  { cfg.docker.repo_cache_dir = (!v.is_empty()).then_some(v); }
+        if let Some(v) = var("PLOYER_MAX_BUILD_LOG_KB") { if let Ok(n) = v.parse() { cfg.docker.max_build_log_kb = n; } }
+        if let Some(v) = var("PLOYER_BUILD_LOG_RETENTION_DAYS") { if let Ok(d) = v.parse() { cfg.docker.build_log_retention_days = d; } }
+        if let Some(v) = var("PLOYER_PRE_BUILD_IMAGE") { cfg.docker.pre_build_image = v; }
+        if let Some(v) = var("PLOYER_CADDY_URL")        { cfg.caddy.admin_url = v; }
+        if let Some(v) = var("PLOYER_CADDYFILE")        { cfg.caddy.caddyfile_path = v; }
+
+        // An empty value turns a proxy setting off
+        let proxy_var = |ployer: &str, standard: &str, current: &mut Option<String>| {
+            let value = var(ployer).or_else(|| {
+                current.is_none().then(|| var(standard).or_else(|| var(&standard.to_lowercase()))).flatten()
+            });
+            if let Some(v) = value {
+                *current = (!v.is_empty()).then_some(v);
+            }
+        };
+        proxy_var("PLOYER_HTTP_PROXY", "HTTP_PROXY", &mut cfg.proxy.http_proxy);
+        proxy_var("PLOYER_HTTPS_PROXY", "HTTPS_PROXY", &mut cfg.proxy.https_proxy);
+        proxy_var("PLOYER_NO_PROXY", "NO_PROXY", &mut cfg.proxy.no_proxy);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    const FILE: &str = r#"
+        [server]
+        port = 4000
+        base_domain = "apps.example.com"
+
+        [auth]
+        jwt_secret = "from-file"
+    "#;
+
+    fn env(vars: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+        let map: HashMap<String, String> = vars
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        move |key| map.get(key).cloned()
+    }
+
+    #[test]
+    fn test_file_beats_default() {
+        let cfg = AppConfig::from_toml_str(FILE).unwrap();
+
+        assert_eq!(cfg.server.port, 4000);
+        assert_eq!(cfg.server.base_domain, "apps.example.com");
+        assert_eq!(cfg.auth.jwt_secret, "from-file");
+        // Fields omitted from the file keep their defaults
+        assert_eq!(cfg.server.host, "0.0.0.0");
+        assert_eq!(cfg.auth.token_expiry_hours, 24);
+        assert_eq!(cfg.docker.socket_path, "/var/run/docker.sock");
+    }
+
+    #[test]
+    fn test_env_beats_file() {
+        let mut cfg = AppConfig::from_toml_str(FILE).unwrap();
+        cfg.apply_env(env(&[
+            ("PLOYER_PORT", "5000"),
+            ("PLOYER_DATABASE_URL", "sqlite://env.db"),
+            ("PLOYER_STATS_INTERVAL_SECS", "0"),
+            ("PLOYER_BUILD_LOG_RETENTION_DAYS", "7"),
+        ]));
+
+        assert_eq!(cfg.server.port, 5000);
+        assert_eq!(cfg.database.url, "sqlite://env.db");
+        assert_eq!(cfg.server.stats_interval_secs, 0);
+        assert_eq!(cfg.docker.build_log_retention_days, 7);
+        // Values not overridden by env still come from the file
+        assert_eq!(cfg.server.base_domain, "apps.example.com");
+        assert_eq!(cfg.auth.jwt_secret, "from-file");
+    }
+
+    #[test]
+    fn test_proxy_env() {
+        let mut cfg = AppConfig::from_toml_str("[proxy]\nhttps_proxy = \"http://file:3128\"").unwrap();
+        cfg.apply_env(env(&[
+            ("HTTP_PROXY", "http://standard:3128"),
+            ("HTTPS_PROXY", "http://standard:3128"),
+            ("no_proxy", ".internal"),
+        ]));
+
+        assert_eq!(cfg.proxy.http_proxy.as_deref(), Some("http://standard:3128"));
+        // The standard vars don't override the config file, Ployer's own do
+        assert_eq!(cfg.proxy.https_proxy.as_deref(), Some("http://file:3128"));
+        assert_eq!(cfg.proxy.no_proxy.as_deref(), Some(".internal"));
+
+        cfg.apply_env(env(&[("PLOYER_HTTPS_PROXY", "http://ployer:3128"), ("PLOYER_NO_PROXY", "")]));
+        assert_eq!(cfg.proxy.https_proxy.as_deref(), Some("http://ployer:3128"));
+        assert_eq!(cfg.proxy.no_proxy, None);
+    }
+
+    #[test]
+    fn test_malformed_toml_is_an_error() {
+        assert!(AppConfig::from_toml_str("[server\nport = ").is_err());
+        assert!(AppConfig::from_toml_str("[server]\nport = \"not-a-number\"").is_err());
     }
 }
@@ -0,0 +1,166 @@
+use chrono::{DateTime, Datelike, Duration, DurationRound, Timelike, Utc};
+
+/// A five-field cron expression (`minute hour day-of-month month day-of-week`),
+/// evaluated in UTC. Fields accept `*`, numbers, ranges, lists and steps.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CronSchedule {
+    minutes: u64,
+    hours: u64,
+    days_of_month: u64,
+    months: u64,
+    days_of_week: u64,
+    /// When both day fields are restricted, a day matching either one fires
+    any_day_of_month: bool,
+    any_day_of_week: bool,
+}
+
+impl CronSchedule {
+    pub fn parse(expr: &str) -> Result<Self, String> {
+        let fields: Vec<&str> = expr.split_whitespace().collect();
+        if fields.len() != 5 {
+            return Err(format!(
+                "expected 5 fields (minute hour day-of-month month day-of-week), got {}",
+                fields.len()
+            ));
+        }
+
+        let mut days_of_week = parse_field(fields[4], 0, 7, "day-of-week")?;
+        // 7 is another name for Sunday
+        if days_of_week & (1 << 7) != 0 {
+            days_of_week = (days_of_week & !(1 << 7)) | 1;
+        }
+
+        Ok(Self {
+            minutes: parse_field(fields[0], 0, 59, "minute")?,
+            hours: parse_field(fields[1], 0, 23, "hour")?,
+            days_of_month: parse_field(fields[2], 1, 31, "day-of-month")?,
+            months: parse_field(fields[3], 1, 12, "month")?,
+            days_of_week,
+            any_day_of_month: fields[2].starts_with('*'),
+            any_day_of_week: fields[4].starts_with('*'),
+        })
+    }
+
+    /// Whether the schedule fires in the minute containing `time`
+    pub fn matches(&self, time: DateTime<Utc>) -> bool {
+        let has = |mask: u64, value: u32| mask & (1 << value) != 0;
+        if !has(self.minutes, time.minute()) || !has(self.hours, time.hour()) || !has(self.months, time.month()) {
+            return false;
+        }
+
+        let day_of_month = has(self.days_of_month, time.day());
+        let day_of_week = has(self.days_of_week, time.weekday().num_days_from_sunday());
+        match (self.any_day_of_month, self.any_day_of_week) {
+            (false, false) => day_of_month || day_of_week,
+            _ => day_of_month && day_of_week,
+        }
+    }
+
+    /// Whether the schedule fires at any minute after `after`, up to and including `until`
+    pub fn fires_between(&self, after: DateTime<Utc>, until: DateTime<Utc>) -> bool {
+        let minute = Duration::minutes(1);
+        let mut time = after.duration_trunc(minute).unwrap_or(after) + minute;
+        while time <= until {
+            if self.matches(time) {
+                return true;
+            }
+            time += minute;
+        }
+        false
+    }
+}
+
+/// Bitmask of the values a field allows
+fn parse_field(field: &str, min: u32, max: u32, name: &str) -> Result<u64, String> {
+    let invalid = || format!("invalid {} field \"{}\"", name, field);
+    let number = |s: &str| -> Result<u32, String> {
+        let value: u32 = s.parse().map_err(|_| invalid())?;
+        if value < min || value > max {
+            return Err(format!("{} must be between {} and {}, got {}", name, min, max, value));
+        }
+        Ok(value)
+    };
+
+    let mut mask = 0;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => (range, Some(step.parse::<u32>().ok().filter(|s| *s > 0).ok_or_else(invalid)?)),
+            None => (part, None),
+        };
+
+        let (start, end) = match range {
+            "*" => (min, max),
+            _ => match range.split_once('-') {
+                Some((start, end)) => (number(start)?, number(end)?),
+                // `5/15` means every 15 starting at 5
+                None if step.is_some() => (number(range)?, max),
+                None => {
+                    let value = number(range)?;
+                    (value, value)
+                }
+            },
+        };
+        if start > end {
+            return Err(invalid());
+        }
+
+        for value in (start..=end).step_by(step.unwrap_or(1) as usize) {
+            mask |= 1 << value;
+        }
+    }
+
+    Ok(mask)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn at(y: i32, mo: u32, d: u32, h: u32, mi: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(y, mo, d, h, mi, 0).unwrap()
+    }
+
+    #[test]
+    fn test_parse_rejects_bad_expressions() {
+        assert!(CronSchedule::parse("0 3 * *").is_err());
+        assert!(CronSchedule::parse("60 3 * * *").is_err());
+        assert!(CronSchedule::parse("0 3 0 * *").is_err());
+        assert!(CronSchedule::parse("*/0 * * * *").is_err());
+        assert!(CronSchedule::parse("0 5-3 * * *").is_err());
+        assert!(CronSchedule::parse("0 3 * * mon").is_err());
+        assert!(CronSchedule::parse("  0  3 * * *  ").is_ok());
+    }
+
+    #[test]
+    fn test_matches() {
+        // 2024-01-07 is a Sunday
+        let nightly = CronSchedule::parse("30 3 * * *").unwrap();
+        assert!(nightly.matches(at(2024, 1, 7, 3, 30)));
+        assert!(!nightly.matches(at(2024, 1, 7, 3, 31)));
+
+        let weekdays = CronSchedule::parse("0 9-17/4 * * 1-5").unwrap();
+        assert!(weekdays.matches(at(2024, 1, 8, 13, 0)));
+        assert!(!weekdays.matches(at(2024, 1, 8, 11, 0)));
+        assert!(!weekdays.matches(at(2024, 1, 7, 13, 0)));
+
+        let sundays = CronSchedule::parse("0 0 * * 7").unwrap();
+        assert!(sundays.matches(at(2024, 1, 7, 0, 0)));
+
+        // Restricted day-of-month and day-of-week: either one fires
+        let first_or_sunday = CronSchedule::parse("0 0 1 * 0").unwrap();
+        assert!(first_or_sunday.matches(at(2024, 1, 1, 0, 0)));
+        assert!(first_or_sunday.matches(at(2024, 1, 7, 0, 0)));
+        assert!(!first_or_sunday.matches(at(2024, 1, 8, 0, 0)));
+    }
+
+    #[test]
+    fn test_fires_between() {
+        let nightly = CronSchedule::parse("0 3 * * *").unwrap();
+        let before = at(2024, 1, 7, 2, 59) + Duration::seconds(30);
+        assert!(nightly.fires_between(before, at(2024, 1, 7, 3, 0)));
+        // The end is inclusive, the start isn't
+        assert!(!nightly.fires_between(at(2024, 1, 7, 3, 0), at(2024, 1, 7, 3, 5)));
+        assert!(!nightly.fires_between(at(2024, 1, 7, 3, 1), at(2024, 1, 8, 2, 59)));
+    }
+}
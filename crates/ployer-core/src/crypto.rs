@@ -5,6 +5,7 @@ use aes_gcm::{
 use anyhow::{anyhow, Result};
 use base64::{Engine as _, engine::general_purpose};
 use rand::RngCore;
+use sha2::{Digest, Sha256};
 
 const NONCE_SIZE: usize = 12;
 
@@ -15,6 +16,13 @@ pub fn generate_key() -> [u8; 32] {
     key
 }
 
+/// Derive a 32-byte encryption key from a secret using SHA-256
+pub fn derive_key(secret: &str) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(secret.as_bytes());
+    hasher.finalize().into()
+}
+
 /// Encrypt a string using AES-256-GCM
 /// Returns base64-encoded: nonce || ciphertext || tag
 pub fn encrypt(plaintext: &str, key: &[u8; 32]) -> Result<String> {
@@ -0,0 +1,82 @@
+use anyhow::Result;
+use std::time::Duration;
+
+use crate::config::ProxyConfig;
+
+/// Reached directly even when `no_proxy` doesn't list them
+const LOOPBACK: [&str; 3] = ["localhost", "127.0.0.1", "::1"];
+
+/// Build a client for outbound HTTP. With `proxy`, requests go through the
+/// configured proxies except to loopback and `no_proxy` hosts; with `None`
+/// they are always sent directly, e.g. to probe containers on this host.
+pub fn build_http_client(proxy: Option<&ProxyConfig>, timeout: Duration) -> Result<reqwest::Client> {
+    Ok(http_client_builder(proxy, timeout)?.build()?)
+}
+
+/// [`build_http_client`] before it is built, for callers that need more settings
+pub fn http_client_builder(proxy: Option<&ProxyConfig>, timeout: Duration) -> Result<reqwest::ClientBuilder> {
+    // Only `ProxyConfig` decides, not reqwest's own lookup of the proxy env vars
+    let mut builder = reqwest::Client::builder().timeout(timeout).no_proxy();
+    let Some(proxy) = proxy else {
+        return Ok(builder);
+    };
+
+    let no_proxy: Vec<&str> = LOOPBACK.into_iter().chain(proxy.no_proxy.as_deref()).collect();
+    let no_proxy = reqwest::NoProxy::from_string(&no_proxy.join(","));
+    if let Some(url) = &proxy.http_proxy {
+        builder = builder.proxy(reqwest::Proxy::http(url)?.no_proxy(no_proxy.clone()));
+    }
+    if let Some(url) = &proxy.https_proxy {
+        builder = builder.proxy(reqwest::Proxy::https(url)?.no_proxy(no_proxy));
+    }
+    Ok(builder)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// Answer one request with 200 and return its request line
+    async fn serve_once(listener: TcpListener) -> String {
+        let (mut socket, _) = listener.accept().await.unwrap();
+        let mut buf = vec![0; 4096];
+        let n = socket.read(&mut buf).await.unwrap();
+        socket
+            .write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 0\r\nconnection: close\r\n\r\n")
+            .await
+            .unwrap();
+        String::from_utf8_lossy(&buf[..n]).lines().next().unwrap_or_default().to_string()
+    }
+
+    #[tokio::test]
+    async fn test_proxy_is_used_except_for_loopback() {
+        let proxy = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let config = ProxyConfig {
+            http_proxy: Some(format!("http://{}", proxy.local_addr().unwrap())),
+            ..ProxyConfig::default()
+        };
+        let client = build_http_client(Some(&config), Duration::from_secs(5)).unwrap();
+
+        let proxied = tokio::spawn(serve_once(proxy));
+        client.get("http://app.example.invalid/health").send().await.unwrap();
+        assert_eq!(proxied.await.unwrap(), "GET http://app.example.invalid/health HTTP/1.1");
+
+        // Loopback goes straight to the target, in origin form
+        let local = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://localhost:{}/health", local.local_addr().unwrap().port());
+        let direct = tokio::spawn(serve_once(local));
+        client.get(&url).send().await.unwrap();
+        assert_eq!(direct.await.unwrap(), "GET /health HTTP/1.1");
+    }
+
+    #[test]
+    fn test_invalid_proxy_url_is_an_error() {
+        let config = ProxyConfig {
+            https_proxy: Some("not a url".to_string()),
+            ..ProxyConfig::default()
+        };
+        assert!(build_http_client(Some(&config), Duration::from_secs(5)).is_err());
+    }
+}
@@ -2,3 +2,5 @@ pub mod models;
 pub mod config;
 pub mod error;
 pub mod crypto;
+pub mod cron;
+pub mod http;
@@ -1,23 +1,109 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct Application {
     pub id: String,
     pub name: String,
     pub server_id: String,
     pub git_url: Option<String>,
     pub git_branch: String,
+    /// Check out submodules recursively when cloning and pulling
+    pub git_submodules: bool,
     pub build_strategy: BuildStrategy,
     pub dockerfile_path: Option<String>,
+    /// Stage of a multi-stage Dockerfile to build; None builds the final stage
+    pub docker_target: Option<String>,
+    /// Prebuilt image (e.g. `postgres:16`) pulled and run instead of building from git
+    pub image: Option<String>,
+    /// Primary port; Caddy routes the app's domains here
     pub port: Option<u16>,
+    /// Additional ports published alongside the primary one
+    pub ports: Vec<PortMapping>,
     pub status: AppStatus,
     pub auto_deploy: bool,
+    /// Cron expression (UTC) for scheduled redeploys, e.g. `0 3 * * *`
+    pub deploy_schedule: Option<String>,
+    pub restart_policy: String,
+    /// How long a stopping container gets to drain before it is killed
+    pub stop_grace_seconds: u32,
+    /// Shell command run before the image build, in a throwaway `PLOYER_PRE_BUILD_IMAGE`
+    /// container holding a copy of the checkout
+    pub pre_build_cmd: Option<String>,
+    /// Shell command run inside the new container once it passes its health check
+    pub post_deploy_cmd: Option<String>,
+    /// Dedicated Docker network, created on first deploy
+    pub network_name: Option<String>,
+    /// Set while the app is archived (hidden, container removed, history kept)
+    pub archived_at: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+impl Application {
+    /// Every port to publish, primary first. The primary port keeps being
+    /// published on the same host port, as it was before `ports` existed.
+    pub fn port_mappings(&self) -> Vec<PortMapping> {
+        let mut mappings: Vec<PortMapping> = self
+            .port
+            .map(|p| PortMapping {
+                container_port: p,
+                host_port: Some(p),
+                protocol: default_protocol(),
+            })
+            .into_iter()
+            .collect();
+
+        for mapping in &self.ports {
+            let duplicate = mappings.iter().any(|m| {
+                m.container_port == mapping.container_port && m.protocol == mapping.protocol
+            });
+            if !duplicate {
+                mappings.push(mapping.clone());
+            }
+        }
+        mappings
+    }
+
+    /// Auto-generated `{name}.{base_domain}` host. The name is turned into a DNS
+    /// label: lowercased, other characters replaced with '-' and cut to 63 characters.
+    pub fn subdomain(&self, base_domain: &str) -> Option<String> {
+        let mut label = String::new();
+        for c in self.name.to_ascii_lowercase().chars() {
+            let c = if c.is_ascii_alphanumeric() { c } else { '-' };
+            if !(c == '-' && (label.is_empty() || label.ends_with('-'))) {
+                label.push(c);
+            }
+        }
+        label.truncate(63);
+        let label = label.trim_end_matches('-');
+        if label.is_empty() {
+            return None;
+        }
+
+        let base_domain = base_domain.trim().trim_matches('.').to_ascii_lowercase();
+        Some(format!("{}.{}", label, base_domain))
+    }
+}
+
+/// A container port published on the host
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, ToSchema)]
+pub struct PortMapping {
+    pub container_port: u16,
+    /// Host port to bind; Docker picks a free one when unset
+    #[serde(default)]
+    pub host_port: Option<u16>,
+    /// "tcp" or "udp"
+    #[serde(default = "default_protocol")]
+    pub protocol: String,
+}
+
+fn default_protocol() -> String {
+    "tcp".to_string()
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, ToSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum BuildStrategy {
     Dockerfile,
@@ -50,7 +136,7 @@ impl BuildStrategy {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, ToSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum AppStatus {
     Idle,
@@ -61,6 +147,14 @@ pub enum AppStatus {
 }
 
 impl AppStatus {
+    pub const ALL: [AppStatus; 5] = [
+        AppStatus::Idle,
+        AppStatus::Building,
+        AppStatus::Running,
+        AppStatus::Stopped,
+        AppStatus::Failed,
+    ];
+
     pub fn as_str(&self) -> &str {
         match self {
             AppStatus::Idle => "idle",
@@ -89,9 +183,68 @@ pub struct EnvironmentVariable {
     pub application_id: String,
     pub key: String,
     pub value_encrypted: String,
+    pub scope: EnvVarScope,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Where an environment variable is made available during a deploy
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum EnvVarScope {
+    /// Passed as a build arg only, so secrets needed to build aren't left in the container
+    Build,
+    /// Set on the running container only
+    Runtime,
+    #[default]
+    Both,
+}
+
+impl EnvVarScope {
+    pub fn as_str(&self) -> &str {
+        match self {
+            EnvVarScope::Build => "build",
+            EnvVarScope::Runtime => "runtime",
+            EnvVarScope::Both => "both",
+        }
+    }
+
+    /// Parse a stored scope; anything unknown falls back to `Both`
+    pub fn parse_or_default(s: &str) -> Self {
+        match s {
+            "build" => EnvVarScope::Build,
+            "runtime" => EnvVarScope::Runtime,
+            _ => EnvVarScope::Both,
+        }
+    }
+
+    pub fn at_build(&self) -> bool {
+        matches!(self, EnvVarScope::Build | EnvVarScope::Both)
+    }
+
+    pub fn at_runtime(&self) -> bool {
+        matches!(self, EnvVarScope::Runtime | EnvVarScope::Both)
+    }
+}
+
+/// Named Docker volume mounted at a path inside the app's containers.
+/// The same volume is reattached on every deploy so data survives redeploys.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ApplicationVolume {
+    pub id: String,
+    pub application_id: String,
+    pub volume_name: String,
+    pub container_path: String,
     pub created_at: DateTime<Utc>,
 }
 
+impl ApplicationVolume {
+    /// Docker volume backing this mount. Prefixed with the app id so two apps
+    /// that both declare e.g. `data` don't share it.
+    pub fn docker_volume_name(&self) -> String {
+        format!("{}_{}", self.application_id, self.volume_name)
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DeployKey {
     pub id: String,
@@ -101,3 +254,96 @@ pub struct DeployKey {
     pub private_key_encrypted: String,
     pub created_at: DateTime<Utc>,
 }
+
+/// Registry repository an application's image is pushed to after each build
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ApplicationRegistry {
+    pub application_id: String,
+    /// Repository without a tag, e.g. `ghcr.io/acme/web`
+    pub image: String,
+    pub username: Option<String>,
+    #[serde(skip_serializing)]
+    pub password_encrypted: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_app() -> Application {
+        let now = Utc::now();
+        Application {
+            id: "a1".to_string(),
+            name: "web".to_string(),
+            server_id: "s1".to_string(),
+            git_url: None,
+            git_branch: "main".to_string(),
+            git_submodules: false,
+            build_strategy: BuildStrategy::Dockerfile,
+            dockerfile_path: None,
+            docker_target: None,
+            image: None,
+            port: Some(3000),
+            ports: vec![
+                PortMapping { container_port: 9100, host_port: None, protocol: "tcp".to_string() },
+                PortMapping { container_port: 3000, host_port: Some(8080), protocol: "tcp".to_string() },
+            ],
+            status: AppStatus::Idle,
+            auto_deploy: false,
+            deploy_schedule: None,
+            restart_policy: "unless-stopped".to_string(),
+            stop_grace_seconds: 10,
+            pre_build_cmd: None,
+            post_deploy_cmd: None,
+            network_name: None,
+            archived_at: None,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    #[test]
+    fn test_port_mappings_put_primary_port_first() {
+        let mut app = sample_app();
+        let mappings = app.port_mappings();
+        assert_eq!(mappings.len(), 2);
+        assert_eq!(mappings[0], PortMapping { container_port: 3000, host_port: Some(3000), protocol: "tcp".to_string() });
+        assert_eq!(mappings[1].container_port, 9100);
+
+        // Single-port apps behave exactly as before
+        app.ports.clear();
+        assert_eq!(app.port_mappings().len(), 1);
+        app.port = None;
+        assert!(app.port_mappings().is_empty());
+    }
+
+    #[test]
+    fn test_docker_volume_name_is_per_app() {
+        let volume = |application_id: &str| ApplicationVolume {
+            id: "v1".to_string(),
+            application_id: application_id.to_string(),
+            volume_name: "data".to_string(),
+            container_path: "/data".to_string(),
+            created_at: Utc::now(),
+        };
+        assert_eq!(volume("a1").docker_volume_name(), "a1_data");
+        assert_ne!(volume("a1").docker_volume_name(), volume("a2").docker_volume_name());
+    }
+
+    #[test]
+    fn test_subdomain_is_a_valid_label() {
+        let mut app = sample_app();
+        assert_eq!(app.subdomain("example.com").as_deref(), Some("web.example.com"));
+
+        app.name = "My_App v2".to_string();
+        assert_eq!(app.subdomain("Example.com.").as_deref(), Some("my-app-v2.example.com"));
+        app.name = "_api_".to_string();
+        assert_eq!(app.subdomain("localhost").as_deref(), Some("api.localhost"));
+        app.name = "a".repeat(70);
+        assert_eq!(app.subdomain("localhost").unwrap().len(), 63 + ".localhost".len());
+        app.name = "___".to_string();
+        assert_eq!(app.subdomain("localhost"), None);
+    }
+}
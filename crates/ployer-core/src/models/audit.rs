@@ -0,0 +1,81 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// One recorded sensitive action
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub id: String,
+    /// `None` for actions run from the CLI
+    pub user_id: Option<String>,
+    pub action: String,
+    pub target_type: String,
+    pub target_id: String,
+    pub ip: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Actions recorded in the audit log
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AuditAction {
+    DeploymentTrigger,
+    DeploymentCancel,
+    ApplicationDelete,
+    ServerDelete,
+    DomainDelete,
+    EnvVarCreate,
+    EnvVarUpdate,
+    EnvVarDelete,
+    EnvVarImport,
+    PasswordReset,
+    AdminCreate,
+    DeployKeyExport,
+}
+
+impl AuditAction {
+    pub const ALL: [AuditAction; 12] = [
+        AuditAction::DeploymentTrigger,
+        AuditAction::DeploymentCancel,
+        AuditAction::ApplicationDelete,
+        AuditAction::ServerDelete,
+        AuditAction::DomainDelete,
+        AuditAction::EnvVarCreate,
+        AuditAction::EnvVarUpdate,
+        AuditAction::EnvVarDelete,
+        AuditAction::EnvVarImport,
+        AuditAction::PasswordReset,
+        AuditAction::AdminCreate,
+        AuditAction::DeployKeyExport,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuditAction::DeploymentTrigger => "deployment.trigger",
+            AuditAction::DeploymentCancel => "deployment.cancel",
+            AuditAction::ApplicationDelete => "application.delete",
+            AuditAction::ServerDelete => "server.delete",
+            AuditAction::DomainDelete => "domain.delete",
+            AuditAction::EnvVarCreate => "env_var.create",
+            AuditAction::EnvVarUpdate => "env_var.update",
+            AuditAction::EnvVarDelete => "env_var.delete",
+            AuditAction::EnvVarImport => "env_var.import",
+            AuditAction::PasswordReset => "user.password_reset",
+            AuditAction::AdminCreate => "user.admin_create",
+            AuditAction::DeployKeyExport => "deploy_key.export",
+        }
+    }
+
+    /// What kind of thing the entry's `target_id` identifies
+    pub fn target_type(&self) -> &'static str {
+        match self {
+            AuditAction::DeploymentTrigger | AuditAction::DeploymentCancel => "deployment",
+            AuditAction::ApplicationDelete | AuditAction::EnvVarImport | AuditAction::DeployKeyExport => {
+                "application"
+            }
+            AuditAction::ServerDelete => "server",
+            AuditAction::DomainDelete => "domain",
+            // `<application id>/<key>`
+            AuditAction::EnvVarCreate | AuditAction::EnvVarUpdate | AuditAction::EnvVarDelete => "env_var",
+            AuditAction::PasswordReset | AuditAction::AdminCreate => "user",
+        }
+    }
+}
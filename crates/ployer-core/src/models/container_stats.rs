@@ -13,3 +13,18 @@ pub struct ContainerStats {
     pub network_tx_mb: Option<f64>,
     pub recorded_at: DateTime<Utc>,
 }
+
+/// Aggregated resource usage for an application over a time window.
+/// All values are None when no samples were recorded in the window.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContainerStatsSummary {
+    pub application_id: String,
+    pub hours: i64,
+    pub samples: i64,
+    pub avg_cpu_percent: Option<f64>,
+    pub max_cpu_percent: Option<f64>,
+    pub p95_cpu_percent: Option<f64>,
+    pub avg_memory_mb: Option<f64>,
+    pub max_memory_mb: Option<f64>,
+    pub p95_memory_mb: Option<f64>,
+}
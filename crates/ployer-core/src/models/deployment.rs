@@ -1,7 +1,9 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use std::ops::RangeInclusive;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct Deployment {
     pub id: String,
     pub application_id: String,
@@ -11,12 +13,21 @@ pub struct Deployment {
     pub status: DeploymentStatus,
     pub build_log: Option<String>,
     pub container_id: Option<String>,
+    /// Host port the app's primary port is published on, once the container exists
+    pub host_port: Option<u16>,
     pub image_tag: String,
     pub started_at: DateTime<Utc>,
     pub finished_at: Option<DateTime<Utc>>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+impl Deployment {
+    /// Whole seconds from start to finish; `None` while still in progress
+    pub fn duration_seconds(&self) -> Option<i64> {
+        self.finished_at.map(|finished| (finished - self.started_at).num_seconds().max(0))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, ToSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum DeploymentStatus {
     Queued,
@@ -30,6 +41,17 @@ pub enum DeploymentStatus {
 }
 
 impl DeploymentStatus {
+    pub const ALL: [DeploymentStatus; 8] = [
+        DeploymentStatus::Queued,
+        DeploymentStatus::Cloning,
+        DeploymentStatus::Building,
+        DeploymentStatus::Deploying,
+        DeploymentStatus::Running,
+        DeploymentStatus::Failed,
+        DeploymentStatus::Cancelled,
+        DeploymentStatus::RolledBack,
+    ];
+
     pub fn as_str(&self) -> &str {
         match self {
             DeploymentStatus::Queued => "queued",
@@ -58,11 +80,40 @@ impl DeploymentStatus {
     }
 }
 
+/// Coarse pipeline step reported to clients for progress display
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum DeploymentPhase {
+    Cloning,
+    Building,
+    Deploying,
+    HealthCheck,
+    Done,
+}
+
+impl DeploymentPhase {
+    pub fn as_str(&self) -> &str {
+        match self {
+            DeploymentPhase::Cloning => "cloning",
+            DeploymentPhase::Building => "building",
+            DeploymentPhase::Deploying => "deploying",
+            DeploymentPhase::HealthCheck => "health_check",
+            DeploymentPhase::Done => "done",
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HealthCheck {
     pub id: String,
     pub application_id: String,
+    pub check_type: HealthCheckType,
+    /// Request path; unused by TCP checks
     pub path: String,
+    /// GET, HEAD or POST
+    pub method: String,
+    /// Accepted response status: "200", "200-299" or a class like "2xx"
+    pub expected_status: String,
     pub interval_seconds: i32,
     pub timeout_seconds: i32,
     pub healthy_threshold: i32,
@@ -70,6 +121,59 @@ pub struct HealthCheck {
     pub created_at: DateTime<Utc>,
 }
 
+/// The settings of an application's health check, as created or replaced by the API
+#[derive(Debug, Clone)]
+pub struct HealthCheckConfig {
+    pub check_type: HealthCheckType,
+    pub path: String,
+    pub method: String,
+    pub expected_status: String,
+    pub interval_seconds: i32,
+    pub timeout_seconds: i32,
+    pub healthy_threshold: i32,
+    pub unhealthy_threshold: i32,
+}
+
+impl From<HealthCheck> for HealthCheckConfig {
+    fn from(check: HealthCheck) -> Self {
+        Self {
+            check_type: check.check_type,
+            path: check.path,
+            method: check.method,
+            expected_status: check.expected_status,
+            interval_seconds: check.interval_seconds,
+            timeout_seconds: check.timeout_seconds,
+            healthy_threshold: check.healthy_threshold,
+            unhealthy_threshold: check.unhealthy_threshold,
+        }
+    }
+}
+
+impl HealthCheck {
+    /// Whether a response status counts as healthy
+    pub fn accepts_status(&self, code: u16) -> bool {
+        parse_status_range(&self.expected_status).is_some_and(|range| range.contains(&code))
+    }
+}
+
+/// Parse an expected-status spec: a single code ("204"), an inclusive
+/// range ("200-299"), or a status class ("2xx")
+pub fn parse_status_range(spec: &str) -> Option<RangeInclusive<u16>> {
+    let spec = spec.trim().to_ascii_lowercase();
+    let valid = |code: u16| (100..=599).contains(&code);
+
+    if let Some(class) = spec.strip_suffix("xx") {
+        let class: u16 = class.parse().ok()?;
+        return (1..=5).contains(&class).then(|| class * 100..=class * 100 + 99);
+    }
+    if let Some((start, end)) = spec.split_once('-') {
+        let (start, end): (u16, u16) = (start.trim().parse().ok()?, end.trim().parse().ok()?);
+        return (valid(start) && valid(end) && start <= end).then_some(start..=end);
+    }
+    let code: u16 = spec.parse().ok()?;
+    valid(code).then_some(code..=code)
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HealthCheckResult {
     pub id: String,
@@ -82,7 +186,34 @@ pub struct HealthCheckResult {
     pub checked_at: DateTime<Utc>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum HealthCheckType {
+    /// Request `path` and compare the response status
+    #[default]
+    Http,
+    /// Healthy as soon as the mapped port accepts a connection
+    Tcp,
+}
+
+impl HealthCheckType {
+    pub fn as_str(&self) -> &str {
+        match self {
+            HealthCheckType::Http => "http",
+            HealthCheckType::Tcp => "tcp",
+        }
+    }
+
+    /// Parse a stored check type; anything unknown falls back to `Http`
+    pub fn parse_or_default(s: &str) -> Self {
+        match s {
+            "tcp" => HealthCheckType::Tcp,
+            _ => HealthCheckType::Http,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, ToSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum HealthCheckStatus {
     Healthy,
@@ -107,3 +238,60 @@ impl HealthCheckStatus {
         }
     }
 }
+
+/// Why an app's container stopped, when it's more specific than "unhealthy"
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum AppAlertKind {
+    /// Killed by the kernel for exceeding its memory limit
+    OutOfMemory,
+    /// Exited with a nonzero code
+    Crashed,
+}
+
+impl AppAlertKind {
+    pub fn as_str(&self) -> &str {
+        match self {
+            AppAlertKind::OutOfMemory => "out_of_memory",
+            AppAlertKind::Crashed => "crashed",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_status_range() {
+        assert_eq!(parse_status_range("2xx"), Some(200..=299));
+        assert_eq!(parse_status_range("204"), Some(204..=204));
+        assert_eq!(parse_status_range("200-399"), Some(200..=399));
+        assert_eq!(parse_status_range("6xx"), None);
+        assert_eq!(parse_status_range("399-200"), None);
+        assert_eq!(parse_status_range("ok"), None);
+    }
+
+    #[test]
+    fn test_duration_seconds() {
+        let started_at: DateTime<Utc> = "2026-02-14T00:00:00Z".parse().unwrap();
+        let mut deployment = Deployment {
+            id: "d1".to_string(),
+            application_id: "a1".to_string(),
+            server_id: "s1".to_string(),
+            commit_sha: None,
+            commit_message: None,
+            status: DeploymentStatus::Building,
+            build_log: None,
+            container_id: None,
+            host_port: None,
+            image_tag: "app:latest".to_string(),
+            started_at,
+            finished_at: None,
+        };
+        assert_eq!(deployment.duration_seconds(), None);
+
+        deployment.finished_at = Some(started_at + chrono::Duration::seconds(134));
+        assert_eq!(deployment.duration_seconds(), Some(134));
+    }
+}
@@ -1,12 +1,68 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use utoipa::ToSchema;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct Domain {
     pub id: String,
     pub application_id: String,
     pub domain: String,
     pub is_primary: bool,
     pub ssl_active: bool,
+    /// Username of the basic auth gate, when one is set (the password hash is never exposed)
+    pub basic_auth_user: Option<String>,
+    /// Per-client-IP request limit enforced by the proxy
+    pub rate_limit: Option<RateLimit>,
+    /// Whether the proxy runs its own health checks against the upstream,
+    /// using the app's HTTP health check path and interval
+    pub proxy_health_check: bool,
+    /// Headers the proxy adds to or removes from requests and responses
+    #[serde(default)]
+    pub proxy_headers: ProxyHeaders,
+    /// Extra hostnames served by the same route; may be wildcards like `*.example.com`
+    #[serde(default)]
+    pub aliases: Vec<String>,
+    /// Whether `www.<domain>` redirects to the domain
+    #[serde(default)]
+    pub www_redirect: bool,
     pub created_at: DateTime<Utc>,
 }
+
+/// At most `requests` requests per `window_secs` seconds from one client IP
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct RateLimit {
+    pub requests: u32,
+    pub window_secs: u32,
+}
+
+/// Header changes made by the proxy on the way to the app and on the way back
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct ProxyHeaders {
+    #[serde(default)]
+    pub request: HeaderRules,
+    #[serde(default)]
+    pub response: HeaderRules,
+}
+
+impl ProxyHeaders {
+    pub fn is_empty(&self) -> bool {
+        self.request.is_empty() && self.response.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct HeaderRules {
+    /// Headers to add, replacing any existing value, e.g. `{"X-Frame-Options": "DENY"}`
+    #[serde(default)]
+    pub set: BTreeMap<String, String>,
+    /// Header names to strip
+    #[serde(default)]
+    pub remove: Vec<String>,
+}
+
+impl HeaderRules {
+    pub fn is_empty(&self) -> bool {
+        self.set.is_empty() && self.remove.is_empty()
+    }
+}
@@ -5,6 +5,7 @@ pub mod deployment;
 pub mod domain;
 pub mod webhook;
 pub mod container_stats;
+pub mod audit;
 
 pub use user::*;
 pub use server::*;
@@ -13,7 +14,9 @@ pub use deployment::*;
 pub use domain::*;
 pub use webhook::*;
 pub use container_stats::*;
+pub use audit::*;
 
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
 /// WebSocket event broadcast to connected clients
@@ -24,11 +27,18 @@ pub enum WsEvent {
         deployment_id: String,
         app_id: String,
         status: DeploymentStatus,
+        /// Set once the deployment has finished, as do `duration_seconds`
+        finished_at: Option<DateTime<Utc>>,
+        duration_seconds: Option<i64>,
     },
     DeploymentLog {
         deployment_id: String,
         line: String,
     },
+    DeploymentPhase {
+        deployment_id: String,
+        phase: DeploymentPhase,
+    },
     ContainerStats {
         container_id: String,
         cpu_percent: f64,
@@ -38,8 +48,37 @@ pub enum WsEvent {
         server_id: String,
         status: ServerStatus,
     },
+    /// Host CPU (percent) and memory (MB) usage of a local server
+    ServerStats {
+        server_id: String,
+        cpu: f64,
+        mem_used: u64,
+        mem_total: u64,
+    },
     AppHealth {
         app_id: String,
         status: HealthCheckStatus,
     },
+    DomainSslActive {
+        app_id: String,
+        domain: String,
+    },
+    AppAlert {
+        app_id: String,
+        kind: AppAlertKind,
+        detail: String,
+    },
+}
+
+impl WsEvent {
+    /// Status event for a deployment as stored, with its timing once finished
+    pub fn deployment_status(deployment: &Deployment) -> Self {
+        WsEvent::DeploymentStatus {
+            deployment_id: deployment.id.clone(),
+            app_id: deployment.application_id.clone(),
+            status: deployment.status.clone(),
+            finished_at: deployment.finished_at,
+            duration_seconds: deployment.duration_seconds(),
+        }
+    }
 }
@@ -1,5 +1,6 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Server {
@@ -11,6 +12,11 @@ pub struct Server {
     #[serde(skip_serializing)]
     pub ssh_key_encrypted: Option<String>,
     pub is_local: bool,
+    /// Platform images are built and pulled for, e.g. `linux/arm64`; unset uses the daemon's own
+    pub platform: Option<String>,
+    /// Free-form grouping labels, e.g. `region=eu`, `env=prod`
+    #[serde(default)]
+    pub tags: BTreeMap<String, String>,
     pub status: ServerStatus,
     pub last_seen_at: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
@@ -21,6 +27,8 @@ pub struct Server {
 #[serde(rename_all = "lowercase")]
 pub enum ServerStatus {
     Online,
+    /// Host is reachable but its Docker daemon is not answering
+    Degraded,
     Offline,
     Unknown,
 }
@@ -29,6 +37,7 @@ impl ServerStatus {
     pub fn as_str(&self) -> &str {
         match self {
             ServerStatus::Online => "online",
+            ServerStatus::Degraded => "degraded",
             ServerStatus::Offline => "offline",
             ServerStatus::Unknown => "unknown",
         }
@@ -37,8 +46,35 @@ impl ServerStatus {
     pub fn from_str(s: &str) -> Self {
         match s {
             "online" => ServerStatus::Online,
+            "degraded" => ServerStatus::Degraded,
             "offline" => ServerStatus::Offline,
             _ => ServerStatus::Unknown,
         }
     }
+
+    /// Status from a reachability check and a Docker ping
+    pub fn from_checks(reachable: bool, docker_up: bool) -> Self {
+        match (reachable, docker_up) {
+            (false, _) => ServerStatus::Offline,
+            (true, false) => ServerStatus::Degraded,
+            (true, true) => ServerStatus::Online,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status_round_trip_and_checks() {
+        for status in [ServerStatus::Online, ServerStatus::Degraded, ServerStatus::Offline, ServerStatus::Unknown] {
+            assert_eq!(ServerStatus::from_str(status.as_str()), status);
+        }
+        assert_eq!(ServerStatus::from_str("rebooting"), ServerStatus::Unknown);
+
+        assert_eq!(ServerStatus::from_checks(true, true), ServerStatus::Online);
+        assert_eq!(ServerStatus::from_checks(true, false), ServerStatus::Degraded);
+        assert_eq!(ServerStatus::from_checks(false, true), ServerStatus::Offline);
+    }
 }
@@ -47,16 +47,35 @@ pub struct WebhookDelivery {
     pub commit_sha: Option<String>,
     pub commit_message: Option<String>,
     pub author: Option<String>,
+    pub author_email: Option<String>,
+    pub committer_name: Option<String>,
+    pub committer_email: Option<String>,
+    /// Avatar of the account that pushed
+    pub avatar_url: Option<String>,
     pub status: WebhookDeliveryStatus,
     pub response_code: Option<i32>,
     pub error_message: Option<String>,
     pub deployment_id: Option<String>,
+    pub attempts: i32,
+    pub last_attempt_at: Option<DateTime<Utc>>,
     pub delivered_at: DateTime<Utc>,
 }
 
+/// Who wrote and pushed the commit behind a delivery
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct CommitAuthor {
+    pub name: String,
+    pub email: Option<String>,
+    pub committer_name: Option<String>,
+    pub committer_email: Option<String>,
+    pub avatar_url: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "lowercase")]
 pub enum WebhookDeliveryStatus {
+    /// A deployment was started and hasn't finished yet
+    Pending,
     Success,
     Failed,
     Skipped,
@@ -65,6 +84,7 @@ pub enum WebhookDeliveryStatus {
 impl WebhookDeliveryStatus {
     pub fn as_str(&self) -> &str {
         match self {
+            WebhookDeliveryStatus::Pending => "pending",
             WebhookDeliveryStatus::Success => "success",
             WebhookDeliveryStatus::Failed => "failed",
             WebhookDeliveryStatus::Skipped => "skipped",
@@ -73,6 +93,7 @@ impl WebhookDeliveryStatus {
 
     pub fn from_str(s: &str) -> Self {
         match s {
+            "pending" => WebhookDeliveryStatus::Pending,
             "success" => WebhookDeliveryStatus::Success,
             "failed" => WebhookDeliveryStatus::Failed,
             "skipped" => WebhookDeliveryStatus::Skipped,
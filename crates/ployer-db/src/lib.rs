@@ -1,16 +1,24 @@
 pub mod repositories;
+mod retry;
 
 use anyhow::Result;
 use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
 use sqlx::SqlitePool;
 use std::str::FromStr;
+use std::time::Duration;
 use tracing::info;
 
+/// How long a connection waits for another writer to release the database
+/// before failing with "database is locked". Stats, health results and
+/// deployment logs are written concurrently, and SQLite allows one writer.
+pub const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
 pub async fn create_pool(database_url: &str) -> Result<SqlitePool> {
     let options = SqliteConnectOptions::from_str(database_url)?
         .create_if_missing(true)
         .journal_mode(sqlx::sqlite::SqliteJournalMode::Wal)
-        .foreign_keys(true);
+        .foreign_keys(true)
+        .busy_timeout(BUSY_TIMEOUT);
 
     let pool = SqlitePoolOptions::new()
         .max_connections(5)
@@ -21,19 +29,70 @@ pub async fn create_pool(database_url: &str) -> Result<SqlitePool> {
     Ok(pool)
 }
 
+/// A migrated in-memory database on a single connection, so every query sees
+/// the same data. Tests use it instead of hand-written table definitions.
+pub async fn memory_pool() -> Result<SqlitePool> {
+    let options = SqliteConnectOptions::from_str("sqlite::memory:")?.foreign_keys(true);
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect_with(options)
+        .await?;
+    run_migrations(&pool).await?;
+    Ok(pool)
+}
+
 pub async fn run_migrations(pool: &SqlitePool) -> Result<()> {
     let migrations = [
         include_str!("../../../migrations/001_initial.sql"),
         include_str!("../../../migrations/002_webhooks.sql"),
         include_str!("../../../migrations/003_health_check_results.sql"),
         include_str!("../../../migrations/004_settings.sql"),
+        include_str!("../../../migrations/005_webhook_delivery_retries.sql"),
+        include_str!("../../../migrations/006_application_restart_policy.sql"),
+        include_str!("../../../migrations/007_refresh_tokens.sql"),
+        include_str!("../../../migrations/008_application_volumes.sql"),
+        include_str!("../../../migrations/009_application_ports.sql"),
+        include_str!("../../../migrations/010_health_check_method.sql"),
+        include_str!("../../../migrations/011_health_check_type.sql"),
+        include_str!("../../../migrations/012_application_archive.sql"),
+        include_str!("../../../migrations/013_deployment_host_port.sql"),
+        include_str!("../../../migrations/014_application_network.sql"),
+        include_str!("../../../migrations/015_domain_basic_auth.sql"),
+        include_str!("../../../migrations/016_domain_rate_limit.sql"),
+        include_str!("../../../migrations/017_webhook_delivery_author.sql"),
+        include_str!("../../../migrations/018_application_docker_target.sql"),
+        include_str!("../../../migrations/019_application_git_submodules.sql"),
+        include_str!("../../../migrations/020_deployments_status_index.sql"),
+        include_str!("../../../migrations/021_application_registries.sql"),
+        include_str!("../../../migrations/022_application_image.sql"),
+        include_str!("../../../migrations/023_deployment_idempotency_key.sql"),
+        include_str!("../../../migrations/024_applications_filter_indexes.sql"),
+        include_str!("../../../migrations/025_application_deploy_schedule.sql"),
+        include_str!("../../../migrations/026_env_var_scope.sql"),
+        include_str!("../../../migrations/027_audit_log.sql"),
+        include_str!("../../../migrations/028_server_platform.sql"),
+        include_str!("../../../migrations/029_domain_proxy_health_check.sql"),
+        include_str!("../../../migrations/030_webhook_deliveries_cursor_index.sql"),
+        include_str!("../../../migrations/031_application_stop_grace.sql"),
+        include_str!("../../../migrations/032_server_tags.sql"),
+        include_str!("../../../migrations/033_domain_proxy_headers.sql"),
+        include_str!("../../../migrations/034_application_deploy_hooks.sql"),
+        include_str!("../../../migrations/035_domain_aliases.sql"),
     ];
 
     for migration_sql in &migrations {
         for statement in migration_sql.split(';') {
             let stmt = statement.trim();
-            if !stmt.is_empty() {
-                sqlx::query(stmt).execute(pool).await?;
+            if stmt.is_empty() {
+                continue;
+            }
+            if let Err(e) = sqlx::query(stmt).execute(pool).await {
+                // Migrations are re-applied on every start; SQLite has no
+                // `ADD COLUMN IF NOT EXISTS`, so columns from a previous run are expected.
+                if e.to_string().contains("duplicate column name") {
+                    continue;
+                }
+                return Err(e.into());
             }
         }
     }
@@ -1,5 +1,5 @@
 use anyhow::Result;
-use ployer_core::models::{Application, AppStatus, BuildStrategy};
+use ployer_core::models::{Application, AppStatus, BuildStrategy, PortMapping};
 use sqlx::SqlitePool;
 use uuid::Uuid;
 
@@ -7,41 +7,81 @@ pub struct ApplicationRepository {
     pool: SqlitePool,
 }
 
+/// Narrows `ApplicationRepository::list_paged`; unset fields match everything
+#[derive(Debug, Default)]
+pub struct ApplicationFilter<'a> {
+    pub include_archived: bool,
+    pub server_id: Option<&'a str>,
+    pub status: Option<AppStatus>,
+    /// Substring of the name, matched literally
+    pub name_query: Option<&'a str>,
+}
+
+/// Settings of a new application, written by `ApplicationRepository::create` in one insert
+#[derive(Debug, Default)]
+pub struct NewApplication<'a> {
+    pub name: &'a str,
+    pub server_id: &'a str,
+    pub git_url: Option<&'a str>,
+    pub git_branch: &'a str,
+    pub git_submodules: bool,
+    pub build_strategy: BuildStrategy,
+    pub dockerfile_path: Option<&'a str>,
+    pub docker_target: Option<&'a str>,
+    pub image: Option<&'a str>,
+    pub port: Option<u16>,
+    pub ports: &'a [PortMapping],
+    pub auto_deploy: bool,
+    pub restart_policy: &'a str,
+    /// None keeps the column default
+    pub stop_grace_seconds: Option<u32>,
+    pub pre_build_cmd: Option<&'a str>,
+    pub post_deploy_cmd: Option<&'a str>,
+}
+
+/// `LIKE` pattern matching `query` anywhere, with `\`, `%` and `_` escaped
+fn like_pattern(query: &str) -> String {
+    let escaped = query
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_");
+    format!("%{}%", escaped)
+}
+
 impl ApplicationRepository {
     pub fn new(pool: SqlitePool) -> Self {
         Self { pool }
     }
 
-    pub async fn create(
-        &self,
-        name: &str,
-        server_id: &str,
-        git_url: Option<&str>,
-        git_branch: &str,
-        build_strategy: BuildStrategy,
-        dockerfile_path: Option<&str>,
-        port: Option<u16>,
-        auto_deploy: bool,
-    ) -> Result<Application> {
+    pub async fn create(&self, app: &NewApplication<'_>) -> Result<Application> {
         let id = Uuid::new_v4().to_string();
         let now = chrono::Utc::now().to_rfc3339();
         let status = AppStatus::Idle.as_str();
-        let strategy = build_strategy.as_str();
+        let strategy = app.build_strategy.as_str();
+        let ports_json = serde_json::to_string(app.ports)?;
 
         sqlx::query(
-            "INSERT INTO applications (id, name, server_id, git_url, git_branch, build_strategy, dockerfile_path, port, status, auto_deploy, created_at, updated_at)
-             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"
+            "INSERT INTO applications (id, name, server_id, git_url, git_branch, git_submodules, build_strategy, dockerfile_path, docker_target, image, port, ports, status, auto_deploy, restart_policy, stop_grace_seconds, pre_build_cmd, post_deploy_cmd, created_at, updated_at)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, COALESCE(?, 10), ?, ?, ?, ?)"
         )
         .bind(&id)
-        .bind(name)
-        .bind(server_id)
-        .bind(git_url)
-        .bind(git_branch)
+        .bind(app.name)
+        .bind(app.server_id)
+        .bind(app.git_url)
+        .bind(app.git_branch)
+        .bind(app.git_submodules)
         .bind(strategy)
-        .bind(dockerfile_path)
-        .bind(port.map(|p| p as i64))
+        .bind(app.dockerfile_path)
+        .bind(app.docker_target)
+        .bind(app.image)
+        .bind(app.port.map(|p| p as i64))
+        .bind(&ports_json)
         .bind(status)
-        .bind(if auto_deploy { 1 } else { 0 })
+        .bind(if app.auto_deploy { 1 } else { 0 })
+        .bind(app.restart_policy)
+        .bind(app.stop_grace_seconds.map(|s| s as i64))
+        .bind(app.pre_build_cmd)
+        .bind(app.post_deploy_cmd)
         .bind(&now)
         .bind(&now)
         .execute(&self.pool)
@@ -53,7 +93,7 @@ impl ApplicationRepository {
 
     pub async fn find_by_id(&self, id: &str) -> Result<Option<Application>> {
         let row = sqlx::query_as::<_, ApplicationRow>(
-            "SELECT id, name, server_id, git_url, git_branch, build_strategy, dockerfile_path, port, status, auto_deploy, created_at, updated_at
+            "SELECT id, name, server_id, git_url, git_branch, git_submodules, build_strategy, dockerfile_path, docker_target, image, port, ports, status, auto_deploy, deploy_schedule, restart_policy, stop_grace_seconds, pre_build_cmd, post_deploy_cmd, network_name, archived_at, created_at, updated_at
              FROM applications WHERE id = ?"
         )
         .bind(id)
@@ -63,21 +103,68 @@ impl ApplicationRepository {
         Ok(row.map(|r| r.into()))
     }
 
-    pub async fn list(&self) -> Result<Vec<Application>> {
+    pub async fn list(&self, include_archived: bool) -> Result<Vec<Application>> {
         let rows = sqlx::query_as::<_, ApplicationRow>(
-            "SELECT id, name, server_id, git_url, git_branch, build_strategy, dockerfile_path, port, status, auto_deploy, created_at, updated_at
-             FROM applications ORDER BY created_at DESC"
+            "SELECT id, name, server_id, git_url, git_branch, git_submodules, build_strategy, dockerfile_path, docker_target, image, port, ports, status, auto_deploy, deploy_schedule, restart_policy, stop_grace_seconds, pre_build_cmd, post_deploy_cmd, network_name, archived_at, created_at, updated_at
+             FROM applications WHERE (? OR archived_at IS NULL) ORDER BY created_at DESC"
         )
+        .bind(include_archived)
         .fetch_all(&self.pool)
         .await?;
 
         Ok(rows.into_iter().map(|r| r.into()).collect())
     }
 
+    /// One page of applications matching `filter`, newest first, with the total match count
+    pub async fn list_paged(
+        &self,
+        filter: &ApplicationFilter<'_>,
+        limit: i64,
+        offset: i64,
+    ) -> Result<(Vec<Application>, i64)> {
+        const WHERE: &str = "WHERE (? OR archived_at IS NULL)
+               AND (? IS NULL OR server_id = ?)
+               AND (? IS NULL OR status = ?)
+               AND (? IS NULL OR name LIKE ? ESCAPE '\\')";
+
+        let status = filter.status.as_ref().map(|s| s.as_str());
+        let pattern = filter.name_query.map(like_pattern);
+
+        let total: i64 = sqlx::query_scalar(&format!("SELECT COUNT(*) FROM applications {}", WHERE))
+            .bind(filter.include_archived)
+            .bind(filter.server_id)
+            .bind(filter.server_id)
+            .bind(status)
+            .bind(status)
+            .bind(&pattern)
+            .bind(&pattern)
+            .fetch_one(&self.pool)
+            .await?;
+
+        let rows = sqlx::query_as::<_, ApplicationRow>(&format!(
+            "SELECT id, name, server_id, git_url, git_branch, git_submodules, build_strategy, dockerfile_path, docker_target, image, port, ports, status, auto_deploy, deploy_schedule, restart_policy, stop_grace_seconds, pre_build_cmd, post_deploy_cmd, network_name, archived_at, created_at, updated_at
+             FROM applications {} ORDER BY created_at DESC LIMIT ? OFFSET ?",
+            WHERE
+        ))
+        .bind(filter.include_archived)
+        .bind(filter.server_id)
+        .bind(filter.server_id)
+        .bind(status)
+        .bind(status)
+        .bind(&pattern)
+        .bind(&pattern)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok((rows.into_iter().map(|r| r.into()).collect(), total))
+    }
+
     pub async fn list_by_server(&self, server_id: &str) -> Result<Vec<Application>> {
         let rows = sqlx::query_as::<_, ApplicationRow>(
-            "SELECT id, name, server_id, git_url, git_branch, build_strategy, dockerfile_path, port, status, auto_deploy, created_at, updated_at
-             FROM applications WHERE server_id = ? ORDER BY created_at DESC"
+            "SELECT id, name, server_id, git_url, git_branch, git_submodules, build_strategy, dockerfile_path, docker_target, image, port, ports, status, auto_deploy, deploy_schedule, restart_policy, stop_grace_seconds, pre_build_cmd, post_deploy_cmd, network_name, archived_at, created_at, updated_at
+             FROM applications WHERE server_id = ? AND archived_at IS NULL ORDER BY created_at DESC"
         )
         .bind(server_id)
         .fetch_all(&self.pool)
@@ -95,14 +182,17 @@ impl ApplicationRepository {
         build_strategy: BuildStrategy,
         dockerfile_path: Option<&str>,
         port: Option<u16>,
+        ports: &[PortMapping],
         auto_deploy: bool,
+        restart_policy: &str,
     ) -> Result<Application> {
         let now = chrono::Utc::now().to_rfc3339();
         let strategy = build_strategy.as_str();
+        let ports_json = serde_json::to_string(ports)?;
 
         sqlx::query(
             "UPDATE applications
-             SET name = ?, git_url = ?, git_branch = ?, build_strategy = ?, dockerfile_path = ?, port = ?, auto_deploy = ?, updated_at = ?
+             SET name = ?, git_url = ?, git_branch = ?, build_strategy = ?, dockerfile_path = ?, port = ?, ports = ?, auto_deploy = ?, restart_policy = ?, updated_at = ?
              WHERE id = ?"
         )
         .bind(name)
@@ -111,7 +201,9 @@ impl ApplicationRepository {
         .bind(strategy)
         .bind(dockerfile_path)
         .bind(port.map(|p| p as i64))
+        .bind(&ports_json)
         .bind(if auto_deploy { 1 } else { 0 })
+        .bind(restart_policy)
         .bind(&now)
         .bind(id)
         .execute(&self.pool)
@@ -139,6 +231,138 @@ impl ApplicationRepository {
         Ok(())
     }
 
+    pub async fn set_network_name(&self, id: &str, network_name: &str) -> Result<()> {
+        sqlx::query("UPDATE applications SET network_name = ? WHERE id = ?")
+            .bind(network_name)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Set the Dockerfile stage to build, or None for the final stage
+    pub async fn set_docker_target(&self, id: &str, docker_target: Option<&str>) -> Result<()> {
+        sqlx::query("UPDATE applications SET docker_target = ? WHERE id = ?")
+            .bind(docker_target)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Set the prebuilt image to run, or None to build from the repository
+    pub async fn set_image(&self, id: &str, image: Option<&str>) -> Result<()> {
+        sqlx::query("UPDATE applications SET image = ? WHERE id = ?")
+            .bind(image)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Set the cron expression for scheduled redeploys, or None for no schedule
+    pub async fn set_deploy_schedule(&self, id: &str, schedule: Option<&str>) -> Result<()> {
+        sqlx::query("UPDATE applications SET deploy_schedule = ? WHERE id = ?")
+            .bind(schedule)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Unarchived applications with a redeploy schedule
+    pub async fn list_scheduled(&self) -> Result<Vec<Application>> {
+        let rows = sqlx::query_as::<_, ApplicationRow>(
+            "SELECT id, name, server_id, git_url, git_branch, git_submodules, build_strategy, dockerfile_path, docker_target, image, port, ports, status, auto_deploy, deploy_schedule, restart_policy, stop_grace_seconds, pre_build_cmd, post_deploy_cmd, network_name, archived_at, created_at, updated_at
+             FROM applications WHERE deploy_schedule IS NOT NULL AND archived_at IS NULL"
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(|r| r.into()).collect())
+    }
+
+    pub async fn set_git_submodules(&self, id: &str, enabled: bool) -> Result<()> {
+        sqlx::query("UPDATE applications SET git_submodules = ? WHERE id = ?")
+            .bind(if enabled { 1 } else { 0 })
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn set_stop_grace_seconds(&self, id: &str, seconds: u32) -> Result<()> {
+        sqlx::query("UPDATE applications SET stop_grace_seconds = ? WHERE id = ?")
+            .bind(seconds as i64)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Set or clear the shell command run before the image build
+    pub async fn set_pre_build_cmd(&self, id: &str, cmd: Option<&str>) -> Result<()> {
+        sqlx::query("UPDATE applications SET pre_build_cmd = ? WHERE id = ?")
+            .bind(cmd)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Set or clear the shell command run in the new container after it's healthy
+    pub async fn set_post_deploy_cmd(&self, id: &str, cmd: Option<&str>) -> Result<()> {
+        sqlx::query("UPDATE applications SET post_deploy_cmd = ? WHERE id = ?")
+            .bind(cmd)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Hide an application without touching its deployment history
+    pub async fn archive(&self, id: &str) -> Result<()> {
+        let now = chrono::Utc::now().to_rfc3339();
+
+        sqlx::query(
+            "UPDATE applications
+             SET archived_at = ?, status = ?, updated_at = ?
+             WHERE id = ?"
+        )
+        .bind(&now)
+        .bind(AppStatus::Stopped.as_str())
+        .bind(&now)
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn unarchive(&self, id: &str) -> Result<()> {
+        let now = chrono::Utc::now().to_rfc3339();
+
+        sqlx::query(
+            "UPDATE applications
+             SET archived_at = NULL, updated_at = ?
+             WHERE id = ?"
+        )
+        .bind(&now)
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
     pub async fn delete(&self, id: &str) -> Result<()> {
         sqlx::query("DELETE FROM applications WHERE id = ?")
             .bind(id)
@@ -156,11 +380,22 @@ struct ApplicationRow {
     server_id: String,
     git_url: Option<String>,
     git_branch: String,
+    git_submodules: i64,
     build_strategy: String,
     dockerfile_path: Option<String>,
+    docker_target: Option<String>,
+    image: Option<String>,
     port: Option<i64>,
+    ports: String,
     status: String,
     auto_deploy: i64,
+    deploy_schedule: Option<String>,
+    restart_policy: String,
+    stop_grace_seconds: i64,
+    pre_build_cmd: Option<String>,
+    post_deploy_cmd: Option<String>,
+    network_name: Option<String>,
+    archived_at: Option<String>,
     created_at: String,
     updated_at: String,
 }
@@ -173,11 +408,26 @@ impl From<ApplicationRow> for Application {
             server_id: row.server_id,
             git_url: row.git_url,
             git_branch: row.git_branch,
+            git_submodules: row.git_submodules != 0,
             build_strategy: BuildStrategy::from_str(&row.build_strategy),
             dockerfile_path: row.dockerfile_path,
+            docker_target: row.docker_target,
+            image: row.image,
             port: row.port.map(|p| p as u16),
+            ports: serde_json::from_str(&row.ports).unwrap_or_default(),
             status: AppStatus::from_str(&row.status),
             auto_deploy: row.auto_deploy != 0,
+            deploy_schedule: row.deploy_schedule,
+            restart_policy: row.restart_policy,
+            stop_grace_seconds: row.stop_grace_seconds as u32,
+            pre_build_cmd: row.pre_build_cmd,
+            post_deploy_cmd: row.post_deploy_cmd,
+            network_name: row.network_name,
+            archived_at: row.archived_at.and_then(|a| {
+                chrono::DateTime::parse_from_rfc3339(&a)
+                    .ok()
+                    .map(|dt| dt.with_timezone(&chrono::Utc))
+            }),
             created_at: chrono::DateTime::parse_from_rfc3339(&row.created_at)
                 .unwrap()
                 .with_timezone(&chrono::Utc),
@@ -187,3 +437,54 @@ impl From<ApplicationRow> for Application {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_list_paged_filters_and_escapes_name() {
+        let pool = crate::memory_pool().await.unwrap();
+        for server_id in ["s1", "s2"] {
+            sqlx::query("INSERT INTO servers (id, name, host, created_at, updated_at) VALUES (?, ?, 'localhost', '2024-01-01T00:00:00Z', '2024-01-01T00:00:00Z')")
+                .bind(server_id)
+                .bind(server_id)
+                .execute(&pool)
+                .await
+                .unwrap();
+        }
+
+        let repo = ApplicationRepository::new(pool);
+        for (name, server_id) in [("web_app", "s1"), ("webXapp", "s1"), ("100%-api", "s2")] {
+            let app = NewApplication {
+                name,
+                server_id,
+                git_branch: "main",
+                restart_policy: "always",
+                ..Default::default()
+            };
+            repo.create(&app).await.unwrap();
+        }
+
+        let (apps, total) = repo.list_paged(&ApplicationFilter::default(), 2, 0).await.unwrap();
+        assert_eq!((apps.len(), total), (2, 3));
+        let (apps, _) = repo.list_paged(&ApplicationFilter::default(), 2, 2).await.unwrap();
+        assert_eq!(apps.len(), 1);
+
+        // `_` and `%` are literal, not wildcards
+        let by_name = |name_query| ApplicationFilter { name_query: Some(name_query), ..Default::default() };
+        let (apps, total) = repo.list_paged(&by_name("b_a"), 10, 0).await.unwrap();
+        assert_eq!((apps[0].name.as_str(), total), ("web_app", 1));
+        let (_, total) = repo.list_paged(&by_name("0%-"), 10, 0).await.unwrap();
+        assert_eq!(total, 1);
+
+        let filter = ApplicationFilter {
+            server_id: Some("s1"),
+            status: Some(AppStatus::Idle),
+            ..Default::default()
+        };
+        assert_eq!(repo.list_paged(&filter, 10, 0).await.unwrap().1, 2);
+        let filter = ApplicationFilter { status: Some(AppStatus::Running), ..Default::default() };
+        assert_eq!(repo.list_paged(&filter, 10, 0).await.unwrap().1, 0);
+    }
+}
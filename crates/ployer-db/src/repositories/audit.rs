@@ -0,0 +1,144 @@
+use anyhow::Result;
+use ployer_core::models::{AuditAction, AuditEntry};
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+pub struct AuditRepository {
+    pool: SqlitePool,
+}
+
+/// Narrows `AuditRepository::list_paged`; unset fields match everything
+#[derive(Debug, Default)]
+pub struct AuditFilter<'a> {
+    pub user_id: Option<&'a str>,
+    pub action: Option<AuditAction>,
+}
+
+impl AuditRepository {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn record(
+        &self,
+        user_id: Option<&str>,
+        action: AuditAction,
+        target_id: &str,
+        ip: Option<&str>,
+    ) -> Result<()> {
+        let id = Uuid::new_v4().to_string();
+        let now = chrono::Utc::now().to_rfc3339();
+
+        sqlx::query(
+            "INSERT INTO audit_log (id, user_id, action, target_type, target_id, ip, created_at)
+             VALUES (?, ?, ?, ?, ?, ?, ?)"
+        )
+        .bind(&id)
+        .bind(user_id)
+        .bind(action.as_str())
+        .bind(action.target_type())
+        .bind(target_id)
+        .bind(ip)
+        .bind(&now)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// One page of entries matching `filter`, newest first, with the total match count
+    pub async fn list_paged(
+        &self,
+        filter: &AuditFilter<'_>,
+        limit: i64,
+        offset: i64,
+    ) -> Result<(Vec<AuditEntry>, i64)> {
+        const WHERE: &str = "WHERE (? IS NULL OR user_id = ?)
+               AND (? IS NULL OR action = ?)";
+
+        let action = filter.action.map(|a| a.as_str());
+
+        let total: i64 = sqlx::query_scalar(&format!("SELECT COUNT(*) FROM audit_log {}", WHERE))
+            .bind(filter.user_id)
+            .bind(filter.user_id)
+            .bind(action)
+            .bind(action)
+            .fetch_one(&self.pool)
+            .await?;
+
+        let rows = sqlx::query_as::<_, AuditRow>(&format!(
+            "SELECT id, user_id, action, target_type, target_id, ip, created_at
+             FROM audit_log {} ORDER BY created_at DESC, rowid DESC LIMIT ? OFFSET ?",
+            WHERE
+        ))
+        .bind(filter.user_id)
+        .bind(filter.user_id)
+        .bind(action)
+        .bind(action)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok((rows.into_iter().map(|r| r.into()).collect(), total))
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct AuditRow {
+    id: String,
+    user_id: Option<String>,
+    action: String,
+    target_type: String,
+    target_id: String,
+    ip: Option<String>,
+    created_at: String,
+}
+
+impl From<AuditRow> for AuditEntry {
+    fn from(row: AuditRow) -> Self {
+        AuditEntry {
+            id: row.id,
+            user_id: row.user_id,
+            action: row.action,
+            target_type: row.target_type,
+            target_id: row.target_id,
+            ip: row.ip,
+            created_at: chrono::DateTime::parse_from_rfc3339(&row.created_at)
+                .unwrap()
+                .with_timezone(&chrono::Utc),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_list_paged_filters_newest_first() {
+        let pool = crate::memory_pool().await.unwrap();
+
+        let repo = AuditRepository::new(pool);
+        repo.record(Some("u1"), AuditAction::DeploymentTrigger, "d1", Some("10.0.0.1")).await.unwrap();
+        repo.record(Some("u2"), AuditAction::ServerDelete, "s1", None).await.unwrap();
+        repo.record(Some("u1"), AuditAction::EnvVarDelete, "a1/API_TOKEN", None).await.unwrap();
+        repo.record(None, AuditAction::PasswordReset, "u2", None).await.unwrap();
+
+        let (entries, total) = repo.list_paged(&AuditFilter::default(), 2, 0).await.unwrap();
+        assert_eq!(total, 4);
+        assert_eq!(entries[0].action, "user.password_reset");
+        assert_eq!(entries[0].user_id, None);
+
+        let by_user = AuditFilter { user_id: Some("u1"), ..Default::default() };
+        let (entries, total) = repo.list_paged(&by_user, 10, 0).await.unwrap();
+        assert_eq!(total, 2);
+        assert_eq!(entries[0].target_type, "env_var");
+        assert_eq!(entries[1].ip.as_deref(), Some("10.0.0.1"));
+
+        let filter = AuditFilter { user_id: Some("u1"), action: Some(AuditAction::ServerDelete) };
+        assert_eq!(repo.list_paged(&filter, 10, 0).await.unwrap().1, 0);
+        let filter = AuditFilter { action: Some(AuditAction::ServerDelete), ..Default::default() };
+        assert_eq!(repo.list_paged(&filter, 10, 0).await.unwrap().0[0].target_id, "s1");
+    }
+}
@@ -1,9 +1,11 @@
 use anyhow::Result;
 use chrono::Utc;
-use ployer_core::models::ContainerStats;
+use ployer_core::models::{ContainerStats, ContainerStatsSummary};
 use sqlx::SqlitePool;
 use uuid::Uuid;
 
+use crate::retry::retry_on_busy;
+
 pub struct ContainerStatsRepository {
     pool: SqlitePool,
 }
@@ -28,25 +30,27 @@ impl ContainerStatsRepository {
         let now = Utc::now();
         let now_str = now.to_rfc3339();
 
-        sqlx::query!(
-            r#"
-            INSERT INTO container_stats (
-                id, container_id, application_id, cpu_percent, memory_mb,
-                memory_limit_mb, network_rx_mb, network_tx_mb, recorded_at
+        retry_on_busy(|| {
+            sqlx::query!(
+                r#"
+                INSERT INTO container_stats (
+                    id, container_id, application_id, cpu_percent, memory_mb,
+                    memory_limit_mb, network_rx_mb, network_tx_mb, recorded_at
+                )
+                VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+                "#,
+                id,
+                container_id,
+                application_id,
+                cpu_percent,
+                memory_mb,
+                memory_limit_mb,
+                network_rx_mb,
+                network_tx_mb,
+                now_str
             )
-            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
-            "#,
-            id,
-            container_id,
-            application_id,
-            cpu_percent,
-            memory_mb,
-            memory_limit_mb,
-            network_rx_mb,
-            network_tx_mb,
-            now_str
-        )
-        .execute(&self.pool)
+            .execute(&self.pool)
+        })
         .await?;
 
         Ok(ContainerStats {
@@ -138,6 +142,115 @@ impl ContainerStatsRepository {
             .collect())
     }
 
+    /// Get avg/max/p95 CPU and memory for an application within a time range.
+    /// p95 uses the nearest-rank method over the window's samples.
+    pub async fn get_app_summary(
+        &self,
+        application_id: &str,
+        hours_ago: i64,
+    ) -> Result<ContainerStatsSummary> {
+        let time_filter = format!("-{} hours", hours_ago);
+        let totals = sqlx::query!(
+            r#"
+            SELECT COUNT(*) as "samples!: i64",
+                   AVG(cpu_percent) as "avg_cpu?: f64",
+                   MAX(cpu_percent) as "max_cpu?: f64",
+                   AVG(memory_mb) as "avg_memory?: f64",
+                   MAX(memory_mb) as "max_memory?: f64"
+            FROM container_stats
+            WHERE application_id = ?
+              AND recorded_at >= datetime('now', ?)
+            "#,
+            application_id,
+            time_filter
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        // Zero-based index of the nearest-rank 95th percentile: ceil(0.95 * n) - 1
+        let p95_offset = (totals.samples * 95 + 99) / 100 - 1;
+        let (p95_cpu, p95_memory) = if totals.samples > 0 {
+            let cpu = sqlx::query_scalar!(
+                r#"
+                SELECT cpu_percent FROM container_stats
+                WHERE application_id = ?
+                  AND recorded_at >= datetime('now', ?)
+                ORDER BY cpu_percent ASC
+                LIMIT 1 OFFSET ?
+                "#,
+                application_id,
+                time_filter,
+                p95_offset
+            )
+            .fetch_optional(&self.pool)
+            .await?;
+
+            let memory = sqlx::query_scalar!(
+                r#"
+                SELECT memory_mb FROM container_stats
+                WHERE application_id = ?
+                  AND recorded_at >= datetime('now', ?)
+                ORDER BY memory_mb ASC
+                LIMIT 1 OFFSET ?
+                "#,
+                application_id,
+                time_filter,
+                p95_offset
+            )
+            .fetch_optional(&self.pool)
+            .await?;
+
+            (cpu, memory)
+        } else {
+            (None, None)
+        };
+
+        Ok(ContainerStatsSummary {
+            application_id: application_id.to_string(),
+            hours: hours_ago,
+            samples: totals.samples,
+            avg_cpu_percent: totals.avg_cpu,
+            max_cpu_percent: totals.max_cpu,
+            p95_cpu_percent: p95_cpu,
+            avg_memory_mb: totals.avg_memory,
+            max_memory_mb: totals.max_memory,
+            p95_memory_mb: p95_memory,
+        })
+    }
+
+    /// Get the most recent stats sample for each application
+    pub async fn get_latest_per_app(&self) -> Result<Vec<ContainerStats>> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT id, container_id, application_id, cpu_percent, memory_mb,
+                   memory_limit_mb, network_rx_mb, network_tx_mb, recorded_at
+            FROM container_stats cs
+            WHERE application_id IS NOT NULL
+              AND recorded_at = (
+                  SELECT MAX(recorded_at) FROM container_stats
+                  WHERE application_id = cs.application_id
+              )
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|r| ContainerStats {
+                id: r.id,
+                container_id: r.container_id,
+                application_id: r.application_id,
+                cpu_percent: r.cpu_percent,
+                memory_mb: r.memory_mb,
+                memory_limit_mb: r.memory_limit_mb,
+                network_rx_mb: r.network_rx_mb,
+                network_tx_mb: r.network_tx_mb,
+                recorded_at: r.recorded_at.parse().unwrap(),
+            })
+            .collect())
+    }
+
     /// Clean up old stats (keep only last N hours)
     pub async fn cleanup_old_stats(&self, hours: i64) -> Result<u64> {
         let time_filter = format!("-{} hours", hours);
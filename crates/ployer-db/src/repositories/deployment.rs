@@ -4,13 +4,25 @@ use ployer_core::models::{Deployment, DeploymentStatus};
 use sqlx::SqlitePool;
 use uuid::Uuid;
 
+use crate::retry::retry_on_busy;
+
+/// Put in place of the lines dropped from the head of an oversized build log
+pub const BUILD_LOG_TRUNCATED_MARKER: &str = "[... earlier output truncated ...]\n";
+
 pub struct DeploymentRepository {
     pool: SqlitePool,
+    max_log_bytes: usize,
 }
 
 impl DeploymentRepository {
     pub fn new(pool: SqlitePool) -> Self {
-        Self { pool }
+        Self { pool, max_log_bytes: 0 }
+    }
+
+    /// Cap the build logs written through this repository at about `max_bytes`; 0 means no cap
+    pub fn with_log_limit(mut self, max_bytes: usize) -> Self {
+        self.max_log_bytes = max_bytes;
+        self
     }
 
     /// Create a new deployment
@@ -21,6 +33,7 @@ impl DeploymentRepository {
         commit_sha: Option<&str>,
         commit_message: Option<&str>,
         image_tag: &str,
+        idempotency_key: Option<&str>,
     ) -> Result<Deployment> {
         let id = Uuid::new_v4().to_string();
         let now = Utc::now();
@@ -32,9 +45,9 @@ impl DeploymentRepository {
             r#"
             INSERT INTO deployments (
                 id, application_id, server_id, commit_sha, commit_message,
-                status, image_tag, started_at
+                status, image_tag, started_at, idempotency_key
             )
-            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
             "#,
             id,
             application_id,
@@ -43,7 +56,8 @@ impl DeploymentRepository {
             commit_message,
             status_str,
             image_tag,
-            now_str
+            now_str,
+            idempotency_key
         )
         .execute(&self.pool)
         .await?;
@@ -57,6 +71,7 @@ impl DeploymentRepository {
             status,
             build_log: None,
             container_id: None,
+            host_port: None,
             image_tag: image_tag.to_string(),
             started_at: now,
             finished_at: None,
@@ -68,7 +83,7 @@ impl DeploymentRepository {
         let row = sqlx::query!(
             r#"
             SELECT id, application_id, server_id, commit_sha, commit_message,
-                   status, build_log, container_id, image_tag, started_at, finished_at
+                   status, build_log, container_id, host_port, image_tag, started_at, finished_at
             FROM deployments
             WHERE id = ?
             "#,
@@ -86,18 +101,47 @@ impl DeploymentRepository {
             status: DeploymentStatus::from_str(&r.status),
             build_log: r.build_log,
             container_id: r.container_id,
+            host_port: r.host_port.and_then(|p| u16::try_from(p).ok()),
             image_tag: r.image_tag,
             started_at: r.started_at.parse().unwrap(),
             finished_at: r.finished_at.and_then(|f| f.parse().ok()),
         }))
     }
 
+    /// Deployment an application created for an idempotency key, if any
+    pub async fn find_by_idempotency_key(&self, application_id: &str, key: &str) -> Result<Option<Deployment>> {
+        let id = sqlx::query_scalar!(
+            "SELECT id FROM deployments WHERE application_id = ? AND idempotency_key = ?",
+            application_id,
+            key
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        match id {
+            Some(id) => self.find_by_id(&id).await,
+            None => Ok(None),
+        }
+    }
+
+    /// Free an idempotency key so it can be used for a new deployment
+    pub async fn release_idempotency_key(&self, application_id: &str, key: &str) -> Result<()> {
+        sqlx::query!(
+            "UPDATE deployments SET idempotency_key = NULL WHERE application_id = ? AND idempotency_key = ?",
+            application_id,
+            key
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
     /// List all deployments (optionally filtered by application)
     pub async fn list(&self, application_id: Option<&str>) -> Result<Vec<Deployment>> {
         let rows = sqlx::query!(
             r#"
             SELECT id, application_id, server_id, commit_sha, commit_message,
-                   status, build_log, container_id, image_tag, started_at, finished_at
+                   status, build_log, container_id, host_port, image_tag, started_at, finished_at
             FROM deployments
             WHERE (? IS NULL OR application_id = ?)
             ORDER BY started_at DESC
@@ -119,6 +163,7 @@ impl DeploymentRepository {
                 status: DeploymentStatus::from_str(&r.status),
                 build_log: r.build_log,
                 container_id: r.container_id,
+                host_port: r.host_port.and_then(|p| u16::try_from(p).ok()),
                 image_tag: r.image_tag,
                 started_at: r.started_at.parse().unwrap(),
                 finished_at: r.finished_at.and_then(|f| f.parse().ok()),
@@ -163,20 +208,92 @@ impl DeploymentRepository {
     /// Append to build log
     pub async fn append_log(&self, id: &str, log_line: &str) -> Result<()> {
         let line_with_newline = format!("{}\n", log_line);
-        sqlx::query!(
+        retry_on_busy(|| {
+            sqlx::query!(
+                r#"
+                UPDATE deployments
+                SET build_log = COALESCE(build_log || ?, ?)
+                WHERE id = ?
+                "#,
+                line_with_newline,
+                line_with_newline,
+                id
+            )
+            .execute(&self.pool)
+        })
+        .await?;
+
+        if self.max_log_bytes > 0 {
+            self.trim_log(id).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Drop whole lines from the head of an oversized build log, keeping the tail.
+    /// Measured in bytes; the cut follows a newline, so no UTF-8 sequence is split.
+    /// It's cut to three quarters of the cap so the next appends don't each trim again.
+    async fn trim_log(&self, id: &str) -> Result<()> {
+        let max = self.max_log_bytes as i64;
+        let keep = max * 3 / 4;
+        retry_on_busy(|| {
+            sqlx::query!(
+                r#"
+                UPDATE deployments
+                SET build_log = ? || substr(
+                    substr(CAST(build_log AS BLOB), -?),
+                    instr(substr(CAST(build_log AS BLOB), -?), X'0A') + 1
+                )
+                WHERE id = ? AND length(CAST(build_log AS BLOB)) > ?
+                "#,
+                BUILD_LOG_TRUNCATED_MARKER,
+                keep,
+                keep,
+                id,
+                max
+            )
+            .execute(&self.pool)
+        })
+        .await?;
+
+        Ok(())
+    }
+
+    /// Clear the build logs of deployments that finished more than `days` ago.
+    /// Each application's latest deployment keeps its log however old it is.
+    pub async fn clear_old_logs(&self, days: u64) -> Result<u64> {
+        let cutoff = (Utc::now() - chrono::Duration::days(days as i64)).to_rfc3339();
+        let result = sqlx::query!(
             r#"
             UPDATE deployments
-            SET build_log = COALESCE(build_log || ?, ?)
-            WHERE id = ?
+            SET build_log = NULL
+            WHERE build_log IS NOT NULL
+              AND finished_at IS NOT NULL
+              AND finished_at < ?
+              AND started_at < (
+                  SELECT MAX(latest.started_at) FROM deployments latest
+                  WHERE latest.application_id = deployments.application_id
+              )
             "#,
-            line_with_newline,
-            line_with_newline,
-            id
+            cutoff
         )
         .execute(&self.pool)
         .await?;
 
-        Ok(())
+        Ok(result.rows_affected())
+    }
+
+    /// Mark the app's other running deployments rolled back once `current_id` is live
+    pub async fn retire_superseded(&self, application_id: &str, current_id: &str) -> Result<u64> {
+        let result = sqlx::query!(
+            "UPDATE deployments SET status = 'rolled_back' WHERE application_id = ? AND status = 'running' AND id != ?",
+            application_id,
+            current_id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
     }
 
     /// Set container ID for deployment
@@ -192,12 +309,57 @@ impl DeploymentRepository {
         Ok(())
     }
 
+    /// Record the host port the container's primary port was bound to
+    pub async fn set_host_port(&self, id: &str, host_port: Option<u16>) -> Result<()> {
+        sqlx::query!(
+            "UPDATE deployments SET host_port = ? WHERE id = ?",
+            host_port,
+            id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Get the most recent deployment for an application, whatever its status
+    pub async fn get_latest(&self, application_id: &str) -> Result<Option<Deployment>> {
+        let row = sqlx::query!(
+            r#"
+            SELECT id, application_id, server_id, commit_sha, commit_message,
+                   status, build_log, container_id, host_port, image_tag, started_at, finished_at
+            FROM deployments
+            WHERE application_id = ?
+            ORDER BY started_at DESC
+            LIMIT 1
+            "#,
+            application_id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row.map(|r| Deployment {
+            id: r.id,
+            application_id: r.application_id,
+            server_id: r.server_id,
+            commit_sha: r.commit_sha,
+            commit_message: r.commit_message,
+            status: DeploymentStatus::from_str(&r.status),
+            build_log: r.build_log,
+            container_id: r.container_id,
+            host_port: r.host_port.and_then(|p| u16::try_from(p).ok()),
+            image_tag: r.image_tag,
+            started_at: r.started_at.parse().unwrap(),
+            finished_at: r.finished_at.and_then(|f| f.parse().ok()),
+        }))
+    }
+
     /// Get the latest successful deployment for an application
     pub async fn get_latest_running(&self, application_id: &str) -> Result<Option<Deployment>> {
         let row = sqlx::query!(
             r#"
             SELECT id, application_id, server_id, commit_sha, commit_message,
-                   status, build_log, container_id, image_tag, started_at, finished_at
+                   status, build_log, container_id, host_port, image_tag, started_at, finished_at
             FROM deployments
             WHERE application_id = ? AND status = 'running'
             ORDER BY started_at DESC
@@ -217,6 +379,123 @@ impl DeploymentRepository {
             status: DeploymentStatus::from_str(&r.status),
             build_log: r.build_log,
             container_id: r.container_id,
+            host_port: r.host_port.and_then(|p| u16::try_from(p).ok()),
+            image_tag: r.image_tag,
+            started_at: r.started_at.parse().unwrap(),
+            finished_at: r.finished_at.and_then(|f| f.parse().ok()),
+        }))
+    }
+
+    /// Most recent deployments in a status across all applications, or just one
+    pub async fn list_by_status(
+        &self,
+        status: &DeploymentStatus,
+        application_id: Option<&str>,
+        limit: i64,
+    ) -> Result<Vec<Deployment>> {
+        let status = status.as_str();
+        let rows = sqlx::query!(
+            r#"
+            SELECT id, application_id, server_id, commit_sha, commit_message,
+                   status, build_log, container_id, host_port, image_tag, started_at, finished_at
+            FROM deployments
+            WHERE status = ? AND (? IS NULL OR application_id = ?)
+            ORDER BY started_at DESC
+            LIMIT ?
+            "#,
+            status,
+            application_id,
+            application_id,
+            limit
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|r| Deployment {
+                id: r.id,
+                application_id: r.application_id,
+                server_id: r.server_id,
+                commit_sha: r.commit_sha,
+                commit_message: r.commit_message,
+                status: DeploymentStatus::from_str(&r.status),
+                build_log: r.build_log,
+                container_id: r.container_id,
+                host_port: r.host_port.and_then(|p| u16::try_from(p).ok()),
+                image_tag: r.image_tag,
+                started_at: r.started_at.parse().unwrap(),
+                finished_at: r.finished_at.and_then(|f| f.parse().ok()),
+            })
+            .collect())
+    }
+
+    /// Count deployments grouped by status
+    pub async fn count_by_status(&self) -> Result<Vec<(DeploymentStatus, i64)>> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT status, COUNT(*) as "count!: i64"
+            FROM deployments
+            GROUP BY status
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|r| (DeploymentStatus::from_str(&r.status), r.count))
+            .collect())
+    }
+
+    /// Whether the application has a deployment that hasn't finished yet
+    pub async fn has_in_progress(&self, application_id: &str) -> Result<bool> {
+        let count = sqlx::query_scalar!(
+            r#"
+            SELECT COUNT(*) as "count!: i64"
+            FROM deployments
+            WHERE application_id = ? AND status IN ('queued', 'cloning', 'building', 'deploying')
+            "#,
+            application_id
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(count > 0)
+    }
+
+    /// Find a deployment for the given commit that is in progress or running
+    pub async fn find_active_for_commit(
+        &self,
+        application_id: &str,
+        commit_sha: &str,
+    ) -> Result<Option<Deployment>> {
+        let row = sqlx::query!(
+            r#"
+            SELECT id, application_id, server_id, commit_sha, commit_message,
+                   status, build_log, container_id, host_port, image_tag, started_at, finished_at
+            FROM deployments
+            WHERE application_id = ? AND commit_sha = ?
+              AND status NOT IN ('failed', 'cancelled', 'rolled_back')
+            ORDER BY started_at DESC
+            LIMIT 1
+            "#,
+            application_id,
+            commit_sha
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row.map(|r| Deployment {
+            id: r.id,
+            application_id: r.application_id,
+            server_id: r.server_id,
+            commit_sha: r.commit_sha,
+            commit_message: r.commit_message,
+            status: DeploymentStatus::from_str(&r.status),
+            build_log: r.build_log,
+            container_id: r.container_id,
+            host_port: r.host_port.and_then(|p| u16::try_from(p).ok()),
             image_tag: r.image_tag,
             started_at: r.started_at.parse().unwrap(),
             finished_at: r.finished_at.and_then(|f| f.parse().ok()),
@@ -241,3 +520,146 @@ impl DeploymentRepository {
         Ok(result.rows_affected() > 0)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Server `s1` with applications `a1` and `a2`, which deployments reference
+    async fn seed(pool: &SqlitePool) {
+        for statement in [
+            "INSERT INTO servers (id, name, host, created_at, updated_at) VALUES ('s1', 's1', 'localhost', '2024-01-01T00:00:00Z', '2024-01-01T00:00:00Z')",
+            "INSERT INTO applications (id, name, server_id, created_at, updated_at) VALUES ('a1', 'web', 's1', '2024-01-01T00:00:00Z', '2024-01-01T00:00:00Z')",
+            "INSERT INTO applications (id, name, server_id, created_at, updated_at) VALUES ('a2', 'api', 's1', '2024-01-01T00:00:00Z', '2024-01-01T00:00:00Z')",
+        ] {
+            sqlx::query(statement).execute(pool).await.unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_latest_any_status() {
+        let pool = crate::memory_pool().await.unwrap();
+        seed(&pool).await;
+
+        let repo = DeploymentRepository::new(pool);
+        assert!(repo.get_latest("a1").await.unwrap().is_none());
+
+        let first = repo.create("a1", "s1", None, None, "web:1", None).await.unwrap();
+        repo.update_status(&first.id, DeploymentStatus::Running).await.unwrap();
+        let second = repo.create("a1", "s1", None, None, "web:2", None).await.unwrap();
+        repo.update_status(&second.id, DeploymentStatus::Failed).await.unwrap();
+        repo.create("a2", "s1", None, None, "api:1", None).await.unwrap();
+
+        let latest = repo.get_latest("a1").await.unwrap().unwrap();
+        assert_eq!(latest.id, second.id);
+        assert_eq!(latest.status, DeploymentStatus::Failed);
+        assert_eq!(repo.get_latest_running("a1").await.unwrap().unwrap().id, first.id);
+    }
+
+    #[tokio::test]
+    async fn test_retire_superseded_leaves_other_apps() {
+        let pool = crate::memory_pool().await.unwrap();
+        seed(&pool).await;
+
+        let repo = DeploymentRepository::new(pool);
+        let old = repo.create("a1", "s1", None, None, "web:1", None).await.unwrap();
+        let current = repo.create("a1", "s1", None, None, "web:2", None).await.unwrap();
+        let other = repo.create("a2", "s1", None, None, "api:1", None).await.unwrap();
+        for id in [&old.id, &current.id, &other.id] {
+            repo.update_status(id, DeploymentStatus::Running).await.unwrap();
+        }
+
+        assert_eq!(repo.retire_superseded("a1", &current.id).await.unwrap(), 1);
+        for (id, status) in [
+            (&old.id, DeploymentStatus::RolledBack),
+            (&current.id, DeploymentStatus::Running),
+            (&other.id, DeploymentStatus::Running),
+        ] {
+            assert_eq!(repo.find_by_id(id).await.unwrap().unwrap().status, status);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_append_log_keeps_the_tail() {
+        let pool = crate::memory_pool().await.unwrap();
+        seed(&pool).await;
+
+        let repo = DeploymentRepository::new(pool).with_log_limit(1000);
+        let deployment = repo.create("a1", "s1", None, None, "web:1", None).await.unwrap();
+        for line in 0..200 {
+            repo.append_log(&deployment.id, &format!("step {:03} ✓", line)).await.unwrap();
+        }
+
+        let log = repo.find_by_id(&deployment.id).await.unwrap().unwrap().build_log.unwrap();
+        assert!(log.len() <= 1000);
+        assert!(log.starts_with(BUILD_LOG_TRUNCATED_MARKER));
+        assert!(log.ends_with("step 199 ✓\n"));
+        // Only whole lines are kept after the marker
+        let kept = &log[BUILD_LOG_TRUNCATED_MARKER.len()..];
+        assert!(kept.lines().all(|l| l.starts_with("step ") && l.ends_with('✓')));
+    }
+
+    #[tokio::test]
+    async fn test_clear_old_logs_keeps_latest_per_app() {
+        let pool = crate::memory_pool().await.unwrap();
+        seed(&pool).await;
+
+        let old = (Utc::now() - chrono::Duration::days(40)).to_rfc3339();
+        let older = (Utc::now() - chrono::Duration::days(41)).to_rfc3339();
+        let recent = Utc::now().to_rfc3339();
+        for (id, app, started_at) in [("d1", "a1", &older), ("d2", "a1", &old), ("d3", "a2", &older), ("d4", "a2", &recent)] {
+            sqlx::query("INSERT INTO deployments (id, application_id, server_id, status, build_log, image_tag, started_at, finished_at) VALUES (?, ?, 's1', 'running', 'log', 'img', ?, ?)")
+                .bind(id)
+                .bind(app)
+                .bind(started_at)
+                .bind(started_at)
+                .execute(&pool)
+                .await
+                .unwrap();
+        }
+
+        let repo = DeploymentRepository::new(pool);
+        assert_eq!(repo.clear_old_logs(30).await.unwrap(), 2);
+
+        for (id, cleared) in [("d1", true), ("d2", false), ("d3", true), ("d4", false)] {
+            let log = repo.find_by_id(id).await.unwrap().unwrap().build_log;
+            assert_eq!(log.is_none(), cleared, "{}", id);
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_concurrent_append_log() {
+        // A file database, so the pool's connections contend for the write lock
+        let path = std::env::temp_dir().join(format!("ployer-append-log-{}.db", std::process::id()));
+        let url = format!("sqlite://{}", path.display());
+        let pool = crate::create_pool(&url).await.unwrap();
+        crate::run_migrations(&pool).await.unwrap();
+        seed(&pool).await;
+
+        let repo = DeploymentRepository::new(pool.clone());
+        let deployment = repo.create("a1", "s1", None, None, "web:1", None).await.unwrap();
+
+        let mut tasks = Vec::new();
+        for task in 0..20 {
+            let repo = DeploymentRepository::new(pool.clone());
+            let id = deployment.id.clone();
+            tasks.push(tokio::spawn(async move {
+                for line in 0..25 {
+                    repo.append_log(&id, &format!("task {} line {}", task, line)).await?;
+                }
+                anyhow::Ok(())
+            }));
+        }
+        for task in tasks {
+            task.await.unwrap().unwrap();
+        }
+
+        let log = repo.find_by_id(&deployment.id).await.unwrap().unwrap().build_log.unwrap();
+        assert_eq!(log.lines().count(), 500);
+
+        pool.close().await;
+        for suffix in ["", "-wal", "-shm"] {
+            let _ = std::fs::remove_file(format!("{}{}", path.display(), suffix));
+        }
+    }
+}
@@ -1,9 +1,29 @@
 use anyhow::Result;
 use chrono::Utc;
-use ployer_core::models::Domain;
+use ployer_core::models::{Domain, ProxyHeaders, RateLimit};
 use sqlx::SqlitePool;
 use uuid::Uuid;
 
+/// Both limit columns are set together; treat anything else as no limit
+fn rate_limit(requests: Option<i64>, window_secs: Option<i64>) -> Option<RateLimit> {
+    Some(RateLimit {
+        requests: requests? as u32,
+        window_secs: window_secs? as u32,
+    })
+}
+
+/// NULL or unreadable JSON means no header rules
+fn proxy_headers(json: Option<String>) -> ProxyHeaders {
+    json.and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+/// NULL or unreadable JSON means no aliases
+fn aliases(json: Option<String>) -> Vec<String> {
+    json.and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
 pub struct DomainRepository {
     pool: SqlitePool,
 }
@@ -45,6 +65,12 @@ impl DomainRepository {
             domain: domain.to_string(),
             is_primary,
             ssl_active: false,
+            basic_auth_user: None,
+            rate_limit: None,
+            proxy_health_check: false,
+            proxy_headers: ProxyHeaders::default(),
+            aliases: Vec::new(),
+            www_redirect: false,
             created_at: now,
         })
     }
@@ -53,7 +79,9 @@ impl DomainRepository {
     pub async fn find_by_id(&self, id: &str) -> Result<Option<Domain>> {
         let row = sqlx::query!(
             r#"
-            SELECT id, application_id, domain, is_primary, ssl_active, created_at
+            SELECT id, application_id, domain, is_primary, ssl_active, basic_auth_user,
+                   rate_limit_requests, rate_limit_window_secs, proxy_health_check, proxy_headers,
+                   aliases, www_redirect, created_at
             FROM domains
             WHERE id = ?
             "#,
@@ -68,6 +96,12 @@ impl DomainRepository {
             domain: r.domain,
             is_primary: r.is_primary != 0,
             ssl_active: r.ssl_active != 0,
+            basic_auth_user: r.basic_auth_user,
+            rate_limit: rate_limit(r.rate_limit_requests, r.rate_limit_window_secs),
+            proxy_health_check: r.proxy_health_check != 0,
+            proxy_headers: proxy_headers(r.proxy_headers),
+            aliases: aliases(r.aliases),
+            www_redirect: r.www_redirect != 0,
             created_at: r.created_at.parse().unwrap(),
         }))
     }
@@ -76,7 +110,9 @@ impl DomainRepository {
     pub async fn find_by_domain(&self, domain: &str) -> Result<Option<Domain>> {
         let row = sqlx::query!(
             r#"
-            SELECT id, application_id, domain, is_primary, ssl_active, created_at
+            SELECT id, application_id, domain, is_primary, ssl_active, basic_auth_user,
+                   rate_limit_requests, rate_limit_window_secs, proxy_health_check, proxy_headers,
+                   aliases, www_redirect, created_at
             FROM domains
             WHERE domain = ?
             "#,
@@ -91,6 +127,12 @@ impl DomainRepository {
             domain: r.domain,
             is_primary: r.is_primary != 0,
             ssl_active: r.ssl_active != 0,
+            basic_auth_user: r.basic_auth_user,
+            rate_limit: rate_limit(r.rate_limit_requests, r.rate_limit_window_secs),
+            proxy_health_check: r.proxy_health_check != 0,
+            proxy_headers: proxy_headers(r.proxy_headers),
+            aliases: aliases(r.aliases),
+            www_redirect: r.www_redirect != 0,
             created_at: r.created_at.parse().unwrap(),
         }))
     }
@@ -99,7 +141,9 @@ impl DomainRepository {
     pub async fn list_by_application(&self, application_id: &str) -> Result<Vec<Domain>> {
         let rows = sqlx::query!(
             r#"
-            SELECT id, application_id, domain, is_primary, ssl_active, created_at
+            SELECT id, application_id, domain, is_primary, ssl_active, basic_auth_user,
+                   rate_limit_requests, rate_limit_window_secs, proxy_health_check, proxy_headers,
+                   aliases, www_redirect, created_at
             FROM domains
             WHERE application_id = ?
             ORDER BY is_primary DESC, created_at ASC
@@ -117,6 +161,45 @@ impl DomainRepository {
                 domain: r.domain,
                 is_primary: r.is_primary != 0,
                 ssl_active: r.ssl_active != 0,
+                basic_auth_user: r.basic_auth_user,
+                rate_limit: rate_limit(r.rate_limit_requests, r.rate_limit_window_secs),
+                proxy_health_check: r.proxy_health_check != 0,
+                proxy_headers: proxy_headers(r.proxy_headers),
+                aliases: aliases(r.aliases),
+                www_redirect: r.www_redirect != 0,
+                created_at: r.created_at.parse().unwrap(),
+            })
+            .collect())
+    }
+
+    /// List every domain across all applications
+    pub async fn list(&self) -> Result<Vec<Domain>> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT id, application_id, domain, is_primary, ssl_active, basic_auth_user,
+                   rate_limit_requests, rate_limit_window_secs, proxy_health_check, proxy_headers,
+                   aliases, www_redirect, created_at
+            FROM domains
+            ORDER BY created_at ASC
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|r| Domain {
+                id: r.id,
+                application_id: r.application_id,
+                domain: r.domain,
+                is_primary: r.is_primary != 0,
+                ssl_active: r.ssl_active != 0,
+                basic_auth_user: r.basic_auth_user,
+                rate_limit: rate_limit(r.rate_limit_requests, r.rate_limit_window_secs),
+                proxy_health_check: r.proxy_health_check != 0,
+                proxy_headers: proxy_headers(r.proxy_headers),
+                aliases: aliases(r.aliases),
+                www_redirect: r.www_redirect != 0,
                 created_at: r.created_at.parse().unwrap(),
             })
             .collect())
@@ -137,6 +220,106 @@ impl DomainRepository {
         Ok(())
     }
 
+    /// Set or clear the basic auth credentials (username, bcrypt hash) for a domain
+    pub async fn set_basic_auth(&self, id: &str, auth: Option<(&str, &str)>) -> Result<()> {
+        let (user, hash) = auth.unzip();
+
+        sqlx::query!(
+            "UPDATE domains SET basic_auth_user = ?, basic_auth_hash = ? WHERE id = ?",
+            user,
+            hash,
+            id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Stored basic auth credentials as (username, bcrypt hash)
+    pub async fn get_basic_auth(&self, id: &str) -> Result<Option<(String, String)>> {
+        let row = sqlx::query!(
+            "SELECT basic_auth_user, basic_auth_hash FROM domains WHERE id = ?",
+            id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row.and_then(|r| r.basic_auth_user.zip(r.basic_auth_hash)))
+    }
+
+    /// Set or clear the per-client request limit for a domain
+    pub async fn set_rate_limit(&self, id: &str, rate_limit: Option<RateLimit>) -> Result<()> {
+        let requests = rate_limit.map(|l| l.requests as i64);
+        let window_secs = rate_limit.map(|l| l.window_secs as i64);
+
+        sqlx::query!(
+            "UPDATE domains SET rate_limit_requests = ?, rate_limit_window_secs = ? WHERE id = ?",
+            requests,
+            window_secs,
+            id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Turn the proxy's own upstream health checks on or off for a domain
+    pub async fn set_proxy_health_check(&self, id: &str, enabled: bool) -> Result<()> {
+        let enabled_int = if enabled { 1 } else { 0 };
+
+        sqlx::query!(
+            "UPDATE domains SET proxy_health_check = ? WHERE id = ?",
+            enabled_int,
+            id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Replace the proxy's header rules for a domain; empty rules are stored as NULL
+    pub async fn set_proxy_headers(&self, id: &str, headers: &ProxyHeaders) -> Result<()> {
+        let json = if headers.is_empty() {
+            None
+        } else {
+            Some(serde_json::to_string(headers)?)
+        };
+
+        sqlx::query!(
+            "UPDATE domains SET proxy_headers = ? WHERE id = ?",
+            json,
+            id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Replace the extra hostnames and www redirect of a domain's route; no aliases are stored as NULL
+    pub async fn set_aliases(&self, id: &str, aliases: &[String], www_redirect: bool) -> Result<()> {
+        let json = if aliases.is_empty() {
+            None
+        } else {
+            Some(serde_json::to_string(aliases)?)
+        };
+        let www_redirect = www_redirect as i64;
+
+        sqlx::query!(
+            "UPDATE domains SET aliases = ?, www_redirect = ? WHERE id = ?",
+            json,
+            www_redirect,
+            id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
     /// Set a domain as primary (and unset others for the same app)
     pub async fn set_primary(&self, id: &str) -> Result<()> {
         // First, get the application_id for this domain
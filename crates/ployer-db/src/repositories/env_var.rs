@@ -1,5 +1,5 @@
 use anyhow::Result;
-use ployer_core::models::EnvironmentVariable;
+use ployer_core::models::{EnvVarScope, EnvironmentVariable};
 use sqlx::SqlitePool;
 use uuid::Uuid;
 
@@ -17,18 +17,20 @@ impl EnvVarRepository {
         application_id: &str,
         key: &str,
         value_encrypted: &str,
+        scope: EnvVarScope,
     ) -> Result<EnvironmentVariable> {
         let id = Uuid::new_v4().to_string();
         let now = chrono::Utc::now().to_rfc3339();
 
         sqlx::query(
-            "INSERT INTO environment_variables (id, application_id, key, value_encrypted, created_at)
-             VALUES (?, ?, ?, ?, ?)"
+            "INSERT INTO environment_variables (id, application_id, key, value_encrypted, scope, created_at)
+             VALUES (?, ?, ?, ?, ?, ?)"
         )
         .bind(&id)
         .bind(application_id)
         .bind(key)
         .bind(value_encrypted)
+        .bind(scope.as_str())
         .bind(&now)
         .execute(&self.pool)
         .await?;
@@ -39,7 +41,7 @@ impl EnvVarRepository {
 
     pub async fn find_by_id(&self, id: &str) -> Result<Option<EnvironmentVariable>> {
         let row = sqlx::query_as::<_, EnvVarRow>(
-            "SELECT id, application_id, key, value_encrypted, created_at
+            "SELECT id, application_id, key, value_encrypted, scope, created_at
              FROM environment_variables WHERE id = ?"
         )
         .bind(id)
@@ -55,7 +57,7 @@ impl EnvVarRepository {
         key: &str,
     ) -> Result<Option<EnvironmentVariable>> {
         let row = sqlx::query_as::<_, EnvVarRow>(
-            "SELECT id, application_id, key, value_encrypted, created_at
+            "SELECT id, application_id, key, value_encrypted, scope, created_at
              FROM environment_variables WHERE application_id = ? AND key = ?"
         )
         .bind(application_id)
@@ -68,7 +70,7 @@ impl EnvVarRepository {
 
     pub async fn list_by_application(&self, application_id: &str) -> Result<Vec<EnvironmentVariable>> {
         let rows = sqlx::query_as::<_, EnvVarRow>(
-            "SELECT id, application_id, key, value_encrypted, created_at
+            "SELECT id, application_id, key, value_encrypted, scope, created_at
              FROM environment_variables WHERE application_id = ? ORDER BY key ASC"
         )
         .bind(application_id)
@@ -83,13 +85,15 @@ impl EnvVarRepository {
         application_id: &str,
         key: &str,
         value_encrypted: &str,
+        scope: EnvVarScope,
     ) -> Result<EnvironmentVariable> {
         sqlx::query(
             "UPDATE environment_variables
-             SET value_encrypted = ?
+             SET value_encrypted = ?, scope = ?
              WHERE application_id = ? AND key = ?"
         )
         .bind(value_encrypted)
+        .bind(scope.as_str())
         .bind(application_id)
         .bind(key)
         .execute(&self.pool)
@@ -99,6 +103,31 @@ impl EnvVarRepository {
             .ok_or_else(|| anyhow::anyhow!("Environment variable not found"))
     }
 
+    /// Insert or overwrite many variables at once; either all are written or none.
+    /// New variables get the `both` scope and existing ones keep theirs.
+    pub async fn upsert_many(&self, application_id: &str, vars: &[(String, String)]) -> Result<()> {
+        let now = chrono::Utc::now().to_rfc3339();
+        let mut tx = self.pool.begin().await?;
+
+        for (key, value_encrypted) in vars {
+            sqlx::query(
+                "INSERT INTO environment_variables (id, application_id, key, value_encrypted, created_at)
+                 VALUES (?, ?, ?, ?, ?)
+                 ON CONFLICT(application_id, key) DO UPDATE SET value_encrypted = excluded.value_encrypted"
+            )
+            .bind(Uuid::new_v4().to_string())
+            .bind(application_id)
+            .bind(key)
+            .bind(value_encrypted)
+            .bind(&now)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
     pub async fn delete(&self, application_id: &str, key: &str) -> Result<()> {
         sqlx::query("DELETE FROM environment_variables WHERE application_id = ? AND key = ?")
             .bind(application_id)
@@ -125,6 +154,7 @@ struct EnvVarRow {
     application_id: String,
     key: String,
     value_encrypted: String,
+    scope: String,
     created_at: String,
 }
 
@@ -135,6 +165,7 @@ impl From<EnvVarRow> for EnvironmentVariable {
             application_id: row.application_id,
             key: row.key,
             value_encrypted: row.value_encrypted,
+            scope: EnvVarScope::parse_or_default(&row.scope),
             created_at: chrono::DateTime::parse_from_rfc3339(&row.created_at)
                 .unwrap()
                 .with_timezone(&chrono::Utc),
@@ -1,6 +1,8 @@
 use anyhow::Result;
 use chrono::Utc;
-use ployer_core::models::deployment::{HealthCheck, HealthCheckResult, HealthCheckStatus};
+use ployer_core::models::deployment::{
+    HealthCheck, HealthCheckConfig, HealthCheckResult, HealthCheckStatus, HealthCheckType,
+};
 use sqlx::SqlitePool;
 use uuid::Uuid;
 
@@ -14,15 +16,19 @@ impl HealthCheckRepository {
     }
 
     /// Create or update health check configuration for an application
-    pub async fn upsert(
-        &self,
-        application_id: &str,
-        path: &str,
-        interval_seconds: i32,
-        timeout_seconds: i32,
-        healthy_threshold: i32,
-        unhealthy_threshold: i32,
-    ) -> Result<HealthCheck> {
+    pub async fn upsert(&self, application_id: &str, config: &HealthCheckConfig) -> Result<HealthCheck> {
+        let check_type = config.check_type.as_str();
+        let HealthCheckConfig {
+            path,
+            method,
+            expected_status,
+            interval_seconds,
+            timeout_seconds,
+            healthy_threshold,
+            unhealthy_threshold,
+            ..
+        } = config;
+
         // Check if health check exists
         let existing = self.get(application_id).await?;
 
@@ -31,11 +37,14 @@ impl HealthCheckRepository {
             sqlx::query!(
                 r#"
                 UPDATE health_checks
-                SET path = ?, interval_seconds = ?, timeout_seconds = ?,
-                    healthy_threshold = ?, unhealthy_threshold = ?
+                SET check_type = ?, path = ?, method = ?, expected_status = ?, interval_seconds = ?,
+                    timeout_seconds = ?, healthy_threshold = ?, unhealthy_threshold = ?
                 WHERE application_id = ?
                 "#,
+                check_type,
                 path,
+                method,
+                expected_status,
                 interval_seconds,
                 timeout_seconds,
                 healthy_threshold,
@@ -56,14 +65,17 @@ impl HealthCheckRepository {
             sqlx::query!(
                 r#"
                 INSERT INTO health_checks (
-                    id, application_id, path, interval_seconds, timeout_seconds,
-                    healthy_threshold, unhealthy_threshold, created_at
+                    id, application_id, check_type, path, method, expected_status,
+                    interval_seconds, timeout_seconds, healthy_threshold, unhealthy_threshold, created_at
                 )
-                VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+                VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
                 "#,
                 id,
                 application_id,
+                check_type,
                 path,
+                method,
+                expected_status,
                 interval_seconds,
                 timeout_seconds,
                 healthy_threshold,
@@ -83,8 +95,8 @@ impl HealthCheckRepository {
     pub async fn get(&self, application_id: &str) -> Result<Option<HealthCheck>> {
         let row = sqlx::query!(
             r#"
-            SELECT id, application_id, path, interval_seconds, timeout_seconds,
-                   healthy_threshold, unhealthy_threshold, created_at
+            SELECT id, application_id, check_type, path, method, expected_status, interval_seconds,
+                   timeout_seconds, healthy_threshold, unhealthy_threshold, created_at
             FROM health_checks
             WHERE application_id = ?
             "#,
@@ -96,7 +108,10 @@ impl HealthCheckRepository {
         Ok(row.map(|r| HealthCheck {
             id: r.id,
             application_id: r.application_id,
+            check_type: HealthCheckType::parse_or_default(&r.check_type),
             path: r.path,
+            method: r.method,
+            expected_status: r.expected_status,
             interval_seconds: r.interval_seconds as i32,
             timeout_seconds: r.timeout_seconds as i32,
             healthy_threshold: r.healthy_threshold as i32,
@@ -105,13 +120,14 @@ impl HealthCheckRepository {
         }))
     }
 
-    /// List all health checks
-    pub async fn list(&self) -> Result<Vec<HealthCheck>> {
+    /// Health checks of applications that aren't archived
+    pub async fn list_active(&self) -> Result<Vec<HealthCheck>> {
         let rows = sqlx::query!(
             r#"
-            SELECT id, application_id, path, interval_seconds, timeout_seconds,
-                   healthy_threshold, unhealthy_threshold, created_at
+            SELECT id, application_id, check_type, path, method, expected_status, interval_seconds,
+                   timeout_seconds, healthy_threshold, unhealthy_threshold, created_at
             FROM health_checks
+            WHERE application_id IN (SELECT id FROM applications WHERE archived_at IS NULL)
             ORDER BY created_at DESC
             "#
         )
@@ -123,7 +139,10 @@ impl HealthCheckRepository {
             .map(|r| HealthCheck {
                 id: r.id,
                 application_id: r.application_id,
+                check_type: HealthCheckType::parse_or_default(&r.check_type),
                 path: r.path,
+                method: r.method,
+                expected_status: r.expected_status,
                 interval_seconds: r.interval_seconds as i32,
                 timeout_seconds: r.timeout_seconds as i32,
                 healthy_threshold: r.healthy_threshold as i32,
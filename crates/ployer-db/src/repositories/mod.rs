@@ -3,23 +3,31 @@ pub mod api_key;
 pub mod server;
 pub mod application;
 pub mod env_var;
+pub mod volume;
 pub mod deploy_key;
+pub mod registry;
 pub mod deployment;
 pub mod domain;
 pub mod webhook;
 pub mod health_check;
 pub mod container_stats;
 pub mod settings;
+pub mod refresh_token;
+pub mod audit;
 
 pub use user::UserRepository;
 pub use api_key::ApiKeyRepository;
 pub use server::ServerRepository;
-pub use application::ApplicationRepository;
+pub use application::{ApplicationFilter, ApplicationRepository, NewApplication};
 pub use env_var::EnvVarRepository;
+pub use volume::VolumeRepository;
 pub use deploy_key::DeployKeyRepository;
+pub use registry::RegistryRepository;
 pub use deployment::DeploymentRepository;
 pub use domain::DomainRepository;
-pub use webhook::WebhookRepository;
+pub use webhook::{DeliveryCursor, WebhookRepository};
 pub use health_check::HealthCheckRepository;
 pub use container_stats::ContainerStatsRepository;
 pub use settings::SettingsRepository;
+pub use refresh_token::RefreshTokenRepository;
+pub use audit::{AuditFilter, AuditRepository};
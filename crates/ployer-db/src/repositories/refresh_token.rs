@@ -0,0 +1,102 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use ployer_core::models::RefreshToken;
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+pub struct RefreshTokenRepository {
+    pool: SqlitePool,
+}
+
+impl RefreshTokenRepository {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn create(&self, user_id: &str, token_hash: &str, expires_at: DateTime<Utc>) -> Result<RefreshToken> {
+        let id = Uuid::new_v4().to_string();
+        let now = chrono::Utc::now().to_rfc3339();
+
+        sqlx::query(
+            "INSERT INTO refresh_tokens (id, user_id, token_hash, expires_at, created_at)
+             VALUES (?, ?, ?, ?, ?)"
+        )
+        .bind(&id)
+        .bind(user_id)
+        .bind(token_hash)
+        .bind(expires_at.to_rfc3339())
+        .bind(&now)
+        .execute(&self.pool)
+        .await?;
+
+        self.find_by_hash(token_hash).await?
+            .ok_or_else(|| anyhow::anyhow!("Failed to retrieve created refresh token"))
+    }
+
+    pub async fn find_by_hash(&self, token_hash: &str) -> Result<Option<RefreshToken>> {
+        let row = sqlx::query_as::<_, RefreshTokenRow>(
+            "SELECT id, user_id, token_hash, expires_at, revoked_at, created_at FROM refresh_tokens WHERE token_hash = ?"
+        )
+        .bind(token_hash)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row.map(|r| r.into()))
+    }
+
+    /// Revoke a token. Returns false if it was already revoked.
+    pub async fn revoke(&self, id: &str) -> Result<bool> {
+        let now = chrono::Utc::now().to_rfc3339();
+
+        let result = sqlx::query("UPDATE refresh_tokens SET revoked_at = ? WHERE id = ? AND revoked_at IS NULL")
+            .bind(&now)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn revoke_all_for_user(&self, user_id: &str) -> Result<()> {
+        let now = chrono::Utc::now().to_rfc3339();
+
+        sqlx::query("UPDATE refresh_tokens SET revoked_at = ? WHERE user_id = ? AND revoked_at IS NULL")
+            .bind(&now)
+            .bind(user_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct RefreshTokenRow {
+    id: String,
+    user_id: String,
+    token_hash: String,
+    expires_at: String,
+    revoked_at: Option<String>,
+    created_at: String,
+}
+
+impl From<RefreshTokenRow> for RefreshToken {
+    fn from(row: RefreshTokenRow) -> Self {
+        RefreshToken {
+            id: row.id,
+            user_id: row.user_id,
+            token_hash: row.token_hash,
+            expires_at: chrono::DateTime::parse_from_rfc3339(&row.expires_at)
+                .unwrap()
+                .with_timezone(&chrono::Utc),
+            revoked_at: row.revoked_at.and_then(|s| {
+                chrono::DateTime::parse_from_rfc3339(&s)
+                    .ok()
+                    .map(|dt| dt.with_timezone(&chrono::Utc))
+            }),
+            created_at: chrono::DateTime::parse_from_rfc3339(&row.created_at)
+                .unwrap()
+                .with_timezone(&chrono::Utc),
+        }
+    }
+}
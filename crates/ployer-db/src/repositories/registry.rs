@@ -0,0 +1,93 @@
+use anyhow::Result;
+use ployer_core::models::ApplicationRegistry;
+use sqlx::SqlitePool;
+
+pub struct RegistryRepository {
+    pool: SqlitePool,
+}
+
+impl RegistryRepository {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Create or replace an application's push target
+    pub async fn upsert(
+        &self,
+        application_id: &str,
+        image: &str,
+        username: Option<&str>,
+        password_encrypted: Option<&str>,
+    ) -> Result<ApplicationRegistry> {
+        let now = chrono::Utc::now().to_rfc3339();
+
+        sqlx::query(
+            "INSERT INTO application_registries (application_id, image, username, password_encrypted, created_at, updated_at)
+             VALUES (?, ?, ?, ?, ?, ?)
+             ON CONFLICT(application_id) DO UPDATE SET
+                image = excluded.image,
+                username = excluded.username,
+                password_encrypted = excluded.password_encrypted,
+                updated_at = excluded.updated_at"
+        )
+        .bind(application_id)
+        .bind(image)
+        .bind(username)
+        .bind(password_encrypted)
+        .bind(&now)
+        .bind(&now)
+        .execute(&self.pool)
+        .await?;
+
+        self.find_by_application(application_id).await?
+            .ok_or_else(|| anyhow::anyhow!("Failed to retrieve saved registry"))
+    }
+
+    pub async fn find_by_application(&self, application_id: &str) -> Result<Option<ApplicationRegistry>> {
+        let row = sqlx::query_as::<_, RegistryRow>(
+            "SELECT application_id, image, username, password_encrypted, created_at, updated_at
+             FROM application_registries WHERE application_id = ?"
+        )
+        .bind(application_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row.map(|r| r.into()))
+    }
+
+    pub async fn delete(&self, application_id: &str) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM application_registries WHERE application_id = ?")
+            .bind(application_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct RegistryRow {
+    application_id: String,
+    image: String,
+    username: Option<String>,
+    password_encrypted: Option<String>,
+    created_at: String,
+    updated_at: String,
+}
+
+impl From<RegistryRow> for ApplicationRegistry {
+    fn from(row: RegistryRow) -> Self {
+        ApplicationRegistry {
+            application_id: row.application_id,
+            image: row.image,
+            username: row.username,
+            password_encrypted: row.password_encrypted,
+            created_at: chrono::DateTime::parse_from_rfc3339(&row.created_at)
+                .unwrap()
+                .with_timezone(&chrono::Utc),
+            updated_at: chrono::DateTime::parse_from_rfc3339(&row.updated_at)
+                .unwrap()
+                .with_timezone(&chrono::Utc),
+        }
+    }
+}
@@ -1,6 +1,7 @@
 use anyhow::Result;
 use ployer_core::models::{Server, ServerStatus};
 use sqlx::SqlitePool;
+use std::collections::BTreeMap;
 use uuid::Uuid;
 
 pub struct ServerRepository {
@@ -49,7 +50,7 @@ impl ServerRepository {
 
     pub async fn find_by_id(&self, id: &str) -> Result<Option<Server>> {
         let row = sqlx::query_as::<_, ServerRow>(
-            "SELECT id, name, host, port, username, ssh_key_encrypted, is_local, status, last_seen_at, created_at, updated_at
+            "SELECT id, name, host, port, username, ssh_key_encrypted, is_local, platform, tags, status, last_seen_at, created_at, updated_at
              FROM servers WHERE id = ?"
         )
         .bind(id)
@@ -61,7 +62,7 @@ impl ServerRepository {
 
     pub async fn list(&self) -> Result<Vec<Server>> {
         let rows = sqlx::query_as::<_, ServerRow>(
-            "SELECT id, name, host, port, username, ssh_key_encrypted, is_local, status, last_seen_at, created_at, updated_at
+            "SELECT id, name, host, port, username, ssh_key_encrypted, is_local, platform, tags, status, last_seen_at, created_at, updated_at
              FROM servers ORDER BY created_at DESC"
         )
         .fetch_all(&self.pool)
@@ -70,6 +71,23 @@ impl ServerRepository {
         Ok(rows.into_iter().map(|r| r.into()).collect())
     }
 
+    /// Servers carrying tag `key`, and when `value` is given, with that value
+    pub async fn list_by_tag(&self, key: &str, value: Option<&str>) -> Result<Vec<Server>> {
+        let rows = sqlx::query_as::<_, ServerRow>(
+            "SELECT id, name, host, port, username, ssh_key_encrypted, is_local, platform, tags, status, last_seen_at, created_at, updated_at
+             FROM servers
+             WHERE EXISTS (SELECT 1 FROM json_each(servers.tags) WHERE json_each.key = ? AND (? IS NULL OR json_each.value = ?))
+             ORDER BY created_at DESC"
+        )
+        .bind(key)
+        .bind(value)
+        .bind(value)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(|r| r.into()).collect())
+    }
+
     pub async fn update(
         &self,
         id: &str,
@@ -103,6 +121,31 @@ impl ServerRepository {
             .ok_or_else(|| anyhow::anyhow!("Server not found"))
     }
 
+    pub async fn set_platform(&self, id: &str, platform: Option<&str>) -> Result<()> {
+        let now = chrono::Utc::now().to_rfc3339();
+        sqlx::query("UPDATE servers SET platform = ?, updated_at = ? WHERE id = ?")
+            .bind(platform)
+            .bind(&now)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn set_tags(&self, id: &str, tags: &BTreeMap<String, String>) -> Result<()> {
+        let now = chrono::Utc::now().to_rfc3339();
+        let tags_json = serde_json::to_string(tags)?;
+        sqlx::query("UPDATE servers SET tags = ?, updated_at = ? WHERE id = ?")
+            .bind(&tags_json)
+            .bind(&now)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
     pub async fn update_status(&self, id: &str, status: ServerStatus, last_seen_at: chrono::DateTime<chrono::Utc>) -> Result<()> {
         let status_str = status.as_str();
         let last_seen_str = last_seen_at.to_rfc3339();
@@ -132,7 +175,7 @@ impl ServerRepository {
 
     pub async fn find_local(&self) -> Result<Option<Server>> {
         let row = sqlx::query_as::<_, ServerRow>(
-            "SELECT id, name, host, port, username, ssh_key_encrypted, is_local, status, last_seen_at, created_at, updated_at
+            "SELECT id, name, host, port, username, ssh_key_encrypted, is_local, platform, tags, status, last_seen_at, created_at, updated_at
              FROM servers WHERE is_local = 1 LIMIT 1"
         )
         .fetch_optional(&self.pool)
@@ -151,6 +194,8 @@ struct ServerRow {
     username: String,
     ssh_key_encrypted: Option<String>,
     is_local: i64,
+    platform: Option<String>,
+    tags: String,
     status: String,
     last_seen_at: Option<String>,
     created_at: String,
@@ -167,6 +212,8 @@ impl From<ServerRow> for Server {
             username: row.username,
             ssh_key_encrypted: row.ssh_key_encrypted,
             is_local: row.is_local != 0,
+            platform: row.platform,
+            tags: serde_json::from_str(&row.tags).unwrap_or_default(),
             status: ServerStatus::from_str(&row.status),
             last_seen_at: row.last_seen_at.and_then(|s| {
                 chrono::DateTime::parse_from_rfc3339(&s)
@@ -182,3 +229,30 @@ impl From<ServerRow> for Server {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_list_by_tag_matches_key_and_value() {
+        let pool = crate::memory_pool().await.unwrap();
+
+        let repo = ServerRepository::new(pool);
+        for (name, region) in [("eu-1", Some("eu")), ("us-1", Some("us")), ("bare", None)] {
+            let server = repo.create(name, "10.0.0.1", 22, "root", None, false).await.unwrap();
+            assert!(server.tags.is_empty());
+            if let Some(region) = region {
+                let tags = BTreeMap::from([("region".to_string(), region.to_string())]);
+                repo.set_tags(&server.id, &tags).await.unwrap();
+            }
+        }
+
+        let servers = repo.list_by_tag("region", Some("eu")).await.unwrap();
+        assert_eq!(servers.len(), 1);
+        assert_eq!(servers[0].name, "eu-1");
+        assert_eq!(servers[0].tags["region"], "eu");
+        assert_eq!(repo.list_by_tag("region", None).await.unwrap().len(), 2);
+        assert!(repo.list_by_tag("env", None).await.unwrap().is_empty());
+    }
+}
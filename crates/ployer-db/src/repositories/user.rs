@@ -65,6 +65,15 @@ impl UserRepository {
         Ok(count)
     }
 
+    pub async fn count_admins(&self) -> Result<i64> {
+        let (count,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM users WHERE role = ?")
+            .bind(UserRole::Admin.as_str())
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(count)
+    }
+
     pub async fn update_password(&self, id: &str, password_hash: &str) -> Result<()> {
         let now = chrono::Utc::now().to_rfc3339();
         sqlx::query(
@@ -0,0 +1,97 @@
+use anyhow::Result;
+use ployer_core::models::ApplicationVolume;
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+pub struct VolumeRepository {
+    pool: SqlitePool,
+}
+
+impl VolumeRepository {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn create(
+        &self,
+        application_id: &str,
+        volume_name: &str,
+        container_path: &str,
+    ) -> Result<ApplicationVolume> {
+        let id = Uuid::new_v4().to_string();
+        let now = chrono::Utc::now().to_rfc3339();
+
+        sqlx::query(
+            "INSERT INTO application_volumes (id, application_id, volume_name, container_path, created_at)
+             VALUES (?, ?, ?, ?, ?)"
+        )
+        .bind(&id)
+        .bind(application_id)
+        .bind(volume_name)
+        .bind(container_path)
+        .bind(&now)
+        .execute(&self.pool)
+        .await?;
+
+        self.find_by_id(&id).await?
+            .ok_or_else(|| anyhow::anyhow!("Failed to retrieve created volume"))
+    }
+
+    pub async fn find_by_id(&self, id: &str) -> Result<Option<ApplicationVolume>> {
+        let row = sqlx::query_as::<_, VolumeRow>(
+            "SELECT id, application_id, volume_name, container_path, created_at
+             FROM application_volumes WHERE id = ?"
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row.map(|r| r.into()))
+    }
+
+    pub async fn list_by_application(&self, application_id: &str) -> Result<Vec<ApplicationVolume>> {
+        let rows = sqlx::query_as::<_, VolumeRow>(
+            "SELECT id, application_id, volume_name, container_path, created_at
+             FROM application_volumes WHERE application_id = ? ORDER BY container_path ASC"
+        )
+        .bind(application_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(|r| r.into()).collect())
+    }
+
+    /// Returns false if no volume with this id belongs to the application
+    pub async fn delete(&self, application_id: &str, id: &str) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM application_volumes WHERE application_id = ? AND id = ?")
+            .bind(application_id)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct VolumeRow {
+    id: String,
+    application_id: String,
+    volume_name: String,
+    container_path: String,
+    created_at: String,
+}
+
+impl From<VolumeRow> for ApplicationVolume {
+    fn from(row: VolumeRow) -> Self {
+        ApplicationVolume {
+            id: row.id,
+            application_id: row.application_id,
+            volume_name: row.volume_name,
+            container_path: row.container_path,
+            created_at: chrono::DateTime::parse_from_rfc3339(&row.created_at)
+                .unwrap()
+                .with_timezone(&chrono::Utc),
+        }
+    }
+}
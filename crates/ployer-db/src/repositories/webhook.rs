@@ -1,6 +1,10 @@
 use anyhow::Result;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
 use chrono::Utc;
-use ployer_core::models::{Webhook, WebhookProvider, WebhookDelivery, WebhookDeliveryStatus};
+use ployer_core::models::{
+    CommitAuthor, Webhook, WebhookDelivery, WebhookDeliveryStatus, WebhookProvider,
+};
 use sqlx::SqlitePool;
 use uuid::Uuid;
 
@@ -8,6 +12,37 @@ pub struct WebhookRepository {
     pool: SqlitePool,
 }
 
+/// Position in the newest-first delivery history: everything strictly older
+/// than this delivery. Encodes to an opaque URL-safe string for clients.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeliveryCursor {
+    pub delivered_at: String,
+    pub id: String,
+}
+
+impl DeliveryCursor {
+    pub fn after(delivery: &WebhookDelivery) -> Self {
+        Self {
+            delivered_at: delivery.delivered_at.to_rfc3339(),
+            id: delivery.id.clone(),
+        }
+    }
+
+    pub fn encode(&self) -> String {
+        URL_SAFE_NO_PAD.encode(format!("{}|{}", self.delivered_at, self.id))
+    }
+
+    pub fn decode(cursor: &str) -> Option<Self> {
+        let raw = String::from_utf8(URL_SAFE_NO_PAD.decode(cursor).ok()?).ok()?;
+        let (delivered_at, id) = raw.split_once('|')?;
+        chrono::DateTime::parse_from_rfc3339(delivered_at).ok()?;
+        Some(Self {
+            delivered_at: delivered_at.to_string(),
+            id: id.to_string(),
+        })
+    }
+}
+
 impl WebhookRepository {
     pub fn new(pool: SqlitePool) -> Self {
         Self { pool }
@@ -127,7 +162,7 @@ impl WebhookRepository {
         branch: Option<&str>,
         commit_sha: Option<&str>,
         commit_message: Option<&str>,
-        author: Option<&str>,
+        author: Option<&CommitAuthor>,
         status: WebhookDeliveryStatus,
         response_code: Option<i32>,
         error_message: Option<&str>,
@@ -138,15 +173,21 @@ impl WebhookRepository {
         let now_str = now.to_rfc3339();
         let provider_str = provider.as_str();
         let status_str = status.as_str();
+        let author_name = author.map(|a| a.name.clone());
+        let author_email = author.and_then(|a| a.email.clone());
+        let committer_name = author.and_then(|a| a.committer_name.clone());
+        let committer_email = author.and_then(|a| a.committer_email.clone());
+        let avatar_url = author.and_then(|a| a.avatar_url.clone());
 
         sqlx::query!(
             r#"
             INSERT INTO webhook_deliveries (
                 id, webhook_id, application_id, provider, event_type,
                 branch, commit_sha, commit_message, author,
+                author_email, committer_name, committer_email, avatar_url,
                 status, response_code, error_message, deployment_id, delivered_at
             )
-            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
             "#,
             id,
             webhook_id,
@@ -156,7 +197,11 @@ impl WebhookRepository {
             branch,
             commit_sha,
             commit_message,
-            author,
+            author_name,
+            author_email,
+            committer_name,
+            committer_email,
+            avatar_url,
             status_str,
             response_code,
             error_message,
@@ -175,28 +220,48 @@ impl WebhookRepository {
             branch: branch.map(|s| s.to_string()),
             commit_sha: commit_sha.map(|s| s.to_string()),
             commit_message: commit_message.map(|s| s.to_string()),
-            author: author.map(|s| s.to_string()),
+            author: author_name,
+            author_email,
+            committer_name,
+            committer_email,
+            avatar_url,
             status,
             response_code,
             error_message: error_message.map(|s| s.to_string()),
             deployment_id: deployment_id.map(|s| s.to_string()),
+            attempts: 1,
+            last_attempt_at: None,
             delivered_at: now,
         })
     }
 
-    /// List webhook deliveries for an application
-    pub async fn list_deliveries(&self, application_id: &str, limit: i64) -> Result<Vec<WebhookDelivery>> {
+    /// List webhook deliveries for an application, newest first, starting
+    /// after `before` when given
+    pub async fn list_deliveries(
+        &self,
+        application_id: &str,
+        limit: i64,
+        before: Option<&DeliveryCursor>,
+    ) -> Result<Vec<WebhookDelivery>> {
+        let before_at = before.map(|c| c.delivered_at.as_str());
+        let before_id = before.map(|c| c.id.as_str());
+
         let rows = sqlx::query!(
             r#"
             SELECT id, webhook_id, application_id, provider, event_type,
                    branch, commit_sha, commit_message, author,
-                   status, response_code, error_message, deployment_id, delivered_at
+                   author_email, committer_name, committer_email, avatar_url,
+                   status, response_code, error_message, deployment_id,
+                   attempts, last_attempt_at, delivered_at
             FROM webhook_deliveries
-            WHERE application_id = ?
-            ORDER BY delivered_at DESC
-            LIMIT ?
+            WHERE application_id = ?1
+              AND (?2 IS NULL OR delivered_at < ?2 OR (delivered_at = ?2 AND id < ?3))
+            ORDER BY delivered_at DESC, id DESC
+            LIMIT ?4
             "#,
             application_id,
+            before_at,
+            before_id,
             limit
         )
         .fetch_all(&self.pool)
@@ -214,12 +279,251 @@ impl WebhookRepository {
                 commit_sha: r.commit_sha,
                 commit_message: r.commit_message,
                 author: r.author,
+                author_email: r.author_email,
+                committer_name: r.committer_name,
+                committer_email: r.committer_email,
+                avatar_url: r.avatar_url,
+                status: WebhookDeliveryStatus::from_str(&r.status),
+                response_code: r.response_code.map(|c| c as i32),
+                error_message: r.error_message,
+                deployment_id: r.deployment_id,
+                attempts: r.attempts as i32,
+                last_attempt_at: r.last_attempt_at.and_then(|t| t.parse().ok()),
+                delivered_at: r.delivered_at.parse().unwrap(),
+            })
+            .collect())
+    }
+
+    /// List failed deliveries that are still eligible for a retry
+    pub async fn list_failed_for_retry(
+        &self,
+        max_age: chrono::Duration,
+        max_attempts: i32,
+    ) -> Result<Vec<WebhookDelivery>> {
+        let cutoff = (Utc::now() - max_age).to_rfc3339();
+
+        let rows = sqlx::query!(
+            r#"
+            SELECT id, webhook_id, application_id, provider, event_type,
+                   branch, commit_sha, commit_message, author,
+                   author_email, committer_name, committer_email, avatar_url,
+                   status, response_code, error_message, deployment_id,
+                   attempts, last_attempt_at, delivered_at
+            FROM webhook_deliveries
+            WHERE status = 'failed' AND delivered_at >= ? AND attempts < ?
+            ORDER BY delivered_at ASC
+            "#,
+            cutoff,
+            max_attempts
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|r| WebhookDelivery {
+                id: r.id,
+                webhook_id: r.webhook_id,
+                application_id: r.application_id,
+                provider: WebhookProvider::from_str(&r.provider),
+                event_type: r.event_type,
+                branch: r.branch,
+                commit_sha: r.commit_sha,
+                commit_message: r.commit_message,
+                author: r.author,
+                author_email: r.author_email,
+                committer_name: r.committer_name,
+                committer_email: r.committer_email,
+                avatar_url: r.avatar_url,
                 status: WebhookDeliveryStatus::from_str(&r.status),
                 response_code: r.response_code.map(|c| c as i32),
                 error_message: r.error_message,
                 deployment_id: r.deployment_id,
+                attempts: r.attempts as i32,
+                last_attempt_at: r.last_attempt_at.and_then(|t| t.parse().ok()),
                 delivered_at: r.delivered_at.parse().unwrap(),
             })
             .collect())
     }
+
+    /// Record the outcome of a delivery retry and bump its attempt counter
+    pub async fn record_retry(
+        &self,
+        id: &str,
+        status: WebhookDeliveryStatus,
+        error_message: Option<&str>,
+        deployment_id: Option<&str>,
+    ) -> Result<()> {
+        let now = Utc::now().to_rfc3339();
+        let status_str = status.as_str();
+
+        sqlx::query!(
+            r#"
+            UPDATE webhook_deliveries
+            SET status = ?, error_message = ?, deployment_id = COALESCE(?, deployment_id),
+                attempts = attempts + 1, last_attempt_at = ?
+            WHERE id = ?
+            "#,
+            status_str,
+            error_message,
+            deployment_id,
+            now,
+            id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Settle pending deliveries whose deployment has finished. A deployment
+    /// that went live counts as success, even if a newer one replaced it since.
+    /// A failed one fails the delivery, so the retrier picks it up again, and a
+    /// cancelled one skips it. Returns how many deliveries were settled.
+    pub async fn resolve_pending(&self) -> Result<u64> {
+        let result = sqlx::query!(
+            r#"
+            UPDATE webhook_deliveries
+            SET status = CASE d.status
+                    WHEN 'running' THEN 'success'
+                    WHEN 'rolled_back' THEN 'success'
+                    WHEN 'cancelled' THEN 'skipped'
+                    ELSE 'failed'
+                END,
+                error_message = CASE d.status
+                    WHEN 'running' THEN NULL
+                    WHEN 'rolled_back' THEN NULL
+                    WHEN 'cancelled' THEN 'Deployment was cancelled'
+                    WHEN 'failed' THEN 'Deployment failed'
+                    ELSE 'Deployment no longer exists'
+                END
+            FROM (
+                SELECT w.id AS delivery_id, deployments.status AS status
+                FROM webhook_deliveries w
+                LEFT JOIN deployments ON deployments.id = w.deployment_id
+                WHERE w.status = 'pending'
+            ) AS d
+            WHERE webhook_deliveries.id = d.delivery_id
+              AND (d.status IS NULL OR d.status IN ('running', 'rolled_back', 'cancelled', 'failed'))
+            "#
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Application `a1` with webhook `w1`
+    async fn seeded_pool() -> SqlitePool {
+        let pool = crate::memory_pool().await.unwrap();
+        for statement in [
+            "INSERT INTO servers (id, name, host, created_at, updated_at) VALUES ('s1', 's1', 'localhost', '2024-01-01T00:00:00Z', '2024-01-01T00:00:00Z')",
+            "INSERT INTO applications (id, name, server_id, created_at, updated_at) VALUES ('a1', 'web', 's1', '2024-01-01T00:00:00Z', '2024-01-01T00:00:00Z')",
+            "INSERT INTO webhooks (id, application_id, provider, secret, created_at, updated_at) VALUES ('w1', 'a1', 'github', 'secret', '2024-01-01T00:00:00Z', '2024-01-01T00:00:00Z')",
+        ] {
+            sqlx::query(statement).execute(&pool).await.unwrap();
+        }
+        pool
+    }
+
+    #[tokio::test]
+    async fn test_list_deliveries_pages_by_cursor() {
+        let pool = seeded_pool().await;
+
+        let repo = WebhookRepository::new(pool.clone());
+        for _ in 0..5 {
+            repo.create_delivery(
+                "w1", "a1", WebhookProvider::GitHub, "push", Some("main"), None, None, None,
+                WebhookDeliveryStatus::Success, Some(200), None, None,
+            )
+            .await
+            .unwrap();
+        }
+        // Deliveries in the same instant are ordered by id
+        sqlx::query("UPDATE webhook_deliveries SET delivered_at = '2026-01-01T00:00:00+00:00'")
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let newest_first: Vec<String> = repo
+            .list_deliveries("a1", 50, None)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|d| d.id)
+            .collect();
+        assert_eq!(newest_first.len(), 5);
+
+        let mut paged = Vec::new();
+        let mut before = None;
+        loop {
+            let page = repo.list_deliveries("a1", 2, before.as_ref()).await.unwrap();
+            let Some(last) = page.last() else { break };
+            before = Some(DeliveryCursor::after(last));
+            paged.extend(page.into_iter().map(|d| d.id));
+        }
+        assert_eq!(paged, newest_first);
+
+        let cursor = before.unwrap();
+        assert_eq!(DeliveryCursor::decode(&cursor.encode()), Some(cursor));
+        assert_eq!(DeliveryCursor::decode("not-a-cursor"), None);
+    }
+
+    #[tokio::test]
+    async fn test_resolve_pending_follows_the_deployment() {
+        let pool = seeded_pool().await;
+        let repo = WebhookRepository::new(pool.clone());
+
+        let mut deliveries = Vec::new();
+        for (deployment_id, status) in [
+            ("d-live", Some("running")),
+            ("d-building", Some("building")),
+            ("d-failed", Some("failed")),
+            ("d-cancelled", Some("cancelled")),
+            ("d-deleted", None),
+        ] {
+            if let Some(status) = status {
+                sqlx::query("INSERT INTO deployments (id, application_id, server_id, status, image_tag, started_at) VALUES (?, 'a1', 's1', ?, 'img', '2024-01-01T00:00:00Z')")
+                    .bind(deployment_id)
+                    .bind(status)
+                    .execute(&pool)
+                    .await
+                    .unwrap();
+            }
+            let delivery = repo
+                .create_delivery(
+                    "w1", "a1", WebhookProvider::GitHub, "push", Some("main"), None, None, None,
+                    WebhookDeliveryStatus::Pending, Some(200), None, Some(deployment_id),
+                )
+                .await
+                .unwrap();
+            deliveries.push(delivery.id);
+        }
+
+        assert_eq!(repo.resolve_pending().await.unwrap(), 4);
+
+        let mut statuses: Vec<(String, Option<String>)> = Vec::new();
+        for id in &deliveries {
+            let row = sqlx::query_as("SELECT status, error_message FROM webhook_deliveries WHERE id = ?")
+                .bind(id)
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+            statuses.push(row);
+        }
+        assert_eq!(statuses[0], ("success".to_string(), None));
+        assert_eq!(statuses[1], ("pending".to_string(), None));
+        assert_eq!(statuses[2], ("failed".to_string(), Some("Deployment failed".to_string())));
+        assert_eq!(statuses[3].0, "skipped");
+        assert_eq!(statuses[4].0, "failed");
+
+        // The failed delivery is now up for another attempt
+        let retryable = repo.list_failed_for_retry(chrono::Duration::hours(24), 5).await.unwrap();
+        assert_eq!(retryable.len(), 2);
+    }
 }
@@ -0,0 +1,100 @@
+use std::future::Future;
+use std::time::Duration;
+use tracing::debug;
+
+/// Attempts made by `retry_on_busy` before the error is returned
+const MAX_ATTEMPTS: u32 = 5;
+
+/// Delay before the first retry; doubled after each one
+const INITIAL_BACKOFF: Duration = Duration::from_millis(20);
+
+/// Whether SQLite refused the query because another connection held the write lock.
+/// The pool's busy timeout covers most contention, but a writer can still get
+/// `SQLITE_BUSY` straight away when a WAL read transaction has to be upgraded.
+pub(crate) fn is_busy(err: &sqlx::Error) -> bool {
+    let sqlx::Error::Database(db_err) = err else {
+        return false;
+    };
+    // Extended codes such as SQLITE_BUSY_SNAPSHOT (517) keep the primary code in the low byte
+    match db_err.code().and_then(|code| code.parse::<i32>().ok()) {
+        Some(code) => matches!(code & 0xff, 5 | 6),
+        None => db_err.message().contains("database is locked"),
+    }
+}
+
+/// Run a write, retrying with exponential backoff while the database is locked
+pub(crate) async fn retry_on_busy<T, F, Fut>(mut op: F) -> Result<T, sqlx::Error>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, sqlx::Error>>,
+{
+    let mut backoff = INITIAL_BACKOFF;
+    let mut attempt = 1;
+    loop {
+        match op().await {
+            Err(e) if attempt < MAX_ATTEMPTS && is_busy(&e) => {
+                debug!("Database busy (attempt {}/{}), retrying in {:?}", attempt, MAX_ATTEMPTS, backoff);
+                tokio::time::sleep(backoff).await;
+                backoff *= 2;
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    #[tokio::test]
+    async fn test_retry_gives_up_on_other_errors() {
+        let calls = AtomicU32::new(0);
+        let result: Result<(), _> = retry_on_busy(|| async {
+            calls.fetch_add(1, Ordering::SeqCst);
+            Err(sqlx::Error::RowNotFound)
+        })
+        .await;
+
+        assert!(matches!(result, Err(sqlx::Error::RowNotFound)));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert!(!is_busy(&sqlx::Error::PoolTimedOut));
+    }
+
+    #[tokio::test]
+    async fn test_retry_waits_out_a_held_write_lock() {
+        use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+        use sqlx::{ConnectOptions, Connection};
+
+        let path = std::env::temp_dir().join(format!("ployer-retry-{}.db", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let options = SqliteConnectOptions::new().filename(&path).create_if_missing(true);
+
+        let mut holder = options.clone().connect().await.unwrap();
+        sqlx::query("CREATE TABLE t (v INTEGER)").execute(&mut holder).await.unwrap();
+        sqlx::query("BEGIN IMMEDIATE").execute(&mut holder).await.unwrap();
+
+        // No busy timeout, so the lock is reported immediately
+        let writer = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect_with(options.busy_timeout(Duration::ZERO))
+            .await
+            .unwrap();
+        let err = sqlx::query("INSERT INTO t VALUES (1)").execute(&writer).await.unwrap_err();
+        assert!(is_busy(&err));
+
+        let release = tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            sqlx::query("COMMIT").execute(&mut holder).await.unwrap();
+            holder.close().await.unwrap();
+        });
+        retry_on_busy(|| sqlx::query("INSERT INTO t VALUES (1)").execute(&writer))
+            .await
+            .unwrap();
+        release.await.unwrap();
+
+        writer.close().await;
+        std::fs::remove_file(&path).unwrap();
+    }
+}
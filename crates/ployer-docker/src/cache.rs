@@ -0,0 +1,88 @@
+use bollard::models::ContainerInspectResponse;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// How long an inspect result is reused before Docker is asked again
+pub const INSPECT_CACHE_TTL: Duration = Duration::from_secs(2);
+
+/// Recent `inspect_container` results, keyed by the id or name they were requested with
+pub(crate) struct InspectCache {
+    ttl: Duration,
+    entries: Mutex<HashMap<String, (Instant, ContainerInspectResponse)>>,
+}
+
+impl InspectCache {
+    pub(crate) fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub(crate) fn get(&self, id: &str) -> Option<ContainerInspectResponse> {
+        let mut entries = self.entries.lock().unwrap();
+        match entries.get(id) {
+            Some((fetched_at, inspect)) if fetched_at.elapsed() < self.ttl => Some(inspect.clone()),
+            Some(_) => {
+                entries.remove(id);
+                None
+            }
+            None => None,
+        }
+    }
+
+    pub(crate) fn insert(&self, id: &str, inspect: ContainerInspectResponse) {
+        let mut entries = self.entries.lock().unwrap();
+        // Expired entries would otherwise pile up for containers that are gone
+        entries.retain(|_, (fetched_at, _)| fetched_at.elapsed() < self.ttl);
+        entries.insert(id.to_string(), (Instant::now(), inspect));
+    }
+
+    /// Drop what's cached for a container, whether it was looked up by id or by name
+    pub(crate) fn invalidate(&self, id_or_name: &str) {
+        let name = format!("/{}", id_or_name.trim_start_matches('/'));
+        self.entries.lock().unwrap().retain(|key, (_, inspect)| {
+            key != id_or_name
+                && inspect.id.as_deref() != Some(id_or_name)
+                && inspect.name.as_deref() != Some(name.as_str())
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn inspect(id: &str, name: &str) -> ContainerInspectResponse {
+        ContainerInspectResponse {
+            id: Some(id.to_string()),
+            name: Some(format!("/{}", name)),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_entries_expire() {
+        let cache = InspectCache::new(Duration::from_millis(50));
+        cache.insert("abc", inspect("abc", "web"));
+        assert_eq!(cache.get("abc").unwrap().id.as_deref(), Some("abc"));
+
+        std::thread::sleep(Duration::from_millis(60));
+        assert!(cache.get("abc").is_none());
+    }
+
+    #[test]
+    fn test_invalidate_by_id_or_name() {
+        let cache = InspectCache::new(INSPECT_CACHE_TTL);
+        cache.insert("ployer-web", inspect("abc", "ployer-web"));
+        cache.insert("def", inspect("def", "ployer-api"));
+
+        // Looked up by name, invalidated by id
+        cache.invalidate("abc");
+        assert!(cache.get("ployer-web").is_none());
+
+        cache.invalidate("ployer-api");
+        assert!(cache.get("def").is_none());
+    }
+}
@@ -0,0 +1,195 @@
+use anyhow::Result;
+use std::path::Path;
+
+/// Patterns from a build context's `.dockerignore`, applied the way Docker does:
+/// the last matching pattern wins, `!` re-includes a path, and excluding a
+/// directory excludes everything under it
+#[derive(Debug, Default)]
+pub(crate) struct DockerIgnore {
+    rules: Vec<Rule>,
+}
+
+#[derive(Debug)]
+struct Rule {
+    segments: Vec<String>,
+    negate: bool,
+}
+
+impl DockerIgnore {
+    /// The context's `.dockerignore`, or no patterns when it has none
+    pub(crate) fn load(context_path: &Path) -> Result<Self> {
+        match std::fs::read_to_string(context_path.join(".dockerignore")) {
+            Ok(contents) => Ok(Self::parse(&contents)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub(crate) fn parse(contents: &str) -> Self {
+        let rules = contents
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .filter_map(|line| {
+                let (negate, pattern) = match line.strip_prefix('!') {
+                    Some(pattern) => (true, pattern.trim()),
+                    None => (false, line),
+                };
+                // Patterns are relative to the context root; `/foo`, `./foo` and `foo/` all mean `foo`
+                let segments: Vec<String> = pattern
+                    .split('/')
+                    .filter(|s| !s.is_empty() && *s != ".")
+                    .map(str::to_string)
+                    .collect();
+                (!segments.is_empty()).then_some(Rule { segments, negate })
+            })
+            .collect();
+
+        Self { rules }
+    }
+
+    /// Whether a `!` pattern could re-include something inside an excluded directory
+    pub(crate) fn has_exceptions(&self) -> bool {
+        self.rules.iter().any(|r| r.negate)
+    }
+
+    /// Whether a path, relative to the context root and `/`-separated, is left out
+    pub(crate) fn is_excluded(&self, rel_path: &str) -> bool {
+        let path: Vec<&str> = rel_path.split('/').filter(|s| !s.is_empty()).collect();
+
+        let mut excluded = false;
+        for rule in &self.rules {
+            let matched = (1..=path.len()).any(|len| match_segments(&rule.segments, &path[..len]));
+            if matched {
+                excluded = !rule.negate;
+            }
+        }
+        excluded
+    }
+}
+
+/// Match path segments against pattern segments, where `**` spans any number of directories
+fn match_segments(pattern: &[String], path: &[&str]) -> bool {
+    match pattern.split_first() {
+        None => path.is_empty(),
+        Some((first, rest)) if first == "**" => (0..=path.len()).any(|skip| match_segments(rest, &path[skip..])),
+        Some((first, rest)) => match path.split_first() {
+            Some((segment, path_rest)) => wildcard(first, segment) && match_segments(rest, path_rest),
+            None => false,
+        },
+    }
+}
+
+/// Shell-style match of one path segment: `*`, `?`, `[a-z]`, `[^a-z]` and `\` escapes
+fn wildcard(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    // Where to resume after the most recent `*` if the rest fails to match
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        let step = match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, t));
+                p += 1;
+                continue;
+            }
+            Some('?') => Some(p + 1),
+            Some('[') => match_class(&pattern, p, text[t]),
+            Some('\\') if p + 1 < pattern.len() => (pattern[p + 1] == text[t]).then_some(p + 2),
+            Some(&c) => (c == text[t]).then_some(p + 1),
+            None => None,
+        };
+
+        match (step, backtrack) {
+            (Some(next), _) => {
+                p = next;
+                t += 1;
+            }
+            (None, Some((star, start))) => {
+                // Let the `*` swallow one more character and try again
+                p = star + 1;
+                t = start + 1;
+                backtrack = Some((star, start + 1));
+            }
+            (None, None) => return false,
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// Match `c` against the character class opening at `pattern[open]`, returning
+/// the index just past the class if it matches
+fn match_class(pattern: &[char], open: usize, c: char) -> Option<usize> {
+    let mut i = open + 1;
+    let negated = matches!(pattern.get(i), Some('^') | Some('!'));
+    if negated {
+        i += 1;
+    }
+
+    let mut matched = false;
+    let mut first = true;
+    loop {
+        let lo = *pattern.get(i)?;
+        if lo == ']' && !first {
+            break;
+        }
+        first = false;
+        if pattern.get(i + 1) == Some(&'-') && pattern.get(i + 2).is_some_and(|&hi| hi != ']') {
+            matched |= lo <= c && c <= pattern[i + 2];
+            i += 3;
+        } else {
+            matched |= lo == c;
+            i += 1;
+        }
+    }
+
+    (matched != negated).then_some(i + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_patterns() {
+        let ignore = DockerIgnore::parse(
+            "# build output\n\
+             /target/\n\
+             *.log\n\
+             **/*.tmp\n\
+             docs/*\n\
+             !docs/README.md\n\
+             file[0-9].txt\n\
+             cache-?\n",
+        );
+
+        assert!(ignore.is_excluded("target"));
+        assert!(ignore.is_excluded("target/debug/app"));
+        assert!(ignore.is_excluded("debug.log"));
+        // `*` doesn't cross directories
+        assert!(!ignore.is_excluded("logs/debug.log"));
+        assert!(ignore.is_excluded("a/b/c.tmp"));
+        assert!(ignore.is_excluded("docs/guide.md"));
+        assert!(!ignore.is_excluded("docs/README.md"));
+        assert!(ignore.is_excluded("file7.txt"));
+        assert!(!ignore.is_excluded("fileX.txt"));
+        assert!(ignore.is_excluded("cache-a"));
+        assert!(!ignore.is_excluded("cache-ab"));
+        assert!(!ignore.is_excluded("src/main.rs"));
+        assert!(ignore.has_exceptions());
+    }
+
+    #[test]
+    fn test_wildcard() {
+        assert!(wildcard("*.rs", "main.rs"));
+        assert!(wildcard("a*b*c", "aXXbYYc"));
+        assert!(!wildcard("a*b*c", "aXXbYY"));
+        assert!(wildcard("[^a-c]x", "dx"));
+        assert!(!wildcard("[!a-c]x", "bx"));
+        assert!(wildcard("\\*", "*"));
+        assert!(!wildcard("\\*", "a"));
+    }
+}
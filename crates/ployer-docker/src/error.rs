@@ -0,0 +1,90 @@
+use thiserror::Error;
+
+/// Docker failures callers react to, classified from the daemon's status codes
+#[derive(Error, Debug)]
+pub enum DockerError {
+    #[error("Not found: {0}")]
+    NotFound(String),
+
+    #[error("Container already started")]
+    AlreadyStarted,
+
+    #[error("Container not running")]
+    NotRunning,
+
+    /// Removing a running container without `force`
+    #[error("Container is running")]
+    StillRunning,
+
+    #[error("In use: {0}")]
+    InUse(String),
+
+    /// Starting a container whose published host port is already bound
+    #[error("Host port already in use: {0}")]
+    PortInUse(String),
+
+    #[error(transparent)]
+    Other(#[from] anyhow::Error),
+}
+
+pub type DockerResult<T> = std::result::Result<T, DockerError>;
+
+impl From<bollard::errors::Error> for DockerError {
+    fn from(err: bollard::errors::Error) -> Self {
+        match err {
+            bollard::errors::Error::DockerResponseServerError { status_code, message } => {
+                match status_code {
+                    404 => DockerError::NotFound(message),
+                    // Removing a volume or image that a container still uses
+                    409 => DockerError::InUse(message),
+                    // The daemon reports a failed port bind as a 500, so only the text tells it apart
+                    500 if message.contains("port is already allocated")
+                        || message.contains("address already in use") =>
+                    {
+                        DockerError::PortInUse(message)
+                    }
+                    _ => DockerError::Other(
+                        bollard::errors::Error::DockerResponseServerError { status_code, message }.into(),
+                    ),
+                }
+            }
+            other => DockerError::Other(other.into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn server_error(status_code: u16, message: &str) -> bollard::errors::Error {
+        bollard::errors::Error::DockerResponseServerError {
+            status_code,
+            message: message.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_classify_status_codes() {
+        assert!(matches!(
+            DockerError::from(server_error(404, "No such container: abc")),
+            DockerError::NotFound(m) if m == "No such container: abc"
+        ));
+        assert!(matches!(
+            DockerError::from(server_error(409, "volume is in use")),
+            DockerError::InUse(_)
+        ));
+
+        assert!(matches!(
+            DockerError::from(server_error(
+                500,
+                "driver failed programming external connectivity on endpoint web: Bind for 0.0.0.0:8080 failed: port is already allocated"
+            )),
+            DockerError::PortInUse(_)
+        ));
+
+        let other = DockerError::from(server_error(500, "boom"));
+        assert!(matches!(other, DockerError::Other(_)));
+        assert!(other.to_string().contains("boom"));
+    }
+}
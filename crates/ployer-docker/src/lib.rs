@@ -1,24 +1,60 @@
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use bollard::container::{
-    Config, CreateContainerOptions, InspectContainerOptions, ListContainersOptions,
-    LogsOptions, RemoveContainerOptions, StartContainerOptions, StatsOptions, StopContainerOptions,
+    Config, CreateContainerOptions, DownloadFromContainerOptions, InspectContainerOptions,
+    KillContainerOptions, ListContainersOptions, LogsOptions, RemoveContainerOptions,
+    RenameContainerOptions, StartContainerOptions, StatsOptions, StopContainerOptions,
+    UploadToContainerOptions, WaitContainerOptions,
+};
+use bollard::auth::DockerCredentials;
+use bollard::exec::{CreateExecOptions, StartExecResults};
+use bollard::image::{BuildImageOptions, CreateImageOptions, PushImageOptions, TagImageOptions};
+use bollard::models::{
+    ChangeType, ContainerInspectResponse, ContainerSummary, HostConfig, MountPoint, PortBinding, RestartPolicy,
+    RestartPolicyNameEnum, SystemDataUsageResponse,
+};
+use bollard::network::{
+    ConnectNetworkOptions, CreateNetworkOptions, DisconnectNetworkOptions, InspectNetworkOptions,
+    ListNetworksOptions,
 };
-use bollard::image::BuildImageOptions;
-use bollard::models::{ContainerInspectResponse, ContainerSummary, HostConfig, PortBinding};
-use bollard::network::{CreateNetworkOptions, InspectNetworkOptions, ListNetworksOptions};
 use bollard::volume::{CreateVolumeOptions, ListVolumesOptions, RemoveVolumeOptions};
 use bollard::Docker;
 use futures_util::StreamExt;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::default::Default;
-use std::path::Path;
+use std::path::{Component, Path};
+use std::time::Duration;
 use tokio::sync::mpsc;
 use tracing::{info, warn};
-use tar::Builder;
+use tar::{Builder, EntryType, Header};
+use utoipa::ToSchema;
+
+mod cache;
+mod dockerignore;
+mod error;
+
+use cache::InspectCache;
+use dockerignore::DockerIgnore;
+pub use cache::INSPECT_CACHE_TTL;
+pub use error::{DockerError, DockerResult};
+
+/// Label identifying the application that owns a container
+pub const LABEL_APP_ID: &str = "ployer.app_id";
+/// Label identifying the deployment that created a container
+pub const LABEL_DEPLOYMENT_ID: &str = "ployer.deployment_id";
+
+/// Build context entry holding a Dockerfile cut down to a target stage
+const TARGET_DOCKERFILE: &str = ".ployer-target.Dockerfile";
+
+/// Directory a workspace command runs in, inside its throwaway container
+const WORKSPACE_DIR: &str = "workspace";
+
+/// Timeout of ordinary Docker API requests
+const REQUEST_TIMEOUT_SECS: u64 = 120;
 
 pub struct DockerClient {
     client: Docker,
+    inspect_cache: InspectCache,
 }
 
 // Container configuration for creating new containers
@@ -28,13 +64,47 @@ pub struct ContainerConfig {
     pub name: Option<String>,
     pub env: Option<Vec<String>>,
     pub ports: Option<HashMap<String, String>>, // container_port -> host_port
-    pub volumes: Option<HashMap<String, String>>, // host_path -> container_path
+    pub volumes: Option<HashMap<String, String>>, // container_path -> host path or volume name
     pub network: Option<String>,
     pub cmd: Option<Vec<String>>,
+    pub restart_policy: Option<String>, // e.g. "always", "unless-stopped", "on-failure:5"
+    pub labels: Option<HashMap<String, String>>,
+    /// Have Docker remove the container once it exits, for one-off jobs.
+    /// Docker rejects it together with a restart policy other than "no".
+    #[serde(default)]
+    pub auto_remove: bool,
+}
+
+// Filters for listing containers
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ContainerFilters {
+    pub status: Option<String>, // e.g. "running", "exited"
+    pub label: Option<String>,  // "key" or "key=value"
+    pub name: Option<String>,   // substring match
+    pub publish: Option<u16>,   // published host port
+}
+
+impl ContainerFilters {
+    fn to_docker_filters(&self) -> HashMap<String, Vec<String>> {
+        let mut filters = HashMap::new();
+        if let Some(status) = &self.status {
+            filters.insert("status".to_string(), vec![status.clone()]);
+        }
+        if let Some(label) = &self.label {
+            filters.insert("label".to_string(), vec![label.clone()]);
+        }
+        if let Some(name) = &self.name {
+            filters.insert("name".to_string(), vec![name.clone()]);
+        }
+        if let Some(port) = self.publish {
+            filters.insert("publish".to_string(), vec![port.to_string()]);
+        }
+        filters
+    }
 }
 
 // Container information summary
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct ContainerInfo {
     pub id: String,
     pub name: String,
@@ -43,17 +113,192 @@ pub struct ContainerInfo {
     pub status: String,
     pub created: i64,
     pub ports: Vec<PortInfo>,
+    pub labels: HashMap<String, String>,
+    /// Times Docker restarted the container; only available from inspect
+    pub restart_count: Option<i64>,
+    /// Exit code of the last run; None while running or if it never ran
+    pub exit_code: Option<i64>,
+    /// Whether the last run was killed for exceeding its memory limit
+    pub oom_killed: Option<bool>,
+    /// Bytes written to the container's writable layer; only when size was requested
+    pub size_rw: Option<i64>,
+    /// Total bytes of the container's filesystem, image included; only when size was requested
+    pub size_root_fs: Option<i64>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// A volume, bind mount or tmpfs in a container, from inspect
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct MountInfo {
+    /// `volume`, `bind`, `tmpfs`, `npipe` or `cluster`
+    #[serde(rename = "type")]
+    pub kind: String,
+    /// Volume name; None for bind mounts and tmpfs
+    pub name: Option<String>,
+    /// Host path, or the volume's directory on the host
+    pub source: Option<String>,
+    pub destination: String,
+    pub read_only: bool,
+}
+
+impl From<MountPoint> for MountInfo {
+    fn from(mount: MountPoint) -> Self {
+        Self {
+            kind: mount.typ.map(|t| t.to_string()).unwrap_or_default(),
+            name: mount.name.filter(|n| !n.is_empty()),
+            source: mount.source.filter(|s| !s.is_empty()),
+            destination: mount.destination.unwrap_or_default(),
+            read_only: !mount.rw.unwrap_or(true),
+        }
+    }
+}
+
+/// A path changed in a container's writable layer, as `docker diff` reports it
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct FileChange {
+    pub path: String,
+    pub kind: FileChangeKind,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum FileChangeKind {
+    Modified,
+    Added,
+    Deleted,
+}
+
+impl From<ChangeType> for FileChangeKind {
+    fn from(kind: ChangeType) -> Self {
+        match kind {
+            ChangeType::_0 => FileChangeKind::Modified,
+            ChangeType::_1 => FileChangeKind::Added,
+            ChangeType::_2 => FileChangeKind::Deleted,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct PortInfo {
     pub container_port: u16,
     pub host_port: Option<u16>,
     pub protocol: String,
 }
 
+// Options for fetching container logs
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LogOptions {
+    /// Number of most recent lines; defaults to 100, or all lines when `since` is set
+    pub tail: Option<usize>,
+    /// Only return logs after this UNIX timestamp
+    pub since: Option<i64>,
+    /// Only return logs before this UNIX timestamp
+    pub until: Option<i64>,
+    /// Prefix each line with its RFC3339 timestamp
+    pub timestamps: bool,
+    /// Keep only matching lines; `tail` then counts matching lines
+    #[serde(skip)]
+    pub filter: Option<LogFilter>,
+}
+
+/// Which log lines to keep
+#[derive(Debug, Clone)]
+pub enum LogFilter {
+    Contains(String),
+    Regex(regex::Regex),
+}
+
+impl LogFilter {
+    pub fn matches(&self, line: &str) -> bool {
+        match self {
+            LogFilter::Contains(needle) => line.contains(needle.as_str()),
+            LogFilter::Regex(pattern) => pattern.is_match(line),
+        }
+    }
+}
+
+/// Drop lines not matching `filter`, then keep the last `keep` of the rest
+fn keep_matching(logs: &mut Vec<String>, filter: &LogFilter, keep: Option<usize>) {
+    logs.retain(|line| filter.matches(line));
+    if let Some(n) = keep {
+        logs.drain(..logs.len().saturating_sub(n));
+    }
+}
+
+/// Unpack a `download_from_container` archive of the workspace into `dest`.
+/// Nothing is written outside `dest`: entries with `..`, entries below a symlink
+/// and directories that are symlinks are skipped, and symlinks are only created
+/// once every file is in place.
+fn unpack_workspace(archive: &[u8], dest: &Path) -> Result<()> {
+    let is_symlink = |path: &Path| {
+        std::fs::symlink_metadata(path).is_ok_and(|m| m.file_type().is_symlink())
+    };
+
+    let mut links = Vec::new();
+    let mut archive = tar::Archive::new(archive);
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let path = entry.path()?.into_owned();
+        let Ok(rel) = path.strip_prefix(WORKSPACE_DIR) else {
+            continue;
+        };
+        if rel.as_os_str().is_empty() || !rel.components().all(|c| matches!(c, Component::Normal(_))) {
+            continue;
+        }
+        if rel.ancestors().skip(1).any(|a| !a.as_os_str().is_empty() && is_symlink(&dest.join(a))) {
+            continue;
+        }
+
+        let target = dest.join(rel);
+        match entry.header().entry_type() {
+            EntryType::Directory if !is_symlink(&target) => {
+                entry.unpack(&target)?;
+            }
+            // An existing file or symlink at the path is replaced, not written through
+            EntryType::Regular => {
+                if let Some(parent) = target.parent() {
+                    std::fs::create_dir_all(parent)?;
+                }
+                entry.unpack(&target)?;
+            }
+            EntryType::Symlink => {
+                if let Some(link) = entry.link_name()? {
+                    links.push((target, link.into_owned()));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    for (target, link) in links {
+        match std::fs::symlink_metadata(&target) {
+            // A directory keeps its contents rather than turning into a link
+            Ok(m) if m.is_dir() => continue,
+            Ok(_) if std::fs::read_link(&target).is_ok_and(|existing| existing == link) => continue,
+            Ok(_) => std::fs::remove_file(&target)?,
+            Err(_) => {}
+        }
+        std::os::unix::fs::symlink(&link, &target)?;
+    }
+    Ok(())
+}
+
+/// Complete lines in `chunk`, keeping an unterminated tail in `pending`
+/// for the next chunk. Trailing `\r` is dropped.
+fn take_lines(pending: &mut String, chunk: &str) -> Vec<String> {
+    pending.push_str(chunk);
+    let Some(end) = pending.rfind('\n') else {
+        return Vec::new();
+    };
+    let lines = pending[..end]
+        .split('\n')
+        .map(|line| line.trim_end_matches('\r').to_string())
+        .collect();
+    pending.drain(..=end);
+    lines
+}
+
 // Container resource statistics
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct ContainerStats {
     pub cpu_usage: f64,
     pub memory_usage_mb: f64,
@@ -63,7 +308,7 @@ pub struct ContainerStats {
 }
 
 // Network information
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct NetworkInfo {
     pub id: String,
     pub name: String,
@@ -74,7 +319,7 @@ pub struct NetworkInfo {
 }
 
 // Volume information
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct VolumeInfo {
     pub name: String,
     pub driver: String,
@@ -82,17 +327,205 @@ pub struct VolumeInfo {
     pub created_at: Option<String>,
 }
 
+// Username and password for a registry push
+#[derive(Debug, Clone)]
+pub struct RegistryCredentials {
+    pub username: String,
+    pub password: String,
+}
+
+// Disk space used by Docker, in MB
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DockerDiskUsage {
+    pub images_mb: u64,
+    pub containers_mb: u64,
+    pub volumes_mb: u64,
+    pub build_cache_mb: u64,
+    pub total_mb: u64,
+}
+
+impl From<SystemDataUsageResponse> for DockerDiskUsage {
+    fn from(df: SystemDataUsageResponse) -> Self {
+        // Sizes Docker hasn't computed are reported as -1
+        let mb = |bytes: i64| bytes.max(0) as u64 / 1024 / 1024;
+
+        let images = df.layers_size.unwrap_or(0);
+        let containers: i64 = df
+            .containers
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|c| c.size_rw)
+            .filter(|s| *s > 0)
+            .sum();
+        let volumes: i64 = df
+            .volumes
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|v| v.usage_data.as_ref())
+            .map(|u| u.size)
+            .filter(|s| *s > 0)
+            .sum();
+        // Shared cache records are already counted under image layers
+        let build_cache: i64 = df
+            .build_cache
+            .unwrap_or_default()
+            .iter()
+            .filter(|b| !b.shared.unwrap_or(false))
+            .filter_map(|b| b.size)
+            .filter(|s| *s > 0)
+            .sum();
+
+        DockerDiskUsage {
+            images_mb: mb(images),
+            containers_mb: mb(containers),
+            volumes_mb: mb(volumes),
+            build_cache_mb: mb(build_cache),
+            total_mb: mb(images + containers + volumes + build_cache),
+        }
+    }
+}
+
+/// Truncate a Dockerfile after the stage named `target` (`FROM ... AS target`).
+/// Later stages can't feed earlier ones, so building the result is the same
+/// as building that stage with `--target`.
+pub fn dockerfile_for_target(contents: &str, target: &str) -> Result<String> {
+    let is_from = |line: &str| {
+        line.split_whitespace()
+            .next()
+            .is_some_and(|word| word.eq_ignore_ascii_case("FROM"))
+    };
+    let names_target = |line: &str| {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        words.len() >= 4
+            && words[words.len() - 2].eq_ignore_ascii_case("AS")
+            && words[words.len() - 1].eq_ignore_ascii_case(target)
+    };
+
+    let lines: Vec<&str> = contents.lines().collect();
+    let start = lines
+        .iter()
+        .position(|l| is_from(l) && names_target(l))
+        .ok_or_else(|| anyhow!("Dockerfile has no build stage named \"{}\"", target))?;
+    let end = lines[start + 1..]
+        .iter()
+        .position(|l| is_from(l))
+        .map_or(lines.len(), |i| start + 1 + i);
+
+    let mut out = lines[..end].join("\n");
+    out.push('\n');
+    Ok(out)
+}
+
+/// Registry host of an image repository, e.g. `ghcr.io` for `ghcr.io/acme/web`.
+/// Repositories without an explicit host live on Docker Hub.
+pub fn registry_host(repository: &str) -> &str {
+    match repository.split_once('/') {
+        Some((first, _)) if first.contains(['.', ':']) || first == "localhost" => first,
+        _ => "docker.io",
+    }
+}
+
+/// Image reference with an explicit tag, defaulting to `latest`. Pulling a
+/// bare repository name would otherwise fetch every tag.
+pub fn image_reference(image: &str) -> String {
+    let name = image.rsplit('/').next().unwrap_or(image);
+    if image.contains('@') || name.contains(':') {
+        image.to_string()
+    } else {
+        format!("{}:latest", image)
+    }
+}
+
+/// Turn a push error from the daemon into something actionable, calling out
+/// rejected credentials explicitly
+pub fn describe_push_error(error: &str) -> String {
+    let lower = error.to_lowercase();
+    let auth_failure = ["unauthorized", "authentication required", "denied", "no basic auth credentials"]
+        .iter()
+        .any(|needle| lower.contains(needle));
+
+    if auth_failure {
+        format!(
+            "Registry rejected the push; check the registry username, password and repository permissions ({})",
+            error.trim()
+        )
+    } else {
+        format!("Image push failed: {}", error.trim())
+    }
+}
+
+/// Spell out a missing-manifest error when a platform was requested, since the
+/// daemon's wording doesn't make clear the image exists but not for that platform
+pub fn describe_platform_error(error: &str, platform: Option<&str>) -> String {
+    let lower = error.to_lowercase();
+    let missing = ["no matching manifest", "no match for platform"]
+        .iter()
+        .any(|needle| lower.contains(needle));
+
+    match platform {
+        Some(platform) if missing => format!(
+            "Image has no build for platform {}; use an image that publishes it or change the server's platform ({})",
+            platform,
+            error.trim()
+        ),
+        _ => error.to_string(),
+    }
+}
+
+/// Parse a restart policy string ("no", "always", "unless-stopped", "on-failure[:N]")
+pub fn parse_restart_policy(policy: &str) -> Result<RestartPolicy> {
+    let (name, retries) = match policy.split_once(':') {
+        Some((name, retries)) => (name, Some(retries)),
+        None => (policy, None),
+    };
+
+    let name = match name {
+        "no" => RestartPolicyNameEnum::NO,
+        "always" => RestartPolicyNameEnum::ALWAYS,
+        "unless-stopped" => RestartPolicyNameEnum::UNLESS_STOPPED,
+        "on-failure" => RestartPolicyNameEnum::ON_FAILURE,
+        other => return Err(anyhow!("Unknown restart policy '{}'", other)),
+    };
+
+    // Docker only accepts a retry count for on-failure
+    let maximum_retry_count = match retries {
+        Some(_) if name != RestartPolicyNameEnum::ON_FAILURE => {
+            return Err(anyhow!("Only on-failure accepts a retry count"));
+        }
+        Some(n) => Some(
+            n.parse::<i64>()
+                .map_err(|_| anyhow!("Invalid restart retry count '{}'", n))?,
+        ),
+        None => None,
+    };
+
+    Ok(RestartPolicy {
+        name: Some(name),
+        maximum_retry_count,
+    })
+}
+
 impl DockerClient {
     pub fn new(socket_path: &str) -> Result<Self> {
-        let client = Docker::connect_with_socket(socket_path, 120, bollard::API_DEFAULT_VERSION)?;
+        let client = Docker::connect_with_socket(socket_path, REQUEST_TIMEOUT_SECS, bollard::API_DEFAULT_VERSION)?;
         info!("Docker client connected via {}", socket_path);
-        Ok(Self { client })
+        Ok(Self {
+            client,
+            inspect_cache: InspectCache::new(INSPECT_CACHE_TTL),
+        })
     }
 
     pub fn inner(&self) -> &Docker {
         &self.client
     }
 
+    /// A client for a request the daemon may hold open for `grace` seconds,
+    /// e.g. a stop that waits for the container to exit
+    fn client_for_grace(&self, grace: i64) -> Docker {
+        let timeout = REQUEST_TIMEOUT_SECS.max(grace.max(0) as u64 + 30);
+        self.client.clone().with_timeout(Duration::from_secs(timeout))
+    }
+
     pub async fn ping(&self) -> Result<bool> {
         match self.client.ping().await {
             Ok(_) => Ok(true),
@@ -100,29 +533,57 @@ impl DockerClient {
         }
     }
 
-    /// Build a Docker image from a context directory
+    /// Build a Docker image from a context directory, optionally ignoring the layer cache.
+    /// Images in `cache_from` (e.g. the previous build) are offered as cache sources,
+    /// and `build_args` are available to the Dockerfile's `ARG`s. `platform`
+    /// (e.g. `linux/arm64`) overrides the daemon's own platform.
     /// Returns a channel that streams build log lines
+    #[allow(clippy::too_many_arguments)]
     pub async fn build_image(
         &self,
         context_path: &Path,
         dockerfile_path: Option<&str>,
+        target: Option<&str>,
         tag: &str,
+        no_cache: bool,
+        cache_from: &[String],
+        build_args: &HashMap<String, String>,
+        platform: Option<&str>,
     ) -> Result<mpsc::Receiver<String>> {
         info!("Building Docker image: {} from {:?}", tag, context_path);
 
+        let mut dockerfile = dockerfile_path.unwrap_or("Dockerfile").to_string();
+
+        // This Engine API client has no `target` option, so a targeted build
+        // ships a copy of the Dockerfile that ends with the requested stage
+        let staged = match target {
+            Some(target) => {
+                let contents = std::fs::read_to_string(context_path.join(&dockerfile))
+                    .map_err(|e| anyhow!("Failed to read {}: {}", dockerfile, e))?;
+                dockerfile = TARGET_DOCKERFILE.to_string();
+                Some(dockerfile_for_target(&contents, target)?)
+            }
+            None => None,
+        };
+
         // Create a tar archive of the build context
-        let tar_data = Self::create_build_context_tar(context_path)?;
+        let tar_data = Self::create_build_context_tar(context_path, &dockerfile, staged.as_deref())?;
 
         let options = BuildImageOptions {
-            dockerfile: dockerfile_path.unwrap_or("Dockerfile").to_string(),
+            dockerfile,
             t: tag.to_string(),
             rm: true, // Remove intermediate containers
             pull: true, // Always pull the latest base image
+            nocache: no_cache,
+            cachefrom: cache_from.to_vec(),
+            buildargs: build_args.clone(),
+            platform: platform.unwrap_or_default().to_string(),
             ..Default::default()
         };
 
         // Clone the client to avoid borrowing self in the spawned task
         let client = self.client.clone();
+        let platform = platform.map(str::to_string);
         let (tx, rx) = mpsc::channel(100);
 
         // Spawn a task to process the build stream
@@ -132,12 +593,21 @@ impl DockerClient {
                 match result {
                     Ok(info) => {
                         // Extract log message from BuildInfo
-                        if let Some(stream) = info.stream {
-                            let _ = tx.send(stream).await;
+                        let line = if let Some(stream) = info.stream {
+                            stream
                         } else if let Some(error) = info.error {
-                            let _ = tx.send(format!("ERROR: {}", error)).await;
+                            format!("ERROR: {}", describe_platform_error(&error, platform.as_deref()))
                         } else if let Some(status) = info.status {
-                            let _ = tx.send(status).await;
+                            status
+                        } else {
+                            continue;
+                        };
+
+                        // Receiver gone means the deployment was aborted; dropping
+                        // the stream closes the request and stops the build
+                        if tx.send(line).await.is_err() {
+                            info!("Build log receiver dropped, aborting build");
+                            break;
                         }
                     }
                     Err(e) => {
@@ -152,52 +622,191 @@ impl DockerClient {
         Ok(rx)
     }
 
-    /// Create a tar archive of the build context directory
-    fn create_build_context_tar(context_path: &Path) -> Result<Vec<u8>> {
+    /// Pull an image (e.g. `postgres:16`), streaming progress lines. `platform`
+    /// selects a variant of a multi-arch image instead of the daemon's own.
+    /// Failures arrive as a line starting with `ERROR: `.
+    pub async fn pull_image(
+        &self,
+        image: &str,
+        credentials: Option<&RegistryCredentials>,
+        platform: Option<&str>,
+    ) -> Result<mpsc::Receiver<String>> {
+        let image = image_reference(image);
+        info!("Pulling Docker image: {}", image);
+
+        let credentials = credentials.map(|c| DockerCredentials {
+            username: Some(c.username.clone()),
+            password: Some(c.password.clone()),
+            serveraddress: Some(registry_host(&image).to_string()),
+            ..Default::default()
+        });
+
+        let client = self.client.clone();
+        let platform = platform.map(str::to_string);
+        let (tx, rx) = mpsc::channel(100);
+
+        tokio::spawn(async move {
+            let options = CreateImageOptions {
+                from_image: image.as_str(),
+                platform: platform.as_deref().unwrap_or_default(),
+                ..Default::default()
+            };
+            let mut stream = client.create_image(Some(options), None, credentials);
+            while let Some(result) = stream.next().await {
+                let line = match result {
+                    Ok(info) => match (info.error, info.status) {
+                        (Some(error), _) => {
+                            format!("ERROR: {}", describe_platform_error(&error, platform.as_deref()))
+                        }
+                        // Per-chunk byte counts would flood the log
+                        (None, Some(_)) if info.progress.is_some() => continue,
+                        (None, Some(status)) => match info.id {
+                            Some(id) => format!("{}: {}", id, status),
+                            None => status,
+                        },
+                        (None, None) => continue,
+                    },
+                    Err(e) => {
+                        warn!("Pull stream error: {}", e);
+                        let _ = tx.send(format!("ERROR: {}", e)).await;
+                        break;
+                    }
+                };
+
+                if tx.send(line).await.is_err() {
+                    info!("Pull log receiver dropped, aborting pull");
+                    break;
+                }
+            }
+        });
+
+        Ok(rx)
+    }
+
+    // Give an existing image an additional repository:tag name
+    pub async fn tag_image(&self, image: &str, repository: &str, tag: &str) -> Result<()> {
+        let options = TagImageOptions { repo: repository, tag };
+        self.client.tag_image(image, Some(options)).await?;
+        Ok(())
+    }
+
+    /// Push `repository:tag` to its registry, streaming progress lines.
+    /// Failures arrive as a line starting with `ERROR: `.
+    pub async fn push_image(
+        &self,
+        repository: &str,
+        tag: &str,
+        credentials: Option<&RegistryCredentials>,
+    ) -> Result<mpsc::Receiver<String>> {
+        info!("Pushing Docker image: {}:{}", repository, tag);
+
+        let credentials = credentials.map(|c| DockerCredentials {
+            username: Some(c.username.clone()),
+            password: Some(c.password.clone()),
+            serveraddress: Some(registry_host(repository).to_string()),
+            ..Default::default()
+        });
+
+        let client = self.client.clone();
+        let repository = repository.to_string();
+        let options = PushImageOptions { tag: tag.to_string() };
+        let (tx, rx) = mpsc::channel(100);
+
+        tokio::spawn(async move {
+            let mut stream = client.push_image(&repository, Some(options), credentials);
+            while let Some(result) = stream.next().await {
+                let line = match result {
+                    Ok(info) => match (info.error, info.status) {
+                        (Some(error), _) => format!("ERROR: {}", error),
+                        // Per-chunk byte counts would flood the log
+                        (None, Some(_)) if info.progress.is_some() => continue,
+                        (None, Some(status)) => status,
+                        (None, None) => continue,
+                    },
+                    Err(e) => {
+                        warn!("Push stream error: {}", e);
+                        let _ = tx.send(format!("ERROR: {}", e)).await;
+                        break;
+                    }
+                };
+
+                if tx.send(line).await.is_err() {
+                    info!("Push log receiver dropped, aborting push");
+                    break;
+                }
+            }
+        });
+
+        Ok(rx)
+    }
+
+    /// Create a tar archive of the build context directory, leaving out `.git` and
+    /// whatever `.dockerignore` excludes. The Dockerfile is always sent, since the
+    /// daemon needs it even when ignored.
+    fn create_build_context_tar(
+        context_path: &Path,
+        dockerfile: &str,
+        target_dockerfile: Option<&str>,
+    ) -> Result<Vec<u8>> {
+        let ignore = DockerIgnore::load(context_path)?;
+        let dockerfile = dockerfile.trim_start_matches("./");
+
         let mut tar_data = Vec::new();
         {
             let mut tar = Builder::new(&mut tar_data);
-            tar.append_dir_all(".", context_path)?;
+            Self::append_context_dir(&mut tar, context_path, "", &ignore, dockerfile)?;
+            if let Some(contents) = target_dockerfile {
+                let mut header = Header::new_gnu();
+                header.set_size(contents.len() as u64);
+                header.set_mode(0o644);
+                header.set_cksum();
+                tar.append_data(&mut header, TARGET_DOCKERFILE, contents.as_bytes())?;
+            }
             tar.finish()?;
         }
         Ok(tar_data)
     }
 
-    /// Force-remove all containers bound to a given host port
-    pub async fn remove_containers_by_port(&self, port: u16) -> Result<Vec<String>> {
-        let mut filters = HashMap::new();
-        filters.insert("publish".to_string(), vec![port.to_string()]);
-
-        let options = ListContainersOptions {
-            all: true,
-            filters,
-            ..Default::default()
-        };
+    fn append_context_dir<W: std::io::Write>(
+        tar: &mut Builder<W>,
+        dir: &Path,
+        rel_dir: &str,
+        ignore: &DockerIgnore,
+        dockerfile: &str,
+    ) -> Result<()> {
+        let mut entries = std::fs::read_dir(dir)?.collect::<std::io::Result<Vec<_>>>()?;
+        entries.sort_by_key(|e| e.file_name());
+
+        for entry in entries {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let rel = if rel_dir.is_empty() { name } else { format!("{}/{}", rel_dir, name) };
+            if rel == ".git" {
+                continue;
+            }
 
-        let containers = self.client.list_containers(Some(options)).await?;
-        let mut removed = Vec::new();
-
-        for c in containers {
-            if let Some(id) = c.id {
-                let options = RemoveContainerOptions { force: true, v: true, ..Default::default() };
-                if self.client.remove_container(&id, Some(options)).await.is_ok() {
-                    let name = c.names
-                        .as_ref()
-                        .and_then(|n| n.first())
-                        .map(|n| n.trim_start_matches('/').to_string())
-                        .unwrap_or_else(|| id[..12].to_string());
-                    removed.push(name);
+            let excluded = ignore.is_excluded(&rel) && rel != dockerfile;
+            let path = entry.path();
+            if path.is_dir() {
+                // A `!` pattern may re-include something below an excluded directory
+                if excluded && !ignore.has_exceptions() && !dockerfile.starts_with(&format!("{}/", rel)) {
+                    continue;
+                }
+                if !excluded {
+                    tar.append_dir(&rel, &path)?;
                 }
+                Self::append_context_dir(tar, &path, &rel, ignore, dockerfile)?;
+            } else if !excluded {
+                tar.append_path_with_name(&path, &rel)?;
             }
         }
-
-        Ok(removed)
+        Ok(())
     }
 
     // List containers
-    pub async fn list_containers(&self, all: bool) -> Result<Vec<ContainerInfo>> {
+    pub async fn list_containers(&self, all: bool, filters: &ContainerFilters) -> Result<Vec<ContainerInfo>> {
         let options = ListContainersOptions::<String> {
             all,
+            filters: filters.to_docker_filters(),
             ..Default::default()
         };
 
@@ -207,9 +816,61 @@ impl DockerClient {
     }
 
     // Inspect container details
-    pub async fn inspect_container(&self, id: &str) -> Result<ContainerInspectResponse> {
-        let options = InspectContainerOptions { size: false };
-        Ok(self.client.inspect_container(id, Some(options)).await?)
+    pub async fn inspect_container(&self, id: &str) -> DockerResult<ContainerInspectResponse> {
+        self.inspect_container_sized(id, false).await
+    }
+
+    /// Like `inspect_container`; with `size`, Docker also reports `size_rw` and
+    /// `size_root_fs`, which means walking the container's filesystem
+    pub async fn inspect_container_sized(&self, id: &str, size: bool) -> DockerResult<ContainerInspectResponse> {
+        let options = InspectContainerOptions { size };
+        let inspect = self.client.inspect_container(id, Some(options)).await?;
+        self.inspect_cache.insert(id, inspect.clone());
+        Ok(inspect)
+    }
+
+    /// Paths added, changed or deleted in the container's writable layer
+    pub async fn container_changes(&self, id: &str) -> DockerResult<Vec<FileChange>> {
+        let changes = self.client.container_changes(id).await?;
+        Ok(changes
+            .unwrap_or_default()
+            .into_iter()
+            .map(|change| FileChange {
+                path: change.path,
+                kind: change.kind.into(),
+            })
+            .collect())
+    }
+
+    /// Like `inspect_container`, but reuses a result up to `INSPECT_CACHE_TTL` old.
+    /// For monitors polling many containers; lifecycle operations drop the cached entry.
+    pub async fn inspect_container_cached(&self, id: &str) -> DockerResult<ContainerInspectResponse> {
+        match self.inspect_cache.get(id) {
+            Some(inspect) => Ok(inspect),
+            None => self.inspect_container(id).await,
+        }
+    }
+
+    // Host port Docker bound to a container port (e.g. an ephemeral one assigned at create)
+    pub async fn host_port(&self, id: &str, container_port: u16) -> Result<Option<u16>> {
+        let inspect = self.inspect_container(id).await?;
+        let key = format!("{}/tcp", container_port);
+
+        Ok(inspect
+            .network_settings
+            .and_then(|ns| ns.ports)
+            .and_then(|mut ports| ports.remove(&key))
+            .flatten()
+            .and_then(|bindings| bindings.into_iter().find_map(|b| b.host_port?.parse().ok())))
+    }
+
+    // Rename a container
+    pub async fn rename_container(&self, id: &str, new_name: &str) -> Result<()> {
+        let options = RenameContainerOptions { name: new_name };
+        let result = self.client.rename_container(id, options).await;
+        self.inspect_cache.invalidate(id);
+        result?;
+        Ok(())
     }
 
     // Create a new container
@@ -245,14 +906,22 @@ impl DockerClient {
         let binds = config.volumes.as_ref().map(|volumes| {
             volumes
                 .iter()
-                .map(|(host, container)| format!("{}:{}", host, container))
+                .map(|(container, source)| format!("{}:{}", source, container))
                 .collect::<Vec<_>>()
         });
 
+        let restart_policy = config
+            .restart_policy
+            .as_deref()
+            .map(parse_restart_policy)
+            .transpose()?;
+
         let host_config = Some(HostConfig {
             port_bindings: Some(port_bindings),
             binds,
             network_mode: config.network,
+            restart_policy,
+            auto_remove: Some(config.auto_remove),
             ..Default::default()
         });
 
@@ -261,6 +930,7 @@ impl DockerClient {
             env: config.env,
             cmd: config.cmd,
             exposed_ports: if exposed_ports.is_empty() { None } else { Some(exposed_ports) },
+            labels: config.labels,
             host_config,
             ..Default::default()
         };
@@ -273,45 +943,242 @@ impl DockerClient {
     }
 
     // Start a container
-    pub async fn start_container(&self, id: &str) -> Result<()> {
-        self.client
+    // Docker answers 304 for a container that's already running, which bollard
+    // reports as success, so the state is checked first
+    pub async fn start_container(&self, id: &str) -> DockerResult<()> {
+        if self.is_running(id).await? {
+            return Err(DockerError::AlreadyStarted);
+        }
+        let result = self.client
             .start_container(id, None::<StartContainerOptions<String>>)
-            .await?;
+            .await;
+        self.inspect_cache.invalidate(id);
+        result?;
         Ok(())
     }
 
     // Stop a container
-    pub async fn stop_container(&self, id: &str, timeout: Option<i64>) -> Result<()> {
-        let options = StopContainerOptions { t: timeout.unwrap_or(10) };
-        self.client.stop_container(id, Some(options)).await?;
+    pub async fn stop_container(&self, id: &str, timeout: Option<i64>) -> DockerResult<()> {
+        if !self.is_running(id).await? {
+            return Err(DockerError::NotRunning);
+        }
+        let t = timeout.unwrap_or(10);
+        // Docker only answers once the container is gone, which can take the whole grace period
+        let result = self
+            .client_for_grace(t)
+            .stop_container(id, Some(StopContainerOptions { t }))
+            .await;
+        self.inspect_cache.invalidate(id);
+        result?;
+        Ok(())
+    }
+
+    /// Stop a container by sending `signal` (e.g. `SIGINT`) instead of its configured
+    /// stop signal. Like `docker stop`, it is killed if still running after `timeout`
+    /// seconds (default 10).
+    pub async fn stop_container_with_signal(&self, id: &str, signal: &str, timeout: Option<i64>) -> DockerResult<()> {
+        self.kill_container(id, signal).await?;
+
+        let t = timeout.unwrap_or(10);
+        let grace = Duration::from_secs(t.max(0) as u64);
+        let options = WaitContainerOptions { condition: "not-running" };
+        // The wait can outlast the default request timeout, which would end it early
+        let client = self.client_for_grace(t);
+        let mut exited = client.wait_container(id, Some(options));
+        if let Ok(Some(Ok(_))) = tokio::time::timeout(grace, exited.next()).await {
+            return Ok(());
+        }
+
+        let result = self
+            .client
+            .kill_container(id, Some(KillContainerOptions { signal: "SIGKILL" }))
+            .await;
+        self.inspect_cache.invalidate(id);
+        match result {
+            Ok(()) => Ok(()),
+            // It exited between the wait giving up and the kill
+            Err(_) if !self.is_running(id).await? => Ok(()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Send `signal` to a running container's main process
+    pub async fn kill_container(&self, id: &str, signal: &str) -> DockerResult<()> {
+        if !self.is_running(id).await? {
+            return Err(DockerError::NotRunning);
+        }
+        let result = self
+            .client
+            .kill_container(id, Some(KillContainerOptions { signal }))
+            .await;
+        self.inspect_cache.invalidate(id);
+        result?;
         Ok(())
     }
 
     // Restart a container
-    pub async fn restart_container(&self, id: &str) -> Result<()> {
-        self.client.restart_container(id, None).await?;
+    pub async fn restart_container(&self, id: &str) -> DockerResult<()> {
+        let result = self.client.restart_container(id, None).await;
+        self.inspect_cache.invalidate(id);
+        result?;
         Ok(())
     }
 
-    // Remove a container
-    pub async fn remove_container(&self, id: &str, force: bool) -> Result<()> {
+    // Remove a container. Without `force`, a running container is refused
+    // rather than killed
+    pub async fn remove_container(&self, id: &str, force: bool) -> DockerResult<()> {
+        if !force && self.is_running(id).await? {
+            return Err(DockerError::StillRunning);
+        }
         let options = RemoveContainerOptions {
             force,
             v: true, // Remove volumes
             ..Default::default()
         };
-        self.client.remove_container(id, Some(options)).await?;
+        let result = self.client.remove_container(id, Some(options)).await;
+        self.inspect_cache.invalidate(id);
+        result?;
         Ok(())
     }
 
+    /// Run `cmd` with `sh -c` in a throwaway container of `image`, on a copy of
+    /// `workdir`. When it exits 0, the files it created or changed are copied back
+    /// to `workdir`. Each line of output goes to `output`. The container is named
+    /// `name`, so a caller that stops waiting can still remove it. Returns the exit code.
+    pub async fn run_in_workspace(
+        &self,
+        name: &str,
+        image: &str,
+        workdir: &Path,
+        cmd: &str,
+        env: &HashMap<String, String>,
+        output: mpsc::Sender<String>,
+    ) -> Result<i64> {
+        if self.client.inspect_image(image).await.is_err() {
+            let mut pull = self.pull_image(image, None, None).await?;
+            while let Some(line) = pull.recv().await {
+                if let Some(error) = line.strip_prefix("ERROR: ") {
+                    return Err(anyhow!("Failed to pull {}: {}", image, error));
+                }
+            }
+        }
+
+        let config = Config {
+            image: Some(image.to_string()),
+            cmd: Some(vec!["sh".to_string(), "-c".to_string(), cmd.to_string()]),
+            env: Some(env.iter().map(|(key, value)| format!("{}={}", key, value)).collect()),
+            working_dir: Some(format!("/{}", WORKSPACE_DIR)),
+            ..Default::default()
+        };
+        let options = CreateContainerOptions { name: name.to_string(), ..Default::default() };
+        self.client.create_container(Some(options), config).await?;
+
+        let result = self.run_workspace_container(name, workdir, output).await;
+        let options = RemoveContainerOptions { force: true, ..Default::default() };
+        if let Err(e) = self.client.remove_container(name, Some(options)).await {
+            warn!("Failed to remove workspace container {}: {}", name, e);
+        }
+        result
+    }
+
+    async fn run_workspace_container(&self, name: &str, workdir: &Path, output: mpsc::Sender<String>) -> Result<i64> {
+        let mut tar_data = Vec::new();
+        {
+            let mut tar = Builder::new(&mut tar_data);
+            tar.follow_symlinks(false);
+            tar.append_dir_all(WORKSPACE_DIR, workdir)?;
+            tar.finish()?;
+        }
+        let options = UploadToContainerOptions { path: "/", ..Default::default() };
+        self.client.upload_to_container(name, Some(options), tar_data.into()).await?;
+        self.client.start_container(name, None::<StartContainerOptions<String>>).await?;
+
+        let options = LogsOptions::<String> { follow: true, stdout: true, stderr: true, ..Default::default() };
+        let mut logs = self.client.logs(name, Some(options));
+        let mut pending = String::new();
+        while let Some(chunk) = logs.next().await {
+            for line in take_lines(&mut pending, &chunk?.to_string()) {
+                let _ = output.send(line).await;
+            }
+        }
+        if !pending.is_empty() {
+            let _ = output.send(pending).await;
+        }
+
+        // Docker reports a non-zero exit as a wait error carrying the code
+        let exit_code = match self.client.wait_container(name, None::<WaitContainerOptions<String>>).next().await {
+            Some(Ok(response)) => response.status_code,
+            Some(Err(bollard::errors::Error::DockerContainerWaitError { code, .. })) => code,
+            Some(Err(e)) => return Err(e.into()),
+            None => return Err(anyhow!("Workspace container {} finished without an exit code", name)),
+        };
+        if exit_code != 0 {
+            return Ok(exit_code);
+        }
+
+        let options = DownloadFromContainerOptions { path: format!("/{}", WORKSPACE_DIR) };
+        let mut archive = Vec::new();
+        let mut stream = self.client.download_from_container(name, Some(options));
+        while let Some(chunk) = stream.next().await {
+            archive.extend_from_slice(&chunk?);
+        }
+        unpack_workspace(&archive, workdir)?;
+        Ok(exit_code)
+    }
+
+    /// Run `cmd` in a running container, sending each line of its stdout and
+    /// stderr to `output` as it arrives. Returns the command's exit code.
+    pub async fn exec(&self, id: &str, cmd: Vec<String>, output: mpsc::Sender<String>) -> DockerResult<i64> {
+        let options = CreateExecOptions {
+            cmd: Some(cmd),
+            attach_stdout: Some(true),
+            attach_stderr: Some(true),
+            ..Default::default()
+        };
+        let exec_id = self.client.create_exec(id, options).await?.id;
+
+        if let StartExecResults::Attached { output: mut stream, .. } = self.client.start_exec(&exec_id, None).await? {
+            let mut pending = String::new();
+            while let Some(chunk) = stream.next().await {
+                for line in take_lines(&mut pending, &chunk?.to_string()) {
+                    let _ = output.send(line).await;
+                }
+            }
+            if !pending.is_empty() {
+                let _ = output.send(pending).await;
+            }
+        }
+
+        let inspect = self.client.inspect_exec(&exec_id).await?;
+        inspect
+            .exit_code
+            .ok_or_else(|| anyhow!("Exec in container {} finished without an exit code", id).into())
+    }
+
     // Get container logs
-    pub async fn get_container_logs(&self, id: &str, tail: Option<usize>) -> Result<Vec<String>> {
+    // When both `tail` and `since` are given, Docker returns the last `tail` lines
+    // of the window starting at `since`
+    pub async fn get_container_logs(&self, id: &str, opts: LogOptions) -> DockerResult<Vec<String>> {
         use futures_util::StreamExt;
 
+        let keep = match (opts.tail, opts.since) {
+            (Some(n), _) => Some(n),
+            (None, Some(_)) => None,
+            (None, None) => Some(100),
+        };
+        // A filter needs every line, so it can keep the last matching ones
+        let tail = match (keep, &opts.filter) {
+            (Some(n), None) => n.to_string(),
+            _ => "all".to_string(),
+        };
+
         let options = LogsOptions {
             stdout: true,
             stderr: true,
-            tail: tail.unwrap_or(100).to_string(),
+            since: opts.since.unwrap_or(0),
+            until: opts.until.unwrap_or(0),
+            timestamps: opts.timestamps,
+            tail,
             ..Default::default()
         };
 
@@ -319,16 +1186,18 @@ impl DockerClient {
         let mut logs = Vec::new();
 
         while let Some(log) = stream.next().await {
-            if let Ok(output) = log {
-                logs.push(output.to_string());
-            }
+            logs.push(log?.to_string());
+        }
+
+        if let Some(filter) = &opts.filter {
+            keep_matching(&mut logs, filter, keep);
         }
 
         Ok(logs)
     }
 
     // Get container stats (one-shot)
-    pub async fn get_container_stats(&self, id: &str) -> Result<ContainerStats> {
+    pub async fn get_container_stats(&self, id: &str) -> DockerResult<ContainerStats> {
         use futures_util::StreamExt;
 
         let options = StatsOptions {
@@ -338,7 +1207,8 @@ impl DockerClient {
 
         let mut stream = self.client.stats(id, Some(options));
 
-        if let Some(Ok(stats)) = stream.next().await {
+        if let Some(stats) = stream.next().await {
+            let stats = stats?;
             let cpu_delta = stats.cpu_stats.cpu_usage.total_usage as f64
                 - stats.precpu_stats.cpu_usage.total_usage as f64;
             let system_delta = stats.cpu_stats.system_cpu_usage.unwrap_or(0) as f64
@@ -370,7 +1240,17 @@ impl DockerClient {
             });
         }
 
-        Err(anyhow::anyhow!("Failed to get container stats"))
+        Err(anyhow!("Failed to get container stats").into())
+    }
+
+    // Disk space used by images, containers, volumes and build cache
+    pub async fn disk_usage(&self) -> Result<DockerDiskUsage> {
+        Ok(self.client.df().await?.into())
+    }
+
+    // Docker's data directory on the host (e.g. /var/lib/docker)
+    pub async fn root_dir(&self) -> Result<Option<String>> {
+        Ok(self.client.info().await?.docker_root_dir)
     }
 
     // ===== Network Management =====
@@ -409,7 +1289,7 @@ impl DockerClient {
     }
 
     // Inspect network
-    pub async fn inspect_network(&self, id: &str) -> Result<NetworkInfo> {
+    pub async fn inspect_network(&self, id: &str) -> DockerResult<NetworkInfo> {
         let options = InspectNetworkOptions {
             verbose: false,
             scope: "".to_string(),
@@ -431,8 +1311,34 @@ impl DockerClient {
     }
 
     // Remove network
-    pub async fn remove_network(&self, id: &str) -> Result<()> {
-        self.client.remove_network(id).await?;
+    pub async fn remove_network(&self, id: &str) -> DockerResult<()> {
+        match self.client.remove_network(id).await {
+            Ok(()) => Ok(()),
+            // Docker refuses with 403 while containers are attached
+            Err(bollard::errors::Error::DockerResponseServerError { status_code: 403, message }) => {
+                Err(DockerError::InUse(message))
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    // Attach a container to a network
+    pub async fn connect_container_to_network(&self, network_id: &str, container_id: &str) -> DockerResult<()> {
+        let options = ConnectNetworkOptions {
+            container: container_id,
+            ..Default::default()
+        };
+        self.client.connect_network(network_id, options).await?;
+        Ok(())
+    }
+
+    // Detach a container from a network
+    pub async fn disconnect_container_from_network(&self, network_id: &str, container_id: &str) -> DockerResult<()> {
+        let options = DisconnectNetworkOptions {
+            container: container_id,
+            force: false,
+        };
+        self.client.disconnect_network(network_id, options).await?;
         Ok(())
     }
 
@@ -474,7 +1380,7 @@ impl DockerClient {
     }
 
     // Inspect volume
-    pub async fn inspect_volume(&self, name: &str) -> Result<VolumeInfo> {
+    pub async fn inspect_volume(&self, name: &str) -> DockerResult<VolumeInfo> {
         let volume = self.client.inspect_volume(name).await?;
 
         Ok(VolumeInfo {
@@ -486,7 +1392,7 @@ impl DockerClient {
     }
 
     // Remove volume
-    pub async fn remove_volume(&self, name: &str, force: bool) -> Result<()> {
+    pub async fn remove_volume(&self, name: &str, force: bool) -> DockerResult<()> {
         let options = RemoveVolumeOptions { force };
         self.client.remove_volume(name, Some(options)).await?;
         Ok(())
@@ -494,6 +1400,11 @@ impl DockerClient {
 
     // ===== Helper Methods =====
 
+    async fn is_running(&self, id: &str) -> DockerResult<bool> {
+        let inspect = self.inspect_container(id).await?;
+        Ok(inspect.state.and_then(|s| s.running).unwrap_or(false))
+    }
+
     // Helper to convert bollard's ContainerSummary to our ContainerInfo
     fn summary_to_info(&self, summary: ContainerSummary) -> ContainerInfo {
         let ports = summary
@@ -515,6 +1426,668 @@ impl DockerClient {
             status: summary.status.unwrap_or_default(),
             created: summary.created.unwrap_or(0),
             ports,
+            labels: summary.labels.unwrap_or_default(),
+            restart_count: None,
+            exit_code: None,
+            oom_killed: None,
+            size_rw: summary.size_rw,
+            size_root_fs: summary.size_root_fs,
+        }
+    }
+
+    /// The container's environment as `(key, value)` pairs, in the order Docker reports them
+    pub fn inspect_env(inspect: &ContainerInspectResponse) -> Vec<(String, String)> {
+        inspect
+            .config
+            .as_ref()
+            .and_then(|config| config.env.as_ref())
+            .into_iter()
+            .flatten()
+            .map(|entry| match entry.split_once('=') {
+                Some((key, value)) => (key.to_string(), value.to_string()),
+                None => (entry.clone(), String::new()),
+            })
+            .collect()
+    }
+
+    /// The container's mounts, sorted by destination
+    pub fn inspect_mounts(inspect: &ContainerInspectResponse) -> Vec<MountInfo> {
+        let mut mounts: Vec<MountInfo> = inspect
+            .mounts
+            .clone()
+            .unwrap_or_default()
+            .into_iter()
+            .map(MountInfo::from)
+            .collect();
+        mounts.sort_by(|a, b| a.destination.cmp(&b.destination));
+        mounts
+    }
+
+    // Helper to convert bollard's ContainerInspectResponse to our ContainerInfo
+    pub fn inspect_to_info(inspect: ContainerInspectResponse) -> ContainerInfo {
+        let container_state = inspect.state.unwrap_or_default();
+        let state = container_state
+            .status
+            .map(|s| s.to_string())
+            .unwrap_or_else(|| "unknown".to_string());
+
+        // Docker reports exit code 0 for containers that never ran, so only
+        // surface it once the container has actually stopped
+        let exit_code = match state.as_str() {
+            "exited" | "dead" => container_state.exit_code,
+            _ => None,
+        };
+        let oom_killed = Some(container_state.oom_killed.unwrap_or(false));
+
+        // Mirror the human-readable status that `docker ps` reports
+        let status = match state.as_str() {
+            "running" => match container_state.started_at {
+                Some(started_at) => format!("Up since {}", started_at),
+                None => "Up".to_string(),
+            },
+            "exited" => format!("Exited ({})", container_state.exit_code.unwrap_or(0)),
+            other => other.to_string(),
+        };
+
+        let created = inspect
+            .created
+            .as_deref()
+            .and_then(|c| chrono::DateTime::parse_from_rfc3339(c).ok())
+            .map(|dt| dt.timestamp())
+            .unwrap_or(0);
+
+        let mut ports: Vec<PortInfo> = inspect
+            .network_settings
+            .and_then(|ns| ns.ports)
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|(key, bindings)| {
+                // Keys look like "8080/tcp"
+                let (port, protocol) = key.split_once('/').unwrap_or((key.as_str(), "tcp"));
+                let container_port = port.parse::<u16>().ok()?;
+                let protocol = protocol.to_string();
+
+                let host_ports: Vec<Option<u16>> = bindings
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|b| b.host_port.and_then(|p| p.parse().ok()))
+                    .collect();
+
+                let entries = if host_ports.is_empty() { vec![None] } else { host_ports };
+                Some(
+                    entries
+                        .into_iter()
+                        .map(|host_port| PortInfo {
+                            container_port,
+                            host_port,
+                            protocol: protocol.clone(),
+                        })
+                        .collect::<Vec<_>>(),
+                )
+            })
+            .flatten()
+            .collect();
+        ports.sort_by_key(|p| (p.container_port, p.host_port));
+
+        let config = inspect.config.unwrap_or_default();
+
+        ContainerInfo {
+            id: inspect.id.unwrap_or_default(),
+            name: inspect.name.unwrap_or_default().trim_start_matches('/').to_string(),
+            image: config.image.unwrap_or_default(),
+            state,
+            status,
+            created,
+            ports,
+            labels: config.labels.unwrap_or_default(),
+            restart_count: Some(inspect.restart_count.unwrap_or(0)),
+            exit_code,
+            oom_killed,
+            size_rw: inspect.size_rw,
+            size_root_fs: inspect.size_root_fs,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bollard::models::{ContainerConfig as BollardConfig, ContainerState, ContainerStateStatusEnum, NetworkSettings};
+
+    fn sample_inspect() -> ContainerInspectResponse {
+        let mut ports = HashMap::new();
+        ports.insert(
+            "3000/tcp".to_string(),
+            Some(vec![PortBinding {
+                host_ip: Some("0.0.0.0".to_string()),
+                host_port: Some("8080".to_string()),
+            }]),
+        );
+        ports.insert("53/udp".to_string(), None);
+
+        ContainerInspectResponse {
+            id: Some("abc123".to_string()),
+            name: Some("/ployer-web".to_string()),
+            created: Some("2024-01-15T10:30:00.123456789Z".to_string()),
+            state: Some(ContainerState {
+                status: Some(ContainerStateStatusEnum::EXITED),
+                exit_code: Some(137),
+                oom_killed: Some(true),
+                ..Default::default()
+            }),
+            restart_count: Some(3),
+            config: Some(BollardConfig {
+                image: Some("nginx:latest".to_string()),
+                ..Default::default()
+            }),
+            network_settings: Some(NetworkSettings {
+                ports: Some(ports),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_inspect_to_info_parses_created_timestamp() {
+        let info = DockerClient::inspect_to_info(sample_inspect());
+        assert_eq!(info.created, 1705314600);
+        assert_eq!(info.name, "ployer-web");
+        assert_eq!(info.state, "exited");
+        assert_eq!(info.status, "Exited (137)");
+    }
+
+    #[test]
+    fn test_inspect_to_info_reports_crash_details() {
+        let info = DockerClient::inspect_to_info(sample_inspect());
+        assert_eq!(info.restart_count, Some(3));
+        assert_eq!(info.exit_code, Some(137));
+        assert_eq!(info.oom_killed, Some(true));
+
+        // Created but never started: no exit code yet, nothing restarted
+        let created = DockerClient::inspect_to_info(ContainerInspectResponse {
+            state: Some(ContainerState {
+                status: Some(ContainerStateStatusEnum::CREATED),
+                exit_code: Some(0),
+                ..Default::default()
+            }),
+            ..Default::default()
+        });
+        assert_eq!(created.restart_count, Some(0));
+        assert_eq!(created.exit_code, None);
+        assert_eq!(created.oom_killed, Some(false));
+    }
+
+    #[test]
+    fn test_inspect_to_info_parses_ports() {
+        let info = DockerClient::inspect_to_info(sample_inspect());
+        assert_eq!(info.ports.len(), 2);
+
+        assert_eq!(info.ports[0].container_port, 53);
+        assert_eq!(info.ports[0].host_port, None);
+        assert_eq!(info.ports[0].protocol, "udp");
+
+        assert_eq!(info.ports[1].container_port, 3000);
+        assert_eq!(info.ports[1].host_port, Some(8080));
+        assert_eq!(info.ports[1].protocol, "tcp");
+    }
+
+    #[test]
+    fn test_container_filters_to_docker_filters() {
+        let filters = ContainerFilters {
+            status: Some("running".to_string()),
+            label: Some(format!("{}=abc", LABEL_APP_ID)),
+            name: None,
+            publish: Some(3000),
+        }
+        .to_docker_filters();
+
+        assert_eq!(filters.len(), 3);
+        assert_eq!(filters["publish"], vec!["3000"]);
+        assert_eq!(filters["status"], vec!["running"]);
+        assert_eq!(filters["label"], vec!["ployer.app_id=abc"]);
+        assert!(ContainerFilters::default().to_docker_filters().is_empty());
+    }
+
+    #[test]
+    fn test_disk_usage_from_df() {
+        use bollard::models::{BuildCache, Volume, VolumeUsageData};
+
+        const MB: i64 = 1024 * 1024;
+        let df = SystemDataUsageResponse {
+            layers_size: Some(500 * MB),
+            containers: Some(vec![
+                ContainerSummary { size_rw: Some(20 * MB), ..Default::default() },
+                ContainerSummary { size_rw: None, ..Default::default() },
+            ]),
+            volumes: Some(vec![
+                Volume {
+                    usage_data: Some(VolumeUsageData { size: 100 * MB, ref_count: 1 }),
+                    ..Default::default()
+                },
+                Volume {
+                    usage_data: Some(VolumeUsageData { size: -1, ref_count: -1 }),
+                    ..Default::default()
+                },
+            ]),
+            build_cache: Some(vec![
+                BuildCache { size: Some(30 * MB), shared: Some(false), ..Default::default() },
+                BuildCache { size: Some(999 * MB), shared: Some(true), ..Default::default() },
+            ]),
+            images: None,
+        };
+
+        let usage = DockerDiskUsage::from(df);
+        assert_eq!(usage.images_mb, 500);
+        assert_eq!(usage.containers_mb, 20);
+        assert_eq!(usage.volumes_mb, 100);
+        assert_eq!(usage.build_cache_mb, 30);
+        assert_eq!(usage.total_mb, 650);
+    }
+
+    #[test]
+    fn test_parse_restart_policy() {
+        let policy = parse_restart_policy("unless-stopped").unwrap();
+        assert_eq!(policy.name, Some(RestartPolicyNameEnum::UNLESS_STOPPED));
+        assert_eq!(policy.maximum_retry_count, None);
+
+        let policy = parse_restart_policy("on-failure:5").unwrap();
+        assert_eq!(policy.name, Some(RestartPolicyNameEnum::ON_FAILURE));
+        assert_eq!(policy.maximum_retry_count, Some(5));
+
+        assert!(parse_restart_policy("sometimes").is_err());
+        assert!(parse_restart_policy("always:3").is_err());
+        assert!(parse_restart_policy("on-failure:x").is_err());
+    }
+
+    #[tokio::test]
+    #[ignore = "requires a local Docker daemon with the alpine image"]
+    async fn test_create_container_applies_restart_policy() {
+        let docker = DockerClient::new("/var/run/docker.sock").unwrap();
+        let id = docker
+            .create_container(ContainerConfig {
+                image: "alpine:latest".to_string(),
+                name: None,
+                env: None,
+                ports: None,
+                volumes: None,
+                network: None,
+                cmd: Some(vec!["true".to_string()]),
+                restart_policy: Some("on-failure:3".to_string()),
+                labels: None,
+                auto_remove: false,
+            })
+            .await
+            .unwrap();
+
+        let inspect = docker.inspect_container(&id).await.unwrap();
+        docker.remove_container(&id, true).await.unwrap();
+
+        let policy = inspect.host_config.and_then(|h| h.restart_policy).unwrap();
+        assert_eq!(policy.name, Some(RestartPolicyNameEnum::ON_FAILURE));
+        assert_eq!(policy.maximum_retry_count, Some(3));
+    }
+
+    #[tokio::test]
+    #[ignore = "requires a local Docker daemon with the alpine image"]
+    async fn test_auto_remove_container_cleans_up_after_exit() {
+        let docker = DockerClient::new("/var/run/docker.sock").unwrap();
+        let id = docker
+            .create_container(ContainerConfig {
+                image: "alpine:latest".to_string(),
+                name: None,
+                env: None,
+                ports: None,
+                volumes: None,
+                network: None,
+                cmd: Some(vec!["true".to_string()]),
+                restart_policy: None,
+                labels: None,
+                auto_remove: true,
+            })
+            .await
+            .unwrap();
+        docker.start_container(&id).await.unwrap();
+
+        for _ in 0..50 {
+            match docker.inspect_container(&id).await {
+                Err(DockerError::NotFound(_)) => return,
+                _ => tokio::time::sleep(Duration::from_millis(200)).await,
+            }
+        }
+        let _ = docker.remove_container(&id, true).await;
+        panic!("container {} was not removed after exiting", id);
+    }
+
+    #[tokio::test]
+    #[ignore = "requires a local Docker daemon with the alpine image"]
+    async fn test_exec_streams_output_and_exit_code() {
+        let docker = DockerClient::new("/var/run/docker.sock").unwrap();
+        let id = docker
+            .create_container(ContainerConfig {
+                image: "alpine:latest".to_string(),
+                name: None,
+                env: None,
+                ports: None,
+                volumes: None,
+                network: None,
+                cmd: Some(vec!["sleep".to_string(), "30".to_string()]),
+                restart_policy: None,
+                labels: None,
+                auto_remove: false,
+            })
+            .await
+            .unwrap();
+        docker.start_container(&id).await.unwrap();
+
+        let (tx, mut rx) = mpsc::channel(10);
+        let cmd = ["sh", "-c", "echo migrating; echo failed >&2; exit 3"].map(String::from).to_vec();
+        let code = docker.exec(&id, cmd, tx).await;
+        docker.remove_container(&id, true).await.unwrap();
+
+        assert_eq!(code.unwrap(), 3);
+        let mut lines = Vec::new();
+        while let Some(line) = rx.recv().await {
+            lines.push(line);
+        }
+        lines.sort();
+        assert_eq!(lines, ["failed", "migrating"]);
+    }
+
+    const TWO_STAGE_DOCKERFILE: &str = "FROM alpine:latest AS builder\nRUN echo built > /artifact\n\nfrom alpine:latest as production\nCOPY --from=builder /artifact /artifact\nCMD [\"cat\", \"/artifact\"]\n";
+
+    #[test]
+    fn test_dockerfile_for_target_stops_after_stage() {
+        let builder = dockerfile_for_target(TWO_STAGE_DOCKERFILE, "builder").unwrap();
+        assert_eq!(builder, "FROM alpine:latest AS builder\nRUN echo built > /artifact\n\n");
+
+        // Stage names match case-insensitively and the last stage is kept whole
+        let production = dockerfile_for_target(TWO_STAGE_DOCKERFILE, "Production").unwrap();
+        assert_eq!(production, TWO_STAGE_DOCKERFILE);
+
+        assert!(dockerfile_for_target(TWO_STAGE_DOCKERFILE, "missing").is_err());
+    }
+
+    #[test]
+    fn test_build_context_respects_dockerignore() {
+        let context = std::env::temp_dir().join(format!("ployer-dockerignore-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&context);
+        for file in [
+            "Dockerfile",
+            "app.js",
+            "debug.log",
+            ".env",
+            ".git/HEAD",
+            "node_modules/left-pad/index.js",
+            "src/lib.js",
+            "src/secret.pem",
+            "docs/guide.md",
+            "docs/README.md",
+        ] {
+            let path = context.join(file);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, file).unwrap();
         }
+        std::fs::write(
+            context.join(".dockerignore"),
+            "node_modules\n*.log\n.env\n**/*.pem\ndocs\n!docs/README.md\nDockerfile\n",
+        )
+        .unwrap();
+
+        let tar_data = DockerClient::create_build_context_tar(&context, "./Dockerfile", None).unwrap();
+        let mut archive = tar::Archive::new(tar_data.as_slice());
+        let mut files: Vec<String> = archive
+            .entries()
+            .unwrap()
+            .map(|e| e.unwrap())
+            .filter(|e| e.header().entry_type().is_file())
+            .map(|e| e.path().unwrap().to_string_lossy().to_string())
+            .collect();
+        files.sort();
+
+        // The Dockerfile is sent even though it's ignored
+        assert_eq!(
+            files,
+            vec![".dockerignore", "Dockerfile", "app.js", "docs/README.md", "src/lib.js"]
+        );
+
+        std::fs::remove_dir_all(&context).unwrap();
+    }
+
+    #[test]
+    fn test_unpack_workspace_stays_inside_dest() {
+        let root = std::env::temp_dir().join(format!("ployer-unpack-{}", std::process::id()));
+        let dest = root.join("checkout");
+        let outside = root.join("outside");
+        std::fs::create_dir_all(&dest).unwrap();
+        std::fs::create_dir_all(&outside).unwrap();
+        std::os::unix::fs::symlink(&outside, dest.join("escape")).unwrap();
+
+        let mut tar_data = Vec::new();
+        {
+            let mut tar = Builder::new(&mut tar_data);
+            let mut file = |path: &str, contents: &[u8]| {
+                let mut header = Header::new_gnu();
+                header.set_size(contents.len() as u64);
+                header.set_mode(0o644);
+                header.set_entry_type(EntryType::Regular);
+                // set_path refuses `..`, so write the name directly
+                header.as_gnu_mut().unwrap().name[..path.len()].copy_from_slice(path.as_bytes());
+                header.set_cksum();
+                tar.append(&header, contents).unwrap();
+            };
+            file("workspace/dist/app.js", b"built");
+            file("workspace/../outside/parent.txt", b"no");
+            file("workspace/escape/through-link.txt", b"no");
+            file("other/ignored.txt", b"no");
+
+            let mut header = Header::new_gnu();
+            header.set_entry_type(EntryType::Symlink);
+            header.set_size(0);
+            tar.append_link(&mut header, "workspace/current", "dist").unwrap();
+            tar.finish().unwrap();
+        }
+
+        unpack_workspace(&tar_data, &dest).unwrap();
+        assert_eq!(std::fs::read_to_string(dest.join("dist/app.js")).unwrap(), "built");
+        assert_eq!(std::fs::read_to_string(dest.join("current/app.js")).unwrap(), "built");
+        assert_eq!(std::fs::read_dir(&outside).unwrap().count(), 0);
+        assert!(!dest.join("other").exists());
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[tokio::test]
+    #[ignore = "requires a local Docker daemon with access to the alpine image"]
+    async fn test_run_in_workspace_copies_output_back() {
+        let workdir = std::env::temp_dir().join(format!("ployer-workspace-{}", std::process::id()));
+        std::fs::create_dir_all(&workdir).unwrap();
+        std::fs::write(workdir.join("input.txt"), "hello").unwrap();
+
+        let docker = DockerClient::new("/var/run/docker.sock").unwrap();
+        let env = HashMap::from([("APP_ENV".to_string(), "production".to_string())]);
+        let (tx, mut rx) = mpsc::channel(10);
+        let name = format!("ployer-workspace-test-{}", std::process::id());
+        let cmd = "cat input.txt; echo \" $APP_ENV\" > output.txt";
+        let code = docker
+            .run_in_workspace(&name, "alpine:latest", &workdir, cmd, &env, tx)
+            .await
+            .unwrap();
+
+        assert_eq!(code, 0);
+        assert_eq!(rx.recv().await.as_deref(), Some("hello"));
+        assert_eq!(std::fs::read_to_string(workdir.join("output.txt")).unwrap(), "hello production\n");
+        assert!(docker.inspect_container(&name).await.is_err());
+        std::fs::remove_dir_all(&workdir).unwrap();
+    }
+
+    #[tokio::test]
+    #[ignore = "requires a local Docker daemon with access to the alpine image"]
+    async fn test_build_image_to_intermediate_target() {
+        let context = std::env::temp_dir().join(format!("ployer-target-{}", std::process::id()));
+        std::fs::create_dir_all(&context).unwrap();
+        std::fs::write(context.join("Dockerfile"), TWO_STAGE_DOCKERFILE).unwrap();
+
+        let docker = DockerClient::new("/var/run/docker.sock").unwrap();
+        let tag = "ployer-test-target:latest";
+        let mut logs = docker
+            .build_image(&context, None, Some("builder"), tag, false, &[], &HashMap::new(), None)
+            .await
+            .unwrap();
+        let mut output = Vec::new();
+        while let Some(line) = logs.recv().await {
+            output.push(line);
+        }
+        std::fs::remove_dir_all(&context).unwrap();
+
+        assert!(!output.iter().any(|l| l.starts_with("ERROR")), "{:?}", output);
+        // The production stage's COPY never ran
+        assert!(!output.iter().any(|l| l.contains("COPY --from=builder")), "{:?}", output);
+
+        let image = docker.client.inspect_image(tag).await.unwrap();
+        let _ = docker.client.remove_image(tag, None, None).await;
+        assert!(image.config.and_then(|c| c.cmd).is_none_or(|cmd| !cmd.contains(&"/artifact".to_string())));
+    }
+
+    #[test]
+    fn test_registry_host() {
+        assert_eq!(registry_host("ghcr.io/acme/web"), "ghcr.io");
+        assert_eq!(registry_host("localhost:5000/web"), "localhost:5000");
+        assert_eq!(registry_host("localhost/web"), "localhost");
+        assert_eq!(registry_host("acme/web"), "docker.io");
+        assert_eq!(registry_host("web"), "docker.io");
+    }
+
+    #[test]
+    fn test_image_reference_defaults_to_latest() {
+        assert_eq!(image_reference("postgres"), "postgres:latest");
+        assert_eq!(image_reference("postgres:16"), "postgres:16");
+        assert_eq!(image_reference("localhost:5000/web"), "localhost:5000/web:latest");
+        assert_eq!(image_reference("ghcr.io/acme/web:abc123"), "ghcr.io/acme/web:abc123");
+        assert_eq!(image_reference("acme/web@sha256:0123"), "acme/web@sha256:0123");
+    }
+
+    #[test]
+    fn test_describe_push_error_flags_auth_failures() {
+        let denied = describe_push_error("denied: requested access to the resource is denied");
+        assert!(denied.starts_with("Registry rejected the push"));
+        assert!(describe_push_error("unauthorized: authentication required").contains("username, password"));
+
+        let other = describe_push_error("connection reset by peer\n");
+        assert_eq!(other, "Image push failed: connection reset by peer");
+    }
+
+    #[test]
+    fn test_describe_platform_error() {
+        let error = "no matching manifest for linux/arm64/v8 in the manifest list entries";
+        let described = describe_platform_error(error, Some("linux/arm64"));
+        assert!(described.starts_with("Image has no build for platform linux/arm64"));
+        assert!(described.contains(error));
+
+        // Without a requested platform, or for other errors, the message is unchanged
+        assert_eq!(describe_platform_error(error, None), error);
+        assert_eq!(describe_platform_error("manifest unknown", Some("linux/amd64")), "manifest unknown");
+    }
+
+    #[test]
+    fn test_take_lines_buffers_partial_lines() {
+        let mut pending = String::new();
+        assert!(take_lines(&mut pending, "Running migra").is_empty());
+        assert_eq!(take_lines(&mut pending, "tions\r\n3 applied\nDo"), ["Running migrations", "3 applied"]);
+        assert_eq!(pending, "Do");
+        assert_eq!(take_lines(&mut pending, "ne\n"), ["Done"]);
+        assert!(pending.is_empty());
+    }
+
+    #[test]
+    fn test_keep_matching_tails_matching_lines() {
+        let lines = || -> Vec<String> {
+            ["GET /a 200", "POST /b 500", "GET /c 502", "GET /d 200", "GET /e 503"]
+                .iter()
+                .map(|s| s.to_string())
+                .collect()
+        };
+
+        let mut logs = lines();
+        keep_matching(&mut logs, &LogFilter::Contains("GET".into()), Some(2));
+        assert_eq!(logs, ["GET /d 200", "GET /e 503"]);
+
+        let mut logs = lines();
+        let errors = LogFilter::Regex(regex::Regex::new(r" 5\d\d$").unwrap());
+        keep_matching(&mut logs, &errors, None);
+        assert_eq!(logs, ["POST /b 500", "GET /c 502", "GET /e 503"]);
+    }
+
+    #[test]
+    fn test_inspect_to_info_reports_size_and_change_kinds() {
+        assert_eq!(DockerClient::inspect_to_info(sample_inspect()).size_rw, None);
+
+        let info = DockerClient::inspect_to_info(ContainerInspectResponse {
+            size_rw: Some(4096),
+            size_root_fs: Some(150_000_000),
+            ..sample_inspect()
+        });
+        assert_eq!(info.size_rw, Some(4096));
+        assert_eq!(info.size_root_fs, Some(150_000_000));
+
+        assert_eq!(FileChangeKind::from(ChangeType::_0), FileChangeKind::Modified);
+        assert_eq!(FileChangeKind::from(ChangeType::_1), FileChangeKind::Added);
+        assert_eq!(FileChangeKind::from(ChangeType::_2), FileChangeKind::Deleted);
+    }
+
+    #[test]
+    fn test_inspect_env_and_mounts() {
+        use bollard::models::{ContainerConfig as InspectConfig, MountPointTypeEnum};
+
+        let inspect = ContainerInspectResponse {
+            config: Some(InspectConfig {
+                env: Some(vec![
+                    "PATH=/usr/bin".to_string(),
+                    "DATABASE_URL=postgres://db/app?sslmode=require".to_string(),
+                    "EMPTY=".to_string(),
+                ]),
+                ..Default::default()
+            }),
+            mounts: Some(vec![
+                MountPoint {
+                    typ: Some(MountPointTypeEnum::VOLUME),
+                    name: Some("ployer-web-data".to_string()),
+                    source: Some("/var/lib/docker/volumes/ployer-web-data/_data".to_string()),
+                    destination: Some("/data".to_string()),
+                    rw: Some(true),
+                    ..Default::default()
+                },
+                MountPoint {
+                    typ: Some(MountPointTypeEnum::BIND),
+                    name: Some(String::new()),
+                    source: Some("/etc/ssl/certs".to_string()),
+                    destination: Some("/certs".to_string()),
+                    rw: Some(false),
+                    ..Default::default()
+                },
+            ]),
+            ..Default::default()
+        };
+
+        let env = DockerClient::inspect_env(&inspect);
+        assert_eq!(env[1], ("DATABASE_URL".to_string(), "postgres://db/app?sslmode=require".to_string()));
+        assert_eq!(env[2], ("EMPTY".to_string(), String::new()));
+
+        let mounts = DockerClient::inspect_mounts(&inspect);
+        assert_eq!(mounts[0].destination, "/certs");
+        assert_eq!(mounts[0].kind, "bind");
+        assert!(mounts[0].read_only && mounts[0].name.is_none());
+        assert_eq!(mounts[1].name.as_deref(), Some("ployer-web-data"));
+        assert!(!mounts[1].read_only);
+
+        assert!(DockerClient::inspect_env(&ContainerInspectResponse::default()).is_empty());
+    }
+
+    #[test]
+    fn test_inspect_to_info_handles_missing_fields() {
+        let info = DockerClient::inspect_to_info(ContainerInspectResponse::default());
+        assert_eq!(info.created, 0);
+        assert_eq!(info.state, "unknown");
+        assert!(info.ports.is_empty());
     }
 }
@@ -1,8 +1,19 @@
 use anyhow::{anyhow, Result};
+use ployer_core::config::ProxyConfig;
+use ployer_core::http::{build_http_client, http_client_builder};
+use ployer_core::models::{HeaderRules, ProxyHeaders, RateLimit};
 use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
+use std::time::Duration;
 use tracing::{info, warn};
 
+/// Longest a call to the Caddy admin API may take
+const ADMIN_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How many times the running config is read back before a change counts as not applied
+const VERIFY_ATTEMPTS: u32 = 10;
+const VERIFY_INTERVAL: Duration = Duration::from_millis(300);
+
 #[derive(Clone)]
 pub struct CaddyClient {
     admin_url: String,
@@ -10,11 +21,246 @@ pub struct CaddyClient {
     caddyfile_path: PathBuf,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize)]
 pub struct ReverseProxyConfig {
     pub domain: String,
     pub upstream: String, // e.g., "localhost:8080"
     pub enable_https: bool,
+    /// Extra hostnames served by the same route, wildcards allowed (e.g. "*.example.com")
+    pub domains: Vec<String>,
+    /// Redirect `www.<domain>` to the apex `domain`
+    pub www_redirect: bool,
+    /// Require HTTP basic auth as (username, bcrypt hash) before proxying
+    pub basic_auth: Option<(String, String)>,
+    /// Per-client-IP request limit; needs Caddy built with the caddy-ratelimit module
+    pub rate_limit: Option<RateLimit>,
+    /// Active health checks run by Caddy, which stops routing to the upstream while they fail
+    pub health_check: Option<UpstreamHealthCheck>,
+    /// Extra request/response header changes, applied just before proxying
+    pub headers: ProxyHeaders,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct UpstreamHealthCheck {
+    /// Request path, e.g. "/health"; any 2xx response counts as healthy
+    pub path: String,
+    pub interval_secs: u32,
+    pub timeout_secs: u32,
+}
+
+impl ReverseProxyConfig {
+    /// Single-domain route with no aliases or redirects
+    pub fn new(domain: &str, upstream: &str, enable_https: bool) -> Self {
+        Self {
+            domain: domain.to_string(),
+            upstream: upstream.to_string(),
+            enable_https,
+            domains: Vec::new(),
+            www_redirect: false,
+            basic_auth: None,
+            rate_limit: None,
+            health_check: None,
+            headers: ProxyHeaders::default(),
+        }
+    }
+
+    /// All hostnames matched by the proxy route, primary domain first
+    fn hosts(&self) -> Vec<String> {
+        let mut hosts = vec![self.domain.to_lowercase()];
+        for host in &self.domains {
+            let host = host.to_lowercase();
+            if !hosts.contains(&host) {
+                hosts.push(host);
+            }
+        }
+        hosts
+    }
+
+    /// Caddy JSON routes for this config, in the order they must be matched.
+    /// The www redirect goes first so a wildcard alias can't shadow it.
+    fn routes(&self) -> Vec<serde_json::Value> {
+        let mut routes = Vec::new();
+
+        if self.www_redirect {
+            let www = format!("www.{}", self.domain.to_lowercase());
+            routes.push(serde_json::json!({
+                "@id": route_id(&www),
+                "match": [{
+                    "host": [www]
+                }],
+                "handle": [{
+                    "handler": "static_response",
+                    "headers": {
+                        "Location": [format!("{{http.request.scheme}}://{}{{http.request.uri}}", self.domain.to_lowercase())]
+                    },
+                    "status_code": 308
+                }],
+                "terminal": true
+            }));
+        }
+
+        // Rate limiting runs first so it also throttles password guessing
+        let mut handle = Vec::new();
+        if let Some(limit) = &self.rate_limit {
+            handle.push(serde_json::json!({
+                "handler": "rate_limit",
+                "rate_limits": {
+                    "per_client": {
+                        "key": "{http.request.remote.host}",
+                        "window": format!("{}s", limit.window_secs),
+                        "max_events": limit.requests
+                    }
+                }
+            }));
+        }
+        if let Some((username, hash)) = &self.basic_auth {
+            handle.push(serde_json::json!({
+                "handler": "authentication",
+                "providers": {
+                    "http_basic": {
+                        "accounts": [{
+                            "username": username,
+                            "password": hash
+                        }],
+                        "hash": {
+                            "algorithm": "bcrypt"
+                        }
+                    }
+                }
+            }));
+        }
+        if !self.headers.is_empty() {
+            let mut headers = serde_json::json!({ "handler": "headers" });
+            if !self.headers.request.is_empty() {
+                headers["request"] = header_ops(&self.headers.request);
+            }
+            if !self.headers.response.is_empty() {
+                // Deferred so the rules also apply to headers set by the upstream
+                headers["response"] = header_ops(&self.headers.response);
+                headers["response"]["deferred"] = serde_json::json!(true);
+            }
+            handle.push(headers);
+        }
+        // The forwarded headers are always overwritten, so clients can't spoof their address
+        let mut reverse_proxy = serde_json::json!({
+            "@id": upstream_id(&self.domain),
+            "handler": "reverse_proxy",
+            "upstreams": [{
+                "dial": self.upstream
+            }],
+            "headers": {
+                "request": {
+                    "set": {
+                        "X-Forwarded-For": ["{http.request.remote.host}"],
+                        "X-Forwarded-Proto": ["{http.request.scheme}"]
+                    }
+                }
+            }
+        });
+        if let Some(check) = &self.health_check {
+            reverse_proxy["health_checks"] = serde_json::json!({
+                "active": {
+                    "uri": check.path,
+                    "interval": format!("{}s", check.interval_secs),
+                    "timeout": format!("{}s", check.timeout_secs)
+                }
+            });
+        }
+        handle.push(reverse_proxy);
+
+        routes.push(serde_json::json!({
+            "@id": route_id(&self.domain),
+            "match": [{
+                "host": self.hosts()
+            }],
+            "handle": handle
+        }));
+
+        routes
+    }
+
+    /// Caddyfile site blocks for apps.caddy carrying the same settings as
+    /// `routes()`, so they survive a reload. Handlers sit in a `route` block
+    /// to keep the order the admin API routes use.
+    fn caddyfile_blocks(&self) -> String {
+        let address = |host: &str| {
+            if self.enable_https {
+                host.to_string()
+            } else {
+                format!("http://{}", host)
+            }
+        };
+        let mut out = String::new();
+
+        if self.www_redirect {
+            let domain = self.domain.to_lowercase();
+            out.push_str(&format!(
+                "\n{} {{\n    redir {{scheme}}://{}{{uri}} 308\n}}\n",
+                address(&format!("www.{}", domain)),
+                domain
+            ));
+        }
+
+        let hosts: Vec<String> = self.hosts().iter().map(|h| address(h)).collect();
+        out.push_str(&format!("\n{} {{\n    route {{\n", hosts.join(", ")));
+        if let Some(limit) = &self.rate_limit {
+            out.push_str("        rate_limit {\n            zone per_client {\n                key {remote_host}\n");
+            out.push_str(&format!(
+                "                events {}\n                window {}s\n            }}\n        }}\n",
+                limit.requests, limit.window_secs
+            ));
+        }
+        if let Some((username, hash)) = &self.basic_auth {
+            out.push_str(&format!("        basic_auth {{\n            {} {}\n        }}\n", username, hash));
+        }
+        for (name, value) in &self.headers.request.set {
+            out.push_str(&format!("        request_header {} {}\n", name, quote(value)));
+        }
+        for name in &self.headers.request.remove {
+            out.push_str(&format!("        request_header -{}\n", name));
+        }
+        if !self.headers.response.is_empty() {
+            // Deferred so the rules also apply to headers set by the upstream
+            out.push_str("        header {\n            defer\n");
+            for (name, value) in &self.headers.response.set {
+                out.push_str(&format!("            {} {}\n", name, quote(value)));
+            }
+            for name in &self.headers.response.remove {
+                out.push_str(&format!("            -{}\n", name));
+            }
+            out.push_str("        }\n");
+        }
+        // The forwarded headers are always overwritten, so clients can't spoof their address
+        out.push_str(&format!("        reverse_proxy {} {{\n", self.upstream));
+        out.push_str("            header_up X-Forwarded-For {remote_host}\n");
+        out.push_str("            header_up X-Forwarded-Proto {scheme}\n");
+        if let Some(check) = &self.health_check {
+            out.push_str(&format!(
+                "            health_uri {}\n            health_interval {}s\n            health_timeout {}s\n",
+                check.path, check.interval_secs, check.timeout_secs
+            ));
+        }
+        out.push_str("        }\n    }\n}\n");
+        out
+    }
+}
+
+/// A Caddyfile token in double quotes
+fn quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Whether an apps.caddy line opens the site block whose first address is `domain`
+fn is_block_header(line: &str, domain: &str) -> bool {
+    let Some(addresses) = line.trim().strip_suffix(" {") else {
+        return false;
+    };
+    let first = addresses.split(", ").next().unwrap_or_default();
+    let host = first
+        .strip_prefix("http://")
+        .or_else(|| first.strip_prefix("https://"))
+        .unwrap_or(first);
+    host == domain
 }
 
 #[derive(Debug, Deserialize)]
@@ -24,14 +270,113 @@ pub struct RouteInfo {
     pub ssl_status: String,
 }
 
+/// `@id` of the admin API route serving a domain
+fn route_id(domain: &str) -> String {
+    format!("ployer-{}", domain.replace('.', "-"))
+}
+
+/// `@id` of the `reverse_proxy` handler inside a domain's route
+fn upstream_id(domain: &str) -> String {
+    format!("{}-upstream", route_id(domain))
+}
+
+/// Caddy header operations (`set`, `delete`) for one direction
+fn header_ops(rules: &HeaderRules) -> serde_json::Value {
+    let mut ops = serde_json::Map::new();
+    if !rules.set.is_empty() {
+        let set: serde_json::Map<_, _> = rules
+            .set
+            .iter()
+            .map(|(name, value)| (name.clone(), serde_json::json!([value])))
+            .collect();
+        ops.insert("set".to_string(), set.into());
+    }
+    if !rules.remove.is_empty() {
+        ops.insert("delete".to_string(), serde_json::json!(rules.remove));
+    }
+    ops.into()
+}
+
+/// `dial` addresses of every `reverse_proxy` handler in a route
+fn upstream_dials(route: &serde_json::Value) -> Vec<&serde_json::Value> {
+    route["handle"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(|handler| handler["handler"] == "reverse_proxy")
+        .flat_map(|handler| handler["upstreams"].as_array().into_iter().flatten())
+        .map(|upstream| &upstream["dial"])
+        .collect()
+}
+
+/// Whether Caddy's running routes include `expected`, judged by its `@id`,
+/// host matchers and upstreams
+fn route_applied(routes: &[serde_json::Value], expected: &serde_json::Value) -> bool {
+    routes.iter().any(|route| {
+        route["@id"] == expected["@id"]
+            && route["match"] == expected["match"]
+            && upstream_dials(route) == upstream_dials(expected)
+    })
+}
+
+/// Replace the `reverse_proxy` target in a domain's apps.caddy block.
+/// Returns None when the file has no block for the domain.
+fn rewrite_upstream(content: &str, domain: &str, upstream: &str) -> Option<String> {
+    let mut in_block = false;
+    let mut replaced = false;
+    let mut out = Vec::new();
+
+    for line in content.split('\n') {
+        if is_block_header(line, domain) {
+            in_block = true;
+        } else if in_block && line == "}" {
+            in_block = false;
+        } else if in_block && line.trim_start().starts_with("reverse_proxy ") {
+            let indent = &line[..line.len() - line.trim_start().len()];
+            // Keep the opening brace of a block with proxy options
+            let options = if line.ends_with(" {") { " {" } else { "" };
+            out.push(format!("{}reverse_proxy {}{}", indent, upstream, options));
+            replaced = true;
+            continue;
+        }
+        out.push(line.to_string());
+    }
+
+    replaced.then(|| out.join("\n"))
+}
+
+/// Drop a domain's block (and the blank line before it) from apps.caddy.
+/// Returns None when the file has no block for the domain.
+fn remove_block(content: &str, domain: &str) -> Option<String> {
+    let mut in_block = false;
+    let mut found = false;
+    let mut out: Vec<&str> = Vec::new();
+
+    for line in content.split('\n') {
+        if is_block_header(line, domain) {
+            in_block = true;
+            found = true;
+            if out.last() == Some(&"") {
+                out.pop();
+            }
+        } else if in_block {
+            in_block = line != "}";
+        } else {
+            out.push(line);
+        }
+    }
+
+    found.then(|| out.join("\n"))
+}
+
 impl CaddyClient {
-    pub fn new(admin_url: &str, caddyfile_path: &str) -> Self {
+    pub fn new(admin_url: &str, caddyfile_path: &str, proxy: &ProxyConfig) -> Result<Self> {
         info!("Caddy client configured for {}", admin_url);
-        Self {
+        Ok(Self {
             admin_url: admin_url.to_string(),
-            client: reqwest::Client::new(),
+            client: build_http_client(Some(proxy), ADMIN_TIMEOUT)?,
             caddyfile_path: PathBuf::from(caddyfile_path),
-        }
+        })
     }
 
     fn apps_caddyfile(&self) -> PathBuf {
@@ -63,6 +408,33 @@ impl CaddyClient {
         }
 
         // Reload Caddy to pick up the new config
+        self.reload();
+
+        Ok(())
+    }
+
+    /// Write a route's full settings to apps.caddy, replacing the domain's block
+    /// and its www redirect, then reload Caddy. Settings pushed only through the
+    /// admin API would be lost on the next reload.
+    pub fn persist_config(&self, config: &ReverseProxyConfig) -> Result<()> {
+        let apps_file = self.apps_caddyfile();
+        let mut content = std::fs::read_to_string(&apps_file).unwrap_or_default();
+        let domain = config.domain.to_lowercase();
+        for host in [domain.clone(), format!("www.{}", domain)] {
+            if let Some(rest) = remove_block(&content, &host) {
+                content = rest;
+            }
+        }
+        content.push_str(&config.caddyfile_blocks());
+        std::fs::write(&apps_file, content)?;
+        info!("Persisted Caddy route for {} -> {}", domain, config.upstream);
+
+        self.reload();
+        Ok(())
+    }
+
+    /// Gracefully reload Caddy from the Caddyfile
+    fn reload(&self) {
         let status = std::process::Command::new("caddy")
             .args(["reload", "--config", self.caddyfile_path.to_str().unwrap_or("/opt/ployer/Caddyfile")])
             .status();
@@ -72,8 +444,66 @@ impl CaddyClient {
             Ok(s) => warn!("Caddy reload exited with status {}", s),
             Err(e) => warn!("Failed to run caddy reload: {}", e),
         }
+    }
 
-        Ok(())
+    /// Whether apps.caddy has a route for the domain
+    pub fn is_persisted(&self, domain: &str) -> bool {
+        let content = std::fs::read_to_string(self.apps_caddyfile()).unwrap_or_default();
+        content.split('\n').any(|line| is_block_header(line, domain))
+    }
+
+    /// Point an existing route at a new upstream.
+    /// Patches the route's upstreams by `@id` through the admin API; routes loaded
+    /// from apps.caddy carry no id, so those are rewritten on disk and reloaded instead.
+    /// Either way Caddy swaps the config without dropping in-flight requests.
+    pub async fn update_upstream(&self, domain: &str, new_upstream: &str) -> Result<()> {
+        info!("Updating Caddy upstream: {} -> {}", domain, new_upstream);
+
+        // Keep the persisted route in sync so the new upstream survives restarts
+        let apps_file = self.apps_caddyfile();
+        let existing = std::fs::read_to_string(&apps_file).unwrap_or_default();
+        let rewritten = rewrite_upstream(&existing, domain, new_upstream);
+        if let Some(ref content) = rewritten {
+            std::fs::write(&apps_file, content)?;
+        }
+
+        let url = format!("{}/id/{}/upstreams", self.admin_url, upstream_id(domain));
+        let resp = self.client
+            .patch(&url)
+            .json(&serde_json::json!([{ "dial": new_upstream }]))
+            .send()
+            .await;
+
+        match resp {
+            Ok(r) if r.status().is_success() => {
+                let id = route_id(domain);
+                let dial = serde_json::json!(new_upstream);
+                let applied = self
+                    .wait_for_routes(|routes| {
+                        routes
+                            .iter()
+                            .any(|route| route["@id"] == id.as_str() && upstream_dials(route) == [&dial])
+                    })
+                    .await?;
+                if !applied {
+                    return Err(anyhow!(
+                        "Caddy accepted the upstream change for {} but is still not routing to {}; check the Caddy logs",
+                        domain, new_upstream
+                    ));
+                }
+                info!("Caddy upstream updated for {}", domain);
+                Ok(())
+            }
+            _ if rewritten.is_some() => {
+                self.reload();
+                Ok(())
+            }
+            Ok(r) => {
+                let error_text = r.text().await.unwrap_or_else(|_| "Unknown error".to_string());
+                Err(anyhow!("Failed to update Caddy upstream for {}: {}", domain, error_text))
+            }
+            Err(e) => Err(anyhow!("Failed to update Caddy upstream for {}: {}", domain, e)),
+        }
     }
 
     pub async fn ping(&self) -> Result<bool> {
@@ -90,49 +520,122 @@ impl CaddyClient {
     /// Add a reverse proxy route for a domain
     /// Caddy will automatically obtain SSL certificates via Let's Encrypt
     pub async fn add_route(&self, config: ReverseProxyConfig) -> Result<()> {
-        info!("Adding Caddy route: {} -> {}", config.domain, config.upstream);
-
-        // Build Caddy JSON config for reverse proxy
-        let caddy_config = serde_json::json!({
-            "match": [{
-                "host": [config.domain]
-            }],
-            "handle": [{
-                "handler": "reverse_proxy",
-                "upstreams": [{
-                    "dial": config.upstream
-                }]
-            }]
-        });
+        info!("Adding Caddy route: {} -> {}", config.hosts().join(", "), config.upstream);
 
-        // POST to Caddy's config API
+        // POST to Caddy's config API; each POST appends, so routes keep their order
         let url = format!("{}/config/apps/http/servers/srv0/routes", self.admin_url);
-        let resp = self.client
-            .post(&url)
-            .json(&caddy_config)
-            .send()
+        let routes = config.routes();
+        for caddy_config in &routes {
+            let resp = self.client
+                .post(&url)
+                .json(caddy_config)
+                .send()
+                .await?;
+
+            if !resp.status().is_success() {
+                let error_text = resp.text().await.unwrap_or_else(|_| "Unknown error".to_string());
+                warn!("Failed to add Caddy route: {}", error_text);
+                if config.rate_limit.is_some() && error_text.contains("rate_limit") {
+                    return Err(anyhow!(
+                        "Caddy rejected the rate_limit handler; Caddy must be built with the caddy-ratelimit module: {}",
+                        error_text
+                    ));
+                }
+                return Err(anyhow!("Failed to add Caddy route: {}", error_text));
+            }
+        }
+
+        // A 200 only means the config was accepted; make sure it's what Caddy is running
+        let applied = self
+            .wait_for_routes(|live| routes.iter().all(|route| route_applied(live, route)))
             .await?;
+        if !applied {
+            return Err(anyhow!(
+                "Caddy accepted the route for {} but it is not in the running config; check the Caddy logs",
+                config.domain
+            ));
+        }
 
-        if resp.status().is_success() {
-            info!("Caddy route added successfully for {}", config.domain);
-            Ok(())
-        } else {
-            let error_text = resp.text().await.unwrap_or_else(|_| "Unknown error".to_string());
-            warn!("Failed to add Caddy route: {}", error_text);
-            Err(anyhow!("Failed to add Caddy route: {}", error_text))
+        info!("Caddy route added successfully for {}", config.domain);
+        Ok(())
+    }
+
+    /// Read the running routes back until `applied` holds for them, retrying briefly.
+    /// Returns false if it still doesn't after the last attempt.
+    async fn wait_for_routes<F>(&self, applied: F) -> Result<bool>
+    where
+        F: Fn(&[serde_json::Value]) -> bool,
+    {
+        let url = format!("{}/config/apps/http/servers/srv0/routes", self.admin_url);
+        for attempt in 1..=VERIFY_ATTEMPTS {
+            let resp = self.client.get(&url).send().await?;
+            if !resp.status().is_success() {
+                let error_text = resp.text().await.unwrap_or_else(|_| "Unknown error".to_string());
+                return Err(anyhow!("Failed to read back Caddy routes: {}", error_text));
+            }
+            // `null` when the server has no routes at all
+            let routes = resp.json::<Option<Vec<serde_json::Value>>>().await?.unwrap_or_default();
+            if applied(&routes) {
+                return Ok(true);
+            }
+            if attempt < VERIFY_ATTEMPTS {
+                tokio::time::sleep(VERIFY_INTERVAL).await;
+            }
+        }
+        Ok(false)
+    }
+
+    /// Swap a domain's admin API route for a new config.
+    /// If Caddy rejects the new route, the previous one is put back.
+    pub async fn replace_route(&self, config: ReverseProxyConfig) -> Result<()> {
+        let id_url = format!("{}/id/{}", self.admin_url, route_id(&config.domain));
+
+        let previous = match self.client.get(&id_url).send().await? {
+            resp if resp.status().is_success() => Some(resp.json::<serde_json::Value>().await?),
+            _ => None,
+        };
+        if previous.is_some() {
+            self.client.delete(&id_url).send().await?.error_for_status()?;
+        }
+
+        let result = self.add_route(config).await;
+        if let (Err(_), Some(previous)) = (&result, previous) {
+            let url = format!("{}/config/apps/http/servers/srv0/routes", self.admin_url);
+            if let Err(e) = self.client.post(&url).json(&previous).send().await {
+                warn!("Failed to restore previous Caddy route: {}", e);
+            }
         }
+        result
     }
 
-    /// Remove a route by domain
+    /// Remove a domain's routes, both the admin API ones and the blocks persisted
+    /// in apps.caddy, including its www redirect. Missing routes are fine.
     pub async fn remove_route(&self, domain: &str) -> Result<()> {
         info!("Removing Caddy route for domain: {}", domain);
 
-        // For simplicity, we'll reload the entire config without this domain
-        // In production, you'd use Caddy's @id-based route removal
-        warn!("Route removal is a stub - implement with Caddy route IDs in production");
+        let apps_file = self.apps_caddyfile();
+        let existing = std::fs::read_to_string(&apps_file).unwrap_or_default();
+        let mut content = existing.clone();
+        for host in [domain.to_string(), format!("www.{}", domain)] {
+            if let Some(rest) = remove_block(&content, &host) {
+                content = rest;
+            }
+        }
+        if content != existing {
+            std::fs::write(&apps_file, content)?;
+            self.reload();
+        }
+
+        for id in [route_id(domain), route_id(&format!("www.{}", domain))] {
+            let url = format!("{}/id/{}", self.admin_url, id);
+            let resp = self.client.delete(&url).send().await?;
+            // Caddy answers 404 for an unknown @id
+            if !resp.status().is_success() && resp.status() != reqwest::StatusCode::NOT_FOUND {
+                let error_text = resp.text().await.unwrap_or_else(|_| "Unknown error".to_string());
+                return Err(anyhow!("Failed to remove Caddy route {}: {}", id, error_text));
+            }
+        }
 
-        // TODO: Implement proper route removal using Caddy's route IDs
-        // For now, just log the intention
         Ok(())
     }
 
@@ -153,20 +656,229 @@ impl CaddyClient {
         Ok(Vec::new())
     }
 
-    /// Get SSL certificate status for a domain
-    pub async fn get_ssl_status(&self, domain: &str) -> Result<String> {
-        info!("Checking SSL status for domain: {}", domain);
+    /// Whether Caddy is serving a publicly trusted certificate for a domain.
+    ///
+    /// The domain is resolved to Caddy's own address, so the check reflects
+    /// certificate issuance rather than public DNS. Errors when Caddy is down.
+    pub async fn get_ssl_status(&self, domain: &str) -> Result<SslStatus> {
+        if !self.ping().await? {
+            return Err(anyhow!("Caddy admin API is unreachable"));
+        }
 
-        // Query Caddy's certificate storage
-        let url = format!("{}/config/apps/tls/certificates", self.admin_url);
-        let resp = self.client.get(&url).send().await;
+        let admin = reqwest::Url::parse(&self.admin_url)?;
+        let host = admin.host_str().unwrap_or("localhost");
+        let addr = tokio::net::lookup_host((host, 443))
+            .await?
+            .next()
+            .ok_or_else(|| anyhow!("Could not resolve Caddy host '{}'", host))?;
 
-        match resp {
-            Ok(r) if r.status().is_success() => {
-                // For MVP, assume SSL is active if Caddy is running
-                Ok("active".to_string())
-            }
-            _ => Ok("pending".to_string())
+        // Never proxied: the handshake has to be with Caddy itself
+        let client = http_client_builder(None, Duration::from_secs(10))?
+            .resolve(domain, addr)
+            .redirect(reqwest::redirect::Policy::none())
+            .build()?;
+
+        // Any HTTP response means the TLS handshake verified; the upstream may still be down
+        match client.head(format!("https://{}/", domain)).send().await {
+            Ok(_) => Ok(SslStatus::Active),
+            Err(_) => Ok(SslStatus::Pending),
+        }
+    }
+}
+
+/// Certificate state of a domain as seen through Caddy
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SslStatus {
+    /// A trusted certificate is being served
+    Active,
+    /// No certificate yet (still issuing, DNS not pointed here, or issuance failed)
+    Pending,
+}
+
+impl SslStatus {
+    pub fn as_str(&self) -> &str {
+        match self {
+            SslStatus::Active => "active",
+            SslStatus::Pending => "pending",
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_single_domain_route_is_unchanged() {
+        let routes = ReverseProxyConfig::new("app.example.com", "localhost:3000", true).routes();
+        assert_eq!(routes.len(), 1);
+        assert_eq!(routes[0]["@id"], "ployer-app-example-com");
+        assert_eq!(routes[0]["match"][0]["host"], serde_json::json!(["app.example.com"]));
+        assert_eq!(routes[0]["handle"][0]["upstreams"][0]["dial"], "localhost:3000");
+    }
+
+    #[test]
+    fn test_wildcard_and_www_redirect_routes() {
+        let mut config = ReverseProxyConfig::new("example.com", "localhost:3000", true);
+        config.domains = vec!["*.example.com".to_string(), "EXAMPLE.com".to_string()];
+        config.www_redirect = true;
+
+        let routes = config.routes();
+        assert_eq!(routes.len(), 2);
+
+        // Redirect must precede the wildcard route, which would otherwise match www
+        assert_eq!(routes[0]["match"][0]["host"], serde_json::json!(["www.example.com"]));
+        assert_eq!(routes[0]["handle"][0]["status_code"], 308);
+        assert_eq!(
+            routes[0]["handle"][0]["headers"]["Location"][0],
+            "{http.request.scheme}://example.com{http.request.uri}"
+        );
+
+        assert_eq!(routes[1]["match"][0]["host"], serde_json::json!(["example.com", "*.example.com"]));
+        assert_eq!(routes[1]["@id"], "ployer-example-com");
+    }
+
+    #[test]
+    fn test_rewrite_upstream_only_touches_matching_block() {
+        let content = "\nhttp://web.example.com {\n    reverse_proxy localhost:3000\n}\n\nhttp://api.example.com {\n    reverse_proxy localhost:4000\n}\n";
+
+        let rewritten = rewrite_upstream(content, "web.example.com", "localhost:49153").unwrap();
+        assert!(rewritten.contains("http://web.example.com {\n    reverse_proxy localhost:49153\n}"));
+        assert!(rewritten.contains("reverse_proxy localhost:4000"));
+        assert_eq!(rewritten.len(), content.len() + 1);
+
+        assert!(rewrite_upstream(content, "other.example.com", "localhost:1").is_none());
+    }
+
+    #[test]
+    fn test_basic_auth_precedes_reverse_proxy() {
+        let mut config = ReverseProxyConfig::new("app.example.com", "localhost:3000", true);
+        config.basic_auth = Some(("admin".to_string(), "$2b$12$hash".to_string()));
+
+        let handle = &config.routes()[0]["handle"];
+        assert_eq!(handle[0]["handler"], "authentication");
+        assert_eq!(handle[0]["providers"]["http_basic"]["accounts"][0]["username"], "admin");
+        assert_eq!(handle[1]["handler"], "reverse_proxy");
+        assert_eq!(handle[1]["@id"], "ployer-app-example-com-upstream");
+    }
+
+    #[test]
+    fn test_active_health_check_on_reverse_proxy() {
+        let mut config = ReverseProxyConfig::new("app.example.com", "localhost:3000", true);
+        assert!(config.routes()[0]["handle"][0].get("health_checks").is_none());
+
+        config.health_check = Some(UpstreamHealthCheck {
+            path: "/health".to_string(),
+            interval_secs: 15,
+            timeout_secs: 3,
+        });
+        let active = &config.routes()[0]["handle"][0]["health_checks"]["active"];
+        assert_eq!(active["uri"], "/health");
+        assert_eq!(active["interval"], "15s");
+        assert_eq!(active["timeout"], "3s");
+    }
+
+    #[test]
+    fn test_forwarded_and_custom_headers() {
+        let mut config = ReverseProxyConfig::new("app.example.com", "localhost:3000", true);
+        let handle = &config.routes()[0]["handle"];
+        assert_eq!(handle.as_array().unwrap().len(), 1);
+        let forwarded = &handle[0]["headers"]["request"]["set"];
+        assert_eq!(forwarded["X-Forwarded-For"], serde_json::json!(["{http.request.remote.host}"]));
+        assert_eq!(forwarded["X-Forwarded-Proto"], serde_json::json!(["{http.request.scheme}"]));
+
+        config.headers.request.set.insert("X-Tenant".to_string(), "acme".to_string());
+        config.headers.response.remove.push("Server".to_string());
+        let handle = &config.routes()[0]["handle"];
+        assert_eq!(handle[0]["handler"], "headers");
+        assert_eq!(handle[0]["request"]["set"]["X-Tenant"], serde_json::json!(["acme"]));
+        assert!(handle[0]["request"].get("delete").is_none());
+        assert_eq!(handle[0]["response"]["delete"], serde_json::json!(["Server"]));
+        assert_eq!(handle[0]["response"]["deferred"], true);
+        assert_eq!(handle[1]["handler"], "reverse_proxy");
+    }
+
+    #[test]
+    fn test_rate_limit_runs_before_basic_auth() {
+        let mut config = ReverseProxyConfig::new("app.example.com", "localhost:3000", true);
+        config.basic_auth = Some(("admin".to_string(), "$2b$12$hash".to_string()));
+        config.rate_limit = Some(RateLimit { requests: 100, window_secs: 60 });
+
+        let handle = &config.routes()[0]["handle"];
+        assert_eq!(handle[0]["handler"], "rate_limit");
+        assert_eq!(handle[0]["rate_limits"]["per_client"]["max_events"], 100);
+        assert_eq!(handle[0]["rate_limits"]["per_client"]["window"], "60s");
+        assert_eq!(handle[1]["handler"], "authentication");
+        assert_eq!(handle[2]["handler"], "reverse_proxy");
+    }
+
+    #[test]
+    fn test_route_applied_checks_hosts_and_upstreams() {
+        let mut config = ReverseProxyConfig::new("example.com", "localhost:3000", true);
+        config.www_redirect = true;
+        let expected = config.routes();
+
+        // Caddy echoes the stored JSON back, possibly alongside other routes
+        let mut live = vec![serde_json::json!({ "match": [{ "host": ["dashboard.example.com"] }] })];
+        live.extend(expected.clone());
+        assert!(expected.iter().all(|route| route_applied(&live, route)));
+        assert!(!route_applied(&live[..2], &expected[1]));
+
+        // Same @id but still pointing at the old upstream
+        let stale = ReverseProxyConfig::new("example.com", "localhost:4000", true).routes();
+        assert!(!route_applied(&stale, &expected[1]));
+        assert_eq!(upstream_dials(&expected[1]), [&serde_json::json!("localhost:3000")]);
+        assert!(upstream_dials(&expected[0]).is_empty());
+    }
+
+    #[test]
+    fn test_caddyfile_blocks_carry_route_settings() {
+        let mut config = ReverseProxyConfig::new("example.com", "localhost:3000", false);
+        config.domains = vec!["*.example.com".to_string()];
+        config.www_redirect = true;
+        config.basic_auth = Some(("admin".to_string(), "$2b$12$hash".to_string()));
+        config.rate_limit = Some(RateLimit { requests: 100, window_secs: 60 });
+        config.health_check = Some(UpstreamHealthCheck {
+            path: "/health".to_string(),
+            interval_secs: 15,
+            timeout_secs: 3,
+        });
+        config.headers.request.set.insert("X-Tenant".to_string(), "a \"b\"".to_string());
+        config.headers.response.remove.push("Server".to_string());
+
+        let blocks = config.caddyfile_blocks();
+        assert!(blocks.starts_with("\nhttp://www.example.com {\n    redir {scheme}://example.com{uri} 308\n}\n"));
+        assert!(blocks.contains("\nhttp://example.com, http://*.example.com {\n    route {\n        rate_limit {"));
+        assert!(blocks.contains("        basic_auth {\n            admin $2b$12$hash\n        }\n"));
+        assert!(blocks.contains("        request_header X-Tenant \"a \\\"b\\\"\"\n"));
+        assert!(blocks.contains("        header {\n            defer\n            -Server\n        }\n"));
+        assert!(blocks.contains("            health_uri /health\n            health_interval 15s\n"));
+
+        // Ordered like the admin API handlers
+        let position = |needle: &str| blocks.find(needle).unwrap();
+        assert!(position("rate_limit") < position("basic_auth"));
+        assert!(position("basic_auth") < position("request_header"));
+        assert!(position("header {") < position("reverse_proxy"));
+
+        // Cutover still finds the upstream and keeps the options block open
+        let swapped = rewrite_upstream(&blocks, "example.com", "localhost:49153").unwrap();
+        assert!(swapped.contains("        reverse_proxy localhost:49153 {\n            header_up"));
+
+        // Replacing the config drops both blocks
+        let rest = remove_block(&blocks, "www.example.com").unwrap();
+        assert_eq!(remove_block(&rest, "example.com").unwrap(), "");
+
+        let https = ReverseProxyConfig::new("example.com", "localhost:3000", true).caddyfile_blocks();
+        assert!(https.starts_with("\nexample.com {\n"));
+    }
+
+    #[test]
+    fn test_remove_block_keeps_other_domains() {
+        let content = "\nhttp://web.example.com {\n    basic_auth {\n        admin $2b$12$hash\n    }\n    reverse_proxy localhost:3000\n}\n\nhttp://api.example.com {\n    reverse_proxy localhost:4000\n}\n";
+
+        let removed = remove_block(content, "web.example.com").unwrap();
+        assert_eq!(removed, "\nhttp://api.example.com {\n    reverse_proxy localhost:4000\n}\n");
+        assert_eq!(remove_block(&removed, "api.example.com").unwrap(), "");
+        assert!(remove_block(content, "other.example.com").is_none());
+    }
+}
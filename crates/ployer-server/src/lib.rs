@@ -1,10 +1,12 @@
 use anyhow::Result;
-use sysinfo::System;
+use sysinfo::{Disks, System};
+use std::path::Path;
 use std::time::Duration;
 use tracing::info;
 
 pub struct ServerManager {
     system: System,
+    disks: Disks,
 }
 
 impl ServerManager {
@@ -12,16 +14,31 @@ impl ServerManager {
         info!("Server manager initialized");
         Self {
             system: System::new_all(),
+            disks: Disks::new_with_refreshed_list(),
         }
     }
 
-    pub fn local_stats(&mut self) -> LocalStats {
+    /// Memory and CPU usage, plus disk usage of the filesystem holding `data_dir`
+    pub fn local_stats(&mut self, data_dir: &Path) -> LocalStats {
         self.system.refresh_all();
+        self.disks.refresh_list();
+
+        let (disk_total, disk_available) = containing_mount(
+            self.disks
+                .list()
+                .iter()
+                .map(|d| (d.mount_point(), d.total_space(), d.available_space())),
+            data_dir,
+        )
+        .unwrap_or((0, 0));
+
         LocalStats {
             total_memory_mb: self.system.total_memory() / 1024 / 1024,
             used_memory_mb: self.system.used_memory() / 1024 / 1024,
             cpu_count: self.system.cpus().len() as u32,
             cpu_usage: self.system.global_cpu_usage(),
+            disk_total_mb: disk_total / 1024 / 1024,
+            disk_used_mb: disk_total.saturating_sub(disk_available) / 1024 / 1024,
         }
     }
 
@@ -53,4 +70,37 @@ pub struct LocalStats {
     pub used_memory_mb: u64,
     pub cpu_count: u32,
     pub cpu_usage: f32,
+    pub disk_total_mb: u64,
+    pub disk_used_mb: u64,
+}
+
+/// (total, available) bytes of the most specific mount point containing `path`
+fn containing_mount<'a>(
+    mounts: impl IntoIterator<Item = (&'a Path, u64, u64)>,
+    path: &Path,
+) -> Option<(u64, u64)> {
+    mounts
+        .into_iter()
+        .filter(|(mount, _, _)| path.starts_with(mount))
+        .max_by_key(|(mount, _, _)| mount.components().count())
+        .map(|(_, total, available)| (total, available))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_containing_mount_picks_most_specific() {
+        let mounts = [
+            (Path::new("/"), 100, 50),
+            (Path::new("/var/lib/docker"), 1000, 200),
+            (Path::new("/var/lib/dockerish"), 7, 7),
+        ];
+
+        assert_eq!(containing_mount(mounts, Path::new("/var/lib/docker")), Some((1000, 200)));
+        assert_eq!(containing_mount(mounts, Path::new("/var/lib/docker/overlay2")), Some((1000, 200)));
+        assert_eq!(containing_mount(mounts, Path::new("/tmp/ployer-builds")), Some((100, 50)));
+        assert_eq!(containing_mount(mounts[1..].iter().copied(), Path::new("/tmp")), None);
+    }
 }
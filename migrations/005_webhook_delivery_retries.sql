@@ -0,0 +1,5 @@
+-- Track retry attempts for failed webhook deliveries
+ALTER TABLE webhook_deliveries ADD COLUMN attempts INTEGER NOT NULL DEFAULT 1;
+ALTER TABLE webhook_deliveries ADD COLUMN last_attempt_at TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_webhook_deliveries_status ON webhook_deliveries(status, delivered_at);
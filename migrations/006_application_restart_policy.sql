@@ -0,0 +1,2 @@
+-- Docker restart policy applied to deployed containers
+ALTER TABLE applications ADD COLUMN restart_policy TEXT NOT NULL DEFAULT 'unless-stopped';
@@ -0,0 +1,11 @@
+-- Named Docker volumes mounted into an application's containers
+CREATE TABLE IF NOT EXISTS application_volumes (
+    id TEXT PRIMARY KEY NOT NULL,
+    application_id TEXT NOT NULL REFERENCES applications(id) ON DELETE CASCADE,
+    volume_name TEXT NOT NULL,
+    container_path TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    UNIQUE (application_id, container_path)
+);
+
+CREATE INDEX IF NOT EXISTS idx_application_volumes_app ON application_volumes(application_id);
@@ -0,0 +1,2 @@
+-- Extra published ports as a JSON array of {container_port, host_port, protocol}
+ALTER TABLE applications ADD COLUMN ports TEXT NOT NULL DEFAULT '[]';
@@ -0,0 +1,3 @@
+-- HTTP method and accepted status ("200", "200-299" or "2xx") for health checks
+ALTER TABLE health_checks ADD COLUMN method TEXT NOT NULL DEFAULT 'GET';
+ALTER TABLE health_checks ADD COLUMN expected_status TEXT NOT NULL DEFAULT '2xx';
@@ -0,0 +1,2 @@
+-- Probe kind for health checks: 'http' requests the path, 'tcp' only connects to the port
+ALTER TABLE health_checks ADD COLUMN check_type TEXT NOT NULL DEFAULT 'http';
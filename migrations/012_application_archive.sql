@@ -0,0 +1,2 @@
+-- Archived applications are hidden from listings but keep their deployment history
+ALTER TABLE applications ADD COLUMN archived_at TEXT;
@@ -0,0 +1,2 @@
+-- Host port the deployment's primary container port was actually published on
+ALTER TABLE deployments ADD COLUMN host_port INTEGER;
@@ -0,0 +1,2 @@
+-- Private Docker network each application's containers are attached to
+ALTER TABLE applications ADD COLUMN network_name TEXT;
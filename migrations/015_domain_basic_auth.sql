@@ -0,0 +1,3 @@
+-- Optional HTTP basic auth gate in front of a domain's proxy route
+ALTER TABLE domains ADD COLUMN basic_auth_user TEXT;
+ALTER TABLE domains ADD COLUMN basic_auth_hash TEXT;
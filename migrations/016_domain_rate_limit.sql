@@ -0,0 +1,3 @@
+-- Optional per-client-IP request limit applied at the proxy
+ALTER TABLE domains ADD COLUMN rate_limit_requests INTEGER;
+ALTER TABLE domains ADD COLUMN rate_limit_window_secs INTEGER;
@@ -0,0 +1,5 @@
+-- Author contact details and the pusher's avatar for each delivery
+ALTER TABLE webhook_deliveries ADD COLUMN author_email TEXT;
+ALTER TABLE webhook_deliveries ADD COLUMN committer_name TEXT;
+ALTER TABLE webhook_deliveries ADD COLUMN committer_email TEXT;
+ALTER TABLE webhook_deliveries ADD COLUMN avatar_url TEXT;
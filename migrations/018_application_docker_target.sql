@@ -0,0 +1,2 @@
+-- Dockerfile stage to build, for multi-stage builds. NULL builds the final stage
+ALTER TABLE applications ADD COLUMN docker_target TEXT;
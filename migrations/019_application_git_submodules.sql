@@ -0,0 +1,2 @@
+-- Opt-in recursive submodule checkout on clone and pull
+ALTER TABLE applications ADD COLUMN git_submodules INTEGER NOT NULL DEFAULT 0;
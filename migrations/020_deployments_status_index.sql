@@ -0,0 +1,2 @@
+-- Cross-application listing of deployments by status, newest first
+CREATE INDEX IF NOT EXISTS idx_deployments_status ON deployments(status, started_at);
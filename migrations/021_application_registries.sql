@@ -0,0 +1,9 @@
+-- Registry each application's image is pushed to after a successful build
+CREATE TABLE IF NOT EXISTS application_registries (
+    application_id TEXT PRIMARY KEY REFERENCES applications(id) ON DELETE CASCADE,
+    image TEXT NOT NULL,
+    username TEXT,
+    password_encrypted TEXT,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL
+);
@@ -0,0 +1,2 @@
+-- Prebuilt image to pull and run instead of cloning and building
+ALTER TABLE applications ADD COLUMN image TEXT;
@@ -0,0 +1,3 @@
+-- Client-supplied key so a retried deploy trigger returns the original deployment
+ALTER TABLE deployments ADD COLUMN idempotency_key TEXT;
+CREATE UNIQUE INDEX IF NOT EXISTS idx_deployments_idempotency_key ON deployments(application_id, idempotency_key);
@@ -0,0 +1,3 @@
+-- Filtering the applications list by server and status
+CREATE INDEX IF NOT EXISTS idx_applications_server_id ON applications(server_id);
+CREATE INDEX IF NOT EXISTS idx_applications_status ON applications(status);
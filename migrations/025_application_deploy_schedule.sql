@@ -0,0 +1,2 @@
+-- Cron expression for scheduled redeploys, NULL for none
+ALTER TABLE applications ADD COLUMN deploy_schedule TEXT;
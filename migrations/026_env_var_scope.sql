@@ -0,0 +1,2 @@
+-- Where a variable applies: build (build arg only), runtime (container env only) or both
+ALTER TABLE environment_variables ADD COLUMN scope TEXT NOT NULL DEFAULT 'both';
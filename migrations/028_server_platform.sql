@@ -0,0 +1,3 @@
+-- Platform (os/arch[/variant], e.g. linux/arm64) images are built and pulled for
+-- on each server. NULL uses the Docker daemon's own platform
+ALTER TABLE servers ADD COLUMN platform TEXT;
@@ -0,0 +1,2 @@
+-- Have the proxy actively health check the upstream and stop routing to it while it's down
+ALTER TABLE domains ADD COLUMN proxy_health_check INTEGER NOT NULL DEFAULT 0;
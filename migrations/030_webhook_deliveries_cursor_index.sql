@@ -0,0 +1,2 @@
+-- Serve each page of an application's delivery history, newest first, from the index
+CREATE INDEX IF NOT EXISTS idx_webhook_deliveries_app ON webhook_deliveries(application_id, delivered_at, id);
@@ -0,0 +1,2 @@
+-- Seconds the old container gets to exit on SIGTERM before it is killed
+ALTER TABLE applications ADD COLUMN stop_grace_seconds INTEGER NOT NULL DEFAULT 10;
@@ -0,0 +1,3 @@
+-- Key/value tags for grouping servers, e.g. {"region": "eu", "env": "prod"},
+-- stored as a JSON object
+ALTER TABLE servers ADD COLUMN tags TEXT NOT NULL DEFAULT '{}';
@@ -0,0 +1,3 @@
+-- Request and response header rules applied by the proxy, as JSON
+-- ({"request": {"set": {...}, "remove": [...]}, "response": {...}}). NULL means none
+ALTER TABLE domains ADD COLUMN proxy_headers TEXT;
@@ -0,0 +1,5 @@
+-- Optional shell commands run around a deploy. pre_build_cmd runs in the
+-- checkout before the image build and post_deploy_cmd inside the new container
+-- once it is healthy
+ALTER TABLE applications ADD COLUMN pre_build_cmd TEXT;
+ALTER TABLE applications ADD COLUMN post_deploy_cmd TEXT;
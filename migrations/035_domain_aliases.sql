@@ -0,0 +1,5 @@
+-- Extra hostnames served by a domain's proxy route, as a JSON array
+-- (wildcards allowed, e.g. ["*.example.com"]). NULL means none
+ALTER TABLE domains ADD COLUMN aliases TEXT;
+-- 1 redirects www.<domain> to the domain
+ALTER TABLE domains ADD COLUMN www_redirect INTEGER NOT NULL DEFAULT 0;